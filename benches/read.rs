@@ -0,0 +1,73 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pos::{Point, Radians, Time};
+use std::io::BufReader;
+
+const POINT_COUNT: usize = 100_000;
+
+fn points() -> Vec<Point> {
+    (0..POINT_COUNT)
+        .map(|i| Point {
+            time: Time(151631. + i as f64 * 0.005),
+            latitude: Radians::from_degrees(38.5 + i as f64 * 1e-7),
+            longitude: Radians::from_degrees(-107.9 - i as f64 * 1e-7),
+            altitude: 1721. + i as f64 * 1e-3,
+            roll: Radians::from_degrees(1.5),
+            pitch: Radians::from_degrees(-0.5),
+            yaw: Radians::from_degrees(i as f64 % 360.),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn pos_bytes() -> Vec<u8> {
+    let mut writer = pos::pos::Writer::new(Vec::new()).unwrap();
+    for point in points() {
+        writer.write_point(&point).unwrap();
+    }
+    writer.into_inner()
+}
+
+fn sbet_bytes() -> Vec<u8> {
+    let mut writer = pos::sbet::Writer::new(Vec::new());
+    for point in points() {
+        writer.write_point(&point).unwrap();
+    }
+    writer.into_inner()
+}
+
+fn read_pos(c: &mut Criterion) {
+    let bytes = pos_bytes();
+    let mut group = c.benchmark_group("pos");
+    let _ = group.throughput(Throughput::Elements(POINT_COUNT as u64));
+    let _ = group.bench_function("read_point", |b| {
+        b.iter(|| {
+            let mut reader = pos::pos::Reader::new(BufReader::new(bytes.as_slice())).unwrap();
+            let mut count = 0;
+            while reader.read_point().unwrap().is_some() {
+                count += 1;
+            }
+            count
+        })
+    });
+    group.finish();
+}
+
+fn read_sbet(c: &mut Criterion) {
+    let bytes = sbet_bytes();
+    let mut group = c.benchmark_group("sbet");
+    let _ = group.throughput(Throughput::Elements(POINT_COUNT as u64));
+    let _ = group.bench_function("read_point", |b| {
+        b.iter(|| {
+            let mut reader = pos::sbet::Reader::new(BufReader::new(bytes.as_slice()));
+            let mut count = 0;
+            while reader.read_point().unwrap().is_some() {
+                count += 1;
+            }
+            count
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read_pos, read_sbet);
+criterion_main!(benches);
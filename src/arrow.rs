@@ -0,0 +1,271 @@
+//! Apache Arrow record batches and Parquet files.
+//!
+//! Each point becomes a row, with one column per point field. Accuracy fields are flattened into
+//! `accuracy_*` columns. Values are in the point's own units, so angles are in radians, and
+//! optional fields are nullable. The satellite count is the total over all constellations.
+//!
+//! Requires the `arrow` feature.
+
+use crate::error;
+use crate::point::Point;
+use crate::sink::Sink;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, UInt16Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The default number of points in each record batch written by a Parquet writer.
+pub const DEFAULT_BATCH_SIZE: usize = 65536;
+
+type Getter = fn(&Point) -> Option<f64>;
+
+const COLUMNS: &[(&str, bool, Getter)] = &[
+    ("time", false, |p| Some(p.time.0)),
+    ("longitude", false, |p| Some(p.longitude.0)),
+    ("latitude", false, |p| Some(p.latitude.0)),
+    ("altitude", false, |p| Some(p.altitude)),
+    ("roll", false, |p| Some(p.roll.0)),
+    ("pitch", false, |p| Some(p.pitch.0)),
+    ("yaw", false, |p| Some(p.yaw.0)),
+    ("distance", true, |p| p.distance),
+    ("x_velocity", true, |p| p.x_velocity),
+    ("y_velocity", true, |p| p.y_velocity),
+    ("z_velocity", true, |p| p.z_velocity),
+    ("wander_angle", true, |p| p.wander_angle.map(|a| a.0)),
+    ("x_acceleration", true, |p| p.x_acceleration),
+    ("y_acceleration", true, |p| p.y_acceleration),
+    ("z_acceleration", true, |p| p.z_acceleration),
+    ("x_angular_rate", true, |p| p.x_angular_rate.map(|a| a.0)),
+    ("y_angular_rate", true, |p| p.y_angular_rate.map(|a| a.0)),
+    ("z_angular_rate", true, |p| p.z_angular_rate.map(|a| a.0)),
+    ("accuracy_x", true, |p| p.accuracy.map(|a| a.x)),
+    ("accuracy_y", true, |p| p.accuracy.map(|a| a.y)),
+    ("accuracy_z", true, |p| p.accuracy.map(|a| a.z)),
+    ("accuracy_roll", true, |p| p.accuracy.map(|a| a.roll.0)),
+    ("accuracy_pitch", true, |p| p.accuracy.map(|a| a.pitch.0)),
+    ("accuracy_yaw", true, |p| p.accuracy.map(|a| a.yaw.0)),
+    ("accuracy_pdop", true, |p| p.accuracy.map(|a| a.pdop)),
+    ("accuracy_x_velocity", true, |p| {
+        p.accuracy.and_then(|a| a.x_velocity)
+    }),
+    ("accuracy_y_velocity", true, |p| {
+        p.accuracy.and_then(|a| a.y_velocity)
+    }),
+    ("accuracy_z_velocity", true, |p| {
+        p.accuracy.and_then(|a| a.z_velocity)
+    }),
+];
+
+/// Returns the schema of point record batches.
+///
+/// # Examples
+///
+/// ```
+/// let schema = pos::arrow::schema();
+/// assert!(schema.field_with_name("latitude").is_ok());
+/// ```
+pub fn schema() -> Schema {
+    let mut fields: Vec<_> = COLUMNS
+        .iter()
+        .map(|&(name, nullable, _)| Field::new(name, DataType::Float64, nullable))
+        .collect();
+    fields.push(Field::new("satellite_count", DataType::UInt16, true));
+    Schema::new(fields)
+}
+
+/// Converts points to a record batch.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// let batch = pos::arrow::to_record_batch(&[Point::default()]).unwrap();
+/// assert_eq!(1, batch.num_rows());
+/// ```
+pub fn to_record_batch(points: &[Point]) -> Result<RecordBatch, error::Error> {
+    let mut columns: Vec<ArrayRef> = COLUMNS
+        .iter()
+        .map(|&(_, _, get)| -> ArrayRef {
+            Arc::new(points.iter().map(get).collect::<Float64Array>())
+        })
+        .collect();
+    let satellite_counts: UInt16Array = points
+        .iter()
+        .map(|p| {
+            p.accuracy
+                .and_then(|a| a.satellite_count)
+                .map(|count| count.total())
+        })
+        .collect();
+    columns.push(Arc::new(satellite_counts));
+    Ok(RecordBatch::try_new(Arc::new(schema()), columns)?)
+}
+
+/// A Parquet writer.
+///
+/// Points are buffered into record batches. The Parquet file isn't complete until the writer is
+/// closed.
+#[derive(Debug)]
+pub struct Writer<W: Write + Send> {
+    /// The number of points in each record batch.
+    pub batch_size: usize,
+
+    points: Vec<Point>,
+    writer: ArrowWriter<W>,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::arrow::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write + Send> Writer<W> {
+    /// Creates a new Parquet writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::arrow::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// ```
+    pub fn new(writer: W) -> Result<Writer<W>, error::Error> {
+        Ok(Writer {
+            batch_size: DEFAULT_BATCH_SIZE,
+            points: Vec::new(),
+            writer: ArrowWriter::try_new(writer, Arc::new(schema()), None)?,
+        })
+    }
+
+    /// Writes a point, writing a record batch if the buffer is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::arrow::Writer;
+    /// let mut writer = Writer::new(Vec::new()).unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        self.points.push(*point);
+        if self.points.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Writes any buffered points and the Parquet footer, and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::arrow::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// let bytes = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        self.write_batch()?;
+        Ok(self.writer.into_inner()?)
+    }
+
+    fn write_batch(&mut self) -> Result<(), error::Error> {
+        if !self.points.is_empty() {
+            self.writer.write(&to_record_batch(&self.points)?)?;
+            self.points.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<W: Debug + Write + Send> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        let _ = Writer::close(*self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::point::{Accuracy, SatelliteCount};
+    use crate::time::Time;
+    use crate::units::Radians;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn points() -> Vec<Point> {
+        (0..5)
+            .map(|i| Point {
+                time: Time(i as f64),
+                latitude: Radians(0.5),
+                x_velocity: if i % 2 == 0 { Some(1.5) } else { None },
+                accuracy: Some(Accuracy {
+                    satellite_count: Some(SatelliteCount::Specified { gps: 8, glonass: 4 }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn record_batch() {
+        let batch = to_record_batch(&points()).unwrap();
+        assert_eq!(5, batch.num_rows());
+        assert_eq!(29, batch.num_columns());
+        let x_velocity = batch.column_by_name("x_velocity").unwrap();
+        assert_eq!(2, x_velocity.null_count());
+        let satellite_count = batch
+            .column_by_name("satellite_count")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt16Array>()
+            .unwrap();
+        assert_eq!(12, satellite_count.value(0));
+    }
+
+    #[test]
+    fn parquet() {
+        let path = ::std::env::temp_dir().join("pos-rs-arrow-test.parquet");
+        let mut writer = Writer::from_path(&path).unwrap();
+        writer.batch_size = 2;
+        for point in points() {
+            writer.write_point(&point).unwrap();
+        }
+        let _ = writer.close().unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(5, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+        let latitude = batches[0]
+            .column_by_name("latitude")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(0.5, latitude.value(0));
+        ::std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,522 @@
+//! Command-line tools for position files.
+//!
+//! Requires the `cli` feature.
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use pos::compare::{self, Offset};
+use pos::csv::AngleUnit;
+use pos::pos::ColumnMap;
+use pos::sink::Kinematics;
+use pos::source::Window;
+use pos::time::{Duration, Time};
+use pos::{sbet, Error, Format, Point, Sink, Trajectory};
+use std::collections::VecDeque;
+use std::error::Error as StdError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process;
+
+type Axis = (&'static str, fn(&Offset) -> f64);
+
+const AXES: &[Axis] = &[
+    ("north (m)", |o| o.north),
+    ("east (m)", |o| o.east),
+    ("up (m)", |o| o.up),
+    ("roll (deg)", |o| o.roll.to_degrees()),
+    ("pitch (deg)", |o| o.pitch.to_degrees()),
+    ("yaw (deg)", |o| o.yaw.to_degrees()),
+];
+
+fn main() {
+    let matches = Command::new("pos")
+        .about("Inspects and converts GNSS/IMU position files")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("clip")
+                .about("Writes the points between two times or indices, in the same format")
+                .arg(
+                    Arg::new("input")
+                        .help("The input position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("The output position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("start")
+                        .long("start")
+                        .help("The first time to keep")
+                        .value_name("TIME")
+                        .value_parser(clap::value_parser!(f64))
+                        .requires("end")
+                        .conflicts_with("first"),
+                )
+                .arg(
+                    Arg::new("end")
+                        .long("end")
+                        .help("The last time to keep")
+                        .value_name("TIME")
+                        .value_parser(clap::value_parser!(f64))
+                        .requires("start"),
+                )
+                .arg(
+                    Arg::new("first")
+                        .long("first")
+                        .help("The index of the first point to keep")
+                        .value_name("INDEX")
+                        .value_parser(clap::value_parser!(usize))
+                        .requires("last")
+                        .conflicts_with("end"),
+                )
+                .arg(
+                    Arg::new("last")
+                        .long("last")
+                        .help("The index after the last point to keep")
+                        .value_name("INDEX")
+                        .value_parser(clap::value_parser!(usize))
+                        .requires("first"),
+                )
+                .group(
+                    ArgGroup::new("window")
+                        .args(["start", "first"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Converts a position file to another format, chosen by extension")
+                .arg(
+                    Arg::new("input")
+                        .help("The input position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("The output position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("decimate")
+                        .long("decimate")
+                        .help("Writes every nth point")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::new("start")
+                        .long("start")
+                        .help("Skips points before this time")
+                        .value_name("TIME")
+                        .value_parser(clap::value_parser!(f64)),
+                )
+                .arg(
+                    Arg::new("end")
+                        .long("end")
+                        .help("Skips points after this time")
+                        .value_name("TIME")
+                        .value_parser(clap::value_parser!(f64)),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compares a trajectory against a reference trajectory")
+                .arg(
+                    Arg::new("test")
+                        .help("The trajectory to check")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("reference")
+                        .help("The reference trajectory")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .help("Writes the per-epoch differences to a CSV file")
+                        .value_name("PATH"),
+                ),
+        )
+        .subcommand(
+            Command::new("head")
+                .about("Prints the first points of a position file")
+                .arg(Arg::new("input").help("The position file").required(true))
+                .arg(
+                    Arg::new("count")
+                        .short('n')
+                        .long("count")
+                        .help("The number of points to print")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
+                ),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Prints a summary of one or more position files")
+                .arg(
+                    Arg::new("input")
+                        .help("The position files")
+                        .required(true)
+                        .num_args(1..),
+                ),
+        )
+        .subcommand(
+            Command::new("pos2sbet")
+                .about("Converts a position file to sbet, for tools that only read sbet")
+                .arg(
+                    Arg::new("input")
+                        .help("The input position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("The output sbet file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("derive")
+                        .long("derive")
+                        .help("Derives missing velocities and accelerations, instead of zeros")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("sbet2csv")
+                .about("Converts a position file to CSV, with only the chosen columns")
+                .arg(
+                    Arg::new("input")
+                        .help("The input position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("The output CSV file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("fields")
+                        .short('f')
+                        .long("fields")
+                        .help("The comma-separated columns, e.g. time,easting,northing,altitude")
+                        .default_value("time,latitude,longitude,altitude,roll,pitch,yaw"),
+                )
+                .arg(
+                    Arg::new("radians")
+                        .long("radians")
+                        .help("Writes angles in radians instead of degrees")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("sbet2pos")
+                .about("Converts a position file to pos, with only the chosen columns")
+                .arg(
+                    Arg::new("input")
+                        .help("The input position file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .help("The output pos file")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("fields")
+                        .short('f')
+                        .long("fields")
+                        .help("The comma-separated columns, e.g. time,latitude,longitude,vz")
+                        .default_value("time,latitude,longitude,altitude,roll,pitch,yaw"),
+                ),
+        )
+        .subcommand(
+            Command::new("tail")
+                .about("Prints the last points of a position file")
+                .arg(Arg::new("input").help("The position file").required(true))
+                .arg(
+                    Arg::new("count")
+                        .short('n')
+                        .long("count")
+                        .help("The number of points to print")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
+                ),
+        )
+        .get_matches();
+    let result = match matches.subcommand() {
+        Some(("clip", matches)) => clip(matches),
+        Some(("convert", matches)) => convert(matches),
+        Some(("diff", matches)) => diff(matches),
+        Some(("head", matches)) => head(matches),
+        Some(("info", matches)) => info(matches),
+        Some(("pos2sbet", matches)) => pos2sbet(matches),
+        Some(("sbet2csv", matches)) => sbet2csv(matches),
+        Some(("sbet2pos", matches)) => sbet2pos(matches),
+        Some(("tail", matches)) => tail(matches),
+        _ => unreachable!("a subcommand is required"),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        let mut source = err.source();
+        while let Some(err) = source {
+            eprintln!("  caused by: {}", err);
+            source = err.source();
+        }
+        process::exit(1);
+    }
+}
+
+fn clip(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let output: &String = matches.get_one("output").unwrap();
+    let window = match (matches.get_one("start"), matches.get_one("end")) {
+        (Some(&start), Some(&end)) => Window::Time(Time(start), Time(end)),
+        _ => Window::Index(
+            *matches.get_one("first").unwrap(),
+            *matches.get_one("last").unwrap(),
+        ),
+    };
+    let count = pos::sink::clip(input, output, window)?;
+    eprintln!("wrote {} points to {}", count, output);
+    Ok(())
+}
+
+fn convert(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let output: &String = matches.get_one("output").unwrap();
+    let decimate = *matches.get_one::<u64>("decimate").unwrap() as usize;
+    let start = matches.get_one::<f64>("start").cloned().map(Time);
+    let end = matches.get_one::<f64>("end").cloned().map(Time);
+    let mut source = pos::open(input)?;
+    let mut sink = pos::create(output)?;
+    let mut index = 0;
+    let mut count = 0;
+    while let Some(point) = source.source()? {
+        if start.is_some_and(|start| point.time < start) || end.is_some_and(|end| point.time > end)
+        {
+            continue;
+        }
+        if index % decimate == 0 {
+            sink.write_point(&point)?;
+            count += 1;
+        }
+        index += 1;
+    }
+    sink.close()?;
+    eprintln!("wrote {} points to {}", count, output);
+    Ok(())
+}
+
+fn diff(matches: &ArgMatches) -> Result<(), Error> {
+    let test: &String = matches.get_one("test").unwrap();
+    let reference: &String = matches.get_one("reference").unwrap();
+    let test = Trajectory::from_source(pos::open(test)?)?;
+    let reference = Trajectory::from_source(pos::open(reference)?)?;
+    let differences = compare::compare(&test, &reference)?;
+    if let Some(path) = matches.get_one::<String>("output") {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "time,north,east,up,roll,pitch,yaw")?;
+        for difference in &differences {
+            let offset = difference.offset;
+            writeln!(
+                writer,
+                "{:.6},{:.4},{:.4},{:.4},{:.6},{:.6},{:.6}",
+                difference.time,
+                offset.north,
+                offset.east,
+                offset.up,
+                offset.roll.to_degrees(),
+                offset.pitch.to_degrees(),
+                offset.yaw.to_degrees()
+            )?;
+        }
+        writer.flush()?;
+    }
+    let summary = match compare::summarize(&differences) {
+        Some(summary) => summary,
+        None => {
+            println!("no overlapping epochs");
+            return Ok(());
+        }
+    };
+    println!("epochs: {}", summary.count);
+    println!("{:<12}{:>12}{:>12}{:>12}", "", "mean", "rms", "max");
+    for &(name, get) in AXES {
+        println!(
+            "{:<12}{:>12.4}{:>12.4}{:>12.4}",
+            name,
+            get(&summary.mean),
+            get(&summary.rms),
+            get(&summary.max)
+        );
+    }
+    Ok(())
+}
+
+fn head(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let count: usize = *matches.get_one("count").unwrap();
+    let mut source = pos::open(input)?;
+    for point in source.read_points(count)? {
+        println!("{}", point);
+    }
+    Ok(())
+}
+
+fn info(matches: &ArgMatches) -> Result<(), Error> {
+    let paths: Vec<&String> = matches.get_many("input").unwrap().collect();
+    for (i, path) in paths.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let mut source = pos::open(path)?;
+        let mut summary = Summary::default();
+        while let Some(point) = source.source()? {
+            summary.add(&point);
+        }
+        println!("{}", path);
+        summary.print();
+    }
+    Ok(())
+}
+
+fn pos2sbet(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let output: &String = matches.get_one("output").unwrap();
+    let kinematics = if matches.get_flag("derive") {
+        Kinematics::Derived
+    } else {
+        Kinematics::Zeros
+    };
+    let count = pos::sink::pos2sbet(input, output, kinematics)?;
+    eprintln!("wrote {} points to {}", count, output);
+    Ok(())
+}
+
+fn sbet2csv(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let output: &String = matches.get_one("output").unwrap();
+    let fields: &String = matches.get_one("fields").unwrap();
+    let angle_unit = if matches.get_flag("radians") {
+        AngleUnit::Radians
+    } else {
+        AngleUnit::Degrees
+    };
+    let columns = ColumnMap::from_fields(fields)?;
+    let count = pos::sink::sbet2csv(input, output, columns, angle_unit)?;
+    eprintln!("wrote {} points to {}", count, output);
+    Ok(())
+}
+
+fn sbet2pos(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let output: &String = matches.get_one("output").unwrap();
+    let fields: &String = matches.get_one("fields").unwrap();
+    let columns = ColumnMap::from_fields(fields)?;
+    let count = pos::sink::sbet2pos(input, output, columns)?;
+    eprintln!("wrote {} points to {}", count, output);
+    Ok(())
+}
+
+fn tail(matches: &ArgMatches) -> Result<(), Error> {
+    let input: &String = matches.get_one("input").unwrap();
+    let count: usize = *matches.get_one("count").unwrap();
+    // Uncompressed sbet files have fixed-size records, so seek straight to the end.
+    if Format::from_path(input)? == Format::Sbet && !pos::format::is_gzip(input)? {
+        let mut reader = sbet::Reader::from_path(input)?;
+        let len = reader.point_count()?;
+        reader.seek(len.saturating_sub(count as u64))?;
+        while let Some(point) = reader.read_point()? {
+            println!("{}", point);
+        }
+        return Ok(());
+    }
+    let mut source = pos::open(input)?;
+    let mut points = VecDeque::new();
+    while let Some(point) = source.source()? {
+        points.push_back(point);
+        if points.len() > count {
+            let _ = points.pop_front();
+        }
+    }
+    for point in points {
+        println!("{}", point);
+    }
+    Ok(())
+}
+
+/// A running summary of a file's points.
+#[derive(Debug, Default)]
+struct Summary {
+    count: usize,
+    start: Time,
+    end: Time,
+    min_latitude: f64,
+    max_latitude: f64,
+    min_longitude: f64,
+    max_longitude: f64,
+    min_altitude: f64,
+    max_altitude: f64,
+}
+
+impl Summary {
+    fn add(&mut self, point: &Point) {
+        let latitude = point.latitude.to_degrees();
+        let longitude = point.longitude.to_degrees();
+        if self.count == 0 {
+            self.start = point.time;
+            self.end = point.time;
+            self.min_latitude = latitude;
+            self.max_latitude = latitude;
+            self.min_longitude = longitude;
+            self.max_longitude = longitude;
+            self.min_altitude = point.altitude;
+            self.max_altitude = point.altitude;
+        } else {
+            self.start = self.start.min(point.time);
+            self.end = self.end.max(point.time);
+            self.min_latitude = self.min_latitude.min(latitude);
+            self.max_latitude = self.max_latitude.max(latitude);
+            self.min_longitude = self.min_longitude.min(longitude);
+            self.max_longitude = self.max_longitude.max(longitude);
+            self.min_altitude = self.min_altitude.min(point.altitude);
+            self.max_altitude = self.max_altitude.max(point.altitude);
+        }
+        self.count += 1;
+    }
+
+    fn print(&self) {
+        println!("  points:      {}", self.count);
+        if self.count == 0 {
+            return;
+        }
+        let duration = self.end - self.start;
+        println!("  start time:  {:.6} s", self.start);
+        println!("  end time:    {:.6} s", self.end);
+        println!("  duration:    {:.3} s", duration);
+        if duration > Duration(0.) {
+            println!(
+                "  sample rate: {:.3} Hz",
+                (self.count - 1) as f64 / duration.0
+            );
+        }
+        println!(
+            "  latitude:    {:.8} to {:.8} degrees",
+            self.min_latitude, self.max_latitude
+        );
+        println!(
+            "  longitude:   {:.8} to {:.8} degrees",
+            self.min_longitude, self.max_longitude
+        );
+        println!(
+            "  altitude:    {:.3} to {:.3} m",
+            self.min_altitude, self.max_altitude
+        );
+    }
+}
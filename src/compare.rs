@@ -0,0 +1,271 @@
+//! Compare two trajectories.
+//!
+//! A test trajectory is compared against a reference trajectory by interpolating the reference at
+//! each of the test trajectory's epochs. Position differences are in meters in a local
+//! north-east-up frame centered on the reference point, and attitude differences are wrapped to
+//! [-π, π).
+
+use crate::error::Error;
+use crate::geodesy;
+use crate::point::Point;
+use crate::time::{Time, TimeBase};
+use crate::trajectory::Trajectory;
+use crate::units::Radians;
+
+/// The difference between a test point and a reference point at one epoch.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Difference {
+    /// The time of the epoch.
+    pub time: Time,
+    /// The per-axis offset, test minus reference.
+    pub offset: Offset,
+}
+
+/// Per-axis differences between two points.
+///
+/// Positions are in meters and attitudes are in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Offset {
+    /// Northing difference.
+    pub north: f64,
+    /// Easting difference.
+    pub east: f64,
+    /// Altitude difference.
+    pub up: f64,
+    /// Roll difference.
+    pub roll: Radians<f64>,
+    /// Pitch difference.
+    pub pitch: Radians<f64>,
+    /// Yaw difference, between the true headings, so that a wander angle isn't counted as error.
+    pub yaw: Radians<f64>,
+}
+
+/// Summary statistics of a set of differences.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    /// The number of differences.
+    pub count: usize,
+    /// The mean of each axis.
+    pub mean: Offset,
+    /// The root mean square of each axis.
+    pub rms: Offset,
+    /// The largest absolute value of each axis.
+    pub max: Offset,
+}
+
+impl Offset {
+    /// Returns the offset of one point from another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::compare::Offset;
+    /// use pos::units::Radians;
+    /// let reference = Point::default();
+    /// let point = Point { altitude: 1.5, yaw: Radians::from_degrees(359.0), ..Default::default() };
+    /// let offset = Offset::between(&point, &reference);
+    /// assert_eq!(1.5, offset.up);
+    /// assert!((offset.yaw.to_degrees() + 1.0).abs() < 1e-9);
+    /// ```
+    pub fn between(point: &Point, reference: &Point) -> Offset {
+        let latitude = reference.latitude.0;
+        let prime_vertical = geodesy::prime_vertical_radius(reference.latitude);
+        let meridian = geodesy::meridian_radius(reference.latitude);
+        Offset {
+            north: (point.latitude - reference.latitude).0 * (meridian + reference.altitude),
+            east: point.longitude.angular_difference(reference.longitude).0
+                * (prime_vertical + reference.altitude)
+                * latitude.cos(),
+            up: point.altitude - reference.altitude,
+            roll: point.roll.angular_difference(reference.roll),
+            pitch: point.pitch.angular_difference(reference.pitch),
+            yaw: point
+                .true_heading()
+                .angular_difference(reference.true_heading()),
+        }
+    }
+
+    fn values(&self) -> [f64; 6] {
+        [
+            self.north,
+            self.east,
+            self.up,
+            self.roll.0,
+            self.pitch.0,
+            self.yaw.0,
+        ]
+    }
+
+    fn from_values(values: [f64; 6]) -> Offset {
+        Offset {
+            north: values[0],
+            east: values[1],
+            up: values[2],
+            roll: Radians(values[3]),
+            pitch: Radians(values[4]),
+            yaw: Radians(values[5]),
+        }
+    }
+}
+
+/// Compares a test trajectory against a reference trajectory.
+///
+/// The reference is interpolated at each test epoch, with attitudes interpolated spherically.
+/// Test epochs outside of the reference's time span are skipped. Returns an error if the two
+/// trajectories' times are in different time bases.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Trajectory};
+/// use pos::time::Time;
+/// let reference: Trajectory = (0..10)
+///     .map(|i| Point { time: Time(i as f64), altitude: i as f64, ..Default::default() })
+///     .collect();
+/// let test: Trajectory = (0..20)
+///     .map(|i| Point { time: Time(i as f64 + 0.5), altitude: i as f64 + 1.0, ..Default::default() })
+///     .collect();
+/// let differences = pos::compare::compare(&test, &reference).unwrap();
+/// assert_eq!(9, differences.len());
+/// assert_eq!(0.5, differences[0].offset.up);
+/// ```
+pub fn compare(test: &Trajectory, reference: &Trajectory) -> Result<Vec<Difference>, Error> {
+    let _ = TimeBase::of(test.iter().chain(reference))?;
+    let points = reference.points();
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (first.time, last.time),
+        _ => return Ok(Vec::new()),
+    };
+    Ok(test
+        .iter()
+        .filter(|point| point.time >= first && point.time <= last)
+        .map(|point| {
+            let index = points.partition_point(|p| p.time < point.time);
+            let reference = if points[index].time == point.time {
+                points[index]
+            } else {
+                points[index - 1].slerp(&points[index], point.time)
+            };
+            Difference {
+                time: point.time,
+                offset: Offset::between(point, &reference),
+            }
+        })
+        .collect())
+}
+
+/// Summarizes a set of differences.
+///
+/// Returns none if there are no differences.
+///
+/// # Examples
+///
+/// ```
+/// use pos::compare::{Difference, Offset};
+/// use pos::time::Time;
+/// let differences = vec![
+///     Difference { time: Time(0.0), offset: Offset { up: 1.0, ..Default::default() } },
+///     Difference { time: Time(1.0), offset: Offset { up: -1.0, ..Default::default() } },
+/// ];
+/// let summary = pos::compare::summarize(&differences).unwrap();
+/// assert_eq!(0.0, summary.mean.up);
+/// assert_eq!(1.0, summary.rms.up);
+/// ```
+pub fn summarize(differences: &[Difference]) -> Option<Summary> {
+    if differences.is_empty() {
+        return None;
+    }
+    let mut sum = [0.; 6];
+    let mut sum_of_squares = [0.; 6];
+    let mut max = [0.; 6];
+    for difference in differences {
+        for (i, value) in difference.offset.values().iter().enumerate() {
+            sum[i] += value;
+            sum_of_squares[i] += value * value;
+            max[i] = value.abs().max(max[i]);
+        }
+    }
+    let count = differences.len() as f64;
+    let mut mean = [0.; 6];
+    let mut rms = [0.; 6];
+    for i in 0..6 {
+        mean[i] = sum[i] / count;
+        rms[i] = (sum_of_squares[i] / count).sqrt();
+    }
+    Some(Summary {
+        count: differences.len(),
+        mean: Offset::from_values(mean),
+        rms: Offset::from_values(rms),
+        max: Offset::from_values(max),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset() {
+        let reference = Point::default();
+        let point = Point {
+            latitude: Radians::from_degrees(1e-5),
+            longitude: Radians::from_degrees(-1e-5),
+            ..Default::default()
+        };
+        let offset = Offset::between(&point, &reference);
+        assert!((offset.north - 1.106).abs() < 1e-3);
+        assert!((offset.east + 1.113).abs() < 1e-3);
+    }
+
+    #[test]
+    fn offset_wander_angle() {
+        let reference = Point {
+            yaw: Radians::from_degrees(90.),
+            ..Default::default()
+        };
+        let point = Point {
+            yaw: Radians::from_degrees(100.5),
+            wander_angle: Some(Radians::from_degrees(10.)),
+            ..Default::default()
+        };
+        let offset = Offset::between(&point, &reference);
+        assert!((offset.yaw.to_degrees() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compare_trajectories() {
+        let reference: Trajectory = (0..5)
+            .map(|i| Point {
+                time: Time(i as f64),
+                yaw: Radians::from_degrees(358. + i as f64),
+                ..Default::default()
+            })
+            .collect();
+        let test: Trajectory = (0..5)
+            .map(|i| Point {
+                time: Time(i as f64 + 0.5),
+                yaw: Radians::from_degrees(359. + i as f64),
+                ..Default::default()
+            })
+            .collect();
+        let differences = compare(&test, &reference).unwrap();
+        assert_eq!(4, differences.len());
+        for difference in &differences {
+            assert!((difference.offset.yaw.to_degrees() - 0.5).abs() < 1e-6);
+        }
+        let summary = summarize(&differences).unwrap();
+        assert_eq!(4, summary.count);
+        assert!((summary.rms.yaw.to_degrees() - 0.5).abs() < 1e-6);
+        assert!(summarize(&[]).is_none());
+        assert!(compare(&test, &Trajectory::default()).unwrap().is_empty());
+        let utc: Trajectory = test
+            .iter()
+            .map(|&point| Point {
+                time_base: TimeBase::UtcSecondsOfDay,
+                ..point
+            })
+            .collect();
+        assert!(compare(&utc, &reference).is_err());
+    }
+}
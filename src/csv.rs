@@ -0,0 +1,574 @@
+//! Delimited text files, e.g. CSV.
+//!
+//! Rows are mapped to points with a pos column map (see `pos::ColumnMap`), so any column layout
+//! can be read and written. If the first row names the columns, e.g. `time,lat,lon,height`, the
+//! column map is built from it, otherwise the default pos columns are used. Angles are in
+//! degrees. Values may be quoted, e.g. to hold the delimiter, and empty values leave their fields
+//! unset.
+//!
+//! With the `serde` feature, dialects, angle units, and column maps can be serialized and
+//! deserialized, so a file layout can be kept in a configuration file instead of in code.
+
+use crate::error;
+use crate::point::Point;
+use crate::pos::{Column, ColumnMap, DEFAULT_PRECISION};
+use crate::sink::Sink;
+use crate::source::Source;
+use crate::utm::Zone;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// The unit that angles and angular rates are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AngleUnit {
+    /// Degrees, which is what readers expect.
+    #[default]
+    Degrees,
+    /// Radians.
+    Radians,
+}
+
+/// How rows are delimited, and whether there's a header row.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dialect {
+    /// The character between values.
+    ///
+    /// If whitespace, values are separated by any amount of whitespace.
+    pub delimiter: char,
+
+    /// Whether there's a header row of column names.
+    ///
+    /// Readers still use the default columns if the header doesn't name them.
+    pub header: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Dialect {
+        Dialect {
+            delimiter: ',',
+            header: true,
+        }
+    }
+}
+
+impl Dialect {
+    /// Splits a row into trimmed values.
+    ///
+    /// Values may be quoted, in which case they can contain the delimiter, and a doubled quote
+    /// inside a quoted value is a literal quote.
+    fn split(&self, line: &str) -> Vec<String> {
+        let whitespace = self.delimiter.is_whitespace();
+        let mut values = Vec::new();
+        let mut value = String::new();
+        let mut started = false;
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if quoted {
+                if c != '"' {
+                    value.push(c);
+                } else if chars.peek() == Some(&'"') {
+                    value.push(c);
+                    let _ = chars.next();
+                } else {
+                    quoted = false;
+                }
+            } else if c == '"' {
+                quoted = true;
+                started = true;
+            } else if c == self.delimiter || (whitespace && c.is_whitespace()) {
+                // Runs of whitespace are one delimiter.
+                if !whitespace || started {
+                    values.push(value.trim().to_string());
+                }
+                value.clear();
+                started = false;
+            } else {
+                value.push(c);
+                started = true;
+            }
+        }
+        if !whitespace || started {
+            values.push(value.trim().to_string());
+        }
+        values
+    }
+}
+
+/// A CSV reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    columns: ColumnMap,
+    dialect: Dialect,
+    pending: Option<String>,
+    reader: R,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path and reads the header row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Reader;
+    /// let reader = Reader::from_path("data/2-points.csv").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader for comma-separated values with a header row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Reader;
+    /// let data = "time,lat,lon,height\n1,45,-105,1600\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// assert_eq!(1600.0, reader.read_point().unwrap().unwrap().altitude);
+    /// ```
+    pub fn new(reader: R) -> Result<Reader<R>, error::Error> {
+        Reader::with_dialect(reader, Dialect::default())
+    }
+
+    /// Creates a new reader with the given dialect.
+    ///
+    /// If the dialect has a header but the first row doesn't name the columns, the row is read
+    /// as data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::{Dialect, Reader};
+    /// let data = "1;45;-105;1600;0;0;0\n";
+    /// let dialect = Dialect { delimiter: ';', header: false };
+    /// let mut reader = Reader::with_dialect(data.as_bytes(), dialect).unwrap();
+    /// assert_eq!(1600.0, reader.read_point().unwrap().unwrap().altitude);
+    /// ```
+    pub fn with_dialect(reader: R, dialect: Dialect) -> Result<Reader<R>, error::Error> {
+        let mut reader = Reader {
+            columns: ColumnMap::default(),
+            dialect,
+            pending: None,
+            reader,
+        };
+        if dialect.header {
+            if let Some(line) = reader.read_line()? {
+                let names = dialect.split(&line);
+                match ColumnMap::from_names(names.iter().map(String::as_str)) {
+                    Some(columns) => reader.columns = columns,
+                    None => reader.pending = Some(line),
+                }
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Returns the column map used to read rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Reader;
+    /// use pos::pos::Column;
+    /// let reader = Reader::new("time,lat,lon\n".as_bytes()).unwrap();
+    /// assert_eq!(Column::Longitude, reader.columns().columns()[2]);
+    /// ```
+    pub fn columns(&self) -> &ColumnMap {
+        &self.columns
+    }
+
+    /// Sets the column map used to read rows, replacing the one from the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Reader;
+    /// use pos::pos::{Column, ColumnMap};
+    /// let mut reader = Reader::new("1,45,-105\n".as_bytes()).unwrap();
+    /// reader.set_columns(ColumnMap::new().column(Column::Time).column(Column::Latitude));
+    /// ```
+    pub fn set_columns(&mut self, columns: ColumnMap) {
+        self.columns = columns;
+    }
+
+    /// Reads the next point.
+    ///
+    /// Blank lines are skipped. Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.csv").unwrap();
+    /// let point = reader.read_point().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let line = match self.pending.take() {
+            Some(line) => line,
+            None => match self.read_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            },
+        };
+        let values = self.dialect.split(&line);
+        self.columns
+            .parse_values(values.iter().map(String::as_str))
+            .map(Some)
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                return Ok(Some(line.trim().to_string()));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over the points of a CSV reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// A CSV writer.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// The number of decimal places used for latitude, longitude, and angles.
+    pub precision: usize,
+
+    /// The UTM zone of the easting and northing columns.
+    ///
+    /// If none, each point is projected into the zone that contains it.
+    pub utm_zone: Option<Zone>,
+
+    /// The unit of angles and angular rates.
+    ///
+    /// Column names in the header row don't change, and readers always expect degrees.
+    pub angle_unit: AngleUnit,
+
+    columns: ColumnMap,
+    dialect: Dialect,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path and writes the header row.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer for comma-separated values, with the default pos columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// ```
+    pub fn new(writer: W) -> Result<Writer<W>, error::Error> {
+        Writer::with_columns(writer, ColumnMap::default(), Dialect::default())
+    }
+
+    /// Creates a new writer with the given columns and dialect.
+    ///
+    /// The header row is written if the dialect has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::{Dialect, Writer};
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::new().column(Column::Time).column(Column::XVelocity);
+    /// let writer = Writer::with_columns(Vec::new(), columns, Dialect::default()).unwrap();
+    /// ```
+    pub fn with_columns(
+        mut writer: W,
+        columns: ColumnMap,
+        dialect: Dialect,
+    ) -> Result<Writer<W>, error::Error> {
+        if dialect.header {
+            let names: Vec<_> = columns.columns().iter().map(|c| c.name()).collect();
+            writeln!(writer, "{}", names.join(&dialect.delimiter.to_string()))?;
+        }
+        Ok(Writer {
+            precision: DEFAULT_PRECISION,
+            utm_zone: None,
+            angle_unit: AngleUnit::default(),
+            columns,
+            dialect,
+            writer,
+        })
+    }
+
+    /// Writes a point as one row.
+    ///
+    /// Values the point doesn't have are left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::csv::Writer;
+    /// let mut writer = Writer::new(Vec::new()).unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        let values: Vec<_> = self
+            .columns
+            .columns()
+            .iter()
+            .map(|&column| match self.value(column, point) {
+                Some(value) => format!("{:.*}", self.precision(column), value),
+                None => String::new(),
+            })
+            .collect();
+        writeln!(
+            self.writer,
+            "{}",
+            values.join(&self.dialect.delimiter.to_string())
+        )?;
+        Ok(())
+    }
+
+    /// Consumes this writer and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::csv::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// let bytes = writer.into_inner();
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn value(&self, column: Column, point: &Point) -> Option<f64> {
+        let value = match (column, self.utm_zone) {
+            (Column::Easting, Some(zone)) => Some(point.to_utm_in_zone(zone).easting),
+            (Column::Northing, Some(zone)) => Some(point.to_utm_in_zone(zone).northing),
+            _ => column.value(point),
+        };
+        match self.angle_unit {
+            AngleUnit::Radians if column.is_angular() => value.map(f64::to_radians),
+            _ => value,
+        }
+    }
+
+    fn precision(&self, column: Column) -> usize {
+        match column {
+            Column::Time => 6,
+            Column::SatelliteCount => 0,
+            _ if column.is_angular() => self.precision,
+            _ => 3,
+        }
+    }
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        Writer::into_inner(*self).flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use crate::units::Radians;
+
+    #[test]
+    fn read_file() {
+        let mut reader = Reader::from_path("data/2-points.csv").unwrap();
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(Time(151631.0), point.time);
+        assert!((38.5 - point.latitude.to_degrees()).abs() < 1e-9);
+        assert_eq!(1721.0, point.altitude);
+        assert_eq!(Some(1.5), point.x_velocity);
+        assert_eq!(0.02, point.accuracy.unwrap().z);
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(None, point.x_velocity);
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[test]
+    fn no_header() {
+        let data = "1 45 -105 1600 0 0 0\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert_eq!(ColumnMap::default(), *reader.columns());
+        assert!(reader.read_point().is_err());
+
+        let dialect = Dialect {
+            delimiter: '\t',
+            header: true,
+        };
+        let mut reader = Reader::with_dialect(data.as_bytes(), dialect).unwrap();
+        assert_eq!(1600.0, reader.read_point().unwrap().unwrap().altitude);
+    }
+
+    #[test]
+    fn quoted() {
+        let dialect = Dialect {
+            delimiter: ',',
+            header: true,
+        };
+        assert_eq!(
+            vec!["a, b", "say \"hi\"", "", "c"],
+            dialect.split(r#""a, b", "say ""hi""",, c"#)
+        );
+        let dialect = Dialect {
+            delimiter: ' ',
+            header: true,
+        };
+        assert_eq!(vec!["a b", "c"], dialect.split(r#"  "a b"   c "#));
+
+        let data = "time,\"Latitude (deg, WGS84)\",\"Longitude (deg, WGS84)\",altitude\n\
+                    1,45,-105,1600\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        let point = reader.read_point().unwrap().unwrap();
+        assert!((-105.0 - point.longitude.to_degrees()).abs() < 1e-9);
+        assert_eq!(1600.0, point.altitude);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let dialect = Dialect {
+            delimiter: ';',
+            header: false,
+        };
+        let json = serde_json::to_string(&dialect).unwrap();
+        assert_eq!(dialect, serde_json::from_str(&json).unwrap());
+        let columns = ColumnMap::from_fields("time,lat,lon").unwrap();
+        let json = serde_json::to_string(&columns).unwrap();
+        assert_eq!(columns, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let columns = ColumnMap::new()
+            .column(Column::Time)
+            .column(Column::Latitude)
+            .column(Column::Longitude)
+            .column(Column::XVelocity);
+        let dialect = Dialect {
+            delimiter: ';',
+            header: true,
+        };
+        let mut writer = Writer::with_columns(Vec::new(), columns.clone(), dialect).unwrap();
+        let point = Point {
+            time: Time(1.5),
+            latitude: Radians::from_degrees(38.5),
+            longitude: Radians::from_degrees(-107.9),
+            ..Default::default()
+        };
+        writer.write_point(&point).unwrap();
+        let bytes = writer.into_inner();
+        assert_eq!(
+            "time;latitude;longitude;vx\n1.500000;38.500000000;-107.900000000;\n",
+            String::from_utf8(bytes.clone()).unwrap()
+        );
+        let mut reader = Reader::with_dialect(bytes.as_slice(), dialect).unwrap();
+        assert_eq!(columns, *reader.columns());
+        let read = reader.read_point().unwrap().unwrap();
+        assert_eq!(point.time, read.time);
+        assert!((point.longitude.0 - read.longitude.0).abs() < 1e-9);
+        assert_eq!(None, read.x_velocity);
+    }
+
+    #[test]
+    fn utm() {
+        let columns = ColumnMap::new()
+            .column(Column::Time)
+            .column(Column::Easting)
+            .column(Column::Northing)
+            .column(Column::Altitude);
+        let point = Point {
+            latitude: Radians::from_degrees(43.642567),
+            longitude: Radians::from_degrees(-79.387139),
+            altitude: 553.33,
+            ..Default::default()
+        };
+        let mut writer =
+            Writer::with_columns(Vec::new(), columns.clone(), Dialect::default()).unwrap();
+        writer.write_point(&point).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!("time,easting,northing,altitude", lines[0]);
+        let values: Vec<f64> = lines[1].split(',').map(|v| v.parse().unwrap()).collect();
+        assert!((values[1] - 630084.).abs() < 1.);
+        assert!((values[2] - 4833439.).abs() < 1.);
+
+        let mut writer = Writer::with_columns(Vec::new(), columns, Dialect::default()).unwrap();
+        writer.utm_zone = Some(Zone {
+            number: 18,
+            north: true,
+        });
+        writer.write_point(&point).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let easting: f64 = output
+            .lines()
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .nth(1)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(easting < 500000.);
+
+        let mut reader = Reader::new(output.as_bytes()).unwrap();
+        assert!(reader.read_point().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! The crate's error type.
+//!
+//! Each module reports its own problems with its own error enum, e.g. `pos::Error` or
+//! `sbet::Error`, and this enum gathers those together with the errors of the libraries we use.
+//! It implements `std::error::Error`, so it works with `?` in functions that return
+//! `Box<dyn std::error::Error>` or `anyhow::Result`, and `source` walks down to the underlying
+//! error.
+
+use crate::events;
+use crate::format;
+use crate::geoid;
+use crate::igi;
+use crate::interpolate;
+use crate::kml;
+#[cfg(feature = "las")]
+use crate::las;
+use crate::nmea;
+use crate::novatel;
+use crate::pof;
+use crate::point;
+use crate::pos;
+use crate::pospac;
+use crate::qc;
+use crate::rtklib;
+use crate::sbet;
+use crate::time;
+use crate::trj;
+use std::io;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::Utf8Error;
+
+/// Any error from this crate.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An arrow error.
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Arrow(#[from] ::arrow_schema::ArrowError),
+    /// An event file error.
+    #[error(transparent)]
+    Events(#[from] events::Error),
+    /// A format error.
+    #[error(transparent)]
+    Format(#[from] format::Error),
+    /// A geoid error.
+    #[error(transparent)]
+    Geoid(#[from] geoid::Error),
+    /// An hdf5 error.
+    #[cfg(feature = "hdf5")]
+    #[error(transparent)]
+    Hdf5(#[from] ::hdf5::Error),
+    /// A string that can't be stored in an hdf5 file.
+    #[cfg(feature = "hdf5")]
+    #[error(transparent)]
+    Hdf5String(#[from] ::hdf5::types::StringError),
+    /// An igi error.
+    #[error(transparent)]
+    Igi(#[from] igi::Error),
+    /// An interpolation error.
+    #[error(transparent)]
+    Interpolate(#[from] interpolate::Error),
+    /// An input or output error.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A kml error.
+    #[error(transparent)]
+    Kml(#[from] kml::Error),
+    /// A LAS error.
+    #[cfg(feature = "las")]
+    #[error(transparent)]
+    Las(#[from] las::Error),
+    /// An nmea error.
+    #[error(transparent)]
+    Nmea(#[from] nmea::Error),
+    /// A NovAtel error.
+    #[error(transparent)]
+    Novatel(#[from] novatel::Error),
+    /// A parquet error.
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Parquet(#[from] ::parquet::errors::ParquetError),
+    /// A number that isn't a float.
+    #[error(transparent)]
+    ParseFloat(#[from] ParseFloatError),
+    /// A number that isn't an integer.
+    #[error(transparent)]
+    ParseInt(#[from] ParseIntError),
+    /// A point error.
+    #[error(transparent)]
+    Point(#[from] point::Error),
+    /// A pof error.
+    #[error(transparent)]
+    Pof(#[from] pof::Error),
+    /// A pos error.
+    #[error(transparent)]
+    Pos(#[from] pos::Error),
+    /// A POSPac error.
+    #[error(transparent)]
+    Pospac(#[from] pospac::Error),
+    /// A proj error.
+    #[cfg(feature = "proj")]
+    #[error(transparent)]
+    Proj(#[from] ::proj::ProjError),
+    /// A proj error from creating a transformation.
+    #[cfg(feature = "proj")]
+    #[error(transparent)]
+    ProjCreate(#[from] ::proj::ProjCreateError),
+    /// A quality control error.
+    #[error(transparent)]
+    Qc(#[from] qc::Error),
+    /// An RTKLIB error.
+    #[error(transparent)]
+    Rtklib(#[from] rtklib::Error),
+    /// An sbet error.
+    #[error(transparent)]
+    Sbet(#[from] sbet::Error),
+    /// An sqlite error.
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] ::rusqlite::Error),
+    /// A time error.
+    #[error(transparent)]
+    Time(#[from] time::Error),
+    /// A trj error.
+    #[error(transparent)]
+    Trj(#[from] trj::Error),
+    /// Text that isn't UTF-8.
+    #[error(transparent)]
+    Utf8(#[from] Utf8Error),
+    /// A zip error.
+    #[cfg(feature = "kmz")]
+    #[error(transparent)]
+    Zip(#[from] ::zip::result::ZipError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::error::Error as StdError;
+
+    #[test]
+    fn source() {
+        let cause = io::Error::other("unplugged");
+        let err: Error = sbet::Error::Read(3, 408, cause).into();
+        assert_eq!("Could not read sbet record 3 at byte 408", err.to_string());
+        assert_eq!("unplugged", err.source().unwrap().to_string());
+    }
+}
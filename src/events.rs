@@ -0,0 +1,227 @@
+//! Event files, e.g. camera trigger times from POSPac or Inertial Explorer.
+//!
+//! Event exports are text files with one event per line, after optional comment lines starting
+//! with `;` or `#` and an optional line of column names. Values are separated by commas or, if
+//! there are none, by whitespace. The time is the first column, in GPS seconds of the week,
+//! unless a column name contains `time`, and the event number comes from a column whose name
+//! contains `event`, `id`, or `photo`. Without one, events are numbered from one in the order
+//! they're read.
+//!
+//! Use `Trajectory::at_events` to interpolate the exterior orientation at each event time.
+
+use crate::error;
+use crate::time::Time;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Event file errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// A line doesn't have the time or event number column.
+    #[error("Missing column {0} in line: {1}")]
+    MissingColumn(usize, String),
+}
+
+/// An event, e.g. a camera exposure.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    /// The event number.
+    pub number: u32,
+    /// The time of the event.
+    pub time: Time,
+}
+
+/// An event file reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    count: u32,
+    number_column: Option<usize>,
+    pending: Option<String>,
+    reader: R,
+    time_column: usize,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path and reads the header lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::events::Reader;
+    /// let reader = Reader::from_path("data/2-points.events").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead` and reads the header lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::events::Reader;
+    /// use pos::time::Time;
+    /// let data = "Event GPS_Time\n7 151631.005\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// let event = reader.read_event().unwrap().unwrap();
+    /// assert_eq!(7, event.number);
+    /// assert_eq!(Time(151631.005), event.time);
+    /// ```
+    pub fn new(reader: R) -> Result<Reader<R>, error::Error> {
+        let mut reader = Reader {
+            count: 0,
+            number_column: None,
+            pending: None,
+            reader,
+            time_column: 0,
+        };
+        while let Some(line) = reader.read_line()? {
+            let values = split(&line);
+            if values
+                .first()
+                .is_some_and(|value| value.parse::<f64>().is_ok())
+            {
+                reader.pending = Some(line);
+                break;
+            }
+            let names: Vec<_> = values.iter().map(|name| name.to_lowercase()).collect();
+            if let Some(column) = names.iter().position(|name| name.contains("time")) {
+                reader.time_column = column;
+                reader.number_column = names.iter().position(|name| {
+                    ["event", "id", "photo"]
+                        .iter()
+                        .any(|word| name.contains(word))
+                });
+                break;
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Reads the next event.
+    ///
+    /// Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::events::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.events").unwrap();
+    /// let event = reader.read_event().unwrap().unwrap();
+    /// ```
+    pub fn read_event(&mut self) -> Result<Option<Event>, error::Error> {
+        let line = match self.pending.take() {
+            Some(line) => line,
+            None => match self.read_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            },
+        };
+        let values = split(&line);
+        let value = |column: usize| {
+            values
+                .get(column)
+                .ok_or_else(|| Error::MissingColumn(column, line.clone()))
+        };
+        let time = Time(value(self.time_column)?.parse()?);
+        self.count += 1;
+        let number = match self.number_column {
+            Some(column) => value(column)?.parse()?,
+            None => self.count,
+        };
+        Ok(Some(Event { number, time }))
+    }
+
+    /// Reads the next line that isn't blank or a comment.
+    fn read_line(&mut self) -> Result<Option<String>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with(';') && !trimmed.starts_with('#') {
+                return Ok(Some(trimmed.to_string()));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Event, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over the events of a reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Event, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_event().transpose()
+    }
+}
+
+/// Splits a line on commas if it has any, so that column names can have spaces, or otherwise on
+/// whitespace.
+fn split(line: &str) -> Vec<&str> {
+    if line.contains(',') {
+        line.split(',').map(str::trim).collect()
+    } else {
+        line.split_whitespace().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_file() {
+        let events = Reader::from_path("data/2-points.events")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!(
+            Event {
+                number: 12,
+                time: Time(151631.005)
+            },
+            events[0]
+        );
+        assert_eq!(13, events[1].number);
+    }
+
+    #[test]
+    fn no_header() {
+        let data = "; camera 1\n151631.5, 3\n151632.5\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert_eq!(1, reader.read_event().unwrap().unwrap().number);
+        let event = reader.read_event().unwrap().unwrap();
+        assert_eq!(2, event.number);
+        assert_eq!(Time(151632.5), event.time);
+        assert!(reader.read_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_column() {
+        let data = "Time,Event\n151631.5\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert!(reader.read_event().is_err());
+        let data = "Photo ID, GPS Time\n1, x\n";
+        assert!(Reader::new(data.as_bytes()).unwrap().read_event().is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! Smoothing filters, for noisy trajectories such as single-receiver GNSS from NMEA sources.
+//!
+//! A `Smoother` replaces each point's position, velocity, and attitude with a weighted average of
+//! the points in a window centered on it, either a plain moving average or a Savitzky–Golay
+//! filter, which fits a polynomial to the window and so keeps peaks and turns sharper. Times are
+//! never changed, and the points are assumed to be evenly spaced in time.
+//!
+//! Angles are averaged as differences from the center point's angle, so a heading that wraps from
+//! 359° to 1° smooths to about 0° rather than 180°, and then wrapped back into range: [-180°, 180°)
+//! for longitude and roll, and the window's own convention for yaw. Near the ends of the
+//! trajectory the window shrinks so that it stays centered, and the first and last points are
+//! kept as they are.
+
+use crate::point::Point;
+use crate::units::Radians;
+
+/// The default number of points in a smoothing window.
+pub const DEFAULT_WINDOW: usize = 5;
+
+/// How the points in a window are weighted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Kernel {
+    /// Every point in the window has the same weight.
+    #[default]
+    MovingAverage,
+    /// The weights of a least-squares polynomial fit of the given order.
+    ///
+    /// Orders zero and one are the same as a moving average. The order is reduced where the
+    /// window is too small for it, e.g. near the ends.
+    SavitzkyGolay {
+        /// The order of the fitted polynomial.
+        order: usize,
+    },
+}
+
+/// Smooths points with a window centered on each one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Smoother {
+    /// How the points in a window are weighted.
+    pub kernel: Kernel,
+
+    /// The number of points in the window.
+    ///
+    /// The window is centered on each point, so an even window is rounded up to the next odd
+    /// number.
+    pub window: usize,
+
+    /// Whether to smooth latitude, longitude, and altitude.
+    pub position: bool,
+
+    /// Whether to smooth the velocities.
+    ///
+    /// Windows where any point is missing a velocity are left alone.
+    pub velocity: bool,
+
+    /// Whether to smooth roll, pitch, and yaw.
+    pub attitude: bool,
+}
+
+impl Default for Smoother {
+    fn default() -> Smoother {
+        Smoother::new(Kernel::default(), DEFAULT_WINDOW)
+    }
+}
+
+impl Smoother {
+    /// Creates a smoother that smooths positions, velocities, and attitudes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::filter::{Kernel, Smoother};
+    /// let smoother = Smoother::new(Kernel::SavitzkyGolay { order: 2 }, 11);
+    /// assert!(smoother.attitude);
+    /// ```
+    pub fn new(kernel: Kernel, window: usize) -> Smoother {
+        Smoother {
+            kernel,
+            window,
+            position: true,
+            velocity: true,
+            attitude: true,
+        }
+    }
+
+    /// Smooths some points, returning a point for each one with the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::filter::Smoother;
+    /// use pos::time::Time;
+    /// let points: Vec<_> = (0..10)
+    ///     .map(|i| Point {
+    ///         time: Time(i as f64),
+    ///         altitude: if i == 5 { 10.0 } else { 0.0 },
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let smoothed = Smoother::default().smooth(&points);
+    /// assert_eq!(2.0, smoothed[5].altitude);
+    /// assert_eq!(Time(5.0), smoothed[5].time);
+    /// ```
+    pub fn smooth(&self, points: &[Point]) -> Vec<Point> {
+        let half = self.window / 2;
+        let weights: Vec<_> = (0..=half).map(|half| self.weights(half)).collect();
+        (0..points.len())
+            .map(|i| {
+                let half = half.min(i).min(points.len() - 1 - i);
+                self.smooth_point(&points[i - half..=i + half], &weights[half])
+            })
+            .collect()
+    }
+
+    fn smooth_point(&self, window: &[Point], weights: &[f64]) -> Point {
+        let center = window[window.len() / 2];
+        let mean = |value: &dyn Fn(&Point) -> f64| -> f64 {
+            window.iter().zip(weights).map(|(p, w)| w * value(p)).sum()
+        };
+        let optional = |value: &dyn Fn(&Point) -> Option<f64>| -> Option<f64> {
+            window
+                .iter()
+                .zip(weights)
+                .map(|(p, w)| value(p).map(|value| w * value))
+                .sum()
+        };
+        let angle = |value: &dyn Fn(&Point) -> Radians<f64>| -> Radians<f64> {
+            let origin = value(&center);
+            (origin + Radians(mean(&|p| value(p).angular_difference(origin).0))).wrap_pi()
+        };
+        let mut point = center;
+        if self.position {
+            point.latitude = Radians(mean(&|p| p.latitude.0));
+            point.longitude = angle(&|p| p.longitude);
+            point.altitude = mean(&|p| p.altitude);
+        }
+        if self.velocity {
+            point.x_velocity = optional(&|p| p.x_velocity).or(center.x_velocity);
+            point.y_velocity = optional(&|p| p.y_velocity).or(center.y_velocity);
+            point.z_velocity = optional(&|p| p.z_velocity).or(center.z_velocity);
+        }
+        if self.attitude {
+            point.roll = angle(&|p| p.roll);
+            point.pitch = angle(&|p| p.pitch);
+            point.yaw = angle(&|p| p.yaw).wrap_like(window.iter().map(|p| p.yaw));
+        }
+        point
+    }
+
+    /// Returns the weights of a window that reaches `half` points either side of its center.
+    fn weights(&self, half: usize) -> Vec<f64> {
+        let len = 2 * half + 1;
+        match self.kernel {
+            Kernel::MovingAverage => vec![1. / len as f64; len],
+            Kernel::SavitzkyGolay { order } => savitzky_golay(half, order.min(2 * half)),
+        }
+    }
+}
+
+/// Returns the Savitzky–Golay smoothing weights for a window and polynomial order.
+///
+/// The smoothed value is the fitted polynomial at the center of the window, which is the first
+/// row of the least-squares solution `(AᵀA)⁻¹Aᵀ`, where `A` holds the powers of each point's
+/// offset from the center.
+fn savitzky_golay(half: usize, order: usize) -> Vec<f64> {
+    let offsets: Vec<f64> = (0..2 * half + 1).map(|i| i as f64 - half as f64).collect();
+    let size = order + 1;
+    let mut normal = vec![vec![0.; size + 1]; size];
+    for (row, values) in normal.iter_mut().enumerate() {
+        for (column, value) in values.iter_mut().take(size).enumerate() {
+            *value = offsets.iter().map(|x| x.powi((row + column) as i32)).sum();
+        }
+    }
+    normal[0][size] = 1.;
+    let coefficients = solve(normal);
+    offsets
+        .iter()
+        .map(|x| {
+            coefficients
+                .iter()
+                .enumerate()
+                .map(|(power, c)| c * x.powi(power as i32))
+                .sum()
+        })
+        .collect()
+}
+
+/// Solves a linear system, given as an augmented matrix, by Gaussian elimination with partial
+/// pivoting.
+fn solve(mut matrix: Vec<Vec<f64>>) -> Vec<f64> {
+    let size = matrix.len();
+    for column in 0..size {
+        let pivot = (column..size)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+            .unwrap_or(column);
+        matrix.swap(column, pivot);
+        let (upper, lower) = matrix.split_at_mut(column + 1);
+        let pivot = &upper[column];
+        for row in lower {
+            let factor = row[column] / pivot[column];
+            for (value, pivot) in row[column..].iter_mut().zip(&pivot[column..]) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+    let mut solution = vec![0.; size];
+    for row in (0..size).rev() {
+        let sum: f64 = (row + 1..size).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (matrix[row][size] - sum) / matrix[row][row];
+    }
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+
+    fn points<F: Fn(f64) -> Point>(f: F) -> Vec<Point> {
+        (0..20)
+            .map(|i| Point {
+                time: Time(i as f64),
+                ..f(i as f64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn savitzky_golay_weights() {
+        let weights = savitzky_golay(2, 2);
+        let expected = [-3., 12., 17., 12., -3.];
+        for (weight, expected) in weights.iter().zip(&expected) {
+            assert!((weight - expected / 35.).abs() < 1e-12);
+        }
+        for weight in savitzky_golay(2, 1) {
+            assert!((weight - 0.2).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn preserves_quadratics() {
+        let points = points(|t| Point {
+            altitude: t * t,
+            ..Default::default()
+        });
+        let smoother = Smoother::new(Kernel::SavitzkyGolay { order: 2 }, 7);
+        for (point, smoothed) in points.iter().zip(smoother.smooth(&points)) {
+            assert_eq!(point.time, smoothed.time);
+            assert!((point.altitude - smoothed.altitude).abs() < 1e-9);
+        }
+        let smoothed = Smoother::new(Kernel::MovingAverage, 7).smooth(&points);
+        assert!((smoothed[10].altitude - 104.0).abs() < 1e-9);
+        assert_eq!(points[0], smoothed[0]);
+    }
+
+    #[test]
+    fn angle_wrap() {
+        let points = points(|t| Point {
+            yaw: Radians::from_degrees(if (t as usize) % 2 == 0 { 359.0 } else { 1.0 }),
+            longitude: Radians::from_degrees(if t < 10. { 179.9 } else { -179.9 }),
+            x_velocity: if t == 15. { None } else { Some(t) },
+            ..Default::default()
+        });
+        let smoothed = Smoother::default().smooth(&points);
+        let yaw = smoothed[10].yaw.to_degrees();
+        assert!(!(2.0..=358.0).contains(&yaw), "{}", yaw);
+        assert!(smoothed[10].longitude.to_degrees().abs() > 179.8);
+        for point in &smoothed {
+            assert!((0.0..360.0).contains(&point.yaw.to_degrees()));
+            assert!((-180.0..180.0).contains(&point.longitude.to_degrees()));
+        }
+        assert_eq!(Some(10.0), smoothed[10].x_velocity);
+        assert_eq!(Some(14.0), smoothed[14].x_velocity);
+        assert_eq!(None, smoothed[15].x_velocity);
+    }
+
+    #[test]
+    fn only_position() {
+        let points = points(|t| Point {
+            altitude: t % 2.,
+            roll: Radians(t % 2.),
+            ..Default::default()
+        });
+        let smoother = Smoother {
+            attitude: false,
+            ..Default::default()
+        };
+        let smoothed = smoother.smooth(&points);
+        assert!((smoothed[10].altitude - 0.4).abs() < 1e-9);
+        assert_eq!(points[10].roll, smoothed[10].roll);
+    }
+}
@@ -0,0 +1,380 @@
+//! Detect the format of position files.
+
+use crate::error;
+use crate::sbet::RECORD_SIZE as SBET_RECORD_SIZE;
+use crate::trj::SIGNATURE as TRJ_SIGNATURE;
+#[cfg(feature = "gzip")]
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// The number of bytes inspected when sniffing a file's contents.
+const SNIFF_LENGTH: u64 = 1024;
+
+/// The starts of the NovAtel logs that can be read, with long and short headers.
+const NOVATEL_LOGS: &[&str] = &["#INSPVAA", "%INSPVASA", "#BESTPOSA"];
+
+/// The first two bytes of every gzip file.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Format errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// The format of the file could not be determined.
+    #[error("Could not determine the format of {0}")]
+    UnknownFormat(String),
+
+    /// The file is gzipped, but the `gzip` feature is not enabled.
+    #[error("Reading gzipped files requires the gzip feature: {0}")]
+    GzipFeature(String),
+
+    /// The file is gzipped, but its format can't be read from a compressed stream.
+    #[error("Cannot read {0:?} files from a gzipped file: {1}")]
+    GzipFormat(Format, String),
+
+    /// The format can be written, but not read.
+    #[error("Cannot read {0:?} files: {1}")]
+    Unreadable(Format, String),
+
+    /// The format can be read, but not written.
+    #[error("Cannot write {0:?} files: {1}")]
+    Unwritable(Format, String),
+}
+
+/// A position file format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Delimited text, e.g. CSV.
+    Csv,
+    /// GeoJSON, which can only be written.
+    GeoJson,
+    /// GPX, which can only be written.
+    Gpx,
+    /// IGI AEROoffice ASCII exports, which can only be read.
+    Igi,
+    /// KML, which can only be written.
+    Kml,
+    /// NMEA 0183 sentences, which can only be read.
+    Nmea,
+    /// NovAtel SPAN ASCII logs, which can only be read.
+    Novatel,
+    /// Riegl's binary pof format, which can only be read.
+    Pof,
+    /// ASCII pos format.
+    Pos,
+    /// POSPac ASCII exports, which can only be read.
+    Pospac,
+    /// RTKLIB solutions, which can only be read.
+    Rtklib,
+    /// Binary sbet format.
+    Sbet,
+    /// TerraScan binary trajectories.
+    Trj,
+}
+
+impl Format {
+    /// Determines the format of the file at the given path.
+    ///
+    /// The extension is checked first, ignoring any trailing `.gz`. If the extension is missing or
+    /// unknown, the start of the file is inspected: text files are assumed to be pos files, and
+    /// binary files whose length is a multiple of the sbet record size are assumed to be sbet
+    /// files. Gzipped files are inspected after decompression, which requires the `gzip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Format;
+    /// assert_eq!(Format::Sbet, Format::from_path("data/2-points.sbet").unwrap());
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Format, error::Error> {
+        let path = path.as_ref();
+        match Format::from_extension(path) {
+            // RTKLIB solutions also use the pos extension.
+            Some(Format::Pos) if !is_gzip(path)? => {
+                let mut bytes = Vec::new();
+                let _ = File::open(path)?
+                    .take(SNIFF_LENGTH)
+                    .read_to_end(&mut bytes)?;
+                if Format::from_magic(&bytes) == Some(Format::Rtklib) {
+                    return Ok(Format::Rtklib);
+                }
+                return Ok(Format::Pos);
+            }
+            Some(format) => return Ok(format),
+            None => {}
+        }
+        let file = File::open(path)?;
+        let mut bytes = Vec::new();
+        let format = if is_gzip(path)? {
+            let _ = decompress(file, path)?
+                .take(SNIFF_LENGTH)
+                .read_to_end(&mut bytes)?;
+            sniff(&bytes, None)
+        } else {
+            let len = file.metadata()?.len();
+            let _ = file.take(SNIFF_LENGTH).read_to_end(&mut bytes)?;
+            sniff(&bytes, Some(len))
+        };
+        format.ok_or_else(|| Error::UnknownFormat(path.display().to_string()).into())
+    }
+
+    /// Returns the format for a path's extension, ignoring any trailing `.gz`.
+    ///
+    /// Returns none if the extension is missing or unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Format;
+    /// assert_eq!(Some(Format::Sbet), Format::from_extension("sbet_mission_1.out"));
+    /// assert_eq!(Some(Format::Csv), Format::from_extension("trajectory.csv.gz"));
+    /// assert_eq!(None, Format::from_extension("trajectory"));
+    /// ```
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let path = path.as_ref();
+        let mut extension = path.extension()?.to_str()?.to_lowercase();
+        if extension == "gz" {
+            extension = Path::new(path.file_stem()?)
+                .extension()?
+                .to_str()?
+                .to_lowercase();
+        }
+        match extension.as_str() {
+            "csv" | "tsv" => Some(Format::Csv),
+            "geojson" | "json" => Some(Format::GeoJson),
+            "gpx" => Some(Format::Gpx),
+            "igi" => Some(Format::Igi),
+            "kml" => Some(Format::Kml),
+            "nmea" | "nma" => Some(Format::Nmea),
+            "novatel" | "asc" => Some(Format::Novatel),
+            "pof" => Some(Format::Pof),
+            "pos" => Some(Format::Pos),
+            "pospac" => Some(Format::Pospac),
+            "rtklib" => Some(Format::Rtklib),
+            "sbet" | "out" => Some(Format::Sbet),
+            "trj" => Some(Format::Trj),
+            _ => None,
+        }
+    }
+
+    /// Returns the format whose signature starts the given bytes.
+    ///
+    /// Only formats with a recognizable start are detected: trj files, NMEA sentences, NovAtel
+    /// logs, RTKLIB solutions with a header, GPX, KML, and GeoJSON. Leading whitespace is
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Format;
+    /// assert_eq!(Some(Format::Nmea), Format::from_magic(b"$GPGGA,012345.00,"));
+    /// assert_eq!(None, Format::from_magic(b"time lat lon"));
+    /// ```
+    pub fn from_magic(bytes: &[u8]) -> Option<Format> {
+        if bytes.starts_with(TRJ_SIGNATURE) {
+            return Some(Format::Trj);
+        }
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+        let bytes = &bytes[start..];
+        let text = String::from_utf8_lossy(&bytes[..bytes.len().min(SNIFF_LENGTH as usize)]);
+        if NOVATEL_LOGS.iter().any(|log| text.starts_with(log)) {
+            Some(Format::Novatel)
+        } else if text.starts_with('$') {
+            Some(Format::Nmea)
+        } else if text.starts_with('%') {
+            Some(Format::Rtklib)
+        } else if text.starts_with('<') && text.contains("<gpx") {
+            Some(Format::Gpx)
+        } else if text.starts_with('<') && text.contains("<kml") {
+            Some(Format::Kml)
+        } else if text.starts_with('{') {
+            Some(Format::GeoJson)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns true if the file at the given path is gzipped.
+///
+/// # Examples
+///
+/// ```
+/// use pos::format;
+/// assert!(!format::is_gzip("data/2-points.sbet").unwrap());
+/// assert!(format::is_gzip("data/2-points.sbet.gz").unwrap());
+/// ```
+pub fn is_gzip<P: AsRef<Path>>(path: P) -> Result<bool, error::Error> {
+    let mut magic = Vec::with_capacity(GZIP_MAGIC.len());
+    let _ = File::open(path)?
+        .take(GZIP_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    Ok(magic == GZIP_MAGIC)
+}
+
+/// A file that is read as is or, if it is gzipped, decompressed as it is read.
+///
+/// Gzipped files can't be seeked, so seeking one is an error.
+#[derive(Debug)]
+pub enum Input {
+    /// A file that isn't compressed.
+    File(File),
+
+    /// A gzipped file.
+    #[cfg(feature = "gzip")]
+    Gzip(MultiGzDecoder<File>),
+}
+
+impl Input {
+    /// Opens a file, decompressing it if it is gzipped.
+    ///
+    /// Gzipped files are errors if the `gzip` feature isn't enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use pos::format::Input;
+    /// let mut bytes = Vec::new();
+    /// let _ = Input::open("data/2-points.sbet").unwrap().read_to_end(&mut bytes).unwrap();
+    /// assert_eq!(272, bytes.len());
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Input, error::Error> {
+        let path = path.as_ref();
+        if is_gzip(path)? {
+            open_gzip(File::open(path)?, path)
+        } else {
+            Ok(Input::File(File::open(path)?))
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::File(file) => file.read(buf),
+            #[cfg(feature = "gzip")]
+            Input::Gzip(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+impl Seek for Input {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match self {
+            Input::File(file) => file.seek(position),
+            #[cfg(feature = "gzip")]
+            Input::Gzip(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cannot seek in a gzipped file",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn open_gzip(file: File, _: &Path) -> Result<Input, error::Error> {
+    Ok(Input::Gzip(MultiGzDecoder::new(file)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn open_gzip(_: File, path: &Path) -> Result<Input, error::Error> {
+    Err(Error::GzipFeature(path.display().to_string()).into())
+}
+
+#[cfg(feature = "gzip")]
+fn decompress(file: File, _: &Path) -> Result<MultiGzDecoder<File>, error::Error> {
+    Ok(MultiGzDecoder::new(file))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress(_: File, path: &Path) -> Result<File, error::Error> {
+    Err(Error::GzipFeature(path.display().to_string()).into())
+}
+
+/// The length is none if it isn't known, e.g. for a compressed file.
+fn sniff(bytes: &[u8], len: Option<u64>) -> Option<Format> {
+    if bytes.is_empty() {
+        None
+    } else if let Some(format) = Format::from_magic(bytes) {
+        Some(format)
+    } else if bytes
+        .iter()
+        .all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+    {
+        Some(Format::Pos)
+    } else if len.map_or(true, |len| len % SBET_RECORD_SIZE == 0) {
+        Some(Format::Sbet)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension() {
+        assert_eq!(Some(Format::Pof), Format::from_extension("a.pof"));
+        assert_eq!(Some(Format::Pos), Format::from_extension("a.POS"));
+        assert_eq!(Some(Format::Sbet), Format::from_extension("sbet_01.out"));
+        assert_eq!(None, Format::from_extension("a.txt"));
+        assert_eq!(None, Format::from_extension("a"));
+        assert_eq!(Some(Format::Sbet), Format::from_extension("a.sbet.gz"));
+        assert_eq!(None, Format::from_extension("a.gz"));
+        assert_eq!(Some(Format::Trj), Format::from_extension("line_1.trj"));
+        assert_eq!(Some(Format::GeoJson), Format::from_extension("a.geojson"));
+    }
+
+    #[test]
+    fn content() {
+        assert_eq!(Some(Format::Pos), sniff(b"time lat lon\n1 2 3\n", Some(19)));
+        assert_eq!(Some(Format::Sbet), sniff(&[0, 1, 2, 255], Some(272)));
+        assert_eq!(None, sniff(&[0, 1, 2, 255], Some(271)));
+        assert_eq!(Some(Format::Sbet), sniff(&[0, 1, 2, 255], None));
+        assert_eq!(None, sniff(&[], Some(0)));
+        assert_eq!(Some(Format::Trj), sniff(b"TSCANTRJ\x00\x01", Some(10)));
+        assert_eq!(
+            Some(Format::Rtklib),
+            sniff(b"% program : RTKLIB\n", Some(19))
+        );
+    }
+
+    #[test]
+    fn magic() {
+        assert_eq!(
+            Some(Format::Novatel),
+            Format::from_magic(b"#INSPVAA,COM1,0")
+        );
+        assert_eq!(Some(Format::Novatel), Format::from_magic(b"%INSPVASA,2053"));
+        assert_eq!(Some(Format::Nmea), Format::from_magic(b"\n$GPRMC,"));
+        assert_eq!(
+            Some(Format::Gpx),
+            Format::from_magic(b"<?xml version=\"1.0\"?>\n<gpx version=\"1.1\">")
+        );
+        assert_eq!(Some(Format::Kml), Format::from_magic(b"<kml xmlns="));
+        assert_eq!(Some(Format::GeoJson), Format::from_magic(b"{\"type\":"));
+        assert_eq!(None, Format::from_magic(b""));
+        assert_eq!(None, Format::from_magic(&[0, 1, 2, 255]));
+    }
+
+    #[test]
+    fn rtklib() {
+        assert_eq!(
+            Format::Rtklib,
+            Format::from_path("data/2-points.rtklib").unwrap()
+        );
+    }
+
+    #[test]
+    fn gzip() {
+        assert!(is_gzip("data/2-points.sbet.gz").unwrap());
+        assert!(!is_gzip("data/2-points.sbet").unwrap());
+        assert_eq!(
+            Format::Sbet,
+            Format::from_path("data/2-points.sbet.gz").unwrap()
+        );
+    }
+}
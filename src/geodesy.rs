@@ -0,0 +1,699 @@
+//! Geodetic conversions on the WGS84 ellipsoid, and distances and azimuths between points.
+
+use crate::point::Point;
+use crate::units::Radians;
+
+/// The WGS84 semi-major axis, in meters.
+pub const SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+
+/// The WGS84 flattening.
+pub const FLATTENING: f64 = 1. / 298.257_223_563;
+
+/// The WGS84 first eccentricity squared.
+pub const ECCENTRICITY_SQUARED: f64 = FLATTENING * (2. - FLATTENING);
+
+/// The mean radius of the WGS84 ellipsoid, in meters, used for haversine distances.
+pub const MEAN_RADIUS: f64 = 6_371_008.771_4;
+
+/// How the distance between two points on the ellipsoid is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Method {
+    /// The great circle distance on a sphere with the mean radius, which is fast but can be off by
+    /// up to about half a percent.
+    Haversine,
+    /// Vincenty's formula for the geodesic on the ellipsoid, which is accurate to well under a
+    /// millimeter.
+    #[default]
+    Vincenty,
+}
+
+impl Method {
+    /// Returns the distance between two points along the surface of the ellipsoid, in meters.
+    ///
+    /// Altitudes are ignored. Vincenty's formula can fail to converge for nearly antipodal
+    /// points, in which case the haversine distance is returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::geodesy::Method;
+    /// let equator = Point::default();
+    /// let pole = Point { latitude: Radians::from_degrees(90.0), ..Default::default() };
+    /// let distance = Method::Vincenty.distance(&equator, &pole);
+    /// assert!((distance - 10_001_965.729).abs() < 1e-3);
+    /// ```
+    pub fn distance(&self, from: &Point, to: &Point) -> f64 {
+        match *self {
+            Method::Haversine => haversine(from, to),
+            Method::Vincenty => vincenty(from, to).unwrap_or_else(|| haversine(from, to)),
+        }
+    }
+
+    /// Returns the azimuth from one point towards another, clockwise from north in [0, 2π).
+    ///
+    /// As with `distance`, Vincenty falls back to the sphere for nearly antipodal points, and the
+    /// azimuth between two points at the same position is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::geodesy::Method;
+    /// let to = Point { longitude: Radians::from_degrees(1.0), ..Default::default() };
+    /// let azimuth = Method::Haversine.azimuth(&Point::default(), &to);
+    /// assert!((azimuth.to_degrees() - 90.0).abs() < 1e-9);
+    /// ```
+    pub fn azimuth(&self, from: &Point, to: &Point) -> Radians<f64> {
+        match *self {
+            Method::Haversine => bearing(from, to),
+            Method::Vincenty => inverse(from, to)
+                .map(|geodesic| geodesic.initial_azimuth)
+                .unwrap_or_else(|| bearing(from, to)),
+        }
+    }
+}
+
+/// The shortest path between two points on the ellipsoid.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Geodesic {
+    /// The length of the path, in meters.
+    pub distance: f64,
+    /// The azimuth at the start of the path, clockwise from north in [0, 2π).
+    pub initial_azimuth: Radians<f64>,
+    /// The azimuth at the end of the path, clockwise from north in [0, 2π).
+    pub final_azimuth: Radians<f64>,
+}
+
+/// Earth-centered, earth-fixed cartesian coordinates, in meters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ecef {
+    /// The distance along the axis through the equator and the prime meridian.
+    pub x: f64,
+    /// The distance along the axis through the equator and 90° east.
+    pub y: f64,
+    /// The distance along the axis through the north pole.
+    pub z: f64,
+}
+
+impl Ecef {
+    /// Converts geodetic coordinates to ECEF.
+    ///
+    /// The height is above the ellipsoid, in meters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geodesy::Ecef;
+    /// use pos::units::Radians;
+    /// let ecef = Ecef::from_geodetic(Radians(0.0), Radians(0.0), 0.0);
+    /// assert_eq!(6378137.0, ecef.x);
+    /// ```
+    pub fn from_geodetic(latitude: Radians<f64>, longitude: Radians<f64>, height: f64) -> Ecef {
+        let (sin_latitude, cos_latitude) = latitude.0.sin_cos();
+        let (sin_longitude, cos_longitude) = longitude.0.sin_cos();
+        let prime_vertical = prime_vertical_radius(latitude);
+        Ecef {
+            x: (prime_vertical + height) * cos_latitude * cos_longitude,
+            y: (prime_vertical + height) * cos_latitude * sin_longitude,
+            z: (prime_vertical * (1. - ECCENTRICITY_SQUARED) + height) * sin_latitude,
+        }
+    }
+
+    /// Converts these coordinates to geodetic latitude, longitude, and height above the
+    /// ellipsoid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geodesy::Ecef;
+    /// let (latitude, longitude, height) = Ecef { x: 6378237.0, y: 0.0, z: 0.0 }.to_geodetic();
+    /// assert_eq!(0.0, latitude.0);
+    /// assert!((height - 100.0).abs() < 1e-6);
+    /// ```
+    pub fn to_geodetic(&self) -> (Radians<f64>, Radians<f64>, f64) {
+        let p = self.x.hypot(self.y);
+        let longitude = self.y.atan2(self.x);
+        let mut latitude = self.z.atan2(p * (1. - ECCENTRICITY_SQUARED));
+        for _ in 0..5 {
+            let prime_vertical = prime_vertical_radius(Radians(latitude));
+            latitude = (self.z + ECCENTRICITY_SQUARED * prime_vertical * latitude.sin()).atan2(p);
+        }
+        let (sin_latitude, cos_latitude) = latitude.sin_cos();
+        let height = p * cos_latitude + self.z * sin_latitude
+            - SEMI_MAJOR_AXIS * (1. - ECCENTRICITY_SQUARED * sin_latitude * sin_latitude).sqrt();
+        (Radians(latitude), Radians(longitude), height)
+    }
+}
+
+/// A seven-parameter Helmert transformation between two datums, in the position vector convention.
+///
+/// The rotations are small angles about the x, y, and z axes, positive counterclockwise when
+/// looking down the axis towards the origin, as published by the IERS for ITRF realizations.
+/// Parameters published in the coordinate frame convention, e.g. by some national agencies, have
+/// the signs of their rotations flipped.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Helmert {
+    /// The x, y, and z translations, in meters.
+    pub translation: [f64; 3],
+    /// The x, y, and z rotations.
+    pub rotation: [Radians<f64>; 3],
+    /// The scale difference, in parts per million.
+    pub scale: f64,
+}
+
+impl Helmert {
+    /// Transforms ECEF coordinates from the first datum to the second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geodesy::{Ecef, Helmert};
+    /// let helmert = Helmert { translation: [1.0, 2.0, 3.0], scale: 1.0, ..Default::default() };
+    /// let ecef = helmert.transform(Ecef { x: 1e6, y: 0.0, z: 0.0 });
+    /// assert!((ecef.x - 1_000_002.0).abs() < 1e-6);
+    /// assert_eq!(2.0, ecef.y);
+    /// ```
+    pub fn transform(&self, ecef: Ecef) -> Ecef {
+        let [tx, ty, tz] = self.translation;
+        let [rx, ry, rz] = [self.rotation[0].0, self.rotation[1].0, self.rotation[2].0];
+        let scale = 1. + self.scale * 1e-6;
+        Ecef {
+            x: tx + scale * (ecef.x - rz * ecef.y + ry * ecef.z),
+            y: ty + scale * (rz * ecef.x + ecef.y - rx * ecef.z),
+            z: tz + scale * (-ry * ecef.x + rx * ecef.y + ecef.z),
+        }
+    }
+
+    /// Returns a point moved from the first datum to the second, with everything but its position
+    /// copied unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::Helmert;
+    /// let helmert = Helmert { translation: [0.0, 0.0, 1.0], ..Default::default() };
+    /// let point = helmert.transform_point(&Point::default());
+    /// assert!(point.latitude.0 > 0.0);
+    /// ```
+    pub fn transform_point(&self, point: &Point) -> Point {
+        let (latitude, longitude, altitude) = self
+            .transform(Ecef::from_geodetic(
+                point.latitude,
+                point.longitude,
+                point.altitude,
+            ))
+            .to_geodetic();
+        Point {
+            latitude,
+            longitude,
+            altitude,
+            ..*point
+        }
+    }
+}
+
+/// East-north-up coordinates in a local tangent plane, in meters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Enu {
+    /// The distance east of the origin.
+    pub east: f64,
+    /// The distance north of the origin.
+    pub north: f64,
+    /// The distance above the origin's tangent plane.
+    pub up: f64,
+}
+
+/// North-east-down coordinates in a local tangent plane, in meters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ned {
+    /// The distance north of the origin.
+    pub north: f64,
+    /// The distance east of the origin.
+    pub east: f64,
+    /// The distance below the origin's tangent plane.
+    pub down: f64,
+}
+
+/// A local tangent plane at an origin, with the rotation from ECEF cached.
+///
+/// Converting a whole trajectory with one frame only computes the rotation once.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalFrame {
+    origin: Ecef,
+    rotation: [[f64; 3]; 3],
+}
+
+impl LocalFrame {
+    /// Creates a local frame with its origin at geodetic coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geodesy::LocalFrame;
+    /// use pos::units::Radians;
+    /// let frame = LocalFrame::new(Radians::from_degrees(45.0), Radians::from_degrees(-120.0), 0.0);
+    /// ```
+    pub fn new(latitude: Radians<f64>, longitude: Radians<f64>, height: f64) -> LocalFrame {
+        let (sin_latitude, cos_latitude) = latitude.0.sin_cos();
+        let (sin_longitude, cos_longitude) = longitude.0.sin_cos();
+        LocalFrame {
+            origin: Ecef::from_geodetic(latitude, longitude, height),
+            rotation: [
+                [-sin_longitude, cos_longitude, 0.],
+                [
+                    -sin_latitude * cos_longitude,
+                    -sin_latitude * sin_longitude,
+                    cos_latitude,
+                ],
+                [
+                    cos_latitude * cos_longitude,
+                    cos_latitude * sin_longitude,
+                    sin_latitude,
+                ],
+            ],
+        }
+    }
+
+    /// Creates a local frame with its origin at a point's position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::LocalFrame;
+    /// let frame = LocalFrame::from_point(&Point::default());
+    /// ```
+    pub fn from_point(point: &Point) -> LocalFrame {
+        LocalFrame::new(point.latitude, point.longitude, point.altitude)
+    }
+
+    /// Converts ECEF coordinates to east-north-up coordinates in this frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::LocalFrame;
+    /// let frame = LocalFrame::from_point(&Point::default());
+    /// let point = Point { altitude: 10.0, ..Default::default() };
+    /// let enu = frame.to_enu(point.to_ecef());
+    /// assert!((enu.up - 10.0).abs() < 1e-9);
+    /// ```
+    pub fn to_enu(&self, ecef: Ecef) -> Enu {
+        let delta = [
+            ecef.x - self.origin.x,
+            ecef.y - self.origin.y,
+            ecef.z - self.origin.z,
+        ];
+        let row = |i: usize| -> f64 { (0..3).map(|j| self.rotation[i][j] * delta[j]).sum() };
+        Enu {
+            east: row(0),
+            north: row(1),
+            up: row(2),
+        }
+    }
+
+    /// Converts ECEF coordinates to north-east-down coordinates in this frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::LocalFrame;
+    /// let frame = LocalFrame::from_point(&Point::default());
+    /// let point = Point { altitude: 10.0, ..Default::default() };
+    /// let ned = frame.to_ned(point.to_ecef());
+    /// assert!((ned.down + 10.0).abs() < 1e-9);
+    /// ```
+    pub fn to_ned(&self, ecef: Ecef) -> Ned {
+        let enu = self.to_enu(ecef);
+        Ned {
+            north: enu.north,
+            east: enu.east,
+            down: -enu.up,
+        }
+    }
+
+    /// Converts east-north-up coordinates in this frame to ECEF coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::{Enu, LocalFrame};
+    /// let frame = LocalFrame::from_point(&Point::default());
+    /// let ecef = frame.from_enu(Enu { east: 0.0, north: 0.0, up: 10.0 });
+    /// assert!((ecef.x - 6378147.0).abs() < 1e-9);
+    /// ```
+    pub fn from_enu(&self, enu: Enu) -> Ecef {
+        let local = [enu.east, enu.north, enu.up];
+        let column = |j: usize| -> f64 { (0..3).map(|i| self.rotation[i][j] * local[i]).sum() };
+        Ecef {
+            x: self.origin.x + column(0),
+            y: self.origin.y + column(1),
+            z: self.origin.z + column(2),
+        }
+    }
+
+    /// Converts north-east-down coordinates in this frame to ECEF coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::{LocalFrame, Ned};
+    /// let frame = LocalFrame::from_point(&Point::default());
+    /// let ecef = frame.from_ned(Ned { north: 0.0, east: 0.0, down: 10.0 });
+    /// assert!((ecef.x - 6378127.0).abs() < 1e-9);
+    /// ```
+    pub fn from_ned(&self, ned: Ned) -> Ecef {
+        self.from_enu(Enu {
+            east: ned.east,
+            north: ned.north,
+            up: -ned.down,
+        })
+    }
+}
+
+/// Returns the radius of curvature in the prime vertical at a latitude, in meters.
+///
+/// # Examples
+///
+/// ```
+/// use pos::geodesy;
+/// use pos::units::Radians;
+/// assert_eq!(6378137.0, geodesy::prime_vertical_radius(Radians(0.0)));
+/// ```
+pub fn prime_vertical_radius(latitude: Radians<f64>) -> f64 {
+    let sin = latitude.0.sin();
+    SEMI_MAJOR_AXIS / (1. - ECCENTRICITY_SQUARED * sin * sin).sqrt()
+}
+
+/// Returns the radius of curvature in the meridian at a latitude, in meters.
+///
+/// # Examples
+///
+/// ```
+/// use pos::geodesy;
+/// use pos::units::Radians;
+/// let radius = geodesy::meridian_radius(Radians(0.0));
+/// assert!((radius - 6335439.327).abs() < 1e-3);
+/// ```
+pub fn meridian_radius(latitude: Radians<f64>) -> f64 {
+    let sin = latitude.0.sin();
+    let denominator = 1. - ECCENTRICITY_SQUARED * sin * sin;
+    SEMI_MAJOR_AXIS * (1. - ECCENTRICITY_SQUARED) / (denominator * denominator.sqrt())
+}
+
+/// Returns the great circle distance between two points on a sphere with the mean radius, in
+/// meters.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::geodesy;
+/// let to = Point { longitude: Radians::from_degrees(1.0), ..Default::default() };
+/// assert!((geodesy::haversine(&Point::default(), &to) - 111_195.080).abs() < 1e-3);
+/// ```
+pub fn haversine(from: &Point, to: &Point) -> f64 {
+    let sin_latitude = ((to.latitude.0 - from.latitude.0) / 2.).sin();
+    let sin_longitude = (to.longitude.angular_difference(from.longitude).0 / 2.).sin();
+    let a = sin_latitude * sin_latitude
+        + from.latitude.0.cos() * to.latitude.0.cos() * sin_longitude * sin_longitude;
+    2. * MEAN_RADIUS * a.sqrt().min(1.).asin()
+}
+
+/// Returns the initial great circle bearing from one point towards another, clockwise from north
+/// in [0, 2π).
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::geodesy;
+/// let to = Point { latitude: Radians::from_degrees(-1.0), ..Default::default() };
+/// let bearing = geodesy::bearing(&Point::default(), &to);
+/// assert!((bearing.to_degrees() - 180.0).abs() < 1e-9);
+/// ```
+pub fn bearing(from: &Point, to: &Point) -> Radians<f64> {
+    let longitude = to.longitude.angular_difference(from.longitude).0;
+    let y = longitude.sin() * to.latitude.0.cos();
+    let x = from.latitude.0.cos() * to.latitude.0.sin()
+        - from.latitude.0.sin() * to.latitude.0.cos() * longitude.cos();
+    Radians(y.atan2(x)).wrap_two_pi()
+}
+
+/// Returns the geodesic distance between two points on the ellipsoid with Vincenty's inverse
+/// formula, in meters, or none if it doesn't converge.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::geodesy;
+/// let to = Point { longitude: Radians::from_degrees(1.0), ..Default::default() };
+/// let distance = geodesy::vincenty(&Point::default(), &to).unwrap();
+/// assert!((distance - 111_319.491).abs() < 1e-3);
+/// ```
+pub fn vincenty(from: &Point, to: &Point) -> Option<f64> {
+    inverse(from, to).map(|geodesic| geodesic.distance)
+}
+
+/// Solves the inverse geodesic problem between two points with Vincenty's formula, or returns
+/// none if it doesn't converge, which can happen for nearly antipodal points.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::geodesy;
+/// let to = Point {
+///     latitude: Radians::from_degrees(1.0),
+///     longitude: Radians::from_degrees(1.0),
+///     ..Default::default()
+/// };
+/// let geodesic = geodesy::inverse(&Point::default(), &to).unwrap();
+/// assert!((geodesic.initial_azimuth.to_degrees() - 45.188).abs() < 1e-3);
+/// assert!(geodesic.final_azimuth.0 > geodesic.initial_azimuth.0);
+/// ```
+pub fn inverse(from: &Point, to: &Point) -> Option<Geodesic> {
+    let semi_minor_axis = SEMI_MAJOR_AXIS * (1. - FLATTENING);
+    let longitude = to.longitude.angular_difference(from.longitude).0;
+    let u1 = ((1. - FLATTENING) * from.latitude.0.tan()).atan();
+    let u2 = ((1. - FLATTENING) * to.latitude.0.tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+    let mut lambda = longitude;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = (cos_u2 * sin_lambda).hypot(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+        if sin_sigma == 0. {
+            return Some(Geodesic::default());
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_squared_alpha = 1. - sin_alpha * sin_alpha;
+        let cos_2_sigma_m = if cos_squared_alpha == 0. {
+            0.
+        } else {
+            cos_sigma - 2. * sin_u1 * sin_u2 / cos_squared_alpha
+        };
+        let c = FLATTENING / 16.
+            * cos_squared_alpha
+            * (4. + FLATTENING * (4. - 3. * cos_squared_alpha));
+        let previous = lambda;
+        lambda = longitude
+            + (1. - c)
+                * FLATTENING
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2_sigma_m
+                            + c * cos_sigma * (2. * cos_2_sigma_m * cos_2_sigma_m - 1.)));
+        if (lambda - previous).abs() < 1e-12 {
+            let u_squared = cos_squared_alpha
+                * (SEMI_MAJOR_AXIS * SEMI_MAJOR_AXIS - semi_minor_axis * semi_minor_axis)
+                / (semi_minor_axis * semi_minor_axis);
+            let a = 1.
+                + u_squared / 16384.
+                    * (4096. + u_squared * (-768. + u_squared * (320. - 175. * u_squared)));
+            let b = u_squared / 1024.
+                * (256. + u_squared * (-128. + u_squared * (74. - 47. * u_squared)));
+            let delta_sigma = b
+                * sin_sigma
+                * (cos_2_sigma_m
+                    + b / 4.
+                        * (cos_sigma * (2. * cos_2_sigma_m * cos_2_sigma_m - 1.)
+                            - b / 6.
+                                * cos_2_sigma_m
+                                * (4. * sin_sigma * sin_sigma - 3.)
+                                * (4. * cos_2_sigma_m * cos_2_sigma_m - 3.)));
+            let initial_azimuth =
+                (cos_u2 * sin_lambda).atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+            let final_azimuth =
+                (cos_u1 * sin_lambda).atan2(cos_u1 * sin_u2 * cos_lambda - sin_u1 * cos_u2);
+            return Some(Geodesic {
+                distance: semi_minor_axis * a * (sigma - delta_sigma),
+                initial_azimuth: Radians(initial_azimuth).wrap_two_pi(),
+                final_azimuth: Radians(final_azimuth).wrap_two_pi(),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for &(latitude, longitude, height) in &[
+            (0., 0., 0.),
+            (45., -120., 1000.),
+            (-33.9, 151.2, -30.),
+            (89.999, 10., 5.),
+            (90., 0., 100.),
+            (-90., 0., 0.),
+            (32.545, -116.978, 107.715),
+        ] {
+            let ecef = Ecef::from_geodetic(
+                Radians::from_degrees(latitude),
+                Radians::from_degrees(longitude),
+                height,
+            );
+            let (actual_latitude, actual_longitude, actual_height) = ecef.to_geodetic();
+            assert!((actual_latitude.to_degrees() - latitude).abs() < 1e-10);
+            if latitude.abs() < 90. {
+                assert!((actual_longitude.to_degrees() - longitude).abs() < 1e-10);
+            }
+            assert!((actual_height - height).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn local_frame() {
+        let origin = Point {
+            latitude: Radians::from_degrees(45.),
+            longitude: Radians::from_degrees(-120.),
+            altitude: 100.,
+            ..Default::default()
+        };
+        let frame = LocalFrame::from_point(&origin);
+        let north = Point {
+            latitude: Radians::from_degrees(45.001),
+            ..origin
+        };
+        let enu = frame.to_enu(north.to_ecef());
+        assert!(enu.east.abs() < 1e-6);
+        let expected = (meridian_radius(origin.latitude) + 100.) * Radians::from_degrees(0.001).0;
+        assert!((enu.north - expected).abs() < 1e-3);
+        assert!(enu.up < 0.);
+        let east = Point {
+            longitude: Radians::from_degrees(-119.999),
+            ..origin
+        };
+        let ned = frame.to_ned(east.to_ecef());
+        assert!(ned.north.abs() < 1e-3);
+        let expected = (prime_vertical_radius(origin.latitude) + 100.)
+            * origin.latitude.0.cos()
+            * Radians::from_degrees(0.001).0;
+        assert!((ned.east - expected).abs() < 1e-3);
+        for ecef in [north.to_ecef(), east.to_ecef()] {
+            let actual = frame.from_enu(frame.to_enu(ecef));
+            assert!((actual.x - ecef.x).abs() < 1e-6);
+            assert!((actual.y - ecef.y).abs() < 1e-6);
+            assert!((actual.z - ecef.z).abs() < 1e-6);
+            let actual = frame.from_ned(frame.to_ned(ecef));
+            assert!((actual.z - ecef.z).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn distance() {
+        let flinders = Point {
+            latitude: Radians::from_degrees(-37.951_033_416_7),
+            longitude: Radians::from_degrees(144.424_867_888_9),
+            ..Default::default()
+        };
+        let buninyong = Point {
+            latitude: Radians::from_degrees(-37.652_821_138_9),
+            longitude: Radians::from_degrees(143.926_495_527_8),
+            ..Default::default()
+        };
+        let distance = vincenty(&flinders, &buninyong).unwrap();
+        assert!((distance - 54_972.271).abs() < 1e-3);
+        assert!((Method::Haversine.distance(&flinders, &buninyong) - distance).abs() < 200.);
+        assert_eq!(0.0, Method::Vincenty.distance(&flinders, &flinders));
+
+        let antipode = Point {
+            longitude: Radians::from_degrees(179.9),
+            latitude: Radians::from_degrees(0.5),
+            ..Default::default()
+        };
+        assert!(vincenty(&Point::default(), &antipode).is_none());
+        let haversine = haversine(&Point::default(), &antipode);
+        assert_eq!(
+            haversine,
+            Method::Vincenty.distance(&Point::default(), &antipode)
+        );
+    }
+
+    #[test]
+    fn azimuth() {
+        let flinders = Point {
+            latitude: Radians::from_degrees(-37.951_033_416_7),
+            longitude: Radians::from_degrees(144.424_867_888_9),
+            ..Default::default()
+        };
+        let buninyong = Point {
+            latitude: Radians::from_degrees(-37.652_821_138_9),
+            longitude: Radians::from_degrees(143.926_495_527_8),
+            ..Default::default()
+        };
+        let geodesic = inverse(&flinders, &buninyong).unwrap();
+        let degrees = |angle: Radians<f64>| angle.to_degrees();
+        assert!((degrees(geodesic.initial_azimuth) - 306.868_159).abs() < 1e-5);
+        assert!((degrees(geodesic.final_azimuth) - 307.173_640).abs() < 1e-5);
+        let bearing = degrees(Method::Haversine.azimuth(&flinders, &buninyong));
+        assert!((bearing - 306.868_159).abs() < 0.5);
+        assert_eq!(Radians(0.), Method::Vincenty.azimuth(&flinders, &flinders));
+    }
+
+    #[test]
+    fn north_pole() {
+        let ecef = Ecef::from_geodetic(Radians::from_degrees(90.), Radians(0.), 0.);
+        assert!(ecef.x.abs() < 1e-6);
+        assert!((ecef.z - 6_356_752.314_245).abs() < 1e-6);
+    }
+
+    #[test]
+    fn helmert() {
+        let helmert = Helmert {
+            rotation: [Radians(0.), Radians(0.), Radians(1e-6)],
+            ..Default::default()
+        };
+        let ecef = helmert.transform(Ecef {
+            x: SEMI_MAJOR_AXIS,
+            y: 0.,
+            z: 0.,
+        });
+        assert!((ecef.y - SEMI_MAJOR_AXIS * 1e-6).abs() < 1e-9);
+        let point = Point {
+            latitude: Radians::from_degrees(40.),
+            altitude: 100.,
+            ..Default::default()
+        };
+        let moved = helmert.transform_point(&point);
+        assert!((moved.longitude.0 - 1e-6).abs() < 1e-12);
+        assert!((moved.altitude - 100.).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,311 @@
+//! Geoid models, for converting between ellipsoidal and orthometric heights.
+//!
+//! Position files almost always store ellipsoidal heights, while survey control and many point
+//! cloud deliverables use orthometric heights, i.e. heights above the geoid. The two differ by the
+//! geoid undulation N: `h = H + N`.
+//!
+//! Geoid grids are read from the PROJ `.gtx` format, which EGM96 and EGM2008 are distributed in.
+//! A gtx file is a 40-byte big-endian header (the latitude and longitude of the south-west grid
+//! node, the latitude and longitude spacing, all in degrees, and the row and column counts) followed
+//! by the undulations as 32-bit floats, row by row from south to north. Undulations are
+//! interpolated bilinearly.
+
+use crate::error;
+use crate::point::Point;
+use crate::units::Radians;
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// The size of a gtx header, in bytes.
+const HEADER_SIZE: u64 = 40;
+
+/// The value gtx files use for grid nodes without data.
+const NO_DATA: f32 = -88.8888;
+
+/// Geoid errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// The grid's size or spacing is invalid.
+    #[error("Invalid geoid grid: {0} rows, {1} columns")]
+    Grid(i32, i32),
+    /// The file is shorter than its header says, with the expected and actual lengths in bytes.
+    #[error("Geoid grid needs {0} bytes, but the file has {1}")]
+    Length(u64, u64),
+}
+
+/// A geoid undulation grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geoid {
+    south: f64,
+    west: f64,
+    latitude_spacing: f64,
+    longitude_spacing: f64,
+    rows: usize,
+    columns: usize,
+    undulations: Vec<f32>,
+}
+
+impl Geoid {
+    /// Reads a geoid grid from a gtx file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geoid::Geoid;
+    /// let geoid = Geoid::from_path("data/geoid.gtx").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Geoid, error::Error> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Geoid::read(BufReader::new(file), Some(len))
+    }
+
+    /// Reads a geoid grid in the gtx format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::geoid::Geoid;
+    /// let geoid = Geoid::read_from(File::open("data/geoid.gtx").unwrap()).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(reader: R) -> Result<Geoid, error::Error> {
+        Geoid::read(reader, None)
+    }
+
+    /// Reads a gtx grid, checking its size against the length of the file if known.
+    fn read<R: Read>(mut reader: R, len: Option<u64>) -> Result<Geoid, error::Error> {
+        let south = reader.read_f64::<BigEndian>()?;
+        let west = reader.read_f64::<BigEndian>()?;
+        let latitude_spacing = reader.read_f64::<BigEndian>()?;
+        let longitude_spacing = reader.read_f64::<BigEndian>()?;
+        let rows = reader.read_i32::<BigEndian>()?;
+        let columns = reader.read_i32::<BigEndian>()?;
+        let spacings = [latitude_spacing, longitude_spacing];
+        if rows < 1 || columns < 1 || spacings.iter().any(|&s| s.is_nan() || s <= 0.) {
+            return Err(Error::Grid(rows, columns).into());
+        }
+        let size = (rows as usize)
+            .checked_mul(columns as usize)
+            .and_then(|count| count.checked_mul(4))
+            .ok_or(Error::Grid(rows, columns))?;
+        if let Some(len) = len {
+            if len < HEADER_SIZE + size as u64 {
+                return Err(Error::Length(HEADER_SIZE + size as u64, len).into());
+            }
+        }
+        // Without a file length, read only as much as there is, so that a corrupt header can't
+        // allocate the whole grid up front.
+        let mut bytes = Vec::new();
+        let _ = reader.take(size as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let mut undulations = vec![0.; size / 4];
+        BigEndian::read_f32_into(&bytes, &mut undulations);
+        Ok(Geoid {
+            south,
+            west,
+            latitude_spacing,
+            longitude_spacing,
+            rows: rows as usize,
+            columns: columns as usize,
+            undulations,
+        })
+    }
+
+    /// Returns the geoid undulation at a position, in meters.
+    ///
+    /// Returns none if the position is outside of the grid or next to a grid node without data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geoid::Geoid;
+    /// use pos::units::Radians;
+    /// let geoid = Geoid::from_path("data/geoid.gtx").unwrap();
+    /// let undulation = geoid.undulation(Radians::from_degrees(40.5), Radians::from_degrees(-105.5));
+    /// assert!(undulation.is_some());
+    /// ```
+    pub fn undulation(&self, latitude: Radians<f64>, longitude: Radians<f64>) -> Option<f64> {
+        let y = (latitude.to_degrees() - self.south) / self.latitude_spacing;
+        let x = (longitude.to_degrees() - self.west).rem_euclid(360.) / self.longitude_spacing;
+        if !(y >= 0. && y <= (self.rows - 1) as f64) {
+            return None;
+        }
+        let (row, column) = (y.floor() as usize, x.floor() as usize);
+        let (dy, dx) = (y - row as f64, x - column as f64);
+        if column >= self.columns {
+            return None;
+        }
+        let next_row = if dy > 0. { row + 1 } else { row };
+        let next_column = if dx == 0. {
+            column
+        } else if column + 1 < self.columns {
+            column + 1
+        } else if self.columns as f64 * self.longitude_spacing >= 360. - 1e-9 {
+            0
+        } else {
+            return None;
+        };
+        let value = |row: usize, column: usize| {
+            let value = self.undulations[row * self.columns + column];
+            if value == NO_DATA {
+                None
+            } else {
+                Some(f64::from(value))
+            }
+        };
+        let south = value(row, column)? * (1. - dx) + value(row, next_column)? * dx;
+        let north = value(next_row, column)? * (1. - dx) + value(next_row, next_column)? * dx;
+        Some(south * (1. - dy) + north * dy)
+    }
+
+    /// Returns a point's orthometric height, treating its altitude as an ellipsoidal height.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geoid::Geoid;
+    /// use pos::units::Radians;
+    /// let geoid = Geoid::from_path("data/geoid.gtx").unwrap();
+    /// let point = Point {
+    ///     latitude: Radians::from_degrees(40.5),
+    ///     longitude: Radians::from_degrees(-105.5),
+    ///     altitude: 1600.0,
+    ///     ..Default::default()
+    /// };
+    /// let height = geoid.orthometric_height(&point).unwrap();
+    /// ```
+    pub fn orthometric_height(&self, point: &Point) -> Option<f64> {
+        self.undulation(point.latitude, point.longitude)
+            .map(|n| point.altitude - n)
+    }
+
+    /// Returns a point's ellipsoidal height, treating its altitude as an orthometric height.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geoid::Geoid;
+    /// use pos::units::Radians;
+    /// let geoid = Geoid::from_path("data/geoid.gtx").unwrap();
+    /// let point = Point {
+    ///     latitude: Radians::from_degrees(40.5),
+    ///     longitude: Radians::from_degrees(-105.5),
+    ///     altitude: 1600.0,
+    ///     ..Default::default()
+    /// };
+    /// let height = geoid.ellipsoidal_height(&point).unwrap();
+    /// let point = Point { altitude: height, ..point };
+    /// assert!((geoid.orthometric_height(&point).unwrap() - 1600.0).abs() < 1e-9);
+    /// ```
+    pub fn ellipsoidal_height(&self, point: &Point) -> Option<f64> {
+        self.undulation(point.latitude, point.longitude)
+            .map(|n| point.altitude + n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use byteorder::WriteBytesExt;
+
+    fn gtx(
+        south: f64,
+        west: f64,
+        spacing: f64,
+        rows: i32,
+        columns: i32,
+        values: &[f32],
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for &value in &[south, west, spacing, spacing] {
+            bytes.write_f64::<BigEndian>(value).unwrap();
+        }
+        bytes.write_i32::<BigEndian>(rows).unwrap();
+        bytes.write_i32::<BigEndian>(columns).unwrap();
+        for &value in values {
+            bytes.write_f32::<BigEndian>(value).unwrap();
+        }
+        bytes
+    }
+
+    fn undulation(geoid: &Geoid, latitude: f64, longitude: f64) -> Option<f64> {
+        geoid.undulation(
+            Radians::from_degrees(latitude),
+            Radians::from_degrees(longitude),
+        )
+    }
+
+    #[test]
+    fn interpolate() {
+        let bytes = gtx(
+            40.,
+            254.,
+            1.,
+            2,
+            3,
+            &[-10., -12., -14., -20., -22., NO_DATA],
+        );
+        let geoid = Geoid::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(Some(-10.), undulation(&geoid, 40., -106.));
+        assert_eq!(Some(-16.), undulation(&geoid, 40.5, -105.5));
+        assert_eq!(Some(-22.), undulation(&geoid, 41., -105.));
+        assert_eq!(None, undulation(&geoid, 40.5, -104.5));
+        assert_eq!(None, undulation(&geoid, 41.5, -105.5));
+        assert_eq!(None, undulation(&geoid, 40.5, -107.));
+    }
+
+    #[test]
+    fn global() {
+        let bytes = gtx(
+            -90.,
+            -180.,
+            90.,
+            3,
+            4,
+            &[1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12.],
+        );
+        let geoid = Geoid::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(Some(6.5), undulation(&geoid, 0., 135.));
+        assert_eq!(Some(5.), undulation(&geoid, 0., 180.));
+        assert_eq!(Some(10.), undulation(&geoid, 90., -90.));
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = gtx(0., 0., 1., 0, 3, &[]);
+        assert!(Geoid::read_from(bytes.as_slice()).is_err());
+        let bytes = gtx(0., 0., 1., 2, 2, &[1., 2., 3.]);
+        assert!(Geoid::read_from(bytes.as_slice()).is_err());
+        let bytes = gtx(0., 0., 1., i32::MAX, i32::MAX, &[1.]);
+        assert!(Geoid::read_from(bytes.as_slice()).is_err());
+
+        let path = ::std::env::temp_dir().join("pos-rs-geoid-truncated.gtx");
+        ::std::fs::write(&path, gtx(0., 0., 1., 2, 2, &[1., 2., 3.])).unwrap();
+        match Geoid::from_path(&path).unwrap_err() {
+            error::Error::Geoid(Error::Length(56, 52)) => {}
+            err => panic!("unexpected error: {}", err),
+        }
+        ::std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn file() {
+        let geoid = Geoid::from_path("data/geoid.gtx").unwrap();
+        let point = Point {
+            latitude: Radians::from_degrees(40.25),
+            longitude: Radians::from_degrees(-105.75),
+            altitude: 1600.,
+            ..Default::default()
+        };
+        let height = geoid.orthometric_height(&point).unwrap();
+        assert!((height - 1617.).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,442 @@
+//! GeoJSON trajectory export, for web maps.
+//!
+//! Points are written either as a single `LineString` feature (see `Mode::LineString` for fewer
+//! than two points) or as a `FeatureCollection` with one
+//! `Point` feature per point, whose properties hold the time, attitude, and velocity. The yaw
+//! property is the true heading. Coordinates are longitude, latitude, and altitude, in degrees and
+//! meters, unless the writer projects them into a UTM zone.
+
+use crate::error;
+use crate::gpx::DEFAULT_LEAP_SECONDS;
+use crate::point::Point;
+use crate::sink::Sink;
+use crate::time;
+use crate::units::Degrees;
+use crate::utm::Zone;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// How points are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    /// A single feature with a line through all points.
+    ///
+    /// A line needs two positions, so one point is written as a point geometry and none as a
+    /// feature without a geometry.
+    #[default]
+    LineString,
+    /// A feature for each point, with properties.
+    Features,
+}
+
+/// A GeoJSON writer.
+///
+/// The GeoJSON document isn't complete until the writer is closed.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// The GPS week of the points' times, which are seconds of the week.
+    ///
+    /// If known, features get a `utc` property.
+    pub week: Option<u16>,
+
+    /// The difference between GPS time and UTC, in seconds.
+    pub leap_seconds: f64,
+
+    /// If set, coordinates are the UTM easting, northing, and altitude in this zone, in meters.
+    ///
+    /// Projected coordinates aren't valid RFC 7946 GeoJSON, but many GIS tools accept them, and
+    /// they line up with point clouds projected into the same zone.
+    pub utm_zone: Option<Zone>,
+
+    count: usize,
+    first: Option<String>,
+    mode: Mode,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path and starts the GeoJSON document.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geojson::{Mode, Writer};
+    /// let writer = Writer::from_path("/dev/null", Mode::Features).unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        mode: Mode,
+    ) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?), mode)
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer and starts the GeoJSON document.
+    ///
+    /// In line string mode, nothing is written until the second point, since the geometry's type
+    /// depends on the number of points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geojson::{Mode, Writer};
+    /// let writer = Writer::new(Vec::new(), Mode::LineString).unwrap();
+    /// ```
+    pub fn new(mut writer: W, mode: Mode) -> Result<Writer<W>, error::Error> {
+        if mode == Mode::Features {
+            write!(writer, r#"{{"type":"FeatureCollection","features":["#)?;
+        }
+        Ok(Writer {
+            week: None,
+            leap_seconds: DEFAULT_LEAP_SECONDS,
+            utm_zone: None,
+            count: 0,
+            first: None,
+            mode,
+            writer,
+        })
+    }
+
+    /// Writes a point, as a line vertex or as a feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geojson::{Mode, Writer};
+    /// let mut writer = Writer::new(Vec::new(), Mode::Features).unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        let coordinates = match self.utm_zone {
+            Some(zone) => {
+                let utm = point.to_utm_in_zone(zone);
+                format!(
+                    "[{},{},{}]",
+                    number(utm.easting, 3),
+                    number(utm.northing, 3),
+                    number(point.altitude, 3)
+                )
+            }
+            None => format!(
+                "[{},{},{}]",
+                number(point.longitude.to_degrees(), 9),
+                number(point.latitude.to_degrees(), 9),
+                number(point.altitude, 3)
+            ),
+        };
+        self.count += 1;
+        if self.mode == Mode::LineString {
+            match self.first.take() {
+                Some(first) if self.count == 2 => write!(
+                    self.writer,
+                    r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"LineString","coordinates":[
+{},
+{}"#,
+                    first, coordinates
+                )?,
+                _ if self.count == 1 => self.first = Some(coordinates),
+                _ => write!(self.writer, ",\n{}", coordinates)?,
+            }
+            return Ok(());
+        }
+        if self.count > 1 {
+            writeln!(self.writer, ",")?;
+        } else {
+            writeln!(self.writer)?;
+        }
+        write!(
+            self.writer,
+            r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":{}}},"properties":{{"time":{}"#,
+            coordinates,
+            number(point.time.0, 6)
+        )?;
+        if let Some(week) = self.week {
+            write!(
+                self.writer,
+                r#","utc":"{}""#,
+                time::to_utc_string(week, point.time.0, self.leap_seconds)
+            )?;
+        }
+        write!(
+            self.writer,
+            r#","roll":{},"pitch":{},"yaw":{}"#,
+            number(point.roll.to_degrees(), 6),
+            number(point.pitch.to_degrees(), 6),
+            number(point.true_heading().to_degrees(), 6)
+        )?;
+        for &(name, value) in &[
+            ("x_velocity", point.x_velocity),
+            ("y_velocity", point.y_velocity),
+            ("z_velocity", point.z_velocity),
+        ] {
+            if let Some(value) = value {
+                write!(self.writer, r#","{}":{}"#, name, number(value, 3))?;
+            }
+        }
+        write!(self.writer, "}}}}")?;
+        Ok(())
+    }
+
+    /// Ends the GeoJSON document and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::geojson::{Mode, Writer};
+    /// let writer = Writer::new(Vec::new(), Mode::LineString).unwrap();
+    /// let bytes = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        match (self.mode, self.first.take()) {
+            (Mode::LineString, Some(first)) => writeln!(
+                self.writer,
+                r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"Point","coordinates":{}}}}}"#,
+                first
+            )?,
+            (Mode::LineString, None) if self.count == 0 => writeln!(
+                self.writer,
+                r#"{{"type":"Feature","properties":{{}},"geometry":null}}"#
+            )?,
+            (Mode::LineString, None) => writeln!(self.writer, "\n]}}}}")?,
+            (Mode::Features, _) => writeln!(self.writer, "\n]}}")?,
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes a ring of longitude and latitude pairs, in degrees, as a GeoJSON polygon feature, e.g. a
+/// trajectory's footprint from `Trajectory::convex_hull`.
+///
+/// Longitudes beyond ±180°, e.g. from a hull that crosses the antimeridian, are wrapped. A ring
+/// with fewer than three distinct positions isn't a valid polygon, so it's written as a point or a
+/// line instead, and an empty ring as a feature without a geometry.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Trajectory};
+/// use pos::geojson;
+/// let trajectory = Trajectory::new(vec![Point::default()]);
+/// let mut bytes = Vec::new();
+/// geojson::write_polygon(&mut bytes, &trajectory.convex_hull()).unwrap();
+/// assert!(String::from_utf8(bytes).unwrap().contains(r#""type":"Point""#));
+/// ```
+pub fn write_polygon<W: Write>(mut writer: W, ring: &[(f64, f64)]) -> Result<(), error::Error> {
+    let mut positions: Vec<_> = ring
+        .iter()
+        .map(|&(longitude, latitude)| {
+            if longitude.abs() > 180. {
+                (Degrees(longitude).wrap_pi().0, latitude)
+            } else {
+                (longitude, latitude)
+            }
+        })
+        .collect();
+    positions.dedup();
+    if positions.len() > 1 && positions.first() == positions.last() {
+        let _ = positions.pop();
+    }
+    let coordinates: Vec<_> = positions
+        .iter()
+        .map(|&(longitude, latitude)| format!("[{},{}]", number(longitude, 9), number(latitude, 9)))
+        .collect();
+    let geometry = match coordinates.len() {
+        0 => "null".to_string(),
+        1 => format!(r#"{{"type":"Point","coordinates":{}}}"#, coordinates[0]),
+        2 => format!(
+            r#"{{"type":"LineString","coordinates":[{}]}}"#,
+            coordinates.join(",")
+        ),
+        _ => format!(
+            r#"{{"type":"Polygon","coordinates":[[{},{}]]}}"#,
+            coordinates.join(","),
+            coordinates[0]
+        ),
+    };
+    writeln!(
+        writer,
+        r#"{{"type":"Feature","geometry":{},"properties":{{}}}}"#,
+        geometry
+    )?;
+    Ok(())
+}
+
+/// Formats a number for JSON, which has no NaN or infinity.
+fn number(value: f64, precision: usize) -> String {
+    if value.is_finite() {
+        format!("{:.*}", precision, value)
+    } else {
+        "null".to_string()
+    }
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        let _ = Writer::close(*self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use crate::units::Radians;
+
+    fn point() -> Point {
+        Point {
+            time: Time(264225.0),
+            latitude: Radians::from_degrees(35.5),
+            longitude: Radians::from_degrees(139.25),
+            altitude: 45.125,
+            yaw: Radians::from_degrees(90.),
+            x_velocity: Some(1.5),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn line_string() {
+        let mut writer = Writer::new(Vec::new(), Mode::LineString).unwrap();
+        writer.write_point(&point()).unwrap();
+        writer.write_point(&point()).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("[139.250000000,35.500000000,45.125],", lines[1]);
+        assert_eq!("[139.250000000,35.500000000,45.125]", lines[2]);
+        assert_eq!("]}}", lines[3]);
+    }
+
+    #[test]
+    fn features() {
+        let mut writer = Writer::new(Vec::new(), Mode::Features).unwrap();
+        writer.week = Some(2053);
+        writer.write_point(&point()).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            r#"{"type":"Feature","geometry":{"type":"Point","coordinates":[139.250000000,35.500000000,45.125]},"properties":{"time":264225.000000,"utc":"2019-05-15T01:23:27.000Z","roll":0.000000,"pitch":0.000000,"yaw":90.000000,"x_velocity":1.500}}"#,
+            lines[1]
+        );
+        assert_eq!("]}", lines[2]);
+    }
+
+    #[test]
+    fn polygon() {
+        let mut bytes = Vec::new();
+        write_polygon(
+            &mut bytes,
+            &[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (0.0, 0.0)],
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!("Polygon", value["geometry"]["type"]);
+        assert_eq!(
+            4,
+            value["geometry"]["coordinates"][0]
+                .as_array()
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn degenerate_polygon() {
+        let geometry = |ring: &[(f64, f64)]| {
+            let mut bytes = Vec::new();
+            write_polygon(&mut bytes, ring).unwrap();
+            let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            value["geometry"].clone()
+        };
+        assert!(geometry(&[]).is_null());
+        assert_eq!("Point", geometry(&[(1.0, 2.0), (1.0, 2.0)])["type"]);
+        let line = geometry(&[(0.0, 0.0), (1.0, 0.0), (0.0, 0.0)]);
+        assert_eq!("LineString", line["type"]);
+        assert_eq!(2, line["coordinates"].as_array().unwrap().len());
+        let polygon = geometry(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        let ring = polygon["coordinates"][0].as_array().unwrap();
+        assert_eq!(4, ring.len());
+        assert_eq!(ring[0], ring[3]);
+    }
+
+    #[test]
+    fn antimeridian_polygon() {
+        let mut bytes = Vec::new();
+        write_polygon(
+            &mut bytes,
+            &[(179.0, 0.0), (181.0, 0.0), (180.0, 1.0), (179.0, 0.0)],
+        )
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let ring = value["geometry"]["coordinates"][0].as_array().unwrap();
+        assert!((ring[1][0].as_f64().unwrap() + 179.0).abs() < 1e-9);
+        assert_eq!(180.0, ring[2][0].as_f64().unwrap());
+    }
+
+    #[test]
+    fn utm() {
+        let mut writer = Writer::new(Vec::new(), Mode::LineString).unwrap();
+        writer.utm_zone = Some(Zone {
+            number: 54,
+            north: true,
+        });
+        writer.write_point(&point()).unwrap();
+        writer.write_point(&point()).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let coordinates: Vec<f64> = output.lines().nth(1).unwrap()[1..]
+            .trim_end_matches("],")
+            .split(',')
+            .map(|v| v.parse().unwrap())
+            .collect();
+        let utm = point().to_utm();
+        assert_eq!(54, utm.zone.number);
+        assert!((coordinates[0] - utm.easting).abs() < 1e-3);
+        assert!((coordinates[1] - utm.northing).abs() < 1e-3);
+        assert_eq!(45.125, coordinates[2]);
+    }
+
+    #[test]
+    fn short_line_string() {
+        let geometry = |count: usize| {
+            let mut writer = Writer::new(Vec::new(), Mode::LineString).unwrap();
+            for _ in 0..count {
+                writer.write_point(&point()).unwrap();
+            }
+            let bytes = writer.close().unwrap();
+            let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!("Feature", value["type"]);
+            value["geometry"].clone()
+        };
+        assert!(geometry(0).is_null());
+        let point = geometry(1);
+        assert_eq!("Point", point["type"]);
+        assert_eq!(139.25, point["coordinates"][0]);
+        let line = geometry(3);
+        assert_eq!("LineString", line["type"]);
+        assert_eq!(3, line["coordinates"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn empty() {
+        let writer = Writer::new(Vec::new(), Mode::Features).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        assert_eq!(
+            "{\"type\":\"FeatureCollection\",\"features\":[\n]}\n",
+            output
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! GPX track export.
+//!
+//! Points are written as a single track segment, with elevation and, if the GPS week is known,
+//! time. GPX files can be opened by QGIS, Google Earth, and most GPS tools.
+
+use crate::error;
+use crate::point::Point;
+use crate::sink::Sink;
+use crate::time;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// The default difference between GPS time and UTC, in seconds, as of 2017.
+pub const DEFAULT_LEAP_SECONDS: f64 = 18.;
+
+/// A GPX writer.
+///
+/// The GPX document isn't complete until the writer is closed.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// The GPS week of the points' times, which are seconds of the week.
+    ///
+    /// If none, points are written without times.
+    pub week: Option<u16>,
+
+    /// The difference between GPS time and UTC, in seconds.
+    pub leap_seconds: f64,
+
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path and starts the GPX document.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::gpx::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer and starts the GPX document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::gpx::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// ```
+    pub fn new(mut writer: W) -> Result<Writer<W>, error::Error> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<gpx version="1.1" creator="pos-rs" xmlns="http://www.topografix.com/GPX/1/1">"#
+        )?;
+        writeln!(writer, "<trk><trkseg>")?;
+        Ok(Writer {
+            week: None,
+            leap_seconds: DEFAULT_LEAP_SECONDS,
+            writer,
+        })
+    }
+
+    /// Writes a point as a track point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::gpx::Writer;
+    /// let mut writer = Writer::new(Vec::new()).unwrap();
+    /// writer.week = Some(2053);
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        write!(
+            self.writer,
+            r#"<trkpt lat="{:.9}" lon="{:.9}"><ele>{:.3}</ele>"#,
+            point.latitude.to_degrees(),
+            point.longitude.to_degrees(),
+            point.altitude
+        )?;
+        if let Some(week) = self.week {
+            write!(
+                self.writer,
+                "<time>{}</time>",
+                time::to_utc_string(week, point.time.0, self.leap_seconds)
+            )?;
+        }
+        writeln!(self.writer, "</trkpt>")?;
+        Ok(())
+    }
+
+    /// Ends the GPX document and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::gpx::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// let bytes = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        writeln!(self.writer, "</trkseg></trk>")?;
+        writeln!(self.writer, "</gpx>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        let _ = Writer::close(*self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use crate::units::Radians;
+
+    #[test]
+    fn write() {
+        let mut writer = Writer::new(Vec::new()).unwrap();
+        let point = Point {
+            time: Time(264225.0),
+            latitude: Radians::from_degrees(35.5),
+            longitude: Radians::from_degrees(139.25),
+            altitude: 45.125,
+            ..Default::default()
+        };
+        writer.write_point(&point).unwrap();
+        writer.week = Some(2053);
+        writer.write_point(&point).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(7, lines.len());
+        assert_eq!("<trk><trkseg>", lines[2]);
+        assert_eq!(
+            r#"<trkpt lat="35.500000000" lon="139.250000000"><ele>45.125</ele></trkpt>"#,
+            lines[3]
+        );
+        assert_eq!(
+            r#"<trkpt lat="35.500000000" lon="139.250000000"><ele>45.125</ele><time>2019-05-15T01:23:27.000Z</time></trkpt>"#,
+            lines[4]
+        );
+        assert_eq!("</gpx>", lines[6]);
+    }
+}
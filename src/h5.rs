@@ -0,0 +1,195 @@
+//! HDF5 export, for MATLAB and Python.
+//!
+//! Each point field is written as a one-dimensional dataset at the root of the file, e.g. `time`,
+//! `latitude`, and `x_velocity`, and accuracy fields are written as `accuracy_*` datasets. Each
+//! dataset has a `units` attribute. Latitude, longitude, and angles are in degrees. Optional
+//! fields are only written if at least one point has them, and missing values are NaN.
+//!
+//! Requires the `hdf5` feature, which links to the HDF5 C library. The feature is experimental:
+//! the writer and its tests haven't yet been run against a real HDF5 library.
+
+use crate::error;
+use crate::point::Point;
+use crate::sink::Sink;
+use hdf5::types::VarLenUnicode;
+use std::path::Path;
+
+type Getter = fn(&Point) -> Option<f64>;
+
+const DATASETS: &[(&str, &str, Getter)] = &[
+    ("time", "s", |p| Some(p.time.0)),
+    ("latitude", "degrees", |p| Some(p.latitude.to_degrees())),
+    ("longitude", "degrees", |p| Some(p.longitude.to_degrees())),
+    ("altitude", "m", |p| Some(p.altitude)),
+    ("roll", "degrees", |p| Some(p.roll.to_degrees())),
+    ("pitch", "degrees", |p| Some(p.pitch.to_degrees())),
+    ("yaw", "degrees", |p| Some(p.yaw.to_degrees())),
+    ("distance", "m", |p| p.distance),
+    ("x_velocity", "m/s", |p| p.x_velocity),
+    ("y_velocity", "m/s", |p| p.y_velocity),
+    ("z_velocity", "m/s", |p| p.z_velocity),
+    ("wander_angle", "degrees", |p| {
+        p.wander_angle.map(|a| a.to_degrees())
+    }),
+    ("x_acceleration", "m/s^2", |p| p.x_acceleration),
+    ("y_acceleration", "m/s^2", |p| p.y_acceleration),
+    ("z_acceleration", "m/s^2", |p| p.z_acceleration),
+    ("x_angular_rate", "degrees/s", |p| {
+        p.x_angular_rate.map(|a| a.to_degrees())
+    }),
+    ("y_angular_rate", "degrees/s", |p| {
+        p.y_angular_rate.map(|a| a.to_degrees())
+    }),
+    ("z_angular_rate", "degrees/s", |p| {
+        p.z_angular_rate.map(|a| a.to_degrees())
+    }),
+    ("accuracy_x", "m", |p| p.accuracy.map(|a| a.x)),
+    ("accuracy_y", "m", |p| p.accuracy.map(|a| a.y)),
+    ("accuracy_z", "m", |p| p.accuracy.map(|a| a.z)),
+    ("accuracy_roll", "degrees", |p| {
+        p.accuracy.map(|a| a.roll.to_degrees())
+    }),
+    ("accuracy_pitch", "degrees", |p| {
+        p.accuracy.map(|a| a.pitch.to_degrees())
+    }),
+    ("accuracy_yaw", "degrees", |p| {
+        p.accuracy.map(|a| a.yaw.to_degrees())
+    }),
+    ("accuracy_pdop", "1", |p| p.accuracy.map(|a| a.pdop)),
+    ("accuracy_x_velocity", "m/s", |p| {
+        p.accuracy.and_then(|a| a.x_velocity)
+    }),
+    ("accuracy_y_velocity", "m/s", |p| {
+        p.accuracy.and_then(|a| a.y_velocity)
+    }),
+    ("accuracy_z_velocity", "m/s", |p| {
+        p.accuracy.and_then(|a| a.z_velocity)
+    }),
+];
+
+/// An HDF5 writer.
+///
+/// HDF5 datasets are written whole, so points are buffered until the writer is closed.
+#[derive(Debug)]
+pub struct Writer {
+    file: hdf5::File,
+    points: Vec<Point>,
+}
+
+impl Writer {
+    /// Creates an HDF5 file.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pos::h5::Writer;
+    /// let writer = Writer::from_path("trajectory.h5").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer, error::Error> {
+        Ok(Writer {
+            file: hdf5::File::create(path)?,
+            points: Vec::new(),
+        })
+    }
+
+    /// Adds a point.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pos::Point;
+    /// use pos::h5::Writer;
+    /// let mut writer = Writer::from_path("trajectory.h5").unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        self.points.push(*point);
+        Ok(())
+    }
+
+    /// Writes the datasets and closes the file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pos::h5::Writer;
+    /// let writer = Writer::from_path("trajectory.h5").unwrap();
+    /// writer.close().unwrap();
+    /// ```
+    pub fn close(self) -> Result<(), error::Error> {
+        for &(name, units, get) in DATASETS {
+            if !self.points.iter().any(|p| get(p).is_some()) {
+                continue;
+            }
+            let values: Vec<f64> = self
+                .points
+                .iter()
+                .map(|p| get(p).unwrap_or(f64::NAN))
+                .collect();
+            let dataset = self
+                .file
+                .new_dataset_builder()
+                .with_data(values.as_slice())
+                .create(name)?;
+            let units: VarLenUnicode = units.parse()?;
+            dataset
+                .new_attr::<VarLenUnicode>()
+                .create("units")?
+                .write_scalar(&units)?;
+        }
+        self.file.close()?;
+        Ok(())
+    }
+}
+
+impl Sink for Writer {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        Writer::close(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use crate::units::Radians;
+
+    #[test]
+    fn write() {
+        let path = ::std::env::temp_dir().join("pos-rs-h5-test.h5");
+        let mut writer = Writer::from_path(&path).unwrap();
+        for i in 0..3 {
+            writer
+                .write_point(&Point {
+                    time: Time(i as f64),
+                    latitude: Radians::from_degrees(45.),
+                    x_velocity: if i == 1 { Some(1.5) } else { None },
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+
+        let file = hdf5::File::open(&path).unwrap();
+        let latitude = file.dataset("latitude").unwrap();
+        assert_eq!(vec![45.; 3], latitude.read_raw::<f64>().unwrap());
+        let units: VarLenUnicode = latitude.attr("units").unwrap().read_scalar().unwrap();
+        assert_eq!("degrees", units.as_str());
+        let x_velocity = file
+            .dataset("x_velocity")
+            .unwrap()
+            .read_raw::<f64>()
+            .unwrap();
+        assert!(x_velocity[0].is_nan());
+        assert_eq!(1.5, x_velocity[1]);
+        assert!(file.dataset("distance").is_err());
+        ::std::fs::remove_file(path).unwrap();
+    }
+}
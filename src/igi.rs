@@ -0,0 +1,258 @@
+//! IGI AEROoffice ASCII exports, from AEROcontrol IMUs.
+//!
+//! Exports start with metadata lines, which may be comments starting with `;` or `#`, followed by
+//! a line of column names, e.g. `GPS-Time(s) Latitude(deg) Longitude(deg) Ell.Height(m)`. Each
+//! following line is either a trajectory record, with one value per column, or an event record,
+//! which is a trajectory record preceded by `EVENT` and the event number. Columns are matched by
+//! name as in pos headers (see `pos::Column::from_name`), so photogrammetric angles (omega, phi,
+//! and kappa) are skipped.
+
+use crate::error;
+use crate::point::Point;
+use crate::pos::ColumnMap;
+use crate::source::Source;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// The tag at the start of event records.
+const EVENT_TAG: &str = "EVENT";
+
+/// IGI errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// An event record has no event number.
+    #[error("Invalid event number in line: {0}")]
+    EventNumber(String),
+}
+
+/// A trajectory or an event record.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Record {
+    /// A trajectory point.
+    Trajectory(Point),
+    /// An event, e.g. a camera exposure, and its number.
+    Event(u32, Point),
+}
+
+/// An IGI reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    columns: ColumnMap,
+    pending: Option<String>,
+    reader: R,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path and reads the header lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::igi::Reader;
+    /// let reader = Reader::from_path("data/2-points.igi").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead` and reads the header lines.
+    ///
+    /// Header lines are read until the line of column names, or until the first record if there
+    /// is no such line, in which case the default pos column map is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::igi::Reader;
+    /// let data = "GPS-Time Latitude Longitude Ell.Height\n1 45 -105 1600\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// assert_eq!(1600.0, reader.read_point().unwrap().unwrap().altitude);
+    /// ```
+    pub fn new(reader: R) -> Result<Reader<R>, error::Error> {
+        let mut reader = Reader {
+            columns: ColumnMap::default(),
+            pending: None,
+            reader,
+        };
+        while let Some(line) = reader.read_line()? {
+            if let Some(columns) = ColumnMap::from_header(&line) {
+                reader.columns = columns;
+                break;
+            }
+            let first = line.split_whitespace().next().unwrap_or("");
+            if first == EVENT_TAG || first.parse::<f64>().is_ok() {
+                reader.pending = Some(line);
+                break;
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Returns the column map used to read records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::igi::Reader;
+    /// use pos::pos::Column;
+    /// let reader = Reader::from_path("data/2-points.igi").unwrap();
+    /// assert_eq!(Column::Yaw, reader.columns().columns()[6]);
+    /// ```
+    pub fn columns(&self) -> &ColumnMap {
+        &self.columns
+    }
+
+    /// Reads the next trajectory or event record.
+    ///
+    /// Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::igi::{Reader, Record};
+    /// let mut reader = Reader::from_path("data/2-points.igi").unwrap();
+    /// match reader.read_record().unwrap().unwrap() {
+    ///     Record::Trajectory(point) => println!("point at {}", point.time),
+    ///     Record::Event(number, point) => println!("event {} at {}", number, point.time),
+    /// }
+    /// ```
+    pub fn read_record(&mut self) -> Result<Option<Record>, error::Error> {
+        let line = match self.pending.take() {
+            Some(line) => line,
+            None => match self.read_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            },
+        };
+        match line.trim_start().strip_prefix(EVENT_TAG) {
+            Some(rest) => {
+                let rest = rest.trim_start();
+                let (number, values) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let number = number
+                    .parse()
+                    .map_err(|_| Error::EventNumber(line.clone()))?;
+                Ok(Some(Record::Event(number, self.columns.parse(values)?)))
+            }
+            None => Ok(Some(Record::Trajectory(self.columns.parse(&line)?))),
+        }
+    }
+
+    /// Reads the next trajectory point, skipping events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::igi::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.igi").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        loop {
+            match self.read_record()? {
+                Some(Record::Trajectory(point)) => return Ok(Some(point)),
+                Some(Record::Event(..)) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Reads the next line that isn't blank or a comment.
+    fn read_line(&mut self) -> Result<Option<String>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with(';') && !trimmed.starts_with('#') {
+                return Ok(Some(trimmed.to_string()));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over the trajectory points of an IGI reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+
+    #[test]
+    fn read_file() {
+        let mut reader = Reader::from_path("data/2-points.igi").unwrap();
+        let point = match reader.read_record().unwrap().unwrap() {
+            Record::Trajectory(point) => point,
+            record => panic!("unexpected record: {:?}", record),
+        };
+        assert_eq!(Time(151631.0), point.time);
+        assert_eq!(1600.0, point.altitude);
+        assert!((90.0 - point.yaw.to_degrees()).abs() < 1e-9);
+        assert_eq!(0.05, point.accuracy.unwrap().z);
+        match reader.read_record().unwrap().unwrap() {
+            Record::Event(number, point) => {
+                assert_eq!(1, number);
+                assert_eq!(Time(151631.005), point.time);
+                assert_eq!(1600.01, point.altitude);
+            }
+            record => panic!("unexpected record: {:?}", record),
+        }
+        assert!(reader.read_record().unwrap().is_some());
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn points() {
+        let points = Reader::from_path("data/2-points.igi")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(Time(151631.01), points[1].time);
+    }
+
+    #[test]
+    fn no_header() {
+        let data = "; metadata\n1 45 -105 1600 0 0 0\nEVENT x 2 45 -105 1600 0 0 0\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert_eq!(
+            Some(Record::Trajectory(
+                ColumnMap::default().parse("1 45 -105 1600 0 0 0").unwrap()
+            )),
+            reader.read_record().unwrap()
+        );
+        assert!(reader.read_record().is_err());
+    }
+}
@@ -1,30 +1,64 @@
 //! Interpolate between two position points.
 
-use failure;
-use point::Point;
-use source::Source;
+use crate::error;
+use crate::point::Point;
+use crate::source::Source;
+use crate::time::Time;
 
 /// Errors for interpolation.
-#[derive(Clone, Copy, Debug, Fail)]
+#[derive(Clone, Copy, Debug, thiserror::Error)]
 pub enum Error {
     /// Error returned when trying to extrapolate with only one point in the source.
-    #[fail(display = "Cannot interpolate in a source with only one point")]
+    #[error("Cannot interpolate in a source with only one point")]
     OnePoint,
 
     /// The time value is below the minimum time of the source.
-    #[fail(display = "Time value is below minimum of the source: {}", _0)]
-    TimeBelowMinimum(f64),
+    #[error("Time value is below minimum of the source: {0}")]
+    TimeBelowMinimum(Time),
 
     /// The time value is above the maximum time of the source.
-    #[fail(display = "Time value is above the maximum of the source: {}", _0)]
-    TimeAboveMaximum(f64),
+    #[error("Time value is above the maximum of the source: {0}")]
+    TimeAboveMaximum(Time),
+}
+
+/// How to interpolate between two points.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Method {
+    /// Interpolate every field linearly.
+    #[default]
+    Linear,
+    /// Spherically interpolate the attitude, and interpolate everything else linearly.
+    Slerp,
+}
+
+impl Method {
+    /// Interpolates a point between two points using this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::interpolate::Method;
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// let p1 = Point { time: Time(1.0), ..Default::default() };
+    /// let p2 = Point { time: Time(2.0), ..Default::default() };
+    /// let point = Method::Slerp.interpolate(&p1, &p2, Time(1.5));
+    /// assert_eq!(Time(1.5), point.time);
+    /// ```
+    pub fn interpolate(self, lhs: &Point, rhs: &Point, time: Time) -> Point {
+        match self {
+            Method::Linear => lhs.interpolate(rhs, time),
+            Method::Slerp => lhs.slerp(rhs, time),
+        }
+    }
 }
 
 /// Structure that handles the interpolation.
 #[derive(Debug)]
 pub struct Interpolator {
     index: usize,
-    source: Box<Source>,
+    method: Method,
+    source: Box<dyn Source>,
     points: Vec<Point>,
 }
 
@@ -39,7 +73,7 @@ impl Interpolator {
     /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
     /// let interpolator = Interpolator::new(Box::new(reader)).unwrap();
     /// ```
-    pub fn new(mut source: Box<Source>) -> Result<Interpolator, failure::Error> {
+    pub fn new(mut source: Box<dyn Source>) -> Result<Interpolator, error::Error> {
         let mut points = Vec::with_capacity(2);
         for _ in 0..2 {
             points.push(match source.source()? {
@@ -53,9 +87,25 @@ impl Interpolator {
             points: points,
             source: source,
             index: 1,
+            method: Method::default(),
         })
     }
 
+    /// Sets the interpolation method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::interpolate::{Interpolator, Method};
+    /// use pos::sbet;
+    /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+    /// let mut interpolator = Interpolator::new(Box::new(reader)).unwrap();
+    /// interpolator.set_method(Method::Slerp);
+    /// ```
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+    }
+
     /// Interpolate a new point for the given time.
     ///
     /// # Examples
@@ -63,11 +113,12 @@ impl Interpolator {
     /// ```
     /// use pos::interpolate::Interpolator;
     /// use pos::sbet;
+    /// use pos::time::Time;
     /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
     /// let mut interpolator = Interpolator::new(Box::new(reader)).unwrap();
-    /// let point = interpolator.interpolate(1.516310048360710e5).unwrap();
+    /// let point = interpolator.interpolate(Time(1.516310048360710e5)).unwrap();
     /// ```
-    pub fn interpolate(&mut self, time: f64) -> Result<Point, failure::Error> {
+    pub fn interpolate(&mut self, time: Time) -> Result<Point, error::Error> {
         loop {
             assert!(self.index != 0 && self.index != self.points.len());
             if time < self.points[self.index - 1].time {
@@ -94,10 +145,9 @@ impl Interpolator {
                 break;
             }
         }
-        Ok(self.points[self.index - 1].interpolate(
-            &self.points[self.index],
-            time,
-        ))
+        Ok(self
+            .method
+            .interpolate(&self.points[self.index - 1], &self.points[self.index], time))
     }
 }
 
@@ -105,7 +155,7 @@ impl Interpolator {
 mod tests {
     use super::*;
 
-    use sbet;
+    use crate::sbet;
 
     #[test]
     fn interp_sbet() {
@@ -113,11 +163,25 @@ mod tests {
             sbet::Reader::from_path(
                 "data/2-point\
                                                                                    s.sbet",
-            ).unwrap(),
-        )).unwrap();
-        let time = 1.516310048360710e5;
+            )
+            .unwrap(),
+        ))
+        .unwrap();
+        let time = Time(1.516310048360710e5);
         let point = interpolator.interpolate(time).unwrap();
         assert_eq!(time, point.time);
-        assert!(interpolator.interpolate(0.0).is_err());
+        assert!(interpolator.interpolate(Time(0.0)).is_err());
+    }
+
+    #[test]
+    fn interp_sbet_slerp() {
+        let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        let mut interpolator = Interpolator::new(Box::new(reader)).unwrap();
+        let time = Time(1.51631004836071e5);
+        let linear = interpolator.interpolate(time).unwrap();
+        interpolator.set_method(Method::Slerp);
+        let slerp = interpolator.interpolate(time).unwrap();
+        assert_eq!(linear.latitude, slerp.latitude);
+        assert!((linear.yaw.0 - slerp.yaw.0).abs() < 1e-6);
     }
 }
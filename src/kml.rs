@@ -0,0 +1,345 @@
+//! KML trajectory export, for Google Earth overlays.
+//!
+//! Points are written as a styled placemark, either as a `LineString` or as a `gx:Track` with a
+//! timestamp for each point. Google Earth needs both lists of a track before it can draw it, so
+//! points are buffered until the writer is closed.
+//!
+//! With the `kmz` feature, a KML document can be zipped into a KMZ file with `kmz`.
+
+use crate::error;
+use crate::gpx::DEFAULT_LEAP_SECONDS;
+use crate::point::Point;
+use crate::sink::Sink;
+use crate::time;
+use std::fmt::Debug;
+use std::fs::File;
+#[cfg(feature = "kmz")]
+use std::io::Seek;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// KML errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// A track needs timestamps, so the GPS week must be known.
+    #[error("Writing a track requires the GPS week")]
+    MissingWeek,
+}
+
+/// How Google Earth interprets altitudes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AltitudeMode {
+    /// Altitudes are above sea level.
+    ///
+    /// Point altitudes are usually ellipsoidal heights, so the line may be offset by the geoid
+    /// height.
+    #[default]
+    Absolute,
+    /// Altitudes are ignored and the line is drawn on the ground.
+    ClampToGround,
+    /// Altitudes are above the ground.
+    RelativeToGround,
+}
+
+impl AltitudeMode {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            AltitudeMode::Absolute => "absolute",
+            AltitudeMode::ClampToGround => "clampToGround",
+            AltitudeMode::RelativeToGround => "relativeToGround",
+        }
+    }
+}
+
+/// The geometry that points are written as.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Geometry {
+    /// A line through all points.
+    #[default]
+    LineString,
+    /// A `gx:Track`, which has a timestamp for each point.
+    Track,
+}
+
+/// A KML writer.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// How altitudes are interpreted.
+    pub altitude_mode: AltitudeMode,
+
+    /// The line color, as `aabbggrr` hex.
+    pub color: String,
+
+    /// Only every nth point is written.
+    ///
+    /// The default is one, which writes every point.
+    pub decimation: usize,
+
+    /// The geometry points are written as.
+    pub geometry: Geometry,
+
+    /// The difference between GPS time and UTC, in seconds.
+    pub leap_seconds: f64,
+
+    /// The name of the placemark.
+    pub name: String,
+
+    /// The GPS week of the points' times, which are seconds of the week.
+    ///
+    /// Required for tracks.
+    pub week: Option<u16>,
+
+    /// The line width, in pixels.
+    pub width: f64,
+
+    count: usize,
+    points: Vec<Point>,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::kml::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Ok(Writer::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer that draws a red line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::kml::Writer;
+    /// let writer = Writer::new(Vec::new());
+    /// ```
+    pub fn new(writer: W) -> Writer<W> {
+        Writer {
+            altitude_mode: AltitudeMode::default(),
+            color: "ff0000ff".to_string(),
+            decimation: 1,
+            geometry: Geometry::default(),
+            leap_seconds: DEFAULT_LEAP_SECONDS,
+            name: "Trajectory".to_string(),
+            week: None,
+            width: 2.,
+            count: 0,
+            points: Vec::new(),
+            writer,
+        }
+    }
+
+    /// Adds a point, keeping only every nth point if decimating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::kml::Writer;
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        if self.count % self.decimation.max(1) == 0 {
+            self.points.push(*point);
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Writes the KML document and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::kml::Writer;
+    /// let writer = Writer::new(Vec::new());
+    /// let bytes = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        if self.geometry == Geometry::Track && self.week.is_none() {
+            return Err(Error::MissingWeek.into());
+        }
+        writeln!(self.writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            self.writer,
+            r#"<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">"#
+        )?;
+        writeln!(self.writer, "<Document>")?;
+        writeln!(
+            self.writer,
+            r#"<Style id="trajectory"><LineStyle><color>{}</color><width>{}</width></LineStyle></Style>"#,
+            escape(&self.color),
+            self.width
+        )?;
+        writeln!(
+            self.writer,
+            "<Placemark><name>{}</name><styleUrl>#trajectory</styleUrl>",
+            escape(&self.name)
+        )?;
+        match self.geometry {
+            Geometry::LineString => {
+                writeln!(
+                    self.writer,
+                    "<LineString><altitudeMode>{}</altitudeMode><coordinates>",
+                    self.altitude_mode.as_str()
+                )?;
+                for point in &self.points {
+                    writeln!(
+                        self.writer,
+                        "{:.9},{:.9},{:.3}",
+                        point.longitude.to_degrees(),
+                        point.latitude.to_degrees(),
+                        point.altitude
+                    )?;
+                }
+                writeln!(self.writer, "</coordinates></LineString>")?;
+            }
+            Geometry::Track => {
+                let week = self.week.unwrap_or_default();
+                writeln!(
+                    self.writer,
+                    "<gx:Track><altitudeMode>{}</altitudeMode>",
+                    self.altitude_mode.as_str()
+                )?;
+                for point in &self.points {
+                    writeln!(
+                        self.writer,
+                        "<when>{}</when>",
+                        time::to_utc_string(week, point.time.0, self.leap_seconds)
+                    )?;
+                }
+                for point in &self.points {
+                    writeln!(
+                        self.writer,
+                        "<gx:coord>{:.9} {:.9} {:.3}</gx:coord>",
+                        point.longitude.to_degrees(),
+                        point.latitude.to_degrees(),
+                        point.altitude
+                    )?;
+                }
+                writeln!(self.writer, "</gx:Track>")?;
+            }
+        }
+        writeln!(self.writer, "</Placemark>")?;
+        writeln!(self.writer, "</Document>")?;
+        writeln!(self.writer, "</kml>")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Zips a KML document into a KMZ file.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use pos::kml::{self, Writer};
+/// let document = Writer::new(Vec::new()).close().unwrap();
+/// let cursor = kml::kmz(Cursor::new(Vec::new()), &document).unwrap();
+/// ```
+#[cfg(feature = "kmz")]
+pub fn kmz<W: Write + Seek>(writer: W, document: &[u8]) -> Result<W, error::Error> {
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file("doc.kml", options)?;
+    zip.write_all(document)?;
+    Ok(zip.finish()?)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        let _ = Writer::close(*self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use crate::units::Radians;
+
+    fn points() -> Vec<Point> {
+        (0..5)
+            .map(|i| Point {
+                time: Time(264225.0 + i as f64),
+                latitude: Radians::from_degrees(35.5),
+                longitude: Radians::from_degrees(139.25),
+                altitude: i as f64,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_string() {
+        let mut writer = Writer::new(Vec::new());
+        writer.decimation = 2;
+        writer.altitude_mode = AltitudeMode::ClampToGround;
+        writer.name = "A & B".to_string();
+        for point in points() {
+            writer.write_point(&point).unwrap();
+        }
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        assert!(output.contains("<name>A &amp; B</name>"));
+        assert!(output.contains("<altitudeMode>clampToGround</altitudeMode>"));
+        assert!(output.contains("139.250000000,35.500000000,0.000\n"));
+        assert!(!output.contains(",1.000\n"));
+        assert!(output.contains(",4.000\n"));
+        assert!(output.trim_end().ends_with("</kml>"));
+    }
+
+    #[test]
+    fn track() {
+        let mut writer = Writer::new(Vec::new());
+        writer.geometry = Geometry::Track;
+        writer.write_point(&points()[0]).unwrap();
+        assert!(writer.close().is_err());
+
+        let mut writer = Writer::new(Vec::new());
+        writer.geometry = Geometry::Track;
+        writer.week = Some(2053);
+        for point in points() {
+            writer.write_point(&point).unwrap();
+        }
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        assert_eq!(5, output.matches("<when>").count());
+        assert!(output.contains("<when>2019-05-15T01:23:27.000Z</when>"));
+        assert!(output.contains("<gx:coord>139.250000000 35.500000000 4.000</gx:coord>"));
+    }
+
+    #[cfg(feature = "kmz")]
+    #[test]
+    fn kmz() {
+        use std::io::Cursor;
+
+        let document = Writer::new(Vec::new()).close().unwrap();
+        let bytes = super::kmz(Cursor::new(Vec::new()), &document)
+            .unwrap()
+            .into_inner();
+        assert_eq!(b"PK", &bytes[..2]);
+    }
+}
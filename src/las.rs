@@ -0,0 +1,384 @@
+//! LAS point clouds, for checking a trajectory against the lidar data it georeferenced.
+//!
+//! A LAS file holds one record per lidar return, with scaled integer coordinates and, for most
+//! point formats, the GPS time of the return. Files are read with the `las` crate, and this module
+//! keeps just each return's time, coordinates, and scan angle. The file's coordinate system isn't
+//! interpreted, so it has to be known some other way.
+//!
+//! GPS times are either seconds of the GPS week or, if the header's global encoding says so,
+//! adjusted standard GPS time, which is converted to seconds of the week so that it matches
+//! trajectory times. Use `Reader::poses` to interpolate the sensor's position and attitude at
+//! each return, and `Return::range` and `Return::off_nadir` to sanity check them.
+
+use crate::error;
+use crate::point::Point;
+use crate::time::Time;
+use crate::trajectory::Trajectory;
+use crate::units::Radians;
+use crate::utm::Zone;
+use las::{GpsTimeType, Header};
+use std::fmt;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::vec;
+
+/// Adjusted standard GPS time is GPS time minus this many seconds.
+const ADJUSTED_STANDARD_OFFSET: f64 = 1e9;
+
+/// The number of seconds in a GPS week.
+const SECONDS_PER_WEEK: f64 = 604800.;
+
+/// The number of point records read from the file at a time.
+const BATCH_SIZE: u64 = 4096;
+
+/// LAS errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An error from the `las` crate, e.g. an invalid header or a truncated file.
+    #[error(transparent)]
+    Las(#[from] las::Error),
+
+    /// The point format doesn't have GPS times.
+    #[error("LAS point format {0} doesn't have GPS times")]
+    PointFormat(u8),
+}
+
+/// A lidar return.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Return {
+    /// The GPS time, in seconds of the week.
+    pub time: Time,
+    /// The x coordinate, scaled and offset.
+    pub x: f64,
+    /// The y coordinate, scaled and offset.
+    pub y: f64,
+    /// The z coordinate, scaled and offset.
+    pub z: f64,
+    /// The scan angle, positive to the left of the direction of flight.
+    pub scan_angle: Radians<f64>,
+}
+
+impl Return {
+    /// Returns the distance from a sensor position to this return, in meters.
+    ///
+    /// This assumes that the return's coordinates are UTM eastings and northings in the given
+    /// zone with ellipsoidal heights, like the sensor's. The distance is in grid units, which is
+    /// close enough to catch timing or datum mistakes but not to calibrate a range bias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::las::Return;
+    /// use pos::utm::Zone;
+    /// let zone = Zone { number: 31, north: true };
+    /// let sensor = Point {
+    ///     longitude: Radians::from_degrees(3.0),
+    ///     altitude: 1000.0,
+    ///     ..Default::default()
+    /// };
+    /// let utm = sensor.to_utm_in_zone(zone);
+    /// let lidar_return = Return { x: utm.easting, y: utm.northing, ..Default::default() };
+    /// assert!((lidar_return.range(&sensor, zone) - 1000.0).abs() < 1e-6);
+    /// ```
+    pub fn range(&self, sensor: &Point, zone: Zone) -> f64 {
+        let (horizontal, vertical) = self.offset_from(sensor, zone);
+        horizontal.hypot(vertical)
+    }
+
+    /// Returns the angle between straight down from a sensor position and this return.
+    ///
+    /// With a level sensor this is the size of the scan angle, so a big difference between the
+    /// two points to a bad time stamp or attitude. Coordinates are treated as in `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::las::Return;
+    /// use pos::utm::Zone;
+    /// let zone = Zone { number: 31, north: true };
+    /// let sensor = Point {
+    ///     longitude: Radians::from_degrees(3.0),
+    ///     altitude: 1000.0,
+    ///     ..Default::default()
+    /// };
+    /// let utm = sensor.to_utm_in_zone(zone);
+    /// let lidar_return = Return {
+    ///     x: utm.easting + 1000.0,
+    ///     y: utm.northing,
+    ///     ..Default::default()
+    /// };
+    /// assert!((lidar_return.off_nadir(&sensor, zone).to_degrees() - 45.0).abs() < 1e-6);
+    /// ```
+    pub fn off_nadir(&self, sensor: &Point, zone: Zone) -> Radians<f64> {
+        let (horizontal, vertical) = self.offset_from(sensor, zone);
+        Radians(horizontal.atan2(-vertical))
+    }
+
+    /// Returns the horizontal and vertical offsets from a sensor to this return.
+    fn offset_from(&self, sensor: &Point, zone: Zone) -> (f64, f64) {
+        let utm = sensor.to_utm_in_zone(zone);
+        let horizontal = (self.x - utm.easting).hypot(self.y - utm.northing);
+        (horizontal, self.z - sensor.altitude)
+    }
+}
+
+/// A LAS reader.
+pub struct Reader {
+    points: vec::IntoIter<las::Point>,
+    reader: las::Reader,
+}
+
+impl Reader {
+    /// Opens a reader for a path and reads the header block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::las::Reader;
+    /// let reader = Reader::from_path("data/2-points.las").unwrap();
+    /// assert_eq!(2, reader.header().number_of_points());
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader, error::Error> {
+        Reader::from_las(las::Reader::from_path(path).map_err(Error::from)?)
+    }
+
+    /// Creates a new reader from any `Read` and `Seek` and reads the header block.
+    ///
+    /// Returns an error if the point format doesn't have GPS times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::las::Reader;
+    /// let reader = Reader::new(File::open("data/2-points.las").unwrap()).unwrap();
+    /// assert_eq!((1, 2), reader.header().version().into());
+    /// ```
+    pub fn new<R: Read + Seek + Send + Sync + 'static>(read: R) -> Result<Reader, error::Error> {
+        Reader::from_las(las::Reader::new(read).map_err(Error::from)?)
+    }
+
+    fn from_las(reader: las::Reader) -> Result<Reader, error::Error> {
+        let format = reader.header().point_format();
+        if !format.has_gps_time {
+            return Err(Error::PointFormat(format.to_u8().map_err(Error::from)?).into());
+        }
+        Ok(Reader {
+            points: Vec::new().into_iter(),
+            reader,
+        })
+    }
+
+    /// Returns the file's header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::las::Reader;
+    /// let reader = Reader::from_path("data/2-points.las").unwrap();
+    /// assert_eq!(1, reader.header().point_format().to_u8().unwrap());
+    /// ```
+    pub fn header(&self) -> &Header {
+        self.reader.header()
+    }
+
+    /// Reads the next return.
+    ///
+    /// Returns none once every point record has been read, and an error if the file is shorter
+    /// than its header says.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::las::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.las").unwrap();
+    /// let lidar_return = reader.read_return().unwrap().unwrap();
+    /// assert_eq!(-12.0, lidar_return.scan_angle.to_degrees().round());
+    /// ```
+    pub fn read_return(&mut self) -> Result<Option<Return>, error::Error> {
+        let point = match self.points.next() {
+            Some(point) => point,
+            None => {
+                let points = self
+                    .reader
+                    .read_points(BATCH_SIZE)
+                    .and_then(|data| data.points().collect::<Result<Vec<_>, _>>())
+                    .map_err(Error::from)?;
+                self.points = points.into_iter();
+                match self.points.next() {
+                    Some(point) => point,
+                    None => return Ok(None),
+                }
+            }
+        };
+        // The point format was checked when the reader was created.
+        let mut time = point.gps_time.unwrap_or_default();
+        if self.header().gps_time_type() == GpsTimeType::Standard {
+            time = (time + ADJUSTED_STANDARD_OFFSET).rem_euclid(SECONDS_PER_WEEK);
+        }
+        Ok(Some(Return {
+            time: Time(time),
+            x: point.x,
+            y: point.y,
+            z: point.z,
+            scan_angle: Radians::from_degrees(f64::from(point.scan_angle)),
+        }))
+    }
+
+    /// Returns an iterator over the returns, each with the sensor's position and attitude at the
+    /// time of the return, interpolated from a trajectory with `Trajectory::pose_at`.
+    ///
+    /// Returns outside of the trajectory's times are errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Trajectory;
+    /// use pos::las::Reader;
+    /// use pos::sbet;
+    /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+    /// let trajectory = Trajectory::from_source(reader).unwrap();
+    /// let reader = Reader::from_path("data/2-points.las").unwrap();
+    /// for result in reader.poses(&trajectory) {
+    ///     let (lidar_return, sensor) = result.unwrap();
+    ///     assert_eq!(lidar_return.time, sensor.time);
+    /// }
+    /// ```
+    pub fn poses(self, trajectory: &Trajectory) -> Poses<'_> {
+        Poses {
+            reader: self,
+            trajectory,
+        }
+    }
+}
+
+impl fmt::Debug for Reader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reader")
+            .field("header", self.header())
+            .finish_non_exhaustive()
+    }
+}
+
+impl IntoIterator for Reader {
+    type Item = Result<Return, error::Error>;
+    type IntoIter = ReaderIterator;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over the returns of a reader.
+#[derive(Debug)]
+pub struct ReaderIterator {
+    reader: Reader,
+}
+
+impl Iterator for ReaderIterator {
+    type Item = Result<Return, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_return().transpose()
+    }
+}
+
+/// An iterator over the returns of a reader, with the sensor's pose at each one.
+#[derive(Debug)]
+pub struct Poses<'a> {
+    reader: Reader,
+    trajectory: &'a Trajectory,
+}
+
+impl Iterator for Poses<'_> {
+    type Item = Result<(Return, Point), error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let lidar_return = match self.reader.read_return() {
+            Ok(Some(lidar_return)) => lidar_return,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(
+            self.trajectory
+                .pose_at(lidar_return.time)
+                .map(|point| (lidar_return, point)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+    use byteorder::{ByteOrder, LittleEndian};
+    use std::io::Cursor;
+
+    #[test]
+    fn read_file() {
+        let reader = Reader::from_path("data/2-points.las").unwrap();
+        assert_eq!(1, reader.header().point_format().to_u8().unwrap());
+        assert_eq!(GpsTimeType::Week, reader.header().gps_time_type());
+        let returns = reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(2, returns.len());
+        assert_eq!(Time(151631.004), returns[0].time);
+        assert!((returns[0].x - 502070.0).abs() < 1e-6);
+        assert!((returns[1].z - 7.72).abs() < 1e-6);
+        assert_eq!(5.0, returns[1].scan_angle.to_degrees().round());
+    }
+
+    #[test]
+    fn adjusted_standard_time() {
+        let mut bytes = std::fs::read("data/2-points.las").unwrap();
+        bytes[6] = 1;
+        // 151631.004 seconds into GPS week 2000, in adjusted standard time.
+        let time = 2000. * SECONDS_PER_WEEK + 151631.004 - ADJUSTED_STANDARD_OFFSET;
+        let offset = LittleEndian::read_u32(&bytes[96..]) as usize;
+        LittleEndian::write_f64(&mut bytes[offset + 20..], time);
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        let lidar_return = reader.read_return().unwrap().unwrap();
+        assert!((lidar_return.time.0 - 151631.004).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut bytes = std::fs::read("data/2-points.las").unwrap();
+        bytes[104] = 0;
+        assert!(Reader::new(Cursor::new(bytes.clone())).is_err());
+        bytes[104] = 6;
+        assert!(Reader::new(Cursor::new(bytes.clone())).is_err());
+        bytes[0] = b'X';
+        assert!(Reader::new(Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = std::fs::read("data/2-points.las").unwrap();
+        let _ = bytes.pop();
+        let mut reader = Reader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.read_return().is_err());
+    }
+
+    #[test]
+    fn poses() {
+        let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        let trajectory = Trajectory::from_source(reader).unwrap();
+        let reader = Reader::from_path("data/2-points.las").unwrap();
+        let poses = reader
+            .poses(&trajectory)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, poses.len());
+        for (lidar_return, sensor) in &poses {
+            assert_eq!(lidar_return.time, sensor.time);
+            let zone = Zone::for_position(sensor.latitude, sensor.longitude);
+            assert_eq!(11, zone.number);
+            let range = lidar_return.range(sensor, zone);
+            assert!(range > 100. && range < 103., "{}", range);
+            let difference =
+                lidar_return.off_nadir(sensor, zone).0 - lidar_return.scan_angle.0.abs();
+            assert!(difference.abs() < 1e-3, "{}", difference);
+        }
+    }
+}
@@ -3,27 +3,91 @@
 //! These files come in a few sorts:
 //!
 //! - `pos`: ASCII format
-//! - `sbet`: binary format, with optional associated `rmsmsg` accuracy file
+//! - `sbet`: binary format, with optional associated `smrmsg` accuracy file
 //! - `pof`: Riegl's binary format, with optional associated `poq` accuracy file
+//! - `rtklib`: RTKLIB's ASCII solution format
+//! - `nmea`: NMEA 0183 sentences
+//! - `novatel`: NovAtel SPAN ASCII logs
+//! - `trj`: TerraScan's binary trajectory format
+//! - `igi`: IGI AEROoffice ASCII exports
+//! - `pospac`: Applanix POSPac ASCII exports
+//! - `csv`: delimited text, e.g. CSV, with any columns
 
-#![deny(missing_copy_implementations, missing_debug_implementations, missing_docs, trivial_casts,
-        trivial_numeric_casts, unsafe_code, unused_extern_crates, unused_import_braces,
-        unused_qualifications, unused_results, variant_size_differences)]
-
-extern crate byteorder;
-#[macro_use]
-extern crate failure;
+#![deny(
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences
+)]
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod compare;
+pub mod csv;
+mod error;
+pub mod events;
+pub mod filter;
+pub mod format;
+pub mod geodesy;
+pub mod geoid;
+pub mod geojson;
+pub mod gpx;
+#[cfg(feature = "hdf5")]
+pub mod h5;
+pub mod igi;
 pub mod interpolate;
+pub mod kml;
+#[cfg(feature = "las")]
+pub mod las;
+pub mod nearest;
+pub mod net;
+pub mod nmea;
+pub mod novatel;
+pub mod opk;
+pub mod pipeline;
 pub mod pof;
 pub mod point;
 pub mod poq;
 pub mod pos;
+pub mod pospac;
+#[cfg(feature = "pyo3")]
+pub mod python;
+pub mod qc;
+pub mod quaternion;
+pub mod region;
+#[cfg(feature = "proj")]
+pub mod reproject;
+pub mod rtklib;
 pub mod sbet;
+pub mod sink;
+pub mod smrmsg;
 pub mod source;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod statistics;
+pub mod tail;
+pub mod time;
+pub mod trajectory;
+pub mod trj;
 pub mod units;
+pub mod utm;
 
-pub use interpolate::Interpolator;
-pub use point::{Accuracy, Point};
-pub use source::{AccuracySource, CombinedSource, FileAccuracySource, FileSource, Source};
-pub use units::Radians;
+pub use crate::error::Error;
+pub use crate::format::Format;
+pub use crate::interpolate::Interpolator;
+pub use crate::point::{Accuracy, Point, Status};
+pub use crate::sink::{convert, create, Sink};
+pub use crate::source::{
+    open, AccuracySource, Chunks, CombinedSource, FileAccuracySource, FileSource, ParseMode,
+    Points, Source,
+};
+pub use crate::time::Time;
+pub use crate::trajectory::{Bounds, Gap, Overlap, Trajectory};
+pub use crate::units::{Degrees, Radians};
@@ -0,0 +1,203 @@
+//! Nearest-point queries by position, e.g. to find the epoch closest to a ground control point or
+//! photo center.
+//!
+//! `Trajectory::nearest_to` scans every point, which is fine for a few queries. For many, build
+//! an `Index` once, which is a k-d tree over the points' positions on the ellipsoid and answers
+//! each query in about logarithmic time. With the `rstar` feature, the index is an `rstar` R-tree
+//! instead of the built-in k-d tree, with the same results. Both find the point with the smallest straight-line
+//! distance through the earth, ignoring altitude, which has the same nearest point as the distance
+//! along the surface.
+
+use crate::geodesy::Ecef;
+use crate::point::Point;
+use crate::trajectory::Trajectory;
+use crate::units::Radians;
+#[cfg(feature = "rstar")]
+use rstar::primitives::GeomWithData;
+#[cfg(feature = "rstar")]
+use rstar::RTree;
+
+/// A spatial index of a trajectory's points.
+#[derive(Clone, Debug)]
+pub struct Index<'a> {
+    #[cfg(not(feature = "rstar"))]
+    nodes: Vec<(usize, [f64; 3])>,
+    #[cfg(feature = "rstar")]
+    tree: RTree<GeomWithData<[f64; 3], usize>>,
+    points: &'a [Point],
+}
+
+impl<'a> Index<'a> {
+    /// Builds an index of a trajectory's points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::nearest::Index;
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// let index = Index::new(&trajectory);
+    /// ```
+    pub fn new(trajectory: &'a Trajectory) -> Index<'a> {
+        let points = trajectory.points();
+        let positions = points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| (i, position(point.latitude, point.longitude)));
+        #[cfg(not(feature = "rstar"))]
+        {
+            let mut nodes: Vec<_> = positions.collect();
+            build(&mut nodes, 0);
+            Index { nodes, points }
+        }
+        #[cfg(feature = "rstar")]
+        {
+            let nodes = positions.map(|(i, position)| GeomWithData::new(position, i));
+            let tree = RTree::bulk_load(nodes.collect());
+            Index { tree, points }
+        }
+    }
+
+    /// Returns the point nearest to a latitude and longitude, or none if there are no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::nearest::Index;
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point {
+    ///         time: Time(i as f64),
+    ///         latitude: Radians::from_degrees(i as f64 * 0.001),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let index = Index::new(&trajectory);
+    /// let point = index.nearest(Radians::from_degrees(0.0042), Radians(0.0)).unwrap();
+    /// assert_eq!(Time(4.0), point.time);
+    /// ```
+    pub fn nearest(&self, latitude: Radians<f64>, longitude: Radians<f64>) -> Option<&'a Point> {
+        let target = position(latitude, longitude);
+        #[cfg(not(feature = "rstar"))]
+        let nearest = {
+            let mut best = None;
+            self.search(&self.nodes, 0, &target, &mut best);
+            best.map(|(i, _)| i)
+        };
+        #[cfg(feature = "rstar")]
+        let nearest = self.tree.nearest_neighbor(&target).map(|node| node.data);
+        nearest.map(|i| &self.points[i])
+    }
+
+    #[cfg(not(feature = "rstar"))]
+    fn search(
+        &self,
+        nodes: &[(usize, [f64; 3])],
+        depth: usize,
+        target: &[f64; 3],
+        best: &mut Option<(usize, f64)>,
+    ) {
+        if nodes.is_empty() {
+            return;
+        }
+        let middle = nodes.len() / 2;
+        let (i, node) = nodes[middle];
+        let distance = squared_distance(&node, target);
+        if !matches!(*best, Some((_, best)) if best <= distance) {
+            *best = Some((i, distance));
+        }
+        let axis = depth % 3;
+        let offset = target[axis] - node[axis];
+        let (near, far) = if offset < 0. {
+            (&nodes[..middle], &nodes[middle + 1..])
+        } else {
+            (&nodes[middle + 1..], &nodes[..middle])
+        };
+        self.search(near, depth + 1, target, best);
+        if !matches!(*best, Some((_, best)) if best <= offset * offset) {
+            self.search(far, depth + 1, target, best);
+        }
+    }
+}
+
+/// Returns the point nearest to a latitude and longitude by checking every one.
+pub(crate) fn scan(
+    points: &[Point],
+    latitude: Radians<f64>,
+    longitude: Radians<f64>,
+) -> Option<&Point> {
+    let target = position(latitude, longitude);
+    points.iter().min_by(|a, b| {
+        let a = squared_distance(&position(a.latitude, a.longitude), &target);
+        let b = squared_distance(&position(b.latitude, b.longitude), &target);
+        a.total_cmp(&b)
+    })
+}
+
+/// Sorts nodes into a k-d tree, where each slice's middle node splits the rest on one axis.
+#[cfg(not(feature = "rstar"))]
+fn build(nodes: &mut [(usize, [f64; 3])], depth: usize) {
+    if nodes.len() < 2 {
+        return;
+    }
+    let axis = depth % 3;
+    let middle = nodes.len() / 2;
+    let _ = nodes.select_nth_unstable_by(middle, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+    let (left, right) = nodes.split_at_mut(middle);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+fn position(latitude: Radians<f64>, longitude: Radians<f64>) -> [f64; 3] {
+    let ecef = Ecef::from_geodetic(latitude, longitude, 0.);
+    [ecef.x, ecef.y, ecef.z]
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+
+    #[test]
+    fn index_matches_scan() {
+        let trajectory: Trajectory = (0..500)
+            .map(|i| {
+                let t = i as f64 * 0.05;
+                Point {
+                    time: Time(t),
+                    latitude: Radians::from_degrees(45.0 + 0.01 * t.sin()),
+                    longitude: Radians::from_degrees(179.99 + 0.001 * t),
+                    altitude: 1000.0 * t.cos(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let index = Index::new(&trajectory);
+        for i in 0..50 {
+            let latitude = Radians::from_degrees(44.99 + 0.0005 * i as f64);
+            let longitude = Radians::from_degrees(-179.999 - 0.0002 * i as f64);
+            let expected = trajectory.nearest_to(latitude, longitude).unwrap();
+            assert_eq!(expected, index.nearest(latitude, longitude).unwrap());
+        }
+        let point = trajectory.points()[123];
+        assert_eq!(
+            point.time,
+            index.nearest(point.latitude, point.longitude).unwrap().time
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let trajectory = Trajectory::default();
+        assert!(trajectory.nearest_to(Radians(0.), Radians(0.)).is_none());
+        assert!(Index::new(&trajectory)
+            .nearest(Radians(0.), Radians(0.))
+            .is_none());
+    }
+}
@@ -0,0 +1,275 @@
+//! Real-time position streams from the network.
+//!
+//! Mobile mapping systems often broadcast their position, either as NMEA sentences or as sbet
+//! records, over TCP or UDP. These sources read such streams into points, so the same point
+//! pipeline works online and offline.
+//!
+//! A TCP source reconnects when the connection drops, waiting `retry_interval` between attempts.
+//! A UDP source reads datagrams that each hold whole sentences or records.
+
+use crate::error::Error;
+use crate::nmea;
+use crate::point::Point;
+use crate::sbet;
+use crate::source::Source;
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+/// The default time to wait between connection attempts.
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The largest UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65_536;
+
+/// How the records in a stream are framed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Framing {
+    /// NMEA 0183 sentences, one per line.
+    Nmea,
+    /// Standard little-endian sbet records.
+    Sbet,
+}
+
+/// A source of points from a TCP stream, which reconnects when the connection drops.
+#[derive(Debug)]
+pub struct TcpSource {
+    /// The time to wait between connection attempts.
+    pub retry_interval: Duration,
+    /// The number of times to try to reconnect before giving up, or none to keep trying forever.
+    pub max_attempts: Option<usize>,
+    addresses: Vec<SocketAddr>,
+    framing: Framing,
+    reader: Option<Reader<BufReader<TcpStream>>>,
+}
+
+/// A source of points from UDP datagrams.
+#[derive(Debug)]
+pub struct UdpSource {
+    reader: Reader<BufReader<Datagrams>>,
+    socket: UdpSocket,
+}
+
+#[derive(Debug)]
+enum Reader<R: BufRead> {
+    Nmea(Box<nmea::Reader<R>>),
+    Sbet(sbet::Reader<R>),
+}
+
+#[derive(Debug)]
+struct Datagrams {
+    buffer: Vec<u8>,
+    framing: Framing,
+    len: usize,
+    position: usize,
+    socket: UdpSocket,
+}
+
+impl TcpSource {
+    /// Connects to a position stream.
+    ///
+    /// Returns an error if the first connection fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pos::net::{Framing, TcpSource};
+    /// let source = TcpSource::connect("192.168.1.50:5601", Framing::Nmea).unwrap();
+    /// ```
+    pub fn connect<A: ToSocketAddrs>(address: A, framing: Framing) -> Result<TcpSource, Error> {
+        let addresses = address.to_socket_addrs()?.collect();
+        let mut source = TcpSource {
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            max_attempts: None,
+            addresses,
+            framing,
+            reader: None,
+        };
+        source.reader = Some(source.open()?);
+        Ok(source)
+    }
+
+    fn open(&self) -> io::Result<Reader<BufReader<TcpStream>>> {
+        let stream = TcpStream::connect(&self.addresses[..])?;
+        Ok(Reader::new(self.framing, BufReader::new(stream)))
+    }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        let mut attempts = 0;
+        loop {
+            thread::sleep(self.retry_interval);
+            attempts += 1;
+            match self.open() {
+                Ok(reader) => {
+                    self.reader = Some(reader);
+                    return Ok(());
+                }
+                Err(err) => {
+                    if self.max_attempts.is_some_and(|max| attempts >= max) {
+                        return Err(err.into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Source for TcpSource {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        loop {
+            if self.reader.is_none() {
+                self.reconnect()?;
+            }
+            let result = match self.reader.as_mut() {
+                Some(reader) => reader.read_point(),
+                None => continue,
+            };
+            match result {
+                Ok(Some(point)) => return Ok(Some(point)),
+                Ok(None) => self.reader = None,
+                Err(ref err) if is_disconnect(err) => self.reader = None,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Returns true if an error means the stream was cut off, rather than that its data is bad.
+fn is_disconnect(err: &Error) -> bool {
+    matches!(*err, Error::Io(_) | Error::Sbet(_))
+}
+
+impl UdpSource {
+    /// Binds a socket to receive a position stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::net::{Framing, UdpSource};
+    /// let source = UdpSource::bind("127.0.0.1:0", Framing::Sbet).unwrap();
+    /// ```
+    pub fn bind<A: ToSocketAddrs>(address: A, framing: Framing) -> Result<UdpSource, Error> {
+        let socket = UdpSocket::bind(address)?;
+        let datagrams = Datagrams {
+            buffer: vec![0; MAX_DATAGRAM_SIZE + 1],
+            framing,
+            len: 0,
+            position: 0,
+            socket: socket.try_clone()?,
+        };
+        Ok(UdpSource {
+            reader: Reader::new(framing, BufReader::new(datagrams)),
+            socket,
+        })
+    }
+
+    /// Returns the socket, e.g. to set a read timeout or find its local address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::net::{Framing, UdpSource};
+    /// let source = UdpSource::bind("127.0.0.1:0", Framing::Sbet).unwrap();
+    /// let address = source.socket().local_addr().unwrap();
+    /// ```
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+impl Source for UdpSource {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        self.reader.read_point()
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    fn new(framing: Framing, reader: R) -> Reader<R> {
+        match framing {
+            Framing::Nmea => Reader::Nmea(Box::new(nmea::Reader::new(reader))),
+            Framing::Sbet => Reader::Sbet(sbet::Reader::new(reader)),
+        }
+    }
+
+    fn read_point(&mut self) -> Result<Option<Point>, Error> {
+        match *self {
+            Reader::Nmea(ref mut reader) => reader.read_point(),
+            Reader::Sbet(ref mut reader) => reader.read_point(),
+        }
+    }
+}
+
+impl Read for Datagrams {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.len {
+            self.len = self.socket.recv(&mut self.buffer[..MAX_DATAGRAM_SIZE])?;
+            self.position = 0;
+            // Sentences are lines, so a datagram without a line ending still ends its sentence.
+            if self.framing == Framing::Nmea && self.len > 0 && self.buffer[self.len - 1] != b'\n' {
+                self.buffer[self.len] = b'\n';
+                self.len += 1;
+            }
+        }
+        let n = (self.len - self.position).min(buf.len());
+        buf[..n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::time::Time;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    const NMEA: &str = "$GPGGA,000001.00,4530.0,N,10530.0,W,4,12,0.8,100.0,M,-10.0,M,,\r\n\
+                        $GPGGA,000002.00,4530.0,N,10530.0,W,4,12,0.8,100.0,M,-10.0,M,,\r\n";
+
+    #[test]
+    fn tcp_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream.write_all(NMEA.as_bytes()).unwrap();
+            }
+        });
+        let mut source = TcpSource::connect(address, Framing::Nmea).unwrap();
+        source.retry_interval = Duration::from_millis(10);
+        source.max_attempts = Some(3);
+        let times: Vec<_> = (0..4)
+            .map(|_| source.source().unwrap().unwrap().time.0)
+            .collect();
+        assert_eq!(vec![1., 2., 1., 2.], times);
+        server.join().unwrap();
+        assert!(source.source().is_err());
+    }
+
+    #[test]
+    fn udp() {
+        let mut source = UdpSource::bind("127.0.0.1:0", Framing::Sbet).unwrap();
+        let address = source.socket().local_addr().unwrap();
+        let bytes = ::std::fs::read("data/2-points.sbet").unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let _ = sender.send_to(&bytes, address).unwrap();
+        assert!(source.source().unwrap().is_some());
+        assert!(source.source().unwrap().is_some());
+    }
+
+    #[test]
+    fn udp_nmea() {
+        let mut source = UdpSource::bind("127.0.0.1:0", Framing::Nmea).unwrap();
+        let address = source.socket().local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        for sentence in NMEA.lines() {
+            let _ = sender.send_to(sentence.as_bytes(), address).unwrap();
+        }
+        let _ = sender.send_to(NMEA.as_bytes(), address).unwrap();
+        assert_eq!(Time(1.), source.source().unwrap().unwrap().time);
+    }
+}
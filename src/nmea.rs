@@ -0,0 +1,532 @@
+//! NMEA 0183 sentence streams, as logged by many low-cost GNSS/INS units.
+//!
+//! Points are assembled from the sentences that share a time of day: position from GGA or RMC,
+//! attitude from PASHR or HDT, and the satellite count from GGA and PDOP from GSA. HDT and GSA
+//! sentences have no time and apply to the current epoch. Other sentences, and lines that aren't
+//! sentences, are ignored.
+
+use crate::error;
+use crate::point::{Point, SatelliteCount, Status};
+use crate::source::Source;
+use crate::time::{self, Duration, Time, TimeBase};
+use crate::units::Radians;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// NMEA errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// A sentence's checksum doesn't match its contents.
+    #[error("Invalid checksum: {0}")]
+    Checksum(String),
+
+    /// A sentence has too few fields.
+    #[error("Missing field {0} in sentence: {1}")]
+    MissingField(usize, String),
+
+    /// A field couldn't be parsed.
+    #[error("Invalid field {0} in sentence: {1}")]
+    InvalidField(usize, String),
+}
+
+/// An NMEA reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    date: Option<(i64, i64, i64)>,
+    epoch: Option<Epoch>,
+    reader: R,
+}
+
+#[derive(Debug)]
+struct Epoch {
+    has_position: bool,
+    point: Point,
+    time_of_day: f64,
+}
+
+#[derive(Debug)]
+enum Sentence {
+    Position {
+        time_of_day: f64,
+        position: Option<(f64, f64, Option<f64>)>,
+        date: Option<(i64, i64, i64)>,
+        status: Option<Status>,
+        satellite_count: Option<u16>,
+    },
+    Attitude {
+        time_of_day: f64,
+        roll: f64,
+        pitch: f64,
+        heading: f64,
+    },
+    Heading(f64),
+    Pdop(f64),
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::nmea::Reader;
+    /// let reader = Reader::from_path("data/2-points.nmea").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Ok(Reader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead`, e.g. a serial port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::nmea::Reader;
+    /// let data = "$GPGGA,012345.00,3507.40741,N,13907.40741,E,4,12,0.8,8.423,M,36.700,M,1.0,0000\n";
+    /// let mut reader = Reader::new(data.as_bytes());
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert!((45.123 - point.altitude).abs() < 1e-9);
+    /// ```
+    pub fn new(reader: R) -> Reader<R> {
+        Reader {
+            date: None,
+            epoch: None,
+            reader,
+        }
+    }
+
+    /// Reads the next point from this reader.
+    ///
+    /// A point is returned once a sentence from a later epoch is read, or at the end of the
+    /// stream. Epochs without a valid position are skipped. The altitude is the ellipsoidal
+    /// height, i.e. GGA's altitude plus the geoid separation, and is zero if only RMC
+    /// sentences were read. An empty geoid separation is taken as zero. Angles are from PASHR,
+    /// and HDT heading overrides PASHR heading. The status is from GGA's fix quality.
+    ///
+    /// The time is in UTC seconds of the week once an RMC sentence has given the date, and UTC
+    /// seconds of the day before then, and the point's time base says which.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::nmea::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.nmea").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(45.5, point.yaw.to_degrees());
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                let epoch = self.epoch.take();
+                return Ok(epoch.and_then(|epoch| self.finish(epoch)));
+            }
+            let sentence = match parse(line.trim())? {
+                Some(sentence) => sentence,
+                None => continue,
+            };
+            let time_of_day = match sentence {
+                Sentence::Position { time_of_day, .. } | Sentence::Attitude { time_of_day, .. } => {
+                    Some(time_of_day)
+                }
+                Sentence::Heading(_) | Sentence::Pdop(_) => None,
+            };
+            let mut finished = None;
+            if let Some(time_of_day) = time_of_day {
+                if self
+                    .epoch
+                    .as_ref()
+                    .map_or(true, |epoch| epoch.time_of_day != time_of_day)
+                {
+                    finished = self.epoch.replace(Epoch {
+                        has_position: false,
+                        point: Point::default(),
+                        time_of_day,
+                    });
+                }
+            }
+            self.apply(sentence);
+            if let Some(point) = finished.and_then(|epoch| self.finish(epoch)) {
+                return Ok(Some(point));
+            }
+        }
+    }
+
+    fn apply(&mut self, sentence: Sentence) {
+        let epoch = match self.epoch.as_mut() {
+            Some(epoch) => epoch,
+            None => return,
+        };
+        match sentence {
+            Sentence::Position {
+                position,
+                date,
+                status,
+                satellite_count,
+                ..
+            } => {
+                if let Some((latitude, longitude, altitude)) = position {
+                    epoch.point.latitude = Radians::from_degrees(latitude);
+                    epoch.point.longitude = Radians::from_degrees(longitude);
+                    if let Some(altitude) = altitude {
+                        epoch.point.altitude = altitude;
+                    }
+                    epoch.has_position = true;
+                }
+                if date.is_some() {
+                    self.date = date;
+                }
+                if status.is_some() {
+                    epoch.point.status = status;
+                }
+                if let Some(satellite_count) = satellite_count {
+                    epoch
+                        .point
+                        .accuracy
+                        .get_or_insert_with(Default::default)
+                        .satellite_count = Some(SatelliteCount::Unspecified(satellite_count));
+                }
+            }
+            Sentence::Attitude {
+                roll,
+                pitch,
+                heading,
+                ..
+            } => {
+                epoch.point.roll = Radians::from_degrees(roll);
+                epoch.point.pitch = Radians::from_degrees(pitch);
+                epoch.point.yaw = Radians::from_degrees(heading);
+            }
+            Sentence::Heading(heading) => epoch.point.yaw = Radians::from_degrees(heading),
+            Sentence::Pdop(pdop) => {
+                epoch
+                    .point
+                    .accuracy
+                    .get_or_insert_with(Default::default)
+                    .pdop = pdop
+            }
+        }
+    }
+
+    fn finish(&self, epoch: Epoch) -> Option<Point> {
+        if !epoch.has_position {
+            return None;
+        }
+        let mut point = epoch.point;
+        point.time = Time(epoch.time_of_day);
+        point.time_base = TimeBase::UtcSecondsOfDay;
+        if let Some((year, month, day)) = self.date {
+            point.time += Duration(time::seconds_of_week(year, month, day));
+            point.time_base = TimeBase::UtcSecondsOfWeek;
+        }
+        if let Some(ref mut accuracy) = point.accuracy {
+            accuracy.time = point.time;
+        }
+        Some(point)
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over an NMEA reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// Parses a sentence, returning none if the line isn't a sentence we use.
+fn parse(line: &str) -> Result<Option<Sentence>, Error> {
+    if !line.starts_with('$') {
+        return Ok(None);
+    }
+    let body = match line[1..].split_once('*') {
+        Some((body, checksum)) => {
+            let expected = body.bytes().fold(0, |checksum, b| checksum ^ b);
+            if u8::from_str_radix(checksum, 16).ok() != Some(expected) {
+                return Err(Error::Checksum(line.to_string()));
+            }
+            body
+        }
+        None => &line[1..],
+    };
+    let fields: Vec<_> = body.split(',').collect();
+    let field = |i: usize| {
+        fields
+            .get(i)
+            .copied()
+            .ok_or_else(|| Error::MissingField(i, line.to_string()))
+    };
+    let number = |i: usize| -> Result<f64, Error> {
+        field(i)?
+            .parse()
+            .map_err(|_| Error::InvalidField(i, line.to_string()))
+    };
+    let angle = |i: usize, hemisphere: usize| -> Result<Option<f64>, Error> {
+        let value = field(i)?;
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let value =
+            from_degrees_minutes(value).ok_or_else(|| Error::InvalidField(i, line.to_string()))?;
+        match field(hemisphere)? {
+            "N" | "E" => Ok(Some(value)),
+            "S" | "W" => Ok(Some(-value)),
+            _ => Err(Error::InvalidField(hemisphere, line.to_string())),
+        }
+    };
+    let time_of_day = |i: usize| -> Result<f64, Error> {
+        from_time_of_day(field(i)?).ok_or_else(|| Error::InvalidField(i, line.to_string()))
+    };
+
+    let address = fields[0];
+    let kind = match address.get(2..) {
+        Some(kind) if address.len() == 5 && address.is_ascii() && !address.starts_with('P') => kind,
+        _ => address,
+    };
+    let sentence = match kind {
+        "GGA" => {
+            let position = if field(6)? == "0" {
+                None
+            } else {
+                match (angle(2, 3)?, angle(4, 5)?) {
+                    (Some(latitude), Some(longitude)) => {
+                        // Many receivers leave the geoid separation empty.
+                        let separation = if field(11)?.is_empty() {
+                            0.
+                        } else {
+                            number(11)?
+                        };
+                        Some((latitude, longitude, Some(number(9)? + separation)))
+                    }
+                    _ => None,
+                }
+            };
+            Sentence::Position {
+                time_of_day: time_of_day(1)?,
+                position,
+                date: None,
+                status: status(field(6)?),
+                satellite_count: field(7)?.parse().ok(),
+            }
+        }
+        "RMC" => {
+            let position = if field(2)? != "A" {
+                None
+            } else {
+                match (angle(3, 4)?, angle(5, 6)?) {
+                    (Some(latitude), Some(longitude)) => Some((latitude, longitude, None)),
+                    _ => None,
+                }
+            };
+            let date =
+                from_date(field(9)?).ok_or_else(|| Error::InvalidField(9, line.to_string()))?;
+            Sentence::Position {
+                time_of_day: time_of_day(1)?,
+                position,
+                date: Some(date),
+                status: None,
+                satellite_count: None,
+            }
+        }
+        "PASHR" => Sentence::Attitude {
+            time_of_day: time_of_day(1)?,
+            heading: number(2)?,
+            roll: number(4)?,
+            pitch: number(5)?,
+        },
+        "HDT" => Sentence::Heading(number(1)?),
+        "GSA" => {
+            if field(15)?.is_empty() {
+                return Ok(None);
+            }
+            Sentence::Pdop(number(15)?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(sentence))
+}
+
+/// Returns the status for a GGA fix quality.
+fn status(quality: &str) -> Option<Status> {
+    match quality {
+        "1" => Some(Status::Sps),
+        "2" => Some(Status::Dgps),
+        "4" => Some(Status::Fixed),
+        "5" => Some(Status::Float),
+        "6" => Some(Status::Predicted),
+        _ => None,
+    }
+}
+
+/// Parses `dddmm.mmmm` into decimal degrees.
+fn from_degrees_minutes(value: &str) -> Option<f64> {
+    let point = value.find('.').unwrap_or(value.len());
+    if point < 2 || !value.is_ascii() {
+        return None;
+    }
+    let degrees: f64 = value[..point - 2].parse().ok()?;
+    let minutes: f64 = value[point - 2..].parse().ok()?;
+    Some(degrees + minutes / 60.)
+}
+
+/// Parses `hhmmss.ss` into seconds of the day.
+fn from_time_of_day(value: &str) -> Option<f64> {
+    if value.len() < 6 || !value.is_ascii() {
+        return None;
+    }
+    let hours: f64 = value[..2].parse().ok()?;
+    let minutes: f64 = value[2..4].parse().ok()?;
+    let seconds: f64 = value[4..].parse().ok()?;
+    Some(hours * 3600. + minutes * 60. + seconds)
+}
+
+/// Parses `ddmmyy` into a year, month, and day.
+fn from_date(value: &str) -> Option<(i64, i64, i64)> {
+    if value.len() != 6 || !value.is_ascii() {
+        return None;
+    }
+    let day = value[..2].parse().ok()?;
+    let month = value[2..4].parse().ok()?;
+    let year: i64 = value[4..].parse().ok()?;
+    let year = if year < 80 { 2000 + year } else { 1900 + year };
+    Some((year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::trajectory::Trajectory;
+
+    #[test]
+    fn read_file() {
+        let points = Reader::from_path("data/2-points.nmea")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        let seconds_of_day = 1. * 3600. + 23. * 60. + 45.;
+        assert_eq!(Time(3. * 86400. + seconds_of_day), points[0].time);
+        assert_eq!(TimeBase::UtcSecondsOfWeek, points[0].time_base);
+        assert!((35.1234568 - points[0].latitude.to_degrees()).abs() < 1e-7);
+        assert!((139.1234568 - points[0].longitude.to_degrees()).abs() < 1e-7);
+        assert!((45.123 - points[0].altitude).abs() < 1e-9);
+        assert_eq!(1.25, points[0].roll.to_degrees());
+        assert_eq!(-2.5, points[0].pitch.to_degrees());
+        assert_eq!(45.5, points[0].yaw.to_degrees());
+        assert!((46.0 - points[1].yaw.to_degrees()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn no_fix() {
+        let data = "$GPGGA,000001.00,,,,,0,00,99.9,,M,,M,,\n\
+                    garbage\n\
+                    $GPGGA,000002.00,4530.0,S,10530.0,W,1,05,1.0,100.0,M,-10.0,M,,\n";
+        let points = Reader::new(data.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(Time(2.0), points[0].time);
+        assert_eq!(TimeBase::UtcSecondsOfDay, points[0].time_base);
+        assert_eq!(-45.5, points[0].latitude.to_degrees());
+        assert_eq!(-105.5, points[0].longitude.to_degrees());
+        assert_eq!(90.0, points[0].altitude);
+        assert_eq!(Some(Status::Sps), points[0].status);
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(5)),
+            points[0].accuracy.unwrap().satellite_count
+        );
+    }
+
+    #[test]
+    fn empty_separation() {
+        let data = "$GPGGA,000002.00,4530.0,S,10530.0,W,1,05,1.0,100.0,M,,M,,\n";
+        let points = Reader::new(data.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(100.0, points[0].altitude);
+    }
+
+    #[test]
+    fn mixed_time_bases() {
+        let data = "$GPGGA,012343.00,4530.0,S,10530.0,W,1,05,1.0,100.0,M,-10.0,M,,\n\
+                    $GPGGA,012344.00,4530.0,S,10530.0,W,1,05,1.0,100.0,M,-10.0,M,,\n\
+                    $GPRMC,012345.00,A,3507.40741,N,13907.40741,E,10.5,45.0,150519,,,D*5B\n";
+        let points = Reader::new(data.as_bytes())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(TimeBase::UtcSecondsOfDay, points[0].time_base);
+        assert_eq!(TimeBase::UtcSecondsOfWeek, points[2].time_base);
+        assert!(Trajectory::from_source(Reader::new(data.as_bytes())).is_err());
+    }
+
+    #[test]
+    fn pdop() {
+        let data = "$GPGGA,000002.00,4530.0,S,10530.0,W,4,12,0.8,100.0,M,-10.0,M,,\n\
+                    $GPGSA,A,3,01,02,03,04,,,,,,,,,1.6,0.8,1.4\n\
+                    $GPGSA,A,1,,,,,,,,,,,,,,,\n";
+        let point = Reader::new(data.as_bytes()).read_point().unwrap().unwrap();
+        let accuracy = point.accuracy.unwrap();
+        assert_eq!(Time(2.0), accuracy.time);
+        assert_eq!(1.6, accuracy.pdop);
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(12)),
+            accuracy.satellite_count
+        );
+    }
+
+    #[test]
+    fn checksum() {
+        let data = "$GPHDT,46.00,T*38\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+    }
+
+    #[test]
+    fn fields() {
+        assert_eq!(Some(45.5), from_degrees_minutes("4530.0"));
+        assert_eq!(Some(105.5), from_degrees_minutes("10530"));
+        assert_eq!(None, from_degrees_minutes("5.0"));
+        assert_eq!(Some(3725.5), from_time_of_day("010205.5"));
+        assert_eq!(Some((2019, 5, 15)), from_date("150519"));
+        assert_eq!(Some((1999, 12, 31)), from_date("311299"));
+    }
+
+    #[test]
+    fn multibyte() {
+        assert_eq!(None, from_degrees_minutes("1é.5"));
+        assert_eq!(None, from_time_of_day("01é205.5"));
+        assert_eq!(None, from_date("15é519"));
+        assert!(parse("$GPGGA,01é205.5,4530.0,S,10530.0,W,1,05,1.0,100.0,M,-10.0,M,,").is_err());
+        assert!(parse("$Gé,1").unwrap().is_none());
+        assert!(parse("$éGGA,1").unwrap().is_none());
+    }
+}
@@ -0,0 +1,391 @@
+//! NovAtel SPAN ASCII logs.
+//!
+//! INSPVA records, in long (`#INSPVAA`) or short (`%INSPVASA`) form, hold the INS position,
+//! velocity, and attitude. INSPVAX records (`#INSPVAXA`) also hold their standard deviations.
+//! BESTPOS records (`#BESTPOSA`) hold the GNSS position and its standard deviations. Other logs are
+//! ignored.
+
+use crate::error;
+use crate::point::{Accuracy, Point, SatelliteCount, Status};
+use crate::source::Source;
+use crate::time::Time;
+use crate::units::Radians;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// The polynomial of NovAtel's 32-bit CRC.
+const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// NovAtel errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// A record's CRC doesn't match its contents.
+    #[error("Invalid CRC: {0}")]
+    Crc(String),
+
+    /// A record has too few fields.
+    #[error("Missing field {0} in record: {1}")]
+    MissingField(usize, String),
+
+    /// A record has no header.
+    #[error("Missing header in record: {0}")]
+    MissingHeader(String),
+}
+
+/// A NovAtel log type that can be read into points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Log {
+    /// INS position, velocity, and attitude.
+    Inspva,
+    /// INS position, velocity, and attitude, with standard deviations.
+    Inspvax,
+    /// Best available GNSS position, with standard deviations.
+    Bestpos,
+}
+
+/// A NovAtel ASCII log reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    logs: Vec<Log>,
+    reader: R,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::novatel::Reader;
+    /// let reader = Reader::from_path("data/2-points.novatel").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Ok(Reader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead`.
+    ///
+    /// The reader reads INSPVA, INSPVAX, and BESTPOS records by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::novatel::Reader;
+    /// use pos::time::Time;
+    /// let data = "%INSPVASA,2053,100.0;2053,100.0,35.1,139.1,45.1,0,0,0,1,2,3,GOOD\n";
+    /// let mut reader = Reader::new(data.as_bytes());
+    /// assert_eq!(Time(100.0), reader.read_point().unwrap().unwrap().time);
+    /// ```
+    pub fn new(reader: R) -> Reader<R> {
+        Reader {
+            logs: vec![Log::Inspva, Log::Inspvax, Log::Bestpos],
+            reader,
+        }
+    }
+
+    /// Sets the logs that are read into points.
+    ///
+    /// SPAN receivers often log INSPVA and BESTPOS together, at different rates, so use this to
+    /// read only one of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::novatel::{Log, Reader};
+    /// let mut reader = Reader::from_path("data/2-points.novatel").unwrap();
+    /// reader.set_logs(&[Log::Inspva]);
+    /// assert_eq!(2, reader.into_iter().count());
+    /// ```
+    pub fn set_logs(&mut self, logs: &[Log]) {
+        self.logs = logs.to_vec();
+    }
+
+    /// Reads a point from the next INSPVA, INSPVAX, or BESTPOS record.
+    ///
+    /// Times are GPS seconds of the week, and altitudes are ellipsoidal heights. INSPVA and
+    /// INSPVAX points have north, east, and up velocities as the y, x, and z velocities, and
+    /// azimuth as yaw. INSPVAX points also have the position, velocity, and attitude standard
+    /// deviations as their accuracy. INSPVAX and BESTPOS points have their position type as their
+    /// status. BESTPOS points have the latitude, longitude, and height standard deviations as their
+    /// accuracy, and no attitude.
+    ///
+    /// Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::novatel::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.novatel").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(0.025, point.accuracy.unwrap().z);
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim();
+            if !line.starts_with('#') && !line.starts_with('%') {
+                continue;
+            }
+            let record = match line[1..].split_once('*') {
+                Some((record, crc)) => {
+                    if u32::from_str_radix(crc, 16).ok() != Some(crc32(record.as_bytes())) {
+                        return Err(Error::Crc(line.to_string()).into());
+                    }
+                    record
+                }
+                None => &line[1..],
+            };
+            let (header, body) = record
+                .split_once(';')
+                .ok_or_else(|| Error::MissingHeader(line.to_string()))?;
+            let header: Vec<_> = header.split(',').collect();
+            let body: Vec<_> = body.split(',').collect();
+            let field = |fields: &[&str], i: usize| -> Result<f64, error::Error> {
+                let value = fields
+                    .get(i)
+                    .ok_or_else(|| Error::MissingField(i, line.to_string()))?;
+                Ok(value.parse()?)
+            };
+            let log = match header[0] {
+                "INSPVAA" | "INSPVASA" => Log::Inspva,
+                "INSPVAXA" => Log::Inspvax,
+                "BESTPOSA" => Log::Bestpos,
+                _ => continue,
+            };
+            if !self.logs.contains(&log) {
+                continue;
+            }
+            let point = match log {
+                Log::Inspva => Point {
+                    time: Time(field(&body, 1)?),
+                    latitude: Radians::from_degrees(field(&body, 2)?),
+                    longitude: Radians::from_degrees(field(&body, 3)?),
+                    altitude: field(&body, 4)?,
+                    y_velocity: Some(field(&body, 5)?),
+                    x_velocity: Some(field(&body, 6)?),
+                    z_velocity: Some(field(&body, 7)?),
+                    roll: Radians::from_degrees(field(&body, 8)?),
+                    pitch: Radians::from_degrees(field(&body, 9)?),
+                    yaw: Radians::from_degrees(field(&body, 10)?),
+                    ..Default::default()
+                },
+                Log::Inspvax => {
+                    let time = Time(field(&header, 6)?);
+                    Point {
+                        time,
+                        latitude: Radians::from_degrees(field(&body, 2)?),
+                        longitude: Radians::from_degrees(field(&body, 3)?),
+                        altitude: field(&body, 4)? + field(&body, 5)?,
+                        y_velocity: Some(field(&body, 6)?),
+                        x_velocity: Some(field(&body, 7)?),
+                        z_velocity: Some(field(&body, 8)?),
+                        roll: Radians::from_degrees(field(&body, 9)?),
+                        pitch: Radians::from_degrees(field(&body, 10)?),
+                        yaw: Radians::from_degrees(field(&body, 11)?),
+                        status: body.get(1).and_then(|&position_type| status(position_type)),
+                        accuracy: Some(Accuracy {
+                            time,
+                            y: field(&body, 12)?,
+                            x: field(&body, 13)?,
+                            z: field(&body, 14)?,
+                            y_velocity: Some(field(&body, 15)?),
+                            x_velocity: Some(field(&body, 16)?),
+                            z_velocity: Some(field(&body, 17)?),
+                            roll: Radians::from_degrees(field(&body, 18)?),
+                            pitch: Radians::from_degrees(field(&body, 19)?),
+                            yaw: Radians::from_degrees(field(&body, 20)?),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }
+                }
+                Log::Bestpos => {
+                    let time = Time(field(&header, 6)?);
+                    let satellite_count: u16 = body
+                        .get(14)
+                        .ok_or_else(|| Error::MissingField(14, line.to_string()))?
+                        .parse()?;
+                    Point {
+                        time,
+                        latitude: Radians::from_degrees(field(&body, 2)?),
+                        longitude: Radians::from_degrees(field(&body, 3)?),
+                        altitude: field(&body, 4)? + field(&body, 5)?,
+                        status: body.get(1).and_then(|&position_type| status(position_type)),
+                        accuracy: Some(Accuracy {
+                            time,
+                            y: field(&body, 7)?,
+                            x: field(&body, 8)?,
+                            z: field(&body, 9)?,
+                            satellite_count: Some(SatelliteCount::Unspecified(satellite_count)),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }
+                }
+            };
+            return Ok(Some(point));
+        }
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over a NovAtel reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// Returns the status for a BESTPOS or INSPVAX position type.
+fn status(position_type: &str) -> Option<Status> {
+    match position_type {
+        "NARROW_INT" | "WIDE_INT" | "L1_INT" | "INS_RTKFIXED" => Some(Status::Fixed),
+        "NARROW_FLOAT" | "L1_FLOAT" | "IONOFREE_FLOAT" | "INS_RTKFLOAT" => Some(Status::Float),
+        "PPP" | "INS_PPP" => Some(Status::Ppp),
+        "PSRDIFF" | "INS_PSRDIFF" => Some(Status::Dgps),
+        "WAAS" | "INS_SBAS" => Some(Status::Sbas),
+        "SINGLE" | "INS_PSRSP" => Some(Status::Sps),
+        "PROPAGATED" | "INS" => Some(Status::Predicted),
+        _ => None,
+    }
+}
+
+/// Computes NovAtel's 32-bit CRC of the bytes between the sync character and the `*`.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_file() {
+        let points = Reader::from_path("data/2-points.novatel")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(3, points.len());
+
+        let bestpos = points[0];
+        assert_eq!(Time(264225.0), bestpos.time);
+        assert!((45.123 - bestpos.altitude).abs() < 1e-9);
+        let accuracy = bestpos.accuracy.unwrap();
+        assert_eq!(0.012, accuracy.y);
+        assert_eq!(0.011, accuracy.x);
+        assert_eq!(Some(Status::Fixed), bestpos.status);
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(18)),
+            accuracy.satellite_count
+        );
+
+        let inspva = points[1];
+        assert_eq!(Time(264225.0), inspva.time);
+        assert!((35.12345678901 - inspva.latitude.to_degrees()).abs() < 1e-9);
+        assert_eq!(45.123, inspva.altitude);
+        assert_eq!(Some(0.5), inspva.y_velocity);
+        assert_eq!(Some(1.25), inspva.x_velocity);
+        assert_eq!(Some(-0.05), inspva.z_velocity);
+        assert!((1.25 - inspva.roll.to_degrees()).abs() < 1e-9);
+        assert!((45.5 - inspva.yaw.to_degrees()).abs() < 1e-9);
+        assert!(inspva.accuracy.is_none());
+
+        assert_eq!(Time(264225.005), points[2].time);
+    }
+
+    #[test]
+    fn logs() {
+        let mut reader = Reader::from_path("data/2-points.novatel").unwrap();
+        reader.set_logs(&[Log::Bestpos]);
+        assert_eq!(1, reader.into_iter().count());
+    }
+
+    #[test]
+    fn inspvax() {
+        let data = "#INSPVAXA,COM1,0,73.5,FINESTEERING,2053,264225.000,00000040,0c68,16248;\
+                    INS_SOLUTION_GOOD,INS_RTKFIXED,35.12345678901,139.12345678901,8.4230,36.7000,\
+                    0.5000,1.2500,-0.0500,1.250000000,-2.500000000,45.500000000,0.0110,0.0120,\
+                    0.0250,0.0031,0.0032,0.0043,0.0051,0.0052,0.0153,00000000,0*15f05e77\n";
+        let point = Reader::new(data.as_bytes()).read_point().unwrap().unwrap();
+        assert_eq!(Time(264225.0), point.time);
+        assert!((45.123 - point.altitude).abs() < 1e-9);
+        assert_eq!(Some(1.25), point.x_velocity);
+        assert!((45.5 - point.yaw.to_degrees()).abs() < 1e-9);
+        let accuracy = point.accuracy.unwrap();
+        assert_eq!(0.011, accuracy.y);
+        assert_eq!(0.012, accuracy.x);
+        assert_eq!(0.025, accuracy.z);
+        assert_eq!(Some(0.0032), accuracy.x_velocity);
+        assert!((0.0153 - accuracy.yaw.to_degrees()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn satellite_count() {
+        let record = |count: &str| {
+            format!(
+                "#BESTPOSA,COM1,0,78.5,FINESTEERING,2053,264225.000,02000040,b1f6,16248;\
+                 SOL_COMPUTED,NARROW_INT,35.1,139.1,8.4,36.7,WGS84,0.012,0.011,0.025,\"0\",\
+                 1.000,0.000,22,{},18,16,00,21,3f,37\n",
+                count
+            )
+        };
+        let point = Reader::new(record("18").as_bytes())
+            .read_point()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(18)),
+            point.accuracy.unwrap().satellite_count
+        );
+        for count in &["-1", "18.5", "70000"] {
+            assert!(Reader::new(record(count).as_bytes()).read_point().is_err());
+        }
+    }
+
+    #[test]
+    fn crc() {
+        let data = "%INSPVASA,2053,100.0;2053,100.0,35.1,139.1,45.1,0,0,0,1,2,3,GOOD*00000000\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+        assert_eq!(0, crc32(&[]));
+        assert_eq!(0x3ab5_51ce, crc32(b"a"));
+    }
+}
@@ -0,0 +1,424 @@
+//! Exterior orientation export for photogrammetry, as omega, phi, and kappa.
+//!
+//! Photogrammetry software such as Pix4D and Metashape wants each image's camera position in a
+//! projected coordinate system and its orientation as omega, phi, and kappa: the rotations about
+//! the map's x (east), y (north), and z (up) axes that take the camera frame to the map frame,
+//! `R = Rx(ω) Ry(φ) Rz(κ)`. The camera frame has x to the right of the image, y to the top, and
+//! z out of the back of the camera, so a level nadir camera with the top of the image to the north
+//! has zero omega, phi, and kappa. Pix4D and Metashape use this same convention. Both also accept
+//! yaw, pitch, and roll instead (see `Convention`).
+//!
+//! Positions are projected into UTM, and headings are corrected for the grid convergence so that
+//! kappa is relative to grid north. The map frame is taken to be the local level frame, which is
+//! the usual approximation over a project area. Apply a lever arm and boresight to the points
+//! first (see `Point::apply_lever_arm` and `Point::apply_boresight`), and convert the altitudes
+//! with a geoid if the project needs orthometric heights.
+
+use crate::error;
+use crate::point::Point;
+use crate::units::Radians;
+use crate::utm::Zone;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Which way the top of the image points on the platform, for a camera looking straight down.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImageTop {
+    /// The top of the image is towards the front of the platform.
+    #[default]
+    Forward,
+    /// The top of the image is towards the back of the platform.
+    Backward,
+    /// The top of the image is towards the left side of the platform.
+    Left,
+    /// The top of the image is towards the right side of the platform.
+    Right,
+}
+
+impl ImageTop {
+    /// Returns the rotation matrix from the camera frame to the body frame.
+    fn rotation_matrix(&self) -> [[f64; 3]; 3] {
+        match *self {
+            ImageTop::Forward => [[0., 1., 0.], [1., 0., 0.], [0., 0., -1.]],
+            ImageTop::Backward => [[0., -1., 0.], [-1., 0., 0.], [0., 0., -1.]],
+            ImageTop::Left => [[1., 0., 0.], [0., -1., 0.], [0., 0., -1.]],
+            ImageTop::Right => [[-1., 0., 0.], [0., 1., 0.], [0., 0., -1.]],
+        }
+    }
+}
+
+/// How a camera's orientation is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Convention {
+    /// Omega, phi, and kappa, with `R = Rx(ω) Ry(φ) Rz(κ)`.
+    #[default]
+    Opk,
+    /// Yaw, pitch, and roll, with `R = Rz(-yaw) Rx(pitch) Ry(roll)`.
+    ///
+    /// Yaw is the direction of the top of the image clockwise from grid north, in [0, 2π). Pitch
+    /// tilts the camera to look towards the top of the image, and roll then rotates it about the
+    /// image's vertical axis. As with omega, phi, and kappa, a level nadir camera with the top of
+    /// the image to the north has zero yaw, pitch, and roll.
+    Ypr,
+}
+
+/// The software that an exterior orientation file is written for, which sets its header.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Software {
+    /// Pix4D's image geolocation and orientation file.
+    #[default]
+    Pix4d,
+    /// Metashape's reference file, whose header line is a comment.
+    Metashape,
+}
+
+/// The position and orientation of a camera in a projected coordinate system.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExteriorOrientation {
+    /// The UTM easting, in meters.
+    pub easting: f64,
+    /// The UTM northing, in meters.
+    pub northing: f64,
+    /// The altitude, in meters.
+    pub altitude: f64,
+    /// The rotation about the map's x axis.
+    pub omega: Radians<f64>,
+    /// The rotation about the map's y axis.
+    pub phi: Radians<f64>,
+    /// The rotation about the map's z axis.
+    pub kappa: Radians<f64>,
+}
+
+impl ExteriorOrientation {
+    /// Computes the exterior orientation of a camera at a point, in a UTM zone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::opk::{ExteriorOrientation, ImageTop};
+    /// use pos::utm::Zone;
+    /// let point = Point { yaw: Radians::from_degrees(90.0), ..Default::default() };
+    /// let zone = Zone { number: 31, north: true };
+    /// let orientation = ExteriorOrientation::new(&point, zone, ImageTop::Forward);
+    /// assert!(orientation.omega.0.abs() < 1e-12);
+    /// assert!((orientation.kappa.to_degrees() + 90.0).abs() < 1e-6);
+    /// ```
+    pub fn new(point: &Point, zone: Zone, image_top: ImageTop) -> ExteriorOrientation {
+        let utm = point.to_utm_in_zone(zone);
+        let north = Point {
+            latitude: point.latitude + Radians(1e-7),
+            ..*point
+        }
+        .to_utm_in_zone(zone);
+        let convergence =
+            Radians((north.easting - utm.easting).atan2(north.northing - utm.northing));
+        let grid = Point {
+            yaw: point.true_heading() + convergence,
+            wander_angle: None,
+            ..*point
+        };
+        let body = grid.rotation_matrix();
+        let camera = image_top.rotation_matrix();
+        // North-east-down to east-north-up swaps the first two rows and negates the third.
+        let mut matrix = [[0.; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            let (ned, sign) = match i {
+                0 => (1, 1.),
+                1 => (0, 1.),
+                _ => (2, -1.),
+            };
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = sign * (0..3).map(|k| body[ned][k] * camera[k][j]).sum::<f64>();
+            }
+        }
+        ExteriorOrientation {
+            easting: utm.easting,
+            northing: utm.northing,
+            altitude: point.altitude,
+            omega: Radians((-matrix[1][2]).atan2(matrix[2][2])),
+            phi: Radians(matrix[0][2].clamp(-1., 1.).asin()),
+            kappa: Radians((-matrix[0][1]).atan2(matrix[0][0])),
+        }
+    }
+
+    /// Returns the camera's yaw, pitch, and roll, as defined by `Convention::Ypr`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::opk::{ExteriorOrientation, ImageTop};
+    /// use pos::utm::Zone;
+    /// let point = Point { yaw: Radians::from_degrees(90.0), ..Default::default() };
+    /// let zone = Zone { number: 31, north: true };
+    /// let orientation = ExteriorOrientation::new(&point, zone, ImageTop::Forward);
+    /// let (yaw, pitch, _) = orientation.yaw_pitch_roll();
+    /// assert!((yaw.to_degrees() - 90.0).abs() < 1e-6);
+    /// assert!(pitch.0.abs() < 1e-12);
+    /// ```
+    pub fn yaw_pitch_roll(&self) -> (Radians<f64>, Radians<f64>, Radians<f64>) {
+        let (sin_omega, cos_omega) = self.omega.0.sin_cos();
+        let (sin_phi, cos_phi) = self.phi.0.sin_cos();
+        let (sin_kappa, cos_kappa) = self.kappa.0.sin_cos();
+        // The elements of Rx(ω) Ry(φ) Rz(κ) that Rz(-yaw) Rx(pitch) Ry(roll) is solved from.
+        let m01 = -cos_phi * sin_kappa;
+        let m11 = cos_omega * cos_kappa - sin_omega * sin_phi * sin_kappa;
+        let m20 = sin_omega * sin_kappa - cos_omega * sin_phi * cos_kappa;
+        let m21 = sin_omega * cos_kappa + cos_omega * sin_phi * sin_kappa;
+        let m22 = cos_omega * cos_phi;
+        (
+            Radians(m01.atan2(m11)).wrap_two_pi(),
+            Radians(m21.clamp(-1., 1.).asin()),
+            Radians((-m20).atan2(m22)),
+        )
+    }
+}
+
+/// An exterior orientation writer, with one comma-separated line per image.
+///
+/// Angles are written in degrees.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// Which way the top of the image points on the platform.
+    pub image_top: ImageTop,
+
+    /// Whether orientations are written as omega, phi, and kappa, or yaw, pitch, and roll.
+    pub convention: Convention,
+
+    /// The UTM zone of the coordinates.
+    ///
+    /// If none, the zone of the first image is used for every image.
+    pub zone: Option<Zone>,
+
+    header: bool,
+    software: Software,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::opk::{Software, Writer};
+    /// let writer = Writer::from_path("/dev/null", Software::Pix4d).unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        software: Software,
+    ) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Ok(Writer::new(BufWriter::new(File::create(path)?), software))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer.
+    ///
+    /// The header is written with the first image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::opk::{Software, Writer};
+    /// let writer = Writer::new(Vec::new(), Software::Metashape);
+    /// ```
+    pub fn new(writer: W, software: Software) -> Writer<W> {
+        Writer {
+            image_top: ImageTop::default(),
+            convention: Convention::default(),
+            zone: None,
+            header: false,
+            software,
+            writer,
+        }
+    }
+
+    /// Writes the exterior orientation of an image taken at a point, e.g. one interpolated with
+    /// `Trajectory::at_events`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::opk::{Software, Writer};
+    /// let mut writer = Writer::new(Vec::new(), Software::Pix4d);
+    /// writer.write_image("IMG_0001.JPG", &Point::default()).unwrap();
+    /// ```
+    pub fn write_image(&mut self, label: &str, point: &Point) -> Result<(), error::Error> {
+        let zone = *self
+            .zone
+            .get_or_insert_with(|| Zone::for_position(point.latitude, point.longitude));
+        if !self.header {
+            let header = match (self.software, self.convention) {
+                (Software::Pix4d, Convention::Opk) => "imagename,X,Y,Z,omega,phi,kappa",
+                (Software::Pix4d, Convention::Ypr) => "imagename,X,Y,Z,yaw,pitch,roll",
+                (Software::Metashape, Convention::Opk) => "# Label,X,Y,Z,Omega,Phi,Kappa",
+                (Software::Metashape, Convention::Ypr) => "# Label,X,Y,Z,Yaw,Pitch,Roll",
+            };
+            writeln!(self.writer, "{}", header)?;
+            self.header = true;
+        }
+        let orientation = ExteriorOrientation::new(point, zone, self.image_top);
+        let angles = match self.convention {
+            Convention::Opk => (orientation.omega, orientation.phi, orientation.kappa),
+            Convention::Ypr => orientation.yaw_pitch_roll(),
+        };
+        writeln!(
+            self.writer,
+            "{},{:.3},{:.3},{:.3},{:.6},{:.6},{:.6}",
+            label,
+            orientation.easting,
+            orientation.northing,
+            orientation.altitude,
+            angles.0.to_degrees(),
+            angles.1.to_degrees(),
+            angles.2.to_degrees()
+        )?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::opk::{Software, Writer};
+    /// let writer = Writer::new(Vec::new(), Software::Pix4d);
+    /// let bytes = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn degrees(orientation: &ExteriorOrientation) -> [f64; 3] {
+        [
+            orientation.omega.to_degrees(),
+            orientation.phi.to_degrees(),
+            orientation.kappa.to_degrees(),
+        ]
+    }
+
+    fn assert_close(expected: [f64; 3], actual: [f64; 3]) {
+        for (expected, actual) in expected.iter().zip(&actual) {
+            let difference = Radians::from_degrees(expected - actual).wrap_pi();
+            assert!(difference.0.abs() < 1e-8, "{:?} {:?}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn central_meridian() {
+        let zone = Zone {
+            number: 31,
+            north: true,
+        };
+        let point = |roll: f64, pitch: f64, yaw: f64| Point {
+            latitude: Radians::from_degrees(45.0),
+            longitude: Radians::from_degrees(3.0),
+            roll: Radians::from_degrees(roll),
+            pitch: Radians::from_degrees(pitch),
+            yaw: Radians::from_degrees(yaw),
+            ..Default::default()
+        };
+        let opk = |point: Point, image_top: ImageTop| {
+            degrees(&ExteriorOrientation::new(&point, zone, image_top))
+        };
+        assert_close([0., 0., 0.], opk(point(0., 0., 0.), ImageTop::Forward));
+        assert_close([0., 0., -90.], opk(point(0., 0., 90.), ImageTop::Forward));
+        assert_close([0., 0., 180.], opk(point(0., 0., 0.), ImageTop::Backward));
+        assert_close([0., 0., 90.], opk(point(0., 0., 0.), ImageTop::Left));
+        // Nose up tilts the camera to look forward, which is north, a positive rotation about x.
+        assert_close([5., 0., 0.], opk(point(0., 5., 0.), ImageTop::Forward));
+        // Right wing down tilts the camera to look left, which is west, and the back of the camera
+        // to the east, a positive rotation about y.
+        assert_close([0., 5., 0.], opk(point(5., 0., 0.), ImageTop::Forward));
+    }
+
+    #[test]
+    fn grid_convergence() {
+        let zone = Zone {
+            number: 31,
+            north: true,
+        };
+        let point = Point {
+            latitude: Radians::from_degrees(45.0),
+            longitude: Radians::from_degrees(5.0),
+            ..Default::default()
+        };
+        let orientation = ExteriorOrientation::new(&point, zone, ImageTop::Forward);
+        // East of the central meridian, true north is west of grid north, so the image is rotated
+        // counterclockwise.
+        let convergence = (2.0f64.to_radians().tan() * 45.0f64.to_radians().sin()).atan();
+        assert!((orientation.kappa.0 - convergence).abs() < 1e-4);
+        assert_eq!(point.to_utm_in_zone(zone).easting, orientation.easting);
+    }
+
+    #[test]
+    fn yaw_pitch_roll() {
+        let zone = Zone {
+            number: 31,
+            north: true,
+        };
+        let point = |roll: f64, pitch: f64, yaw: f64| Point {
+            latitude: Radians::from_degrees(45.0),
+            longitude: Radians::from_degrees(3.0),
+            roll: Radians::from_degrees(roll),
+            pitch: Radians::from_degrees(pitch),
+            yaw: Radians::from_degrees(yaw),
+            ..Default::default()
+        };
+        let ypr = |point: Point| {
+            let (yaw, pitch, roll) =
+                ExteriorOrientation::new(&point, zone, ImageTop::Forward).yaw_pitch_roll();
+            [yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees()]
+        };
+        assert_close([0., 0., 0.], ypr(point(0., 0., 0.)));
+        assert_close([270., 0., 0.], ypr(point(0., 0., 270.)));
+        assert_close([0., 5., 0.], ypr(point(0., 5., 0.)));
+        assert_close([0., 0., 5.], ypr(point(5., 0., 0.)));
+        // Pitch comes before roll, so a tilted camera that then yaws keeps its pitch.
+        assert_close([90., 5., 0.], ypr(point(0., 5., 90.)));
+        assert!((0.0..360.0).contains(&ypr(point(0., 0., -1.))[0]));
+    }
+
+    #[test]
+    fn write() {
+        let mut writer = Writer::new(Vec::new(), Software::Metashape);
+        writer
+            .write_image("IMG_0001.JPG", &Point::default())
+            .unwrap();
+        writer
+            .write_image("IMG_0002.JPG", &Point::default())
+            .unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!("# Label,X,Y,Z,Omega,Phi,Kappa", lines[0]);
+        assert!(lines[1].starts_with("IMG_0001.JPG,166021.443,0.000,0.000,"));
+        assert_eq!(7, lines[2].split(',').count());
+
+        let mut writer = Writer::new(Vec::new(), Software::Pix4d);
+        writer.convention = Convention::Ypr;
+        let point = Point {
+            yaw: Radians::from_degrees(-90.0),
+            ..Default::default()
+        };
+        writer.write_image("IMG_0001.JPG", &point).unwrap();
+        let output = String::from_utf8(writer.close().unwrap()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!("imagename,X,Y,Z,yaw,pitch,roll", lines[0]);
+        let yaw: f64 = lines[1].split(',').nth(4).unwrap().parse().unwrap();
+        assert!(yaw > 180.0);
+    }
+}
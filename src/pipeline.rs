@@ -0,0 +1,185 @@
+//! Pipelines of per-point transforms, for conversions that stay streaming.
+//!
+//! A `Pipeline` wraps any `Source` and applies its transforms, in order, to each point as it's
+//! read, so a conversion never holds more than one point in memory. For a one-off change, use
+//! `Source::map_points` with a closure instead.
+//!
+//! Reprojection with PROJ is a source of its own, `reproject::Reproject` with the `proj`
+//! feature, so wrap a pipeline in one to reproject after the other transforms. `Transform::Utm`
+//! covers the common case without PROJ.
+
+use crate::error::Error;
+use crate::geodesy::Helmert;
+use crate::point::{Boresight, Point};
+use crate::source::Source;
+use crate::time::Duration;
+use crate::units::Radians;
+use crate::utm::Zone;
+
+/// A change to a point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    /// Adds an offset to the time, e.g. to convert between time systems.
+    TimeShift(Duration),
+
+    /// Moves the position to a sensor at a lever arm, in meters in the body frame (x forward, y
+    /// right, z down), with `Point::apply_lever_arm`.
+    LeverArm([f64; 3]),
+
+    /// Rotates the attitude to a sensor's, with `Point::apply_boresight`.
+    Boresight(Boresight),
+
+    /// Moves the position to another datum.
+    Helmert(Helmert),
+
+    /// Projects the position into a UTM zone, with the easting and northing in meters stored as
+    /// the longitude and latitude in degrees, as `reproject::Reproject` does.
+    ///
+    /// Geodetic transforms don't make sense after this one, so it should come last.
+    Utm(Zone),
+}
+
+impl Transform {
+    /// Applies this transform to a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::pipeline::Transform;
+    /// use pos::time::{Duration, Time};
+    /// let point = Transform::TimeShift(Duration(-18.0)).apply(Point::default());
+    /// assert_eq!(Time(-18.0), point.time);
+    /// ```
+    pub fn apply(&self, mut point: Point) -> Point {
+        match *self {
+            Transform::TimeShift(offset) => {
+                point.time += offset;
+                point
+            }
+            Transform::LeverArm([x, y, z]) => point.apply_lever_arm(x, y, z),
+            Transform::Boresight(ref boresight) => point.apply_boresight(boresight),
+            Transform::Helmert(ref helmert) => helmert.transform_point(&point),
+            Transform::Utm(zone) => {
+                let utm = point.to_utm_in_zone(zone);
+                point.longitude = Radians::from_degrees(utm.easting);
+                point.latitude = Radians::from_degrees(utm.northing);
+                point
+            }
+        }
+    }
+}
+
+/// A source that applies a list of transforms to the points of another source.
+#[derive(Debug)]
+pub struct Pipeline<S: Source> {
+    source: S,
+    transforms: Vec<Transform>,
+}
+
+impl<S: Source> Pipeline<S> {
+    /// Creates a pipeline with no transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pipeline::Pipeline;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let pipeline = Pipeline::new(source);
+    /// ```
+    pub fn new(source: S) -> Pipeline<S> {
+        Pipeline {
+            source,
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Adds a transform to the end of the pipeline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// use pos::pipeline::{Pipeline, Transform};
+    /// use pos::time::Duration;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let mut pipeline = Pipeline::new(source)
+    ///     .then(Transform::TimeShift(Duration(18.0)))
+    ///     .then(Transform::LeverArm([0.0, 0.0, -1.5]));
+    /// let point = pipeline.source().unwrap().unwrap();
+    /// ```
+    pub fn then(mut self, transform: Transform) -> Pipeline<S> {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Returns the transforms, in the order they're applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pipeline::{Pipeline, Transform};
+    /// use pos::time::Duration;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let pipeline = Pipeline::new(source).then(Transform::TimeShift(Duration(18.0)));
+    /// assert_eq!(1, pipeline.transforms().len());
+    /// ```
+    pub fn transforms(&self) -> &[Transform] {
+        &self.transforms
+    }
+
+    /// Applies every transform to a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::pipeline::{Pipeline, Transform};
+    /// use pos::time::{Duration, Time};
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let pipeline = Pipeline::new(source)
+    ///     .then(Transform::TimeShift(Duration(1.0)))
+    ///     .then(Transform::TimeShift(Duration(2.0)));
+    /// assert_eq!(Time(3.0), pipeline.apply(Point::default()).time);
+    /// ```
+    pub fn apply(&self, point: Point) -> Point {
+        self.transforms
+            .iter()
+            .fold(point, |point, transform| transform.apply(point))
+    }
+}
+
+impl<S: Source> Source for Pipeline<S> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        Ok(self.source.source()?.map(|point| self.apply(point)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+
+    #[test]
+    fn sbet() {
+        let expected: Vec<_> = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let zone = Zone::for_position(expected[0].latitude, expected[0].longitude);
+        let mut pipeline = Pipeline::new(sbet::Reader::from_path("data/2-points.sbet").unwrap())
+            .then(Transform::TimeShift(Duration(-0.5)))
+            .then(Transform::LeverArm([0., 0., -2.]))
+            .then(Transform::Utm(zone));
+        for expected in &expected {
+            let point = pipeline.source().unwrap().unwrap();
+            assert_eq!(expected.time - Duration(0.5), point.time);
+            let utm = expected.to_utm_in_zone(zone);
+            assert!((utm.easting - point.longitude.to_degrees()).abs() < 2.);
+            assert!((expected.altitude + 2. - point.altitude).abs() < 0.1);
+        }
+        assert!(pipeline.source().unwrap().is_none());
+    }
+}
@@ -2,26 +2,27 @@
 //!
 //! These are Riegl-specific GNSS/IMU data files.
 
+use crate::error;
+use crate::point::Point;
+use crate::source::Source;
+use crate::time::Time;
+use crate::units::Radians;
 use byteorder::{LittleEndian, ReadBytesExt};
-use failure;
-use point::Point;
-use source::Source;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::iter::IntoIterator;
 use std::path::Path;
-use units::Radians;
 
 /// Pof errors.
-#[derive(Clone, Copy, Debug, Fail)]
+#[derive(Clone, Copy, Debug, thiserror::Error)]
 pub enum Error {
     /// The time unit code is invalid.
-    #[fail(display = "The time unit code is invalid: {}", _0)]
+    #[error("The time unit code is invalid: {0}")]
     TimeUnit(u8),
 
     /// The time info code is invalid.
-    #[fail(display = "The time info code is invalid: {}", _0)]
+    #[error("The time info code is invalid: {0}")]
     TimeInfo(u8),
 }
 
@@ -107,14 +108,14 @@ impl Reader<BufReader<File>> {
     /// use pos::pof::Reader;
     /// let reader = Reader::from_path("data/sbet_mission_1.pof").unwrap();
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, failure::Error> {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
         let reader = BufReader::new(File::open(path)?);
         Reader::new(reader)
     }
 }
 
 impl<R: Read + Seek> Reader<R> {
-    fn new(mut reader: R) -> Result<Reader<R>, failure::Error> {
+    fn new(mut reader: R) -> Result<Reader<R>, error::Error> {
         let mut preamble = [0; 27];
         reader.read_exact(&mut preamble)?;
 
@@ -192,7 +193,7 @@ impl<R: Read + Seek> Reader<R> {
     /// let mut reader = Reader::from_path("data/sbet_mission_1.pof").unwrap();
     /// let point = reader.read_point().unwrap();
     /// ```
-    pub fn read_point(&mut self) -> Result<Option<Point>, failure::Error> {
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
         if self.position == self.entries {
             return Ok(None);
         }
@@ -213,7 +214,7 @@ impl<R: Read + Seek> Reader<R> {
         self.position += 1;
 
         Ok(Some(Point {
-            time: time,
+            time: Time(time),
             longitude: Radians::from_degrees(longitude),
             latitude: Radians::from_degrees(latitude),
             altitude: altitude,
@@ -227,7 +228,7 @@ impl<R: Read + Seek> Reader<R> {
 }
 
 impl<R: Read + Seek> IntoIterator for Reader<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     type IntoIter = ReaderIterator<R>;
     fn into_iter(self) -> Self::IntoIter {
         ReaderIterator { reader: self }
@@ -241,9 +242,9 @@ pub struct ReaderIterator<R: Read + Seek> {
 }
 
 impl<R: Read + Seek> Iterator for ReaderIterator<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.reader.read_point().unwrap()
+        self.reader.read_point().transpose()
     }
 }
 
@@ -320,7 +321,7 @@ impl TimeInfo {
 }
 
 impl<R: Debug + Seek + Read> Source for Reader<R> {
-    fn source(&mut self) -> Result<Option<Point>, failure::Error> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
         self.read_point()
     }
 }
@@ -343,7 +344,7 @@ mod tests {
     fn point() {
         let mut reader = Reader::from_path("data/sbet_mission_1.pof").unwrap();
         let point = reader.read_point().unwrap().unwrap();
-        assert_eq!(5.380900320500246e4, point.time);
+        assert_eq!(Time(5.380900320500246e4), point.time);
         assert_eq!(-107.8941420696491, point.longitude.to_degrees());
         assert_eq!(3.852696630463423e1, point.latitude.to_degrees());
         assert_eq!(1721.1666764324254, point.altitude);
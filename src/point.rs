@@ -1,11 +1,43 @@
 //! Points.
 
-use units::Radians;
+use crate::geodesy::{Ecef, Enu, LocalFrame, Method, Ned};
+use crate::quaternion::Quaternion;
+use crate::time::{GpsTime, Time, TimeBase};
+use crate::units::{Degrees, Radians};
+use crate::utm::{Utm, Zone};
+use std::f64::consts::PI;
+use std::fmt;
 
 macro_rules! interpolate {
     ($lhs:ident, $rhs:ident, $factor:ident, $var:ident) => {{
         $lhs.$var + $factor * ($rhs.$var - $lhs.$var)
-    }}
+    }};
+}
+
+macro_rules! interpolate_angle {
+    ($lhs:ident, $rhs:ident, $factor:ident, $var:ident) => {{
+        ($lhs.$var + $factor * $rhs.$var.angular_difference($lhs.$var)).wrap_pi()
+    }};
+}
+
+macro_rules! degrees {
+    ($name:ident, $field:ident) => {
+        #[doc = concat!("Returns this point's ", stringify!($field), " in degrees.")]
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use pos::{Degrees, Point, Radians};
+        /// let point = Point {
+        #[doc = concat!("    ", stringify!($field), ": Radians::from_degrees(45.0),")]
+        ///     ..Default::default()
+        /// };
+        #[doc = concat!("assert_eq!(Degrees(45.0), point.", stringify!($name), "());")]
+        /// ```
+        pub fn $name(&self) -> Degrees<f64> {
+            Degrees::from(self.$field)
+        }
+    };
 }
 
 macro_rules! interpolate_optional {
@@ -19,16 +51,19 @@ macro_rules! interpolate_optional {
         } else {
             None
         }
-    }}
+    }};
 }
 
 /// A position point.
 ///
 /// This must contain position and attidue information, and may contain error information.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Point {
-    pub time: f64,
+    pub time: Time,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub time_base: TimeBase,
     pub longitude: Radians<f64>,
     pub latitude: Radians<f64>,
     pub altitude: f64,
@@ -47,31 +82,137 @@ pub struct Point {
     pub y_angular_rate: Option<Radians<f64>>,
     pub z_angular_rate: Option<Radians<f64>>,
     pub accuracy: Option<Accuracy>,
+    pub status: Option<Status>,
 }
 
 impl Point {
+    /// Returns a builder for a point.
+    ///
+    /// Building points with `Point { ..Default::default() }` breaks when fields are added, and
+    /// doesn't check the values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// use pos::time::Time;
+    /// let point = Point::builder()
+    ///     .time(Time(151631.0))
+    ///     .latitude(Degrees(38.5))
+    ///     .longitude(Degrees(-107.9))
+    ///     .altitude(1721.0)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(1721.0, point.altitude);
+    /// ```
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Checks that this point's values are finite and its angles are in range.
+    ///
+    /// The latitude and pitch must be within [-90°, 90°], the longitude and roll within
+    /// [-180°, 180°], and the yaw within [-180°, 360°], which allows both heading conventions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// assert!(Point::default().validate().is_ok());
+    /// let point = Point { longitude: Radians::from_degrees(190.0), ..Default::default() };
+    /// assert_eq!(
+    ///     "The longitude is out of range: 190 degrees",
+    ///     point.validate().unwrap_err().to_string()
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), Error> {
+        let angles = [
+            ("latitude", self.latitude, -90., 90.),
+            ("longitude", self.longitude, -180., 180.),
+            ("roll", self.roll, -180., 180.),
+            ("pitch", self.pitch, -90., 90.),
+            ("yaw", self.yaw, -180., 360.),
+        ];
+        for &(name, angle, min, max) in &angles {
+            if !angle.0.is_finite() {
+                return Err(Error::NotFinite(name));
+            }
+            // Round to avoid rejecting e.g. 180° that went through a conversion to radians.
+            let degrees = (angle.to_degrees() * 1e9).round() / 1e9;
+            if degrees < min || degrees > max {
+                return Err(Error::OutOfRange(name, degrees));
+            }
+        }
+        let values = [
+            ("time", Some(self.time.0)),
+            ("altitude", Some(self.altitude)),
+            ("distance", self.distance),
+            ("x velocity", self.x_velocity),
+            ("y velocity", self.y_velocity),
+            ("z velocity", self.z_velocity),
+            ("wander angle", self.wander_angle.map(|a| a.0)),
+            ("x acceleration", self.x_acceleration),
+            ("y acceleration", self.y_acceleration),
+            ("z acceleration", self.z_acceleration),
+            ("x angular rate", self.x_angular_rate.map(|a| a.0)),
+            ("y angular rate", self.y_angular_rate.map(|a| a.0)),
+            ("z angular rate", self.z_angular_rate.map(|a| a.0)),
+        ];
+        for &(name, value) in &values {
+            if value.is_some_and(|value| !value.is_finite()) {
+                return Err(Error::NotFinite(name));
+            }
+        }
+        Ok(())
+    }
+
+    degrees!(latitude_degrees, latitude);
+    degrees!(longitude_degrees, longitude);
+    degrees!(roll_degrees, roll);
+    degrees!(pitch_degrees, pitch);
+    degrees!(yaw_degrees, yaw);
+
     /// Linearly interpolate a new point between these two.
     ///
+    /// Longitude, roll, and yaw are interpolated the short way around, so a yaw going from 359° to
+    /// 1° passes through 360° rather than 180°. Longitude and roll are wrapped to [-180°, 180°), and
+    /// yaw to [-180°, 180°) if either point's yaw is negative, or to [0°, 360°) otherwise.
+    ///
     /// # Examples
     ///
     /// ```
     /// use pos::point::Point;
+    /// use pos::time::Time;
     /// let mut p1: Point = Default::default();
-    /// p1.time = 10.0;
+    /// p1.time = Time(10.0);
     /// let mut p2: Point = Default::default();
-    /// p2.time = 20.0;
-    /// let p3 = p1.interpolate(&p2, 15.0);
+    /// p2.time = Time(20.0);
+    /// let p3 = p1.interpolate(&p2, Time(15.0));
     /// ```
-    pub fn interpolate(&self, other: &Point, time: f64) -> Point {
+    pub fn interpolate(&self, other: &Point, time: Time) -> Point {
         let factor = (time - self.time) / (other.time - self.time);
+        Point {
+            accuracy: match (self.accuracy, other.accuracy) {
+                (Some(a1), Some(a2)) => Some(a1.interpolate(&a2, time)),
+                _ => None,
+            },
+            ..self.blend(other, factor)
+        }
+    }
+
+    /// Blends this point with another, from this point at a factor of zero to the other at one.
+    ///
+    /// Angles are blended as in `Point::interpolate`, and the accuracy is this point's.
+    pub(crate) fn blend(&self, other: &Point, factor: f64) -> Point {
         Point {
             time: interpolate!(self, other, factor, time),
-            longitude: interpolate!(self, other, factor, longitude),
+            time_base: self.time_base,
+            longitude: interpolate_angle!(self, other, factor, longitude),
             latitude: interpolate!(self, other, factor, latitude),
             altitude: interpolate!(self, other, factor, altitude),
-            roll: interpolate!(self, other, factor, roll),
+            roll: interpolate_angle!(self, other, factor, roll),
             pitch: interpolate!(self, other, factor, pitch),
-            yaw: interpolate!(self, other, factor, yaw),
+            yaw: interpolate_angle!(self, other, factor, yaw).wrap_like([self.yaw, other.yaw]),
             distance: interpolate_optional!(self, other, factor, distance),
             x_velocity: interpolate_optional!(self, other, factor, x_velocity),
             y_velocity: interpolate_optional!(self, other, factor, y_velocity),
@@ -83,33 +224,827 @@ impl Point {
             x_angular_rate: interpolate_optional!(self, other, factor, x_angular_rate),
             y_angular_rate: interpolate_optional!(self, other, factor, y_angular_rate),
             z_angular_rate: interpolate_optional!(self, other, factor, z_angular_rate),
-            accuracy: if let Some(a1) = self.accuracy {
-                if let Some(a2) = other.accuracy {
-                    Some(a1.interpolate(&a2, time))
-                } else {
-                    None
-                }
+            accuracy: self.accuracy,
+            status: if self.status == other.status {
+                self.status
             } else {
                 None
             },
         }
+    }
 
+    /// Interpolate a new point between these two, spherically interpolating the attitude.
+    ///
+    /// Linear interpolation of roll, pitch, and yaw breaks down when an angle wraps, e.g. when yaw
+    /// goes from 359° to 1°. Here the attitudes are converted to quaternions and slerped, and
+    /// everything else is interpolated linearly. The yaw is returned in the same range as this
+    /// point's yaw, e.g. [0, 2π) or (-π, π].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::point::Point;
+    /// use pos::time::Time;
+    /// use pos::units::Radians;
+    /// let mut p1: Point = Default::default();
+    /// p1.time = Time(10.0);
+    /// p1.yaw = Radians::from_degrees(359.0);
+    /// let mut p2: Point = Default::default();
+    /// p2.time = Time(20.0);
+    /// p2.yaw = Radians::from_degrees(1.0);
+    /// let p3 = p1.slerp(&p2, Time(15.0));
+    /// assert!((p3.yaw.to_degrees() - 360.0).abs() < 1e-9);
+    /// ```
+    pub fn slerp(&self, other: &Point, time: Time) -> Point {
+        let factor = (time - self.time) / (other.time - self.time);
+        let mut point = self.interpolate(other, time);
+        let (roll, pitch, yaw) = Quaternion::from_euler(self.roll, self.pitch, self.yaw)
+            .slerp(
+                &Quaternion::from_euler(other.roll, other.pitch, other.yaw),
+                factor,
+            )
+            .to_euler();
+        let turns = ((self.yaw - yaw).0 / (2.0 * PI)).round();
+        point.roll = roll;
+        point.pitch = pitch;
+        point.yaw = yaw + Radians(2.0 * PI * turns);
+        point
+    }
+
+    /// Returns this point's true heading, clockwise from north.
+    ///
+    /// If the point has a wander angle, its yaw is taken to be the platform heading in a
+    /// wander-azimuth frame, as in Applanix sbet files, and the true heading is the yaw minus the
+    /// wander angle. Otherwise the true heading is the yaw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// let point = Point {
+    ///     yaw: Radians::from_degrees(100.0),
+    ///     wander_angle: Some(Radians::from_degrees(10.0)),
+    ///     ..Default::default()
+    /// };
+    /// assert!((point.true_heading().to_degrees() - 90.0).abs() < 1e-9);
+    /// ```
+    pub fn true_heading(&self) -> Radians<f64> {
+        match self.wander_angle {
+            Some(wander_angle) => self.yaw - wander_angle,
+            None => self.yaw,
+        }
+    }
+
+    /// Returns the horizontal speed over ground from the x (east) and y (north) velocities, in
+    /// meters per second.
+    ///
+    /// Returns none if the point doesn't have both velocities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point { x_velocity: Some(3.0), y_velocity: Some(4.0), ..Default::default() };
+    /// assert_eq!(Some(5.0), point.speed());
+    /// assert_eq!(None, Point::default().speed());
+    /// ```
+    pub fn speed(&self) -> Option<f64> {
+        Some(self.x_velocity?.hypot(self.y_velocity?))
+    }
+
+    /// Returns the course over ground, clockwise from north in [0, 2π), from the x (east) and y
+    /// (north) velocities.
+    ///
+    /// Unlike the heading, this is the direction the platform is moving, which differs in a
+    /// crosswind or when reversing. Returns none if the point doesn't have both velocities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point { x_velocity: Some(-1.0), y_velocity: Some(0.0), ..Default::default() };
+    /// assert_eq!(270.0, point.course().unwrap().to_degrees());
+    /// ```
+    pub fn course(&self) -> Option<Radians<f64>> {
+        let course = self.x_velocity?.atan2(self.y_velocity?);
+        Some(Radians(course.rem_euclid(2.0 * PI)))
+    }
+
+    /// Returns the rotation from the body frame to the local north-east-down frame, as a unit
+    /// quaternion.
+    ///
+    /// The body frame's x axis points forward, y to the right, and z down. Positive roll is right
+    /// wing down, positive pitch is nose up, and heading is clockwise from north, applied in the
+    /// aerospace z-y-x sequence. The heading is `Point::true_heading`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let quaternion = Point::default().quaternion();
+    /// assert_eq!(1.0, quaternion.w);
+    /// ```
+    pub fn quaternion(&self) -> Quaternion {
+        Quaternion::from_euler(self.roll, self.pitch, self.true_heading())
+    }
+
+    /// Returns the rotation matrix from the body frame to the local north-east-down frame.
+    ///
+    /// The conventions are those of `Point::quaternion`. The matrix is row-major, so
+    /// `ned[i] = sum(matrix[i][j] * body[j])`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// let point = Point { yaw: Radians::from_degrees(90.0), ..Default::default() };
+    /// let matrix = point.rotation_matrix();
+    /// // Forward points east.
+    /// assert!((matrix[1][0] - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn rotation_matrix(&self) -> [[f64; 3]; 3] {
+        self.quaternion().to_rotation_matrix()
+    }
+
+    /// Returns the rotation matrix from the body frame to earth-centered, earth-fixed axes.
+    ///
+    /// This is `Point::rotation_matrix` followed by the rotation from north-east-down to ECEF at
+    /// this point's latitude and longitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let matrix = Point::default().ecef_rotation_matrix();
+    /// // At zero latitude and longitude, down points toward the center of the earth.
+    /// assert!((matrix[0][2] + 1.0).abs() < 1e-12);
+    /// ```
+    pub fn ecef_rotation_matrix(&self) -> [[f64; 3]; 3] {
+        let (sin_latitude, cos_latitude) = self.latitude.0.sin_cos();
+        let (sin_longitude, cos_longitude) = self.longitude.0.sin_cos();
+        let ned_to_ecef = [
+            [
+                -sin_latitude * cos_longitude,
+                -sin_longitude,
+                -cos_latitude * cos_longitude,
+            ],
+            [
+                -sin_latitude * sin_longitude,
+                cos_longitude,
+                -cos_latitude * sin_longitude,
+            ],
+            [cos_latitude, 0., -sin_latitude],
+        ];
+        let body_to_ned = self.rotation_matrix();
+        let mut matrix = [[0.; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| ned_to_ecef[i][k] * body_to_ned[k][j]).sum();
+            }
+        }
+        matrix
+    }
+
+    /// Returns the point of a sensor mounted at a lever arm from this point.
+    ///
+    /// The lever arm is in meters in the body frame (x forward, y right, z down), from the IMU
+    /// reference point to the sensor. It's rotated by this point's attitude (see
+    /// `Point::ecef_rotation_matrix`) and added to the position. Everything but the position is
+    /// copied unchanged, so velocities aren't corrected for rotation about the IMU.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point { altitude: 100.0, ..Default::default() };
+    /// // An antenna 1.5 meters above the IMU.
+    /// let antenna = point.apply_lever_arm(0.0, 0.0, -1.5);
+    /// assert!((antenna.altitude - 101.5).abs() < 1e-6);
+    /// ```
+    pub fn apply_lever_arm(&self, x: f64, y: f64, z: f64) -> Point {
+        let matrix = self.ecef_rotation_matrix();
+        let lever_arm = [x, y, z];
+        let offset = |i: usize| -> f64 { (0..3).map(|j| matrix[i][j] * lever_arm[j]).sum() };
+        let ecef = self.to_ecef();
+        let (latitude, longitude, altitude) = Ecef {
+            x: ecef.x + offset(0),
+            y: ecef.y + offset(1),
+            z: ecef.z + offset(2),
+        }
+        .to_geodetic();
+        Point {
+            latitude,
+            longitude,
+            altitude,
+            ..*self
+        }
+    }
+
+    /// Returns the point of a sensor whose axes are rotated from the body frame by a boresight.
+    ///
+    /// The boresight is applied first, in the sensor frame, and then the platform attitude, so
+    /// the result's rotation matrix is `self.rotation_matrix() * boresight.rotation_matrix()`. The
+    /// wander angle is kept, and the yaw stays in the same range as this point's yaw. Everything
+    /// but the attitude is copied unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::point::Boresight;
+    /// let point = Point { yaw: Radians::from_degrees(90.0), ..Default::default() };
+    /// let boresight = Boresight { yaw: Radians::from_degrees(0.5), ..Default::default() };
+    /// let sensor = point.apply_boresight(&boresight);
+    /// assert!((sensor.yaw.to_degrees() - 90.5).abs() < 1e-9);
+    /// ```
+    pub fn apply_boresight(&self, boresight: &Boresight) -> Point {
+        let (roll, pitch, heading) = self
+            .quaternion()
+            .multiply(&boresight.quaternion())
+            .to_euler();
+        let yaw = match self.wander_angle {
+            Some(wander_angle) => heading + wander_angle,
+            None => heading,
+        };
+        let turns = ((self.yaw - yaw).0 / (2.0 * PI)).round();
+        Point {
+            roll,
+            pitch,
+            yaw: yaw + Radians(2.0 * PI * turns),
+            ..*self
+        }
+    }
+
+    /// Returns the distance along the ellipsoid from this point to another, in meters.
+    ///
+    /// Altitudes are ignored. Uses Vincenty's formula, see `geodesy::Method` for the faster
+    /// haversine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// let other = Point { longitude: Radians::from_degrees(1.0), ..Default::default() };
+    /// assert!((Point::default().distance_to(&other) - 111_319.491).abs() < 1e-3);
+    /// ```
+    pub fn distance_to(&self, other: &Point) -> f64 {
+        Method::Vincenty.distance(self, other)
+    }
+
+    /// Returns true if this point's time, position, and attitude are each within a tolerance of
+    /// another point's.
+    ///
+    /// Positions are compared in meters, in a local east-north-up frame centered on the other
+    /// point, and angles are compared across wraps, so 359.9999° is close to 0°. The other fields
+    /// aren't compared; use `==` for those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::point::Tolerance;
+    /// let point = Point { latitude: Radians::from_degrees(40.0), ..Default::default() };
+    /// let other = Point {
+    ///     latitude: Radians::from_degrees(40.0 + 1e-9),
+    ///     yaw: Radians::from_degrees(359.99999),
+    ///     ..point
+    /// };
+    /// assert!(point != other);
+    /// assert!(point.approx_eq(&other, &Tolerance::default()));
+    /// ```
+    pub fn approx_eq(&self, other: &Point, tolerance: &Tolerance) -> bool {
+        let enu = LocalFrame::from_point(other).to_enu(self.to_ecef());
+        let arcseconds =
+            |a: Radians<f64>, b: Radians<f64>| a.angular_difference(b).to_degrees().abs() * 3600.;
+        (self.time - other.time).0.abs() <= tolerance.time
+            && enu.east.hypot(enu.north) <= tolerance.horizontal
+            && enu.up.abs() <= tolerance.vertical
+            && arcseconds(self.roll, other.roll) <= tolerance.angle
+            && arcseconds(self.pitch, other.pitch) <= tolerance.angle
+            && arcseconds(self.yaw, other.yaw) <= tolerance.angle
+    }
+
+    /// Returns the azimuth from this point towards another, clockwise from north in [0, 2π).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// let other = Point { longitude: Radians::from_degrees(-1.0), ..Default::default() };
+    /// let azimuth = Point::default().azimuth_to(&other);
+    /// assert!((azimuth.to_degrees() - 270.0).abs() < 1e-9);
+    /// ```
+    pub fn azimuth_to(&self, other: &Point) -> Radians<f64> {
+        Method::Vincenty.azimuth(self, other)
+    }
+
+    /// Returns this point's position in earth-centered, earth-fixed coordinates.
+    ///
+    /// The altitude is taken to be the height above the WGS84 ellipsoid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let ecef = Point::default().to_ecef();
+    /// assert_eq!(6378137.0, ecef.x);
+    /// ```
+    pub fn to_ecef(&self) -> Ecef {
+        Ecef::from_geodetic(self.latitude, self.longitude, self.altitude)
+    }
+
+    /// Creates a point at a position in earth-centered, earth-fixed coordinates.
+    ///
+    /// Every other field is left at its default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::geodesy::Ecef;
+    /// let point = Point::from_ecef(Ecef { x: 6378137.0, y: 0.0, z: 0.0 });
+    /// assert!(point.altitude.abs() < 1e-9);
+    /// ```
+    pub fn from_ecef(ecef: Ecef) -> Point {
+        let (latitude, longitude, altitude) = ecef.to_geodetic();
+        Point {
+            latitude,
+            longitude,
+            altitude,
+            ..Default::default()
+        }
+    }
+
+    /// Returns this point's position in east-north-up coordinates, relative to an origin point.
+    ///
+    /// Use a `LocalFrame` to convert many points relative to the same origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let origin = Point::default();
+    /// let point = Point { altitude: 10.0, ..Default::default() };
+    /// assert!((point.to_enu(&origin).up - 10.0).abs() < 1e-9);
+    /// ```
+    pub fn to_enu(&self, origin: &Point) -> Enu {
+        LocalFrame::from_point(origin).to_enu(self.to_ecef())
+    }
+
+    /// Returns this point's position in north-east-down coordinates, relative to an origin point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let origin = Point::default();
+    /// let point = Point { altitude: 10.0, ..Default::default() };
+    /// assert!((point.to_ned(&origin).down + 10.0).abs() < 1e-9);
+    /// ```
+    pub fn to_ned(&self, origin: &Point) -> Ned {
+        LocalFrame::from_point(origin).to_ned(self.to_ecef())
+    }
+
+    /// Returns this point's position in UTM coordinates, in the zone that contains it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point::default();
+    /// let utm = point.to_utm();
+    /// assert_eq!(31, utm.zone.number);
+    /// assert!(utm.easting < 500000.0);
+    /// ```
+    pub fn to_utm(&self) -> Utm {
+        Utm::from_geodetic(self.latitude, self.longitude)
+    }
+
+    /// Returns this point's position in UTM coordinates in the given zone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::utm::Zone;
+    /// let zone = Zone { number: 30, north: true };
+    /// let utm = Point::default().to_utm_in_zone(zone);
+    /// assert!(utm.easting > 500000.0);
+    /// ```
+    pub fn to_utm_in_zone(&self, zone: Zone) -> Utm {
+        Utm::from_geodetic_in_zone(self.latitude, self.longitude, zone)
+    }
+
+    /// Returns this point's time as a GPS time, given the GPS week that the time is counted from.
+    ///
+    /// Returns none if the time can't be a GPS time, as in `GpsTime::new`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// let point = Point { time: Time(264207.0), ..Default::default() };
+    /// assert_eq!(2053, point.gps_time(2053).unwrap().week);
+    /// ```
+    pub fn gps_time(&self, week: u16) -> Option<GpsTime> {
+        GpsTime::new(week, self.time.0)
+    }
+
+    /// Returns this point's time as Unix time, given the GPS week that the time is counted from.
+    ///
+    /// Returns none if the time can't be a GPS time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// let point = Point { time: Time(264207.0), ..Default::default() };
+    /// assert_eq!(Some(1557883389.0), point.unix_time(2053));
+    /// ```
+    pub fn unix_time(&self, week: u16) -> Option<f64> {
+        self.gps_time(week).map(|time| time.to_unix())
+    }
+
+    /// Returns this point's time as an ISO 8601 UTC date and time, given the GPS week that the
+    /// time is counted from.
+    ///
+    /// Returns none if the time can't be a GPS time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// let point = Point { time: Time(264207.0), ..Default::default() };
+    /// assert_eq!(Some("2019-05-15T01:23:09.000Z".to_string()), point.utc(2053));
+    /// ```
+    pub fn utc(&self, week: u16) -> Option<String> {
+        self.gps_time(week).map(|time| time.to_utc())
+    }
+}
+
+/// Points are displayed on one line with the time in seconds, latitude and longitude in degrees,
+/// altitude in meters, and attitude in degrees.
+///
+/// The formatter's precision is the number of decimal places of the latitude and longitude,
+/// eight by default, which is about a millimeter.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::time::Time;
+/// let point = Point {
+///     time: Time(151631.0),
+///     latitude: Radians::from_degrees(40.0),
+///     longitude: Radians::from_degrees(-105.0),
+///     altitude: 1655.0,
+///     yaw: Radians::from_degrees(90.0),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     "151631.000000 s, 40.000° -105.000°, 1655.000 m, roll 0.0000° pitch 0.0000° yaw 90.0000°",
+///     format!("{:.3}", point)
+/// );
+/// ```
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(8);
+        write!(
+            f,
+            "{:.6} s, {:.*} {:.*}, {:.3} m, roll {:.4} pitch {:.4} yaw {:.4}",
+            self.time,
+            precision,
+            self.latitude,
+            precision,
+            self.longitude,
+            self.altitude,
+            self.roll,
+            self.pitch,
+            self.yaw
+        )
+    }
+}
+
+/// Point errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// A field is NaN or infinite.
+    #[error("The {0} is not finite")]
+    NotFinite(&'static str),
+
+    /// An angle is out of range, in degrees.
+    #[error("The {0} is out of range: {1} degrees")]
+    OutOfRange(&'static str, f64),
+}
+
+/// Builds a point field by field, with angles in either `Degrees` or `Radians`.
+///
+/// Unset fields keep their default values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Builder {
+    point: Point,
+}
+
+impl Builder {
+    /// Sets the time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// assert_eq!(Time(1.5), Point::builder().time(Time(1.5)).build().unwrap().time);
+    /// ```
+    pub fn time(mut self, time: Time) -> Builder {
+        self.point.time = time;
+        self
+    }
+
+    /// Sets the time base.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::TimeBase;
+    /// let point = Point::builder().time_base(TimeBase::UtcSecondsOfDay).build().unwrap();
+    /// assert_eq!(TimeBase::UtcSecondsOfDay, point.time_base);
+    /// ```
+    pub fn time_base(mut self, time_base: TimeBase) -> Builder {
+        self.point.time_base = time_base;
+        self
+    }
+
+    /// Sets the latitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// let point = Point::builder().latitude(Degrees(45.0)).build().unwrap();
+    /// assert_eq!(Degrees(45.0), point.latitude_degrees());
+    /// ```
+    pub fn latitude<A: Into<Radians<f64>>>(mut self, latitude: A) -> Builder {
+        self.point.latitude = latitude.into();
+        self
+    }
+
+    /// Sets the longitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// let point = Point::builder().longitude(Radians(-1.8)).build().unwrap();
+    /// assert_eq!(Radians(-1.8), point.longitude);
+    /// ```
+    pub fn longitude<A: Into<Radians<f64>>>(mut self, longitude: A) -> Builder {
+        self.point.longitude = longitude.into();
+        self
+    }
+
+    /// Sets the altitude, in meters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// assert_eq!(1600.0, Point::builder().altitude(1600.0).build().unwrap().altitude);
+    /// ```
+    pub fn altitude(mut self, altitude: f64) -> Builder {
+        self.point.altitude = altitude;
+        self
+    }
+
+    /// Sets the roll, pitch, and yaw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// let point = Point::builder()
+    ///     .attitude(Degrees(1.0), Degrees(-2.0), Degrees(270.0))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(Degrees(270.0), point.yaw_degrees());
+    /// ```
+    pub fn attitude<A: Into<Radians<f64>>>(mut self, roll: A, pitch: A, yaw: A) -> Builder {
+        self.point.roll = roll.into();
+        self.point.pitch = pitch.into();
+        self.point.yaw = yaw.into();
+        self
+    }
+
+    /// Sets the wander angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// let point = Point::builder().wander_angle(Degrees(10.0)).build().unwrap();
+    /// assert!(point.wander_angle.is_some());
+    /// ```
+    pub fn wander_angle<A: Into<Radians<f64>>>(mut self, wander_angle: A) -> Builder {
+        self.point.wander_angle = Some(wander_angle.into());
+        self
+    }
+
+    /// Sets the distance traveled, in meters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point::builder().distance(12.0).build().unwrap();
+    /// assert_eq!(Some(12.0), point.distance);
+    /// ```
+    pub fn distance(mut self, distance: f64) -> Builder {
+        self.point.distance = Some(distance);
+        self
+    }
+
+    /// Sets the x, y, and z velocities, in meters per second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point::builder().velocity(3.0, 4.0, 0.0).build().unwrap();
+    /// assert_eq!(Some(5.0), point.speed());
+    /// ```
+    pub fn velocity(mut self, x: f64, y: f64, z: f64) -> Builder {
+        self.point.x_velocity = Some(x);
+        self.point.y_velocity = Some(y);
+        self.point.z_velocity = Some(z);
+        self
+    }
+
+    /// Sets the x, y, and z accelerations, in meters per second squared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// let point = Point::builder().acceleration(0.0, 0.0, -9.8).build().unwrap();
+    /// assert_eq!(Some(-9.8), point.z_acceleration);
+    /// ```
+    pub fn acceleration(mut self, x: f64, y: f64, z: f64) -> Builder {
+        self.point.x_acceleration = Some(x);
+        self.point.y_acceleration = Some(y);
+        self.point.z_acceleration = Some(z);
+        self
+    }
+
+    /// Sets the x, y, and z angular rates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// let point = Point::builder()
+    ///     .angular_rate(Degrees(0.0), Degrees(0.0), Degrees(3.0))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(point.z_angular_rate.is_some());
+    /// ```
+    pub fn angular_rate<A: Into<Radians<f64>>>(mut self, x: A, y: A, z: A) -> Builder {
+        self.point.x_angular_rate = Some(x.into());
+        self.point.y_angular_rate = Some(y.into());
+        self.point.z_angular_rate = Some(z.into());
+        self
+    }
+
+    /// Sets the accuracy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Accuracy, Point};
+    /// let accuracy = Accuracy { z: 0.05, ..Default::default() };
+    /// let point = Point::builder().accuracy(accuracy).build().unwrap();
+    /// assert_eq!(0.05, point.accuracy.unwrap().z);
+    /// ```
+    pub fn accuracy(mut self, accuracy: Accuracy) -> Builder {
+        self.point.accuracy = Some(accuracy);
+        self
+    }
+
+    /// Validates and returns the point.
+    ///
+    /// See `Point::validate` for the checks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Degrees, Point};
+    /// assert!(Point::builder().latitude(Degrees(91.0)).build().is_err());
+    /// assert!(Point::builder().altitude(std::f64::NAN).build().is_err());
+    /// ```
+    pub fn build(self) -> Result<Point, Error> {
+        self.point.validate()?;
+        Ok(self.point)
+    }
+}
+
+/// How far apart two points can be and still be approximately equal, for `Point::approx_eq`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The largest time difference, in seconds.
+    pub time: f64,
+    /// The largest horizontal distance, in meters.
+    pub horizontal: f64,
+    /// The largest vertical distance, in meters.
+    pub vertical: f64,
+    /// The largest roll, pitch, or yaw difference, in arcseconds.
+    pub angle: f64,
+}
+
+impl Default for Tolerance {
+    /// A microsecond, a millimeter, and an arcsecond, which is finer than any of the formats store.
+    fn default() -> Tolerance {
+        Tolerance {
+            time: 1e-6,
+            horizontal: 1e-3,
+            vertical: 1e-3,
+            angle: 1.,
+        }
     }
 }
 
-/// The accuracy of a position.
+/// A small rotation from a sensor's axes to the body frame, e.g. a camera or lidar misalignment.
+///
+/// The angles use the same conventions as a point's attitude (see `Point::quaternion`).
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
-#[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Boresight {
+    /// The roll misalignment.
+    pub roll: Radians<f64>,
+    /// The pitch misalignment.
+    pub pitch: Radians<f64>,
+    /// The yaw misalignment.
+    pub yaw: Radians<f64>,
+}
+
+impl Boresight {
+    /// Returns the rotation from the sensor frame to the body frame, as a unit quaternion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::point::Boresight;
+    /// assert_eq!(1.0, Boresight::default().quaternion().w);
+    /// ```
+    pub fn quaternion(&self) -> Quaternion {
+        Quaternion::from_euler(self.roll, self.pitch, self.yaw)
+    }
+
+    /// Returns the rotation matrix from the sensor frame to the body frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::point::Boresight;
+    /// assert_eq!([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]], Boresight::default().rotation_matrix());
+    /// ```
+    pub fn rotation_matrix(&self) -> [[f64; 3]; 3] {
+        self.quaternion().to_rotation_matrix()
+    }
+}
+
+/// The accuracy of a position, velocity, and attitude, as standard deviations.
+///
+/// Formats that carry accuracy, e.g. smrmsg, RTKLIB, and NovAtel, store it here, while formats
+/// that don't, e.g. sbet, leave a point's accuracy as none.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Accuracy {
-    pub time: f64,
+    /// The time of the accuracy record, which may differ from the point's time.
+    pub time: Time,
+    /// The east position standard deviation, in meters.
     pub x: f64,
+    /// The north position standard deviation, in meters.
     pub y: f64,
+    /// The vertical position standard deviation, in meters.
     pub z: f64,
+    /// The roll standard deviation.
     pub roll: Radians<f64>,
+    /// The pitch standard deviation.
     pub pitch: Radians<f64>,
+    /// The yaw standard deviation.
     pub yaw: Radians<f64>,
+    /// The position dilution of precision.
     pub pdop: f64,
+    /// The number of satellites.
     pub satellite_count: Option<SatelliteCount>,
+    /// The east velocity standard deviation, in meters per second.
+    pub x_velocity: Option<f64>,
+    /// The north velocity standard deviation, in meters per second.
+    pub y_velocity: Option<f64>,
+    /// The vertical velocity standard deviation, in meters per second.
+    pub z_velocity: Option<f64>,
 }
 
 impl Accuracy {
@@ -119,13 +1054,14 @@ impl Accuracy {
     ///
     /// ```
     /// use pos::point::Accuracy;
+    /// use pos::time::Time;
     /// let mut accuracy1: Accuracy = Default::default();
-    /// accuracy1.time = 10.0;
+    /// accuracy1.time = Time(10.0);
     /// let mut accuracy2: Accuracy = Default::default();
-    /// accuracy2.time = 20.0;
-    /// let accuracy3 = accuracy1.interpolate(&accuracy2, 15.0);
+    /// accuracy2.time = Time(20.0);
+    /// let accuracy3 = accuracy1.interpolate(&accuracy2, Time(15.0));
     /// ```
-    pub fn interpolate(&self, other: &Accuracy, time: f64) -> Accuracy {
+    pub fn interpolate(&self, other: &Accuracy, time: Time) -> Accuracy {
         let factor = (time - self.time) / (other.time - self.time);
         Accuracy {
             time: interpolate!(self, other, factor, time),
@@ -137,12 +1073,81 @@ impl Accuracy {
             yaw: interpolate!(self, other, factor, yaw),
             pdop: interpolate!(self, other, factor, pdop),
             satellite_count: None,
+            x_velocity: interpolate_optional!(self, other, factor, x_velocity),
+            y_velocity: interpolate_optional!(self, other, factor, y_velocity),
+            z_velocity: interpolate_optional!(self, other, factor, z_velocity),
+        }
+    }
+}
+
+/// The quality of a position solution, for the formats that record it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status {
+    /// A carrier-phase solution with fixed integer ambiguities, e.g. RTK fixed.
+    Fixed,
+    /// A carrier-phase solution with float ambiguities.
+    Float,
+    /// A precise point positioning solution.
+    Ppp,
+    /// A differential code solution.
+    Dgps,
+    /// A code solution with SBAS corrections.
+    Sbas,
+    /// A standalone code solution, from the standard positioning service.
+    Sps,
+    /// A solution predicted without GNSS, e.g. by dead reckoning.
+    Predicted,
+}
+
+impl Status {
+    /// Returns a status from its name, as returned by `Status::name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Status;
+    /// assert_eq!(Some(Status::Float), Status::from_name("float"));
+    /// assert_eq!(None, Status::from_name("unknown"));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Status> {
+        let status = match name {
+            "fixed" => Status::Fixed,
+            "float" => Status::Float,
+            "ppp" => Status::Ppp,
+            "dgps" => Status::Dgps,
+            "sbas" => Status::Sbas,
+            "sps" => Status::Sps,
+            "predicted" => Status::Predicted,
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// Returns this status's name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Status;
+    /// assert_eq!("dgps", Status::Dgps.name());
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Fixed => "fixed",
+            Status::Float => "float",
+            Status::Ppp => "ppp",
+            Status::Dgps => "dgps",
+            Status::Sbas => "sbas",
+            Status::Sps => "sps",
+            Status::Predicted => "predicted",
         }
     }
 }
 
 /// A count of the number of satellites.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SatelliteCount {
     /// The type of the satellites being counted is unspecified.
     Unspecified(u16),
@@ -155,8 +1160,271 @@ pub enum SatelliteCount {
     },
 }
 
+impl SatelliteCount {
+    /// Returns the total number of satellites, saturating at `u16::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::point::SatelliteCount;
+    /// assert_eq!(12, SatelliteCount::Specified { gps: 8, glonass: 4 }.total());
+    /// let count = SatelliteCount::Specified { gps: u16::MAX, glonass: 4 };
+    /// assert_eq!(u16::MAX, count.total());
+    /// ```
+    pub fn total(&self) -> u16 {
+        match *self {
+            SatelliteCount::Unspecified(n) => n,
+            SatelliteCount::Specified { gps, glonass } => gps.saturating_add(glonass),
+        }
+    }
+}
+
 impl Default for SatelliteCount {
     fn default() -> SatelliteCount {
         SatelliteCount::Unspecified(0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use super::*;
+
+        let point = Point {
+            time: Time(1.5),
+            latitude: Radians(0.5),
+            accuracy: Some(Accuracy {
+                satellite_count: Some(SatelliteCount::Specified { gps: 8, glonass: 4 }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&point).unwrap();
+        assert!(json.contains(r#""latitude":0.5"#));
+        assert_eq!(point, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn interpolate_across_wrap() {
+        use super::*;
+
+        let p1 = Point {
+            longitude: Radians::from_degrees(179.),
+            yaw: Radians::from_degrees(359.),
+            roll: Radians::from_degrees(-179.),
+            ..Default::default()
+        };
+        let p2 = Point {
+            time: Time(1.),
+            longitude: Radians::from_degrees(-179.),
+            yaw: Radians::from_degrees(1.),
+            roll: Radians::from_degrees(179.),
+            ..Default::default()
+        };
+        let point = p1.interpolate(&p2, Time(0.25));
+        assert!((point.longitude.to_degrees() - 179.5).abs() < 1e-9);
+        assert!((point.yaw.to_degrees() - 359.5).abs() < 1e-9);
+        assert!((point.roll.to_degrees() + 179.5).abs() < 1e-9);
+        let point = p1.interpolate(&p2, Time(0.75));
+        assert!((point.longitude.to_degrees() + 179.5).abs() < 1e-9);
+        assert!((point.yaw.to_degrees() - 0.5).abs() < 1e-9);
+        assert!((point.roll.to_degrees() - 179.5).abs() < 1e-9);
+        assert!(point.validate().is_ok());
+        let p2 = Point {
+            yaw: Radians::from_degrees(-179.),
+            ..p2
+        };
+        let p1 = Point {
+            yaw: Radians::from_degrees(179.),
+            ..p1
+        };
+        let point = p1.interpolate(&p2, Time(0.75));
+        assert!((point.yaw.to_degrees() + 179.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lever_arm() {
+        use super::*;
+
+        let point = Point {
+            latitude: Radians::from_degrees(40.),
+            longitude: Radians::from_degrees(-105.),
+            altitude: 1600.,
+            roll: Radians::from_degrees(2.),
+            pitch: Radians::from_degrees(-3.),
+            yaw: Radians::from_degrees(90.),
+            x_velocity: Some(50.),
+            ..Default::default()
+        };
+        let sensor = point.apply_lever_arm(1., 0.2, 0.5);
+        assert_eq!(point.yaw, sensor.yaw);
+        assert_eq!(Some(50.), sensor.x_velocity);
+        let ned = sensor.to_ned(&point);
+        let matrix = point.rotation_matrix();
+        let expected = [
+            matrix[0][0] + 0.2 * matrix[0][1] + 0.5 * matrix[0][2],
+            matrix[1][0] + 0.2 * matrix[1][1] + 0.5 * matrix[1][2],
+            matrix[2][0] + 0.2 * matrix[2][1] + 0.5 * matrix[2][2],
+        ];
+        assert!((ned.north - expected[0]).abs() < 1e-6);
+        assert!((ned.east - expected[1]).abs() < 1e-6);
+        assert!((ned.down - expected[2]).abs() < 1e-6);
+        assert!(ned.east > 0.9);
+        let back = sensor.apply_lever_arm(-1., -0.2, -0.5);
+        assert!((back.latitude - point.latitude).0.abs() < 1e-12);
+        assert!((back.altitude - point.altitude).abs() < 1e-6);
+    }
+
+    #[test]
+    fn boresight() {
+        use super::*;
+
+        let point = Point {
+            roll: Radians(0.1),
+            pitch: Radians(-0.2),
+            yaw: Radians(6.),
+            wander_angle: Some(Radians(0.3)),
+            altitude: 10.,
+            ..Default::default()
+        };
+        let boresight = Boresight {
+            roll: Radians(0.01),
+            pitch: Radians(0.02),
+            yaw: Radians(-0.03),
+        };
+        let sensor = point.apply_boresight(&boresight);
+        assert_eq!(10., sensor.altitude);
+        assert_eq!(point.wander_angle, sensor.wander_angle);
+        assert!(sensor.yaw.0 > PI);
+        let (platform, boresight) = (point.rotation_matrix(), boresight.rotation_matrix());
+        let matrix = sensor.rotation_matrix();
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected: f64 = (0..3).map(|k| platform[i][k] * boresight[k][j]).sum();
+                assert!((expected - matrix[i][j]).abs() < 1e-12);
+            }
+        }
+        let unchanged = point.apply_boresight(&Boresight::default());
+        assert!((unchanged.roll - point.roll).0.abs() < 1e-12);
+        assert!((unchanged.yaw - point.yaw).0.abs() < 1e-12);
+    }
+
+    #[test]
+    fn rotation() {
+        use super::*;
+
+        let rotate = |matrix: [[f64; 3]; 3], vector: [f64; 3]| -> [f64; 3] {
+            let mut rotated = [0.; 3];
+            for i in 0..3 {
+                rotated[i] = (0..3).map(|j| matrix[i][j] * vector[j]).sum();
+            }
+            rotated
+        };
+        let assert_near = |expected: [f64; 3], actual: [f64; 3]| {
+            for i in 0..3 {
+                assert!((expected[i] - actual[i]).abs() < 1e-12, "{:?}", actual);
+            }
+        };
+        let pitch = Radians::from_degrees(30.);
+        let point = Point {
+            pitch,
+            ..Default::default()
+        };
+        let (sin, cos) = pitch.0.sin_cos();
+        assert_near(
+            [cos, 0., -sin],
+            rotate(point.rotation_matrix(), [1., 0., 0.]),
+        );
+        let point = Point {
+            roll: Radians::from_degrees(30.),
+            ..Default::default()
+        };
+        assert_near(
+            [0., cos, 0.5],
+            rotate(point.rotation_matrix(), [0., 1., 0.]),
+        );
+        let point = Point {
+            yaw: Radians::from_degrees(100.),
+            wander_angle: Some(Radians::from_degrees(10.)),
+            ..Default::default()
+        };
+        assert_near([0., 1., 0.], rotate(point.rotation_matrix(), [1., 0., 0.]));
+
+        let point = Point {
+            latitude: Radians::from_degrees(90.),
+            yaw: Radians::from_degrees(90.),
+            ..Default::default()
+        };
+        let matrix = point.ecef_rotation_matrix();
+        assert_near([0., 1., 0.], rotate(matrix, [1., 0., 0.]));
+        assert_near([0., 0., -1.], rotate(matrix, [0., 0., 1.]));
+        let point = Point {
+            latitude: Radians::from_degrees(45.),
+            longitude: Radians::from_degrees(-105.),
+            roll: Radians(0.1),
+            pitch: Radians(-0.2),
+            yaw: Radians(3.),
+            ..Default::default()
+        };
+        let matrix = point.ecef_rotation_matrix();
+        for i in 0..3 {
+            for j in 0..3 {
+                let dot: f64 = (0..3).map(|k| matrix[k][i] * matrix[k][j]).sum();
+                assert!((dot - if i == j { 1. } else { 0. }).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn approx_eq() {
+        use super::*;
+
+        let point = Point {
+            time: Time(151631.),
+            latitude: Radians::from_degrees(60.),
+            longitude: Radians::from_degrees(180.),
+            altitude: 100.,
+            roll: Radians::from_degrees(-180.),
+            ..Default::default()
+        };
+        let tolerance = Tolerance::default();
+        assert!(point.approx_eq(&point, &tolerance));
+        let other = Point {
+            longitude: Radians::from_degrees(-180.),
+            roll: Radians::from_degrees(180.),
+            ..point
+        };
+        assert!(point.approx_eq(&other, &tolerance));
+        let east = Point {
+            longitude: point.longitude + Radians(0.002 / 3_194_000.),
+            ..point
+        };
+        assert!(!point.approx_eq(&east, &tolerance));
+        assert!(point.approx_eq(
+            &east,
+            &Tolerance {
+                horizontal: 0.0025,
+                ..tolerance
+            }
+        ));
+        let checks = [
+            Point {
+                time: Time(151631.00001),
+                ..point
+            },
+            Point {
+                altitude: 100.002,
+                ..point
+            },
+            Point {
+                pitch: Radians::from_degrees(2. / 3600.),
+                ..point
+            },
+        ];
+        for check in &checks {
+            assert!(!point.approx_eq(check, &tolerance), "{:?}", check);
+        }
+    }
+}
@@ -1,13 +1,14 @@
 //! Position and orientation quality files.
 
+use crate::error::Error;
+use crate::point::{Accuracy, SatelliteCount};
+use crate::time::Time;
+use crate::units::Radians;
 use byteorder::{LittleEndian, ReadBytesExt};
-use failure::Error;
-use point::{Accuracy, SatelliteCount};
 use std::fs::File;
 use std::io::{BufReader, Read, Seek};
 use std::iter::IntoIterator;
 use std::path::Path;
-use units::Radians;
 
 /// A poq file reader.
 #[derive(Debug)]
@@ -71,12 +72,10 @@ impl<R: Seek + Read> Reader<R> {
 
         let time = match self.reader.read_f64::<LittleEndian>() {
             Ok(time) => time,
-            Err(err) => {
-                match err.kind() {
-                    ErrorKind::UnexpectedEof => return Ok(None),
-                    _ => return Err(err.into()),
-                }
-            }
+            Err(err) => match err.kind() {
+                ErrorKind::UnexpectedEof => return Ok(None),
+                _ => return Err(err.into()),
+            },
         };
         let north = self.reader.read_f64::<LittleEndian>()?;
         let east = self.reader.read_f64::<LittleEndian>()?;
@@ -97,7 +96,7 @@ impl<R: Seek + Read> Reader<R> {
         };
 
         Ok(Some(Accuracy {
-            time: time,
+            time: Time(time),
             y: north,
             x: east,
             z: down,
@@ -106,12 +105,15 @@ impl<R: Seek + Read> Reader<R> {
             yaw: Radians::from_degrees(yaw),
             pdop: pdop,
             satellite_count: Some(satellite_count),
+            x_velocity: None,
+            y_velocity: None,
+            z_velocity: None,
         }))
     }
 }
 
 impl<R: Seek + Read> IntoIterator for Reader<R> {
-    type Item = Accuracy;
+    type Item = Result<Accuracy, Error>;
     type IntoIter = ReaderIterator<R>;
     fn into_iter(self) -> Self::IntoIter {
         ReaderIterator { reader: self }
@@ -125,9 +127,9 @@ pub struct ReaderIterator<R: Read + Seek> {
 }
 
 impl<R: Read + Seek> Iterator for ReaderIterator<R> {
-    type Item = Accuracy;
+    type Item = Result<Accuracy, Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.reader.read_accuracy().unwrap()
+        self.reader.read_accuracy().transpose()
     }
 }
 
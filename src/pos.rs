@@ -1,40 +1,812 @@
 //! Pos files are ASCII position files.
 
-use failure::Error;
-use point::Point;
-use source::Source;
+use crate::error;
+use crate::format::Input;
+use crate::point::{Accuracy, Point, SatelliteCount};
+use crate::sink::Sink;
+use crate::source::{ParseMode, Source};
+use crate::time::Time;
+use crate::units::Radians;
+use crate::utm::Zone;
+#[cfg(feature = "tokio")]
+use futures_core::Stream;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::num::ParseFloatError;
 use std::path::Path;
-use units::Radians;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+#[cfg(feature = "tokio")]
+use std::{io, str};
+#[cfg(feature = "tokio")]
+use tokio::io::AsyncBufRead;
+
+/// The header line written by a pos writer with the default columns.
+pub const HEADER: &str = "time latitude longitude altitude roll pitch yaw";
+
+/// The default number of decimal places used for latitude, longitude, and angles.
+pub const DEFAULT_PRECISION: usize = 9;
+
+/// The default prefixes of comment lines, which are skipped by pos readers.
+pub const DEFAULT_COMMENT_PREFIXES: &[&str] = &[";", "#"];
+
+/// Pos errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// A line has fewer values than the column map has columns.
+    #[error("Missing a value for column {0}")]
+    MissingColumn(usize),
+    /// A value isn't a number, or isn't valid for its column, e.g. a fractional satellite count,
+    /// with the column, the value, and the parse error if it isn't a number.
+    #[error("Invalid value for column {0}: {1:?}")]
+    InvalidValue(usize, String, #[source] Option<ParseFloatError>),
+    /// A line of a file can't be parsed, with the line number (starting at one) and the error.
+    #[error("Could not parse line {0}")]
+    Line(usize, #[source] Box<Error>),
+    /// A field name isn't a known column.
+    #[error("Unknown column: {0:?}")]
+    UnknownColumn(String),
+    /// An easting or northing column was read, which can't be converted without a UTM zone.
+    #[error("Column {0} is a projected coordinate, which can't be read")]
+    ProjectedColumn(usize),
+    /// A point with a wander angle was written to columns with a yaw but no wander angle, so its
+    /// yaw couldn't be read back as the same heading.
+    #[error("Can't write a point with a wander angle without a wander angle column")]
+    WanderAngle,
+}
+
+/// A column in a pos file.
+///
+/// Angles and angular rates are in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Column {
+    Time,
+    Latitude,
+    Longitude,
+    Altitude,
+    Roll,
+    Pitch,
+    Yaw,
+    Distance,
+    XVelocity,
+    YVelocity,
+    ZVelocity,
+    WanderAngle,
+    XAcceleration,
+    YAcceleration,
+    ZAcceleration,
+    XAngularRate,
+    YAngularRate,
+    ZAngularRate,
+    /// The standard deviation of the easting, in meters.
+    XStandardDeviation,
+    /// The standard deviation of the northing, in meters.
+    YStandardDeviation,
+    /// The standard deviation of the height, in meters.
+    ZStandardDeviation,
+    RollStandardDeviation,
+    PitchStandardDeviation,
+    YawStandardDeviation,
+    XVelocityStandardDeviation,
+    YVelocityStandardDeviation,
+    ZVelocityStandardDeviation,
+    /// The position dilution of precision.
+    Pdop,
+    /// The total number of satellites.
+    SatelliteCount,
+    /// The UTM easting, in meters, in the zone that contains the point.
+    ///
+    /// Projected columns can be written, but reading one is an error, since it would leave the
+    /// point without a latitude or longitude.
+    Easting,
+    /// The UTM northing, in meters, in the zone that contains the point.
+    Northing,
+    /// A column that isn't read into the point.
+    Skip,
+}
+
+impl Column {
+    /// Returns the column with the given name, as it might appear in a pos header.
+    ///
+    /// Matching ignores case, punctuation, and any units in parentheses or brackets, so `Time`,
+    /// `time(s)`, and `GPS_Time` all match. Returns none for unknown names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Column;
+    /// assert_eq!(Some(Column::Latitude), Column::from_name("Lat(deg)"));
+    /// assert_eq!(Some(Column::ZStandardDeviation), Column::from_name("SD-Height"));
+    /// assert_eq!(None, Column::from_name("Q"));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Column> {
+        let name: String = name
+            .split(['(', '['])
+            .next()
+            .unwrap_or("")
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(|c| c.to_lowercase())
+            .collect();
+        let column = match name.as_str() {
+            "time" | "gpstime" | "gpst" | "sow" | "seconds" => Column::Time,
+            "lat" | "latitude" => Column::Latitude,
+            "lon" | "long" | "longitude" => Column::Longitude,
+            "alt" | "altitude" | "height" | "hell" | "ellheight" | "ellipsoidheight"
+            | "elevation" => Column::Altitude,
+            "roll" => Column::Roll,
+            "pitch" => Column::Pitch,
+            "yaw" | "heading" | "azimuth" => Column::Yaw,
+            "distance" | "dist" => Column::Distance,
+            "vx" | "ve" | "veast" | "xvelocity" | "eastvelocity" => Column::XVelocity,
+            "vy" | "vn" | "vnorth" | "yvelocity" | "northvelocity" => Column::YVelocity,
+            "vz" | "vu" | "vup" | "zvelocity" | "upvelocity" => Column::ZVelocity,
+            "wander" | "wanderangle" => Column::WanderAngle,
+            "ax" | "xacceleration" => Column::XAcceleration,
+            "ay" | "yacceleration" => Column::YAcceleration,
+            "az" | "zacceleration" => Column::ZAcceleration,
+            "xangularrate" => Column::XAngularRate,
+            "yangularrate" => Column::YAngularRate,
+            "zangularrate" => Column::ZAngularRate,
+            "sdx" | "sde" | "sdeast" | "stdx" | "stde" | "sigmax" | "sigmae" => {
+                Column::XStandardDeviation
+            }
+            "sdy" | "sdn" | "sdnorth" | "stdy" | "stdn" | "sigmay" | "sigman" => {
+                Column::YStandardDeviation
+            }
+            "sdz" | "sdu" | "sdh" | "sdup" | "sdheight" | "stdz" | "stdu" | "stdh" | "sigmaz"
+            | "sigmau" | "sigmah" => Column::ZStandardDeviation,
+            "sdroll" | "stdroll" | "sigmaroll" => Column::RollStandardDeviation,
+            "sdpitch" | "stdpitch" | "sigmapitch" => Column::PitchStandardDeviation,
+            "sdyaw" | "sdheading" | "stdyaw" | "stdheading" | "sigmayaw" | "sigmaheading" => {
+                Column::YawStandardDeviation
+            }
+            "sdvx" | "sdve" | "stdvx" | "stdve" => Column::XVelocityStandardDeviation,
+            "sdvy" | "sdvn" | "stdvy" | "stdvn" => Column::YVelocityStandardDeviation,
+            "sdvz" | "sdvu" | "stdvz" | "stdvu" => Column::ZVelocityStandardDeviation,
+            "pdop" => Column::Pdop,
+            "ns" | "nsat" | "nsats" | "numsats" | "sats" | "satellites" | "satellitecount" => {
+                Column::SatelliteCount
+            }
+            "easting" | "utmeasting" => Column::Easting,
+            "northing" | "utmnorthing" => Column::Northing,
+            _ => return None,
+        };
+        Some(column)
+    }
+
+    /// Returns this column's name, as written in headers.
+    ///
+    /// Names are recognized by `Column::from_name`, except for `skip`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Column;
+    /// assert_eq!("sdz", Column::ZStandardDeviation.name());
+    /// assert_eq!(Some(Column::Latitude), Column::from_name(Column::Latitude.name()));
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Time => "time",
+            Column::Latitude => "latitude",
+            Column::Longitude => "longitude",
+            Column::Altitude => "altitude",
+            Column::Roll => "roll",
+            Column::Pitch => "pitch",
+            Column::Yaw => "yaw",
+            Column::Distance => "distance",
+            Column::XVelocity => "vx",
+            Column::YVelocity => "vy",
+            Column::ZVelocity => "vz",
+            Column::WanderAngle => "wander",
+            Column::XAcceleration => "ax",
+            Column::YAcceleration => "ay",
+            Column::ZAcceleration => "az",
+            Column::XAngularRate => "xangularrate",
+            Column::YAngularRate => "yangularrate",
+            Column::ZAngularRate => "zangularrate",
+            Column::XStandardDeviation => "sdx",
+            Column::YStandardDeviation => "sdy",
+            Column::ZStandardDeviation => "sdz",
+            Column::RollStandardDeviation => "sdroll",
+            Column::PitchStandardDeviation => "sdpitch",
+            Column::YawStandardDeviation => "sdyaw",
+            Column::XVelocityStandardDeviation => "sdvx",
+            Column::YVelocityStandardDeviation => "sdvy",
+            Column::ZVelocityStandardDeviation => "sdvz",
+            Column::Pdop => "pdop",
+            Column::SatelliteCount => "nsats",
+            Column::Easting => "easting",
+            Column::Northing => "northing",
+            Column::Skip => "skip",
+        }
+    }
+
+    /// Returns this column's value for a point, with angles in degrees.
+    ///
+    /// Returns none if the point doesn't have the value, or for skipped columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::pos::Column;
+    /// let point = Point { altitude: 1600.0, ..Default::default() };
+    /// assert_eq!(Some(1600.0), Column::Altitude.value(&point));
+    /// assert_eq!(None, Column::XVelocity.value(&point));
+    /// ```
+    pub fn value(self, point: &Point) -> Option<f64> {
+        let accuracy = point.accuracy.as_ref();
+        match self {
+            Column::Time => Some(point.time.0),
+            Column::Latitude => Some(point.latitude.to_degrees()),
+            Column::Longitude => Some(point.longitude.to_degrees()),
+            Column::Altitude => Some(point.altitude),
+            Column::Roll => Some(point.roll.to_degrees()),
+            Column::Pitch => Some(point.pitch.to_degrees()),
+            Column::Yaw => Some(point.yaw.to_degrees()),
+            Column::Distance => point.distance,
+            Column::XVelocity => point.x_velocity,
+            Column::YVelocity => point.y_velocity,
+            Column::ZVelocity => point.z_velocity,
+            Column::WanderAngle => point.wander_angle.map(|a| a.to_degrees()),
+            Column::XAcceleration => point.x_acceleration,
+            Column::YAcceleration => point.y_acceleration,
+            Column::ZAcceleration => point.z_acceleration,
+            Column::XAngularRate => point.x_angular_rate.map(|a| a.to_degrees()),
+            Column::YAngularRate => point.y_angular_rate.map(|a| a.to_degrees()),
+            Column::ZAngularRate => point.z_angular_rate.map(|a| a.to_degrees()),
+            Column::XStandardDeviation => accuracy.map(|a| a.x),
+            Column::YStandardDeviation => accuracy.map(|a| a.y),
+            Column::ZStandardDeviation => accuracy.map(|a| a.z),
+            Column::RollStandardDeviation => accuracy.map(|a| a.roll.to_degrees()),
+            Column::PitchStandardDeviation => accuracy.map(|a| a.pitch.to_degrees()),
+            Column::YawStandardDeviation => accuracy.map(|a| a.yaw.to_degrees()),
+            Column::XVelocityStandardDeviation => accuracy.and_then(|a| a.x_velocity),
+            Column::YVelocityStandardDeviation => accuracy.and_then(|a| a.y_velocity),
+            Column::ZVelocityStandardDeviation => accuracy.and_then(|a| a.z_velocity),
+            Column::Pdop => accuracy.map(|a| a.pdop),
+            Column::SatelliteCount => accuracy
+                .and_then(|a| a.satellite_count)
+                .map(|count| f64::from(count.total())),
+            Column::Easting => Some(point.to_utm().easting),
+            Column::Northing => Some(point.to_utm().northing),
+            Column::Skip => None,
+        }
+    }
+
+    /// Returns true if this column's values are angles or angular rates.
+    pub(crate) fn is_angular(self) -> bool {
+        matches!(
+            self,
+            Column::Latitude
+                | Column::Longitude
+                | Column::Roll
+                | Column::Pitch
+                | Column::Yaw
+                | Column::WanderAngle
+                | Column::XAngularRate
+                | Column::YAngularRate
+                | Column::ZAngularRate
+                | Column::RollStandardDeviation
+                | Column::PitchStandardDeviation
+                | Column::YawStandardDeviation
+        )
+    }
+
+    /// Returns true if a value can be applied to a point, i.e. it's a count that fits in a `u16`
+    /// or it's for any other column.
+    fn is_valid(self, value: f64) -> bool {
+        match self {
+            Column::SatelliteCount => {
+                (0. ..=f64::from(u16::MAX)).contains(&value) && value.fract() == 0.
+            }
+            _ => true,
+        }
+    }
+
+    fn apply(self, point: &mut Point, accuracy: &mut Option<Accuracy>, value: f64) {
+        match self {
+            Column::Time => point.time = Time(value),
+            Column::Latitude => point.latitude = Radians::from_degrees(value),
+            Column::Longitude => point.longitude = Radians::from_degrees(value),
+            Column::Altitude => point.altitude = value,
+            Column::Roll => point.roll = Radians::from_degrees(value),
+            Column::Pitch => point.pitch = Radians::from_degrees(value),
+            Column::Yaw => point.yaw = Radians::from_degrees(value),
+            Column::Distance => point.distance = Some(value),
+            Column::XVelocity => point.x_velocity = Some(value),
+            Column::YVelocity => point.y_velocity = Some(value),
+            Column::ZVelocity => point.z_velocity = Some(value),
+            Column::WanderAngle => point.wander_angle = Some(Radians::from_degrees(value)),
+            Column::XAcceleration => point.x_acceleration = Some(value),
+            Column::YAcceleration => point.y_acceleration = Some(value),
+            Column::ZAcceleration => point.z_acceleration = Some(value),
+            Column::XAngularRate => point.x_angular_rate = Some(Radians::from_degrees(value)),
+            Column::YAngularRate => point.y_angular_rate = Some(Radians::from_degrees(value)),
+            Column::ZAngularRate => point.z_angular_rate = Some(Radians::from_degrees(value)),
+            Column::XStandardDeviation => accuracy.get_or_insert_with(Default::default).x = value,
+            Column::YStandardDeviation => accuracy.get_or_insert_with(Default::default).y = value,
+            Column::ZStandardDeviation => accuracy.get_or_insert_with(Default::default).z = value,
+            Column::RollStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).roll = Radians::from_degrees(value)
+            }
+            Column::PitchStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).pitch = Radians::from_degrees(value)
+            }
+            Column::YawStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).yaw = Radians::from_degrees(value)
+            }
+            Column::XVelocityStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).x_velocity = Some(value)
+            }
+            Column::YVelocityStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).y_velocity = Some(value)
+            }
+            Column::ZVelocityStandardDeviation => {
+                accuracy.get_or_insert_with(Default::default).z_velocity = Some(value)
+            }
+            Column::Pdop => accuracy.get_or_insert_with(Default::default).pdop = value,
+            Column::SatelliteCount => {
+                accuracy
+                    .get_or_insert_with(Default::default)
+                    .satellite_count = Some(SatelliteCount::Unspecified(value as u16))
+            }
+            Column::Easting | Column::Northing | Column::Skip => {}
+        }
+    }
+}
+
+/// Maps the columns of a pos file to point fields.
+///
+/// The default map is the one used by pos writers: time, latitude, longitude, altitude, roll,
+/// pitch, and yaw.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnMap {
+    columns: Vec<Column>,
+}
+
+impl ColumnMap {
+    /// Creates a new, empty column map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::new()
+    ///     .column(Column::Time)
+    ///     .skip()
+    ///     .column(Column::Latitude)
+    ///     .column(Column::Longitude);
+    /// assert_eq!(4, columns.columns().len());
+    /// ```
+    pub fn new() -> ColumnMap {
+        ColumnMap {
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds the next column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::new().column(Column::Time);
+    /// ```
+    pub fn column(mut self, column: Column) -> ColumnMap {
+        self.columns.push(column);
+        self
+    }
+
+    /// Adds a column that isn't read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::new().skip().column(Column::Time);
+    /// assert_eq!(Column::Skip, columns.columns()[0]);
+    /// ```
+    pub fn skip(self) -> ColumnMap {
+        self.column(Column::Skip)
+    }
+
+    /// Builds a column map from a header line of column names.
+    ///
+    /// Names may be separated by whitespace or commas, and unknown names become skipped columns.
+    /// Returns none unless the header names a time column and at least one other known column,
+    /// since otherwise it probably isn't a list of column names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::from_header("Time Lat Lon H-Ell Q").unwrap();
+    /// assert_eq!(
+    ///     &[Column::Time, Column::Latitude, Column::Longitude, Column::Altitude, Column::Skip],
+    ///     columns.columns()
+    /// );
+    /// assert!(ColumnMap::from_header("Project: survey").is_none());
+    /// ```
+    pub fn from_header(header: &str) -> Option<ColumnMap> {
+        ColumnMap::from_names(
+            header
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|name| !name.is_empty()),
+        )
+    }
+
+    /// Builds a column map from already-split column names.
+    ///
+    /// As with `ColumnMap::from_header`, unknown names become skipped columns, and there must be
+    /// a time column and at least one other known column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::from_names(vec!["GPS Time (s)", "Ell. Height (m)"]).unwrap();
+    /// assert_eq!(&[Column::Time, Column::Altitude], columns.columns());
+    /// ```
+    pub fn from_names<'a, I: IntoIterator<Item = &'a str>>(names: I) -> Option<ColumnMap> {
+        let columns: Vec<_> = names
+            .into_iter()
+            .map(|name| Column::from_name(name).unwrap_or(Column::Skip))
+            .collect();
+        let known = columns.iter().filter(|&&c| c != Column::Skip).count();
+        if columns.contains(&Column::Time) && known > 1 {
+            Some(ColumnMap { columns })
+        } else {
+            None
+        }
+    }
+
+    /// Builds a column map from a comma-separated list of field names, e.g. `time,lat,lon,vx`.
+    ///
+    /// Unlike `ColumnMap::from_names`, this is for lists that a user typed, so every name must be
+    /// a known column (see `Column::from_name`), and a time column isn't required.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// let columns = ColumnMap::from_fields("time, easting, northing, sdz").unwrap();
+    /// assert_eq!(Column::ZStandardDeviation, columns.columns()[3]);
+    /// assert!(ColumnMap::from_fields("time,q").is_err());
+    /// ```
+    pub fn from_fields(fields: &str) -> Result<ColumnMap, error::Error> {
+        let columns = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Column::from_name(name).ok_or_else(|| Error::UnknownColumn(name.into())))
+            .collect::<Result<_, _>>()?;
+        Ok(ColumnMap { columns })
+    }
+
+    /// Returns the columns, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap};
+    /// assert_eq!(Column::Time, ColumnMap::default().columns()[0]);
+    /// ```
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Parses a line of whitespace-separated values into a point.
+    ///
+    /// Values beyond the last column are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::ColumnMap;
+    /// let point = ColumnMap::default().parse("1 45 -105 1600 0 0 0").unwrap();
+    /// assert_eq!(1600.0, point.altitude);
+    /// ```
+    pub fn parse(&self, line: &str) -> Result<Point, error::Error> {
+        self.parse_values(line.split_whitespace())
+    }
+
+    /// Parses already-split values into a point.
+    ///
+    /// Empty values leave their fields unset, and values beyond the last column are ignored.
+    /// Easting and northing columns are errors (see `Column::Easting`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::ColumnMap;
+    /// let point = ColumnMap::default()
+    ///     .parse_values(vec!["1", "45", "-105", "1600", "", "", ""])
+    ///     .unwrap();
+    /// assert_eq!(1600.0, point.altitude);
+    /// ```
+    pub fn parse_values<'a, I: IntoIterator<Item = &'a str>>(
+        &self,
+        values: I,
+    ) -> Result<Point, error::Error> {
+        Ok(self.parse_point(values)?)
+    }
+
+    fn parse_point<'a, I: IntoIterator<Item = &'a str>>(&self, values: I) -> Result<Point, Error> {
+        let mut values = values.into_iter();
+        let mut point = Point::default();
+        let mut accuracy = None;
+        for (i, &column) in self.columns.iter().enumerate() {
+            if column == Column::Easting || column == Column::Northing {
+                return Err(Error::ProjectedColumn(i));
+            }
+            let value = values.next().ok_or(Error::MissingColumn(i))?;
+            if column != Column::Skip && !value.is_empty() {
+                let number = value
+                    .parse()
+                    .map_err(|err| Error::InvalidValue(i, value.to_string(), Some(err)))?;
+                if !column.is_valid(number) {
+                    return Err(Error::InvalidValue(i, value.to_string(), None));
+                }
+                column.apply(&mut point, &mut accuracy, number);
+            }
+        }
+        if let Some(ref mut accuracy) = accuracy {
+            accuracy.time = point.time;
+        }
+        point.accuracy = accuracy;
+        Ok(point)
+    }
+}
+
+impl Default for ColumnMap {
+    fn default() -> ColumnMap {
+        ColumnMap::new()
+            .column(Column::Time)
+            .column(Column::Latitude)
+            .column(Column::Longitude)
+            .column(Column::Altitude)
+            .column(Column::Roll)
+            .column(Column::Pitch)
+            .column(Column::Yaw)
+    }
+}
+
+impl From<Vec<Column>> for ColumnMap {
+    fn from(columns: Vec<Column>) -> ColumnMap {
+        ColumnMap { columns }
+    }
+}
 
 /// A pos reader.
 #[derive(Debug)]
 pub struct Reader<R: BufRead> {
+    columns: ColumnMap,
+    comment_prefixes: Vec<String>,
+    line: String,
+    line_number: usize,
+    parse_mode: ParseMode,
     reader: R,
+    skipped: usize,
 }
 
-impl Reader<BufReader<File>> {
+impl Reader<BufReader<Input>> {
     /// Creates a new reader from a path.
     ///
+    /// Gzipped files are decompressed as they are read, which requires the `gzip` feature.
+    ///
     /// # Examples
     ///
     /// ```
     /// use pos::pos::Reader;
     /// let reader = Reader::from_path("data/0916_2014_ie.pos").unwrap();
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, Error> {
-        let mut reader = BufReader::new(File::open(path)?);
-        let ref mut header: String = String::new();
-        let _ = reader.read_line(header)?;
-        Ok(Reader { reader: reader })
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<Input>>, error::Error> {
+        Reader::new(BufReader::new(Input::open(path)?))
     }
 }
 
 impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead`, reading the header line.
+    ///
+    /// If the header line names the columns, it is used to build the column map (see
+    /// `ColumnMap::from_header`). Otherwise the default column map is used.
+    ///
+    /// The data is read as a stream, so this works with pipes and standard input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Reader;
+    /// use pos::time::Time;
+    /// let data = "time latitude longitude altitude roll pitch yaw\n1 45 -105 1600 0 0 0\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(Time(1.0), point.time);
+    /// ```
+    ///
+    /// Reading from standard input:
+    ///
+    /// ```no_run
+    /// use std::io;
+    /// use pos::pos::Reader;
+    /// let stdin = io::stdin();
+    /// for point in Reader::new(stdin.lock()).unwrap() {
+    ///     println!("{}", point.unwrap().time);
+    /// }
+    /// ```
+    pub fn new(reader: R) -> Result<Reader<R>, error::Error> {
+        Reader::with_header_lines(reader, 1)
+    }
+
+    /// Creates a new reader from any `BufRead`, reading some number of header lines.
+    ///
+    /// Blank lines and comment lines don't count towards the header lines, except for a comment
+    /// line that names the columns, e.g. `# time lat lon`. The last header line that names the
+    /// columns is used to build the column map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Reader;
+    /// let data = "; Inertial Explorer export\n\
+    ///             Project: survey\n\
+    ///             Datum: WGS84\n\
+    ///             GPSTime Latitude Longitude H-Ell\n\
+    ///             \n\
+    ///             1 45 -105 1600\n";
+    /// let mut reader = Reader::with_header_lines(data.as_bytes(), 3).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(1600.0, point.altitude);
+    /// ```
+    pub fn with_header_lines(reader: R, header_lines: usize) -> Result<Reader<R>, error::Error> {
+        let mut reader = Reader {
+            columns: ColumnMap::default(),
+            comment_prefixes: DEFAULT_COMMENT_PREFIXES
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+            line: String::new(),
+            line_number: 0,
+            parse_mode: ParseMode::default(),
+            reader,
+            skipped: 0,
+        };
+        let mut columns = None;
+        let mut count = 0;
+        let mut line = String::new();
+        while count < header_lines {
+            line.clear();
+            if reader.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            reader.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match reader.comment(&line) {
+                Some(comment) => {
+                    if let Some(map) = ColumnMap::from_header(comment) {
+                        columns = Some(map);
+                        count += 1;
+                    }
+                }
+                None => {
+                    columns = ColumnMap::from_header(&line).or(columns);
+                    count += 1;
+                }
+            }
+        }
+        if let Some(columns) = columns {
+            reader.columns = columns;
+        }
+        Ok(reader)
+    }
+
+    /// Returns the column map used to read points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, Reader};
+    /// let reader = Reader::new("time lat lon sdn\n".as_bytes()).unwrap();
+    /// assert_eq!(Column::YStandardDeviation, reader.columns().columns()[3]);
+    /// ```
+    pub fn columns(&self) -> &ColumnMap {
+        &self.columns
+    }
+
+    /// Sets the column map used to read points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{Column, ColumnMap, Reader};
+    /// use pos::time::Time;
+    /// let data = "header\n45 -105 1.5\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// reader.set_columns(
+    ///     ColumnMap::new()
+    ///         .column(Column::Latitude)
+    ///         .column(Column::Longitude)
+    ///         .column(Column::Time),
+    /// );
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(Time(1.5), point.time);
+    /// ```
+    pub fn set_columns(&mut self, columns: ColumnMap) {
+        self.columns = columns;
+    }
+
+    /// Sets the prefixes of comment lines, which are skipped.
+    ///
+    /// Leading whitespace is ignored when matching prefixes. The defaults are `;` and `#`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Reader;
+    /// use pos::time::Time;
+    /// let data = "time lat lon\n% comment\n1 45 -105\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// reader.set_comment_prefixes(&["%"]);
+    /// assert_eq!(Time(1.0), reader.read_point().unwrap().unwrap().time);
+    /// ```
+    pub fn set_comment_prefixes(&mut self, prefixes: &[&str]) {
+        self.comment_prefixes = prefixes.iter().map(|prefix| prefix.to_string()).collect();
+    }
+
+    /// Sets how this reader handles lines that can't be parsed.
+    ///
+    /// In strict mode, the default, a bad line is an error. In lenient mode, it is skipped and
+    /// counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::ParseMode;
+    /// use pos::pos::Reader;
+    /// use pos::time::Time;
+    /// let data = "time lat lon\n1 45\n2 45 -105\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// reader.set_parse_mode(ParseMode::Lenient);
+    /// assert_eq!(Time(2.0), reader.read_point().unwrap().unwrap().time);
+    /// ```
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    /// Returns the number of bad lines that have been skipped in lenient mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::ParseMode;
+    /// use pos::pos::Reader;
+    /// let data = "time lat lon\n1 45\n2 45 -105\n";
+    /// let mut reader = Reader::new(data.as_bytes()).unwrap();
+    /// reader.set_parse_mode(ParseMode::Lenient);
+    /// let _ = reader.read_point().unwrap();
+    /// assert_eq!(1, reader.skipped());
+    /// ```
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
     /// Reads a point from the file.
     ///
+    /// Values beyond the last mapped column are ignored, and blank and comment lines are skipped.
+    /// Returns none at the end of the file. In lenient mode, lines that can't be parsed are
+    /// skipped too. Otherwise, the error says which line and value couldn't be parsed.
+    ///
     /// # Examples
     ///
     /// ```
@@ -42,28 +814,52 @@ impl<R: BufRead> Reader<R> {
     /// let mut reader = Reader::from_path("data/0916_2014_ie.pos").unwrap();
     /// let point = reader.read_point().unwrap();
     /// ```
-    pub fn read_point(&mut self) -> Result<Option<Point>, Error> {
-        let mut line = String::new();
-        let _ = self.reader.read_line(&mut line)?;
-        let values: Vec<_> = line.split_whitespace().map(|s| s.clone()).collect();
-        if values.is_empty() {
-            return Ok(None);
-        }
-        Ok(Some(Point {
-            time: values[0].parse()?,
-            latitude: Radians::from_degrees(values[1].parse()?),
-            longitude: Radians::from_degrees(values[2].parse()?),
-            altitude: values[3].parse()?,
-            roll: Radians::from_degrees(values[4].parse()?),
-            pitch: Radians::from_degrees(values[5].parse()?),
-            yaw: Radians::from_degrees(values[6].parse()?),
-            ..Default::default()
-        }))
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use pos::pos::Reader;
+    /// let mut reader = Reader::new("time lat lon\n1 45 -105\n2 4S -105\n".as_bytes()).unwrap();
+    /// let _ = reader.read_point().unwrap();
+    /// let err = reader.read_point().unwrap_err();
+    /// assert_eq!("Could not parse line 3", err.to_string());
+    /// let err = err.source().unwrap();
+    /// assert_eq!("Invalid value for column 1: \"4S\"", err.to_string());
+    /// assert_eq!("invalid float literal", err.source().unwrap().to_string());
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            if self.line.trim().is_empty() || self.comment(&self.line).is_some() {
+                continue;
+            }
+            match self.columns.parse_point(self.line.split_whitespace()) {
+                Ok(point) => return Ok(Some(point)),
+                Err(_) if self.parse_mode == ParseMode::Lenient => self.skipped += 1,
+                Err(err) => return Err(Error::Line(self.line_number, Box::new(err)).into()),
+            }
+        }
+    }
+
+    fn comment<'a>(&self, line: &'a str) -> Option<&'a str> {
+        comment(&self.comment_prefixes, line)
     }
 }
 
+/// Returns the rest of the line if it is a comment.
+fn comment<'a>(prefixes: &[String], line: &'a str) -> Option<&'a str> {
+    let line = line.trim_start();
+    prefixes
+        .iter()
+        .find(|prefix| line.starts_with(prefix.as_str()))
+        .map(|prefix| &line[prefix.len()..])
+}
+
 impl<R: BufRead> IntoIterator for Reader<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     type IntoIter = ReaderIterator<R>;
     fn into_iter(self) -> Self::IntoIter {
         ReaderIterator { reader: self }
@@ -77,22 +873,322 @@ pub struct ReaderIterator<R: BufRead> {
 }
 
 impl<R: BufRead> Iterator for ReaderIterator<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.reader.read_point().unwrap()
+        self.reader.read_point().transpose()
     }
 }
 
 impl<R: Debug + BufRead> Source for Reader<R> {
-    fn source(&mut self) -> Result<Option<Point>, Error> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
         self.read_point()
     }
 }
 
+/// An asynchronous pos reader, which is a stream of points.
+///
+/// Like `Reader::new`, the first line that isn't blank or a comment is the header line, and it is
+/// used to build the column map if it names the columns. Lines that can't be parsed are errors.
+///
+/// Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncReader<R: AsyncBufRead + Unpin> {
+    columns: ColumnMap,
+    comment_prefixes: Vec<String>,
+    header: bool,
+    line: Vec<u8>,
+    line_number: usize,
+    reader: R,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: AsyncBufRead + Unpin> AsyncReader<R> {
+    /// Creates a new asynchronous reader from any `AsyncBufRead`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate futures_util;
+    /// extern crate tokio;
+    /// # extern crate pos;
+    /// # fn main() {
+    /// use futures_util::StreamExt;
+    /// use pos::pos::AsyncReader;
+    /// let data = "time latitude longitude\n1 45 -105\n2 45 -105\n";
+    /// let reader = AsyncReader::new(data.as_bytes());
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let points: Vec<_> = runtime.block_on(reader.collect());
+    /// assert_eq!(2, points.len());
+    /// # }
+    /// ```
+    pub fn new(reader: R) -> AsyncReader<R> {
+        AsyncReader {
+            columns: ColumnMap::default(),
+            comment_prefixes: DEFAULT_COMMENT_PREFIXES
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+            header: false,
+            line: Vec::new(),
+            line_number: 0,
+            reader,
+        }
+    }
+
+    /// Sets the column map used to read points.
+    ///
+    /// The header line is still skipped, but it no longer changes the column map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{AsyncReader, ColumnMap};
+    /// let mut reader = AsyncReader::new("".as_bytes());
+    /// reader.set_columns(ColumnMap::default());
+    /// ```
+    pub fn set_columns(&mut self, columns: ColumnMap) {
+        self.columns = columns;
+        self.header = true;
+    }
+
+    /// Sets the prefixes of comment lines, which are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::AsyncReader;
+    /// let mut reader = AsyncReader::new("".as_bytes());
+    /// reader.set_comment_prefixes(&["%"]);
+    /// ```
+    pub fn set_comment_prefixes(&mut self, prefixes: &[&str]) {
+        self.comment_prefixes = prefixes.iter().map(|prefix| prefix.to_string()).collect();
+    }
+
+    /// Polls for the next line, returning false at the end of the stream.
+    fn poll_line(&mut self, cx: &mut Context) -> Poll<io::Result<bool>> {
+        loop {
+            let (done, used) = {
+                let available = match Pin::new(&mut self.reader).poll_fill_buf(cx) {
+                    Poll::Ready(Ok(available)) => available,
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                };
+                if available.is_empty() {
+                    return Poll::Ready(Ok(!self.line.is_empty()));
+                }
+                match available.iter().position(|&b| b == b'\n') {
+                    Some(i) => {
+                        self.line.extend_from_slice(&available[..=i]);
+                        (true, i + 1)
+                    }
+                    None => {
+                        self.line.extend_from_slice(available);
+                        (false, available.len())
+                    }
+                }
+            };
+            Pin::new(&mut self.reader).consume(used);
+            if done {
+                return Poll::Ready(Ok(true));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: AsyncBufRead + Unpin> Stream for AsyncReader<R> {
+    type Item = Result<Point, error::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.poll_line(cx) {
+                Poll::Ready(Ok(true)) => this.line_number += 1,
+                Poll::Ready(Ok(false)) => return Poll::Ready(None),
+                Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Pending => return Poll::Pending,
+            }
+            let result = match str::from_utf8(&this.line) {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => match comment(&this.comment_prefixes, line) {
+                    Some(comment) => {
+                        if !this.header {
+                            if let Some(columns) = ColumnMap::from_header(comment) {
+                                this.columns = columns;
+                                this.header = true;
+                            }
+                        }
+                        None
+                    }
+                    None if !this.header => {
+                        if let Some(columns) = ColumnMap::from_header(line) {
+                            this.columns = columns;
+                        }
+                        this.header = true;
+                        None
+                    }
+                    None => Some(
+                        this.columns
+                            .parse_point(line.split_whitespace())
+                            .map_err(|err| Error::Line(this.line_number, Box::new(err)).into()),
+                    ),
+                },
+                Err(err) => Some(Err(err.into())),
+            };
+            this.line.clear();
+            if result.is_some() {
+                return Poll::Ready(result);
+            }
+        }
+    }
+}
+
+/// A pos writer.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    /// The number of decimal places used for latitude, longitude, and angles.
+    pub precision: usize,
+
+    /// The UTM zone of the easting and northing columns.
+    ///
+    /// If none, each point is projected into the zone that contains it.
+    pub utm_zone: Option<Zone>,
+
+    columns: ColumnMap,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path and writes the header line.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer with the default columns and writes the header line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// ```
+    pub fn new(writer: W) -> Result<Writer<W>, error::Error> {
+        Writer::with_columns(writer, ColumnMap::default())
+    }
+
+    /// Creates a new writer with the given columns and writes the header line.
+    ///
+    /// The header names the columns, so pos readers build the same column map from it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::{ColumnMap, Writer};
+    /// let columns = ColumnMap::from_fields("time,lat,lon,vz").unwrap();
+    /// let writer = Writer::with_columns(Vec::new(), columns).unwrap();
+    /// ```
+    pub fn with_columns(mut writer: W, columns: ColumnMap) -> Result<Writer<W>, error::Error> {
+        let names: Vec<_> = columns.columns().iter().map(|c| c.name()).collect();
+        writeln!(writer, "{}", names.join(" "))?;
+        Ok(Writer {
+            precision: DEFAULT_PRECISION,
+            utm_zone: None,
+            columns,
+            writer,
+        })
+    }
+
+    /// Writes a point as one line.
+    ///
+    /// Latitude, longitude, and angles are written in degrees. Values the point doesn't have are
+    /// written as `nan`, since a whitespace-separated line can't leave a value empty.
+    ///
+    /// The yaw column is the point's yaw, which is relative to its wander angle, if it has one. A
+    /// point with a wander angle is an error if there's a yaw column but no wander angle column,
+    /// since the wander angle would be lost, e.g. when writing sbet points with the default
+    /// columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::pos::Writer;
+    /// let mut writer = Writer::new(Vec::new()).unwrap();
+    /// writer.precision = 4;
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        let columns = self.columns.columns();
+        if point.wander_angle.is_some()
+            && columns.contains(&Column::Yaw)
+            && !columns.contains(&Column::WanderAngle)
+        {
+            return Err(Error::WanderAngle.into());
+        }
+        let values: Vec<_> = columns
+            .iter()
+            .map(|&column| {
+                let value = match (column, self.utm_zone) {
+                    (Column::Easting, Some(zone)) => Some(point.to_utm_in_zone(zone).easting),
+                    (Column::Northing, Some(zone)) => Some(point.to_utm_in_zone(zone).northing),
+                    _ => column.value(point),
+                };
+                let precision = match column {
+                    Column::Time => 6,
+                    Column::SatelliteCount => 0,
+                    _ if column.is_angular() => self.precision,
+                    _ => 3,
+                };
+                format!("{:.*}", precision, value.unwrap_or(f64::NAN))
+            })
+            .collect();
+        writeln!(self.writer, "{}", values.join(" "))?;
+        Ok(())
+    }
+
+    /// Consumes this writer and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pos::Writer;
+    /// let writer = Writer::new(Vec::new()).unwrap();
+    /// let bytes = writer.into_inner();
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        Writer::into_inner(*self).flush()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::point::Point;
+
     #[test]
     fn point_count() {
         let points: Vec<_> = Reader::from_path("data/0916_2014_ie.pos")
@@ -101,4 +1197,298 @@ mod tests {
             .collect();
         assert_eq!(722800, points.len());
     }
+
+    #[test]
+    fn write() {
+        let point = Point {
+            time: Time(1.5),
+            latitude: Radians::from_degrees(45.123456789),
+            longitude: Radians::from_degrees(-105.5),
+            altitude: 1600.25,
+            roll: Radians::from_degrees(1.0),
+            pitch: Radians::from_degrees(-2.0),
+            yaw: Radians::from_degrees(359.5),
+            ..Default::default()
+        };
+        let mut writer = Writer::new(Vec::new()).unwrap();
+        writer.precision = 3;
+        writer.write_point(&point).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            "time latitude longitude altitude roll pitch yaw\n\
+             1.500000 45.123 -105.500 1600.250 1.000 -2.000 359.500\n",
+            output
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let point = Point {
+            time: Time(42.0),
+            latitude: Radians::from_degrees(38.5),
+            longitude: Radians::from_degrees(-107.9),
+            altitude: 1721.0,
+            ..Default::default()
+        };
+        let mut writer = Writer::new(Vec::new()).unwrap();
+        writer.write_point(&point).unwrap();
+        let bytes = writer.into_inner();
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        let read = reader.read_point().unwrap().unwrap();
+        assert_eq!(point.time, read.time);
+        assert!((point.latitude.0 - read.latitude.0).abs() < 1e-9);
+        assert!((point.longitude.0 - read.longitude.0).abs() < 1e-9);
+        assert_eq!(point.altitude, read.altitude);
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[test]
+    fn wander_angle() {
+        let point = Point {
+            yaw: Radians::from_degrees(30.),
+            wander_angle: Some(Radians::from_degrees(10.)),
+            ..Default::default()
+        };
+        let mut writer = Writer::new(Vec::new()).unwrap();
+        assert!(matches!(
+            writer.write_point(&point),
+            Err(error::Error::Pos(Error::WanderAngle))
+        ));
+
+        let columns = ColumnMap::from_fields("time,yaw,wander").unwrap();
+        let mut writer = Writer::with_columns(Vec::new(), columns).unwrap();
+        writer.write_point(&point).unwrap();
+        let bytes = writer.into_inner();
+        let read = Reader::new(bytes.as_slice())
+            .unwrap()
+            .read_point()
+            .unwrap()
+            .unwrap();
+        assert!((point.yaw.0 - read.yaw.0).abs() < 1e-9);
+        assert!((10. - read.wander_angle.unwrap().to_degrees()).abs() < 1e-9);
+
+        let columns = ColumnMap::from_fields("time,vz").unwrap();
+        let mut writer = Writer::with_columns(Vec::new(), columns).unwrap();
+        writer.write_point(&point).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn from_path_gzip() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"time lat lon alt\n42.0 38.5 -107.9 1721.0\n")
+            .unwrap();
+        let path = ::std::env::temp_dir().join("pos-rs-pos-gzip.pos.gz");
+        ::std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+        let points = Reader::from_path(&path)
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>();
+        ::std::fs::remove_file(&path).unwrap();
+        let points = points.unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(Time(42.0), points[0].time);
+        assert_eq!(1721.0, points[0].altitude);
+    }
+
+    #[test]
+    fn columns() {
+        let data = "header\n\
+                    1.0 38.5 -107.9 1721.0 1.5 2.5 3.5 0.01 0.02 0.03 extra\n\
+                    2.0 38.6\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        reader.set_columns(
+            ColumnMap::new()
+                .column(Column::Time)
+                .column(Column::Latitude)
+                .column(Column::Longitude)
+                .column(Column::Altitude)
+                .column(Column::XVelocity)
+                .column(Column::YVelocity)
+                .skip()
+                .column(Column::XStandardDeviation)
+                .column(Column::YStandardDeviation)
+                .column(Column::ZStandardDeviation),
+        );
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(Time(1.0), point.time);
+        assert_eq!(Some(1.5), point.x_velocity);
+        assert_eq!(Some(2.5), point.y_velocity);
+        assert_eq!(None, point.z_velocity);
+        let accuracy = point.accuracy.unwrap();
+        assert_eq!(Time(1.0), accuracy.time);
+        assert_eq!(0.01, accuracy.x);
+        assert_eq!(0.02, accuracy.y);
+        assert_eq!(0.03, accuracy.z);
+        assert!(reader.read_point().is_err());
+    }
+
+    #[test]
+    fn header() {
+        let reader = Reader::new(HEADER.as_bytes()).unwrap();
+        assert_eq!(&ColumnMap::default(), reader.columns());
+
+        let data = "GPSTime(s), Latitude, Longitude, Ellipsoid-Height, SDEast, Status\n\
+                    10 45 -105 1600 0.5 4\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert_eq!(
+            &[
+                Column::Time,
+                Column::Latitude,
+                Column::Longitude,
+                Column::Altitude,
+                Column::XStandardDeviation,
+                Column::Skip,
+            ],
+            reader.columns().columns()
+        );
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(Time(10.0), point.time);
+        assert_eq!(0.5, point.accuracy.unwrap().x);
+
+        let reader = Reader::new("Mission 1 time\n".as_bytes()).unwrap();
+        assert_eq!(&ColumnMap::default(), reader.columns());
+    }
+
+    #[test]
+    fn comments() {
+        let data = "# time lat lon alt\n\
+                    ; exported by hand\n\
+                    1 45 -105 1600\n\
+                    \n\
+                    # a gap\n\
+                    2 45 -105 1601\n\
+                    \n";
+        let points = Reader::new(data.as_bytes())
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(1601.0, points[1].altitude);
+    }
+
+    #[test]
+    fn projected_columns() {
+        let mut reader = Reader::new("time easting northing\n1 500000 0\n".as_bytes()).unwrap();
+        match reader.read_point().unwrap_err() {
+            error::Error::Pos(Error::Line(2, err)) => {
+                assert!(matches!(*err, Error::ProjectedColumn(1)))
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn satellite_count() {
+        let mut reader = Reader::new("time nsats\n1 8\n2 7.9\n3 -3\n4 nan\n".as_bytes()).unwrap();
+        let point = reader.read_point().unwrap().unwrap();
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(8)),
+            point.accuracy.unwrap().satellite_count
+        );
+        for (line, value) in [(3, "7.9"), (4, "-3"), (5, "nan")] {
+            match reader.read_point().unwrap_err() {
+                error::Error::Pos(Error::Line(number, err)) => {
+                    assert_eq!(line, number);
+                    assert!(matches!(*err, Error::InvalidValue(1, ref v, None) if v == value));
+                }
+                err => panic!("unexpected error: {}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn lenient() {
+        let data = "time lat lon\n1 45 -105\n2 45\n3 x -105\n4 45 -105\n";
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        assert!(reader.read_point().unwrap().is_some());
+        match reader.read_point().unwrap_err() {
+            error::Error::Pos(Error::Line(3, err)) => {
+                assert_eq!("Missing a value for column 2", err.to_string())
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+
+        let mut reader = Reader::new(data.as_bytes()).unwrap();
+        reader.set_parse_mode(ParseMode::Lenient);
+        assert_eq!(Time(1.0), reader.read_point().unwrap().unwrap().time);
+        assert_eq!(Time(4.0), reader.read_point().unwrap().unwrap().time);
+        assert!(reader.read_point().unwrap().is_none());
+        assert_eq!(2, reader.skipped());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_reader() {
+        use futures_util::StreamExt;
+
+        let data = "; exported\n# time lat lon sdn\n\n1 45 -105 0.5\n2 45.5 -105.5 0.25\n";
+        let points = Reader::new(data.as_bytes())
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        let runtime = ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let read: Vec<_> = runtime.block_on(AsyncReader::new(data.as_bytes()).collect());
+        let read = read.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(points, read);
+
+        let data = "time lat lon\n1 45\n";
+        let read: Vec<_> = runtime.block_on(AsyncReader::new(data.as_bytes()).collect());
+        match read[0] {
+            Err(error::Error::Pos(Error::Line(2, ref err))) => {
+                assert_eq!("Missing a value for column 2", err.to_string())
+            }
+            ref result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn names() {
+        let columns = [
+            Column::Time,
+            Column::Latitude,
+            Column::Longitude,
+            Column::Altitude,
+            Column::Roll,
+            Column::Pitch,
+            Column::Yaw,
+            Column::Distance,
+            Column::XVelocity,
+            Column::YVelocity,
+            Column::ZVelocity,
+            Column::WanderAngle,
+            Column::XAcceleration,
+            Column::YAcceleration,
+            Column::ZAcceleration,
+            Column::XAngularRate,
+            Column::YAngularRate,
+            Column::ZAngularRate,
+            Column::XStandardDeviation,
+            Column::YStandardDeviation,
+            Column::ZStandardDeviation,
+            Column::RollStandardDeviation,
+            Column::PitchStandardDeviation,
+            Column::YawStandardDeviation,
+            Column::XVelocityStandardDeviation,
+            Column::YVelocityStandardDeviation,
+            Column::ZVelocityStandardDeviation,
+            Column::Pdop,
+            Column::SatelliteCount,
+            Column::Easting,
+            Column::Northing,
+        ];
+        for &column in &columns {
+            assert_eq!(Some(column), Column::from_name(column.name()));
+        }
+        assert_eq!(None, Column::from_name(Column::Skip.name()));
+    }
 }
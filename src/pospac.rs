@@ -0,0 +1,424 @@
+//! Applanix POSPac ASCII exports.
+//!
+//! POSPac's export tool writes one record per line, with columns chosen by an export template.
+//! The common templates are available as `Template::geographic` and `Template::grid`, and other
+//! layouts can be described field by field. Lines that don't start with a number, e.g. the header
+//! block, are skipped.
+
+use crate::error;
+use crate::point::{Accuracy, Point};
+use crate::source::Source;
+use crate::time::Time;
+use crate::units::Radians;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// POSPac errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// A line has fewer values than the template has fields.
+    #[error("Missing a value for field {0}")]
+    MissingField(usize),
+}
+
+/// A field in a POSPac export.
+///
+/// Angles are in degrees, and standard deviations are in meters or degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum Field {
+    Time,
+    Distance,
+    Latitude,
+    Longitude,
+    Easting,
+    Northing,
+    /// The ellipsoidal height.
+    Height,
+    Roll,
+    Pitch,
+    Heading,
+    EastVelocity,
+    NorthVelocity,
+    UpVelocity,
+    EastStandardDeviation,
+    NorthStandardDeviation,
+    HeightStandardDeviation,
+    RollStandardDeviation,
+    PitchStandardDeviation,
+    HeadingStandardDeviation,
+    /// A field that isn't read.
+    Skip,
+}
+
+/// The direction in which an export's heading increases.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HeadingConvention {
+    /// Heading increases clockwise from north, the POSPac default.
+    #[default]
+    Clockwise,
+    /// Heading increases counter-clockwise from north.
+    CounterClockwise,
+}
+
+/// The fields of a POSPac export, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    fields: Vec<Field>,
+}
+
+impl Template {
+    /// Creates a template from its fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::{Field, Template};
+    /// let template = Template::new(vec![Field::Time, Field::Latitude, Field::Longitude]);
+    /// ```
+    pub fn new(fields: Vec<Field>) -> Template {
+        Template { fields }
+    }
+
+    /// The default geographic export: time, distance, latitude, longitude, ellipsoidal height,
+    /// roll, pitch, heading, east, north, and up velocity, and the standard deviations of the
+    /// east, north, and height positions and of roll, pitch, and heading.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::Template;
+    /// assert_eq!(17, Template::geographic().fields().len());
+    /// ```
+    pub fn geographic() -> Template {
+        Template::new(vec![
+            Field::Time,
+            Field::Distance,
+            Field::Latitude,
+            Field::Longitude,
+            Field::Height,
+            Field::Roll,
+            Field::Pitch,
+            Field::Heading,
+            Field::EastVelocity,
+            Field::NorthVelocity,
+            Field::UpVelocity,
+            Field::EastStandardDeviation,
+            Field::NorthStandardDeviation,
+            Field::HeightStandardDeviation,
+            Field::RollStandardDeviation,
+            Field::PitchStandardDeviation,
+            Field::HeadingStandardDeviation,
+        ])
+    }
+
+    /// The default grid export, which is the geographic export with easting and northing in
+    /// place of latitude and longitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::{Field, Template};
+    /// assert_eq!(Field::Easting, Template::grid().fields()[2]);
+    /// ```
+    pub fn grid() -> Template {
+        let mut template = Template::geographic();
+        template.fields[2] = Field::Easting;
+        template.fields[3] = Field::Northing;
+        template
+    }
+
+    /// Returns this template's fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::{Field, Template};
+    /// assert_eq!(Field::Time, Template::default().fields()[0]);
+    /// ```
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+}
+
+impl Default for Template {
+    fn default() -> Template {
+        Template::geographic()
+    }
+}
+
+/// A POSPac record.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Record {
+    /// The record as a point.
+    ///
+    /// For grid exports, the point's latitude and longitude are zero.
+    pub point: Point,
+
+    /// The easting, for grid exports.
+    pub easting: Option<f64>,
+
+    /// The northing, for grid exports.
+    pub northing: Option<f64>,
+}
+
+/// A POSPac export reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    heading_convention: HeadingConvention,
+    reader: R,
+    template: Template,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::Reader;
+    /// let reader = Reader::from_path("data/2-points.pospac").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Ok(Reader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead`, using the geographic template.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::Reader;
+    /// let reader = Reader::new("".as_bytes());
+    /// ```
+    pub fn new(reader: R) -> Reader<R> {
+        Reader {
+            heading_convention: HeadingConvention::default(),
+            reader,
+            template: Template::default(),
+        }
+    }
+
+    /// Sets the template used to read records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::{Reader, Template};
+    /// let data = "1.0 0.0 500000.0 4000000.0 1600.0 0 0 0 0 0 0 0 0 0 0 0 0\n";
+    /// let mut reader = Reader::new(data.as_bytes());
+    /// reader.set_template(Template::grid());
+    /// let record = reader.read_record().unwrap().unwrap();
+    /// assert_eq!(Some(500000.0), record.easting);
+    /// ```
+    pub fn set_template(&mut self, template: Template) {
+        self.template = template;
+    }
+
+    /// Sets the heading convention of the export.
+    ///
+    /// Counter-clockwise headings are negated, so a point's yaw always increases clockwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::{Field, HeadingConvention, Reader, Template};
+    /// let mut reader = Reader::new("1.0 90.0\n".as_bytes());
+    /// reader.set_template(Template::new(vec![Field::Time, Field::Heading]));
+    /// reader.set_heading_convention(HeadingConvention::CounterClockwise);
+    /// assert_eq!(-90.0, reader.read_point().unwrap().unwrap().yaw.to_degrees());
+    /// ```
+    pub fn set_heading_convention(&mut self, heading_convention: HeadingConvention) {
+        self.heading_convention = heading_convention;
+    }
+
+    /// Reads the next record.
+    ///
+    /// Velocities are stored as x (east), y (north), and z (up) velocities. If the template has
+    /// any standard deviation fields, the point has an accuracy. Values beyond the last field are
+    /// ignored.
+    ///
+    /// Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.pospac").unwrap();
+    /// let record = reader.read_record().unwrap().unwrap();
+    /// assert_eq!(None, record.easting);
+    /// ```
+    pub fn read_record(&mut self) -> Result<Option<Record>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let is_record = line
+                .split_whitespace()
+                .next()
+                .is_some_and(|value| value.parse::<f64>().is_ok());
+            if is_record {
+                return self.parse(&line).map(Some);
+            }
+        }
+    }
+
+    /// Reads the next record as a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::pospac::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.pospac").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_record()
+            .map(|record| record.map(|record| record.point))
+    }
+
+    fn parse(&self, line: &str) -> Result<Record, error::Error> {
+        let mut values = line.split_whitespace();
+        let mut record = Record::default();
+        let mut accuracy = None;
+        for (i, &field) in self.template.fields.iter().enumerate() {
+            let value = values.next().ok_or(Error::MissingField(i))?;
+            if field == Field::Skip {
+                continue;
+            }
+            let value: f64 = value.parse()?;
+            let point = &mut record.point;
+            match field {
+                Field::Time => point.time = Time(value),
+                Field::Distance => point.distance = Some(value),
+                Field::Latitude => point.latitude = Radians::from_degrees(value),
+                Field::Longitude => point.longitude = Radians::from_degrees(value),
+                Field::Easting => record.easting = Some(value),
+                Field::Northing => record.northing = Some(value),
+                Field::Height => point.altitude = value,
+                Field::Roll => point.roll = Radians::from_degrees(value),
+                Field::Pitch => point.pitch = Radians::from_degrees(value),
+                Field::Heading => {
+                    point.yaw = match self.heading_convention {
+                        HeadingConvention::Clockwise => Radians::from_degrees(value),
+                        HeadingConvention::CounterClockwise => Radians::from_degrees(-value),
+                    }
+                }
+                Field::EastVelocity => point.x_velocity = Some(value),
+                Field::NorthVelocity => point.y_velocity = Some(value),
+                Field::UpVelocity => point.z_velocity = Some(value),
+                Field::EastStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).x = value
+                }
+                Field::NorthStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).y = value
+                }
+                Field::HeightStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).z = value
+                }
+                Field::RollStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).roll =
+                        Radians::from_degrees(value)
+                }
+                Field::PitchStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).pitch =
+                        Radians::from_degrees(value)
+                }
+                Field::HeadingStandardDeviation => {
+                    accuracy.get_or_insert_with(Accuracy::default).yaw =
+                        Radians::from_degrees(value)
+                }
+                Field::Skip => {}
+            }
+        }
+        if let Some(ref mut accuracy) = accuracy {
+            accuracy.time = record.point.time;
+        }
+        record.point.accuracy = accuracy;
+        Ok(record)
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over a POSPac reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_file() {
+        let points = Reader::from_path("data/2-points.pospac")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        let point = points[0];
+        assert_eq!(Time(151631.0), point.time);
+        assert_eq!(Some(0.0), point.distance);
+        assert!((40.1 - point.latitude.to_degrees()).abs() < 1e-9);
+        assert!((-105.1 - point.longitude.to_degrees()).abs() < 1e-9);
+        assert_eq!(1600.0, point.altitude);
+        assert!((350.0 - point.yaw.to_degrees()).abs() < 1e-9);
+        assert_eq!(Some(1.5), point.x_velocity);
+        assert_eq!(Some(-0.1), point.z_velocity);
+        let accuracy = point.accuracy.unwrap();
+        assert_eq!(Time(151631.0), accuracy.time);
+        assert_eq!(0.03, accuracy.y);
+        assert!((0.05 - accuracy.yaw.to_degrees()).abs() < 1e-12);
+        assert_eq!(Some(0.15), points[1].distance);
+    }
+
+    #[test]
+    fn grid() {
+        let data = "  TIME  DISTANCE  EASTING  NORTHING\n\
+                    (sec)  (m)  (m)  (m)\n\
+                    1.0 2.0 500000.0 4000000.0 1600.0\n\
+                    2.0 3.0 500001.0\n";
+        let mut reader = Reader::new(data.as_bytes());
+        reader.set_template(Template::new(vec![
+            Field::Time,
+            Field::Skip,
+            Field::Easting,
+            Field::Northing,
+            Field::Height,
+        ]));
+        let record = reader.read_record().unwrap().unwrap();
+        assert_eq!(Some(500000.0), record.easting);
+        assert_eq!(Some(4000000.0), record.northing);
+        assert_eq!(None, record.point.distance);
+        assert_eq!(None, record.point.accuracy);
+        assert!(reader.read_record().is_err());
+    }
+}
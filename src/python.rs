@@ -0,0 +1,275 @@
+//! Python bindings, as an extension module named `pos`.
+//!
+//! The module has a `Reader` that iterates over the points of any file that `open` can read, a
+//! `Trajectory` that holds a file's points in memory, and `read_arrays`, which reads a file
+//! straight into one NumPy array per point field without making a Python object for each point.
+//! Fields have the names and units of `Point`'s, with accuracy fields flattened into
+//! `accuracy_*` names as in the `arrow` module, so angles are in radians. Missing values are
+//! `None` on points and NaN in arrays.
+//!
+//! ```python
+//! import pos
+//!
+//! arrays = pos.read_arrays("data/2-points.sbet", ["time", "altitude"])
+//! for point in pos.Reader("data/2-points.sbet"):
+//!     print(point.time, point.x_velocity)
+//! ```
+//!
+//! Requires the `pyo3` feature, and NumPy in the Python environment. `pyproject.toml` sets the
+//! features for maturin, so `maturin develop` builds and installs the module into the current
+//! environment.
+
+use crate::error::Error;
+use crate::point::Point;
+use crate::source::{self, Source};
+use crate::time::Time;
+use crate::trajectory::Trajectory;
+use numpy::PyArray1;
+use pyo3::exceptions::{PyAttributeError, PyIOError, PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+type Getter = fn(&Point) -> Option<f64>;
+
+const FIELDS: &[(&str, Getter)] = &[
+    ("time", |p| Some(p.time.0)),
+    ("latitude", |p| Some(p.latitude.0)),
+    ("longitude", |p| Some(p.longitude.0)),
+    ("altitude", |p| Some(p.altitude)),
+    ("roll", |p| Some(p.roll.0)),
+    ("pitch", |p| Some(p.pitch.0)),
+    ("yaw", |p| Some(p.yaw.0)),
+    ("distance", |p| p.distance),
+    ("x_velocity", |p| p.x_velocity),
+    ("y_velocity", |p| p.y_velocity),
+    ("z_velocity", |p| p.z_velocity),
+    ("wander_angle", |p| p.wander_angle.map(|a| a.0)),
+    ("x_acceleration", |p| p.x_acceleration),
+    ("y_acceleration", |p| p.y_acceleration),
+    ("z_acceleration", |p| p.z_acceleration),
+    ("x_angular_rate", |p| p.x_angular_rate.map(|a| a.0)),
+    ("y_angular_rate", |p| p.y_angular_rate.map(|a| a.0)),
+    ("z_angular_rate", |p| p.z_angular_rate.map(|a| a.0)),
+    ("accuracy_x", |p| p.accuracy.map(|a| a.x)),
+    ("accuracy_y", |p| p.accuracy.map(|a| a.y)),
+    ("accuracy_z", |p| p.accuracy.map(|a| a.z)),
+    ("accuracy_roll", |p| p.accuracy.map(|a| a.roll.0)),
+    ("accuracy_pitch", |p| p.accuracy.map(|a| a.pitch.0)),
+    ("accuracy_yaw", |p| p.accuracy.map(|a| a.yaw.0)),
+    ("accuracy_pdop", |p| p.accuracy.map(|a| a.pdop)),
+    ("accuracy_x_velocity", |p| {
+        p.accuracy.and_then(|a| a.x_velocity)
+    }),
+    ("accuracy_y_velocity", |p| {
+        p.accuracy.and_then(|a| a.y_velocity)
+    }),
+    ("accuracy_z_velocity", |p| {
+        p.accuracy.and_then(|a| a.z_velocity)
+    }),
+];
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        match err {
+            Error::Io(err) => PyIOError::new_err(err.to_string()),
+            err => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// A point, with each field as an attribute.
+#[pyclass(name = "Point", frozen, from_py_object)]
+#[derive(Clone, Copy, Debug)]
+pub struct PyPoint(Point);
+
+#[pymethods]
+impl PyPoint {
+    fn __getattr__(&self, name: &str) -> PyResult<Option<f64>> {
+        FIELDS
+            .iter()
+            .find(|&&(field, _)| field == name)
+            .map(|&(_, get)| get(&self.0))
+            .ok_or_else(|| PyAttributeError::new_err(format!("Point has no field {:?}", name)))
+    }
+
+    fn __dir__(&self) -> Vec<&'static str> {
+        FIELDS.iter().map(|&(name, _)| name).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Point({})", self.0)
+    }
+}
+
+/// A reader for any file that `open` can read, which is an iterator over its points.
+#[pyclass(name = "Reader", unsendable)]
+#[derive(Debug)]
+pub struct PyReader {
+    source: Box<dyn Source>,
+}
+
+#[pymethods]
+impl PyReader {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<PyReader> {
+        Ok(PyReader {
+            source: source::open(path)?,
+        })
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<PyPoint>> {
+        Ok(self.source.source()?.map(PyPoint))
+    }
+}
+
+/// A file's points, in memory, sorted by time.
+#[pyclass(name = "Trajectory", frozen, skip_from_py_object)]
+#[derive(Clone, Debug)]
+pub struct PyTrajectory(Trajectory);
+
+#[pymethods]
+impl PyTrajectory {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<PyTrajectory> {
+        Ok(PyTrajectory(Trajectory::from_source(source::open(path)?)?))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<PyPoint> {
+        let points = self.0.points();
+        let index = if index < 0 {
+            index + points.len() as isize
+        } else {
+            index
+        };
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| points.get(index))
+            .map(|&point| PyPoint(point))
+            .ok_or_else(|| PyIndexError::new_err("trajectory index out of range"))
+    }
+
+    /// Interpolates a point at a time, with `Trajectory::interpolate_at`.
+    fn interpolate_at(&self, time: f64) -> PyResult<PyPoint> {
+        Ok(PyPoint(self.0.interpolate_at(Time(time))?))
+    }
+
+    /// Returns a dictionary of one NumPy array per field, for every field or just the named ones.
+    #[pyo3(signature = (fields = None))]
+    fn arrays<'py>(
+        &self,
+        py: Python<'py>,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let fields = select(fields)?;
+        let columns = columns(&fields, self.0.iter().map(|&point| Ok(point)))?;
+        dict(py, &fields, columns)
+    }
+}
+
+/// Reads a file into a dictionary of one NumPy array per field, for every field or just the named
+/// ones.
+///
+/// The Python interpreter isn't blocked while the file is read.
+#[pyfunction]
+#[pyo3(signature = (path, fields = None))]
+pub fn read_arrays(
+    py: Python<'_>,
+    path: PathBuf,
+    fields: Option<Vec<String>>,
+) -> PyResult<Bound<'_, PyDict>> {
+    let fields = select(fields)?;
+    let columns = py.detach(|| {
+        let mut source = source::open(path)?;
+        columns(&fields, std::iter::from_fn(|| source.source().transpose()))
+    })?;
+    dict(py, &fields, columns)
+}
+
+#[pymodule]
+#[pyo3(name = "pos")]
+fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPoint>()?;
+    m.add_class::<PyReader>()?;
+    m.add_class::<PyTrajectory>()?;
+    m.add_function(wrap_pyfunction!(read_arrays, m)?)?;
+    Ok(())
+}
+
+/// Returns the named fields, in order, or every field if there are no names.
+fn select(names: Option<Vec<String>>) -> PyResult<Vec<(&'static str, Getter)>> {
+    match names {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                FIELDS
+                    .iter()
+                    .find(|&&(field, _)| field == name)
+                    .copied()
+                    .ok_or_else(|| PyValueError::new_err(format!("Unknown field: {:?}", name)))
+            })
+            .collect(),
+        None => Ok(FIELDS.to_vec()),
+    }
+}
+
+/// Collects each field's values from points, with NaN for missing values.
+fn columns<I: Iterator<Item = Result<Point, Error>>>(
+    fields: &[(&str, Getter)],
+    points: I,
+) -> Result<Vec<Vec<f64>>, Error> {
+    let mut columns = vec![Vec::new(); fields.len()];
+    for point in points {
+        let point = point?;
+        for (column, &(_, get)) in columns.iter_mut().zip(fields) {
+            column.push(get(&point).unwrap_or(f64::NAN));
+        }
+    }
+    Ok(columns)
+}
+
+fn dict<'py>(
+    py: Python<'py>,
+    fields: &[(&str, Getter)],
+    columns: Vec<Vec<f64>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (&(name, _), column) in fields.iter().zip(columns) {
+        dict.set_item(name, PyArray1::from_vec(py, column))?;
+    }
+    Ok(dict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+
+    #[test]
+    fn columns_from_file() {
+        let fields = select(Some(vec!["time".to_string(), "accuracy_x".to_string()])).unwrap();
+        let mut reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        let columns = columns(&fields, std::iter::from_fn(|| reader.source().transpose())).unwrap();
+        assert_eq!(2, columns.len());
+        assert!((columns[0][0] - 151631.002836).abs() < 1e-6);
+        assert_eq!(2, columns[1].len());
+        assert!(columns[1].iter().all(|value| value.is_nan()));
+    }
+
+    #[test]
+    fn select_fields() {
+        assert_eq!(FIELDS.len(), select(None).unwrap().len());
+        let fields = select(Some(vec!["yaw".to_string(), "time".to_string()])).unwrap();
+        assert_eq!("yaw", fields[0].0);
+        assert!(select(Some(vec!["heading".to_string()])).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+//! Quality control of position points.
+//!
+//! Corrupt records show up as physically impossible jumps in position or attitude. Each point is
+//! checked against the last good point, so one bad record doesn't also flag the good record after
+//! it. The first point is always assumed to be good.
+
+use crate::compare::Offset;
+use crate::error;
+use crate::point::{self, Point};
+use crate::source::Source;
+use crate::time::Time;
+use crate::units::Radians;
+
+/// Quality control errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// A point failed validation, with the point's index.
+    #[error("Invalid point {0}")]
+    InvalidPoint(usize, #[source] point::Error),
+}
+
+/// The default maximum implied speed, in meters per second.
+pub const DEFAULT_MAX_SPEED: f64 = 500.;
+
+/// The default maximum implied angular rate, in degrees per second.
+pub const DEFAULT_MAX_ANGULAR_RATE_DEGREES: f64 = 360.;
+
+/// Physical limits on the motion between points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    /// The maximum speed implied by the position change between points, in meters per second.
+    pub max_speed: f64,
+    /// The maximum roll, pitch, or yaw rate implied by the attitude change between points.
+    pub max_angular_rate: Radians<f64>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_speed: DEFAULT_MAX_SPEED,
+            max_angular_rate: Radians::from_degrees(DEFAULT_MAX_ANGULAR_RATE_DEGREES),
+        }
+    }
+}
+
+impl Limits {
+    /// Returns true if moving from a good point to another point exceeds these limits.
+    ///
+    /// Points with the same time as the good point exceed the limits if they moved at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::qc::Limits;
+    /// use pos::time::Time;
+    /// let good = Point::default();
+    /// let point = Point { time: Time(1.0), altitude: 1000.0, ..Default::default() };
+    /// assert!(Limits::default().exceeded(&good, &point));
+    /// ```
+    pub fn exceeded(&self, good: &Point, point: &Point) -> bool {
+        let dt = (point.time - good.time).abs().0;
+        let offset = Offset::between(point, good);
+        let distance = offset.north.hypot(offset.east).hypot(offset.up);
+        let angle = offset
+            .roll
+            .0
+            .abs()
+            .max(offset.pitch.0.abs())
+            .max(offset.yaw.0.abs());
+        distance > self.max_speed * dt || angle > self.max_angular_rate.0 * dt
+    }
+}
+
+/// Returns the indices of the points that exceed the limits.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// use pos::qc::{self, Limits};
+/// use pos::time::Time;
+/// let mut points: Vec<_> = (0..5)
+///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+///     .collect();
+/// points[2].altitude = 1e6;
+/// assert_eq!(vec![2], qc::outliers(&points, &Limits::default()));
+/// ```
+pub fn outliers(points: &[Point], limits: &Limits) -> Vec<usize> {
+    let mut outliers = Vec::new();
+    let mut good = match points.first() {
+        Some(point) => point,
+        None => return outliers,
+    };
+    for (i, point) in points.iter().enumerate().skip(1) {
+        if limits.exceeded(good, point) {
+            outliers.push(i);
+        } else {
+            good = point;
+        }
+    }
+    outliers
+}
+
+/// A problem with the timestamps of a sequence of points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeIssue {
+    /// The point at this index has the same time as the point before it.
+    Duplicate {
+        /// The index of the point.
+        index: usize,
+        /// The repeated time.
+        time: Time,
+    },
+    /// The point at this index is earlier than the point before it, e.g. because a logger
+    /// restarted its GPS seconds of week.
+    Backwards {
+        /// The index of the point.
+        index: usize,
+        /// The time of the point.
+        time: Time,
+        /// The time of the point before it.
+        previous: Time,
+    },
+}
+
+/// Checks that the times of some points strictly increase.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// use pos::qc::{self, TimeIssue};
+/// use pos::time::Time;
+/// let points: Vec<_> = [1.0, 2.0, 2.0, 0.5]
+///     .iter()
+///     .map(|&time| Point { time: Time(time), ..Default::default() })
+///     .collect();
+/// let issues = qc::validate_time(&points);
+/// assert_eq!(TimeIssue::Duplicate { index: 2, time: Time(2.0) }, issues[0]);
+/// assert_eq!(TimeIssue::Backwards { index: 3, time: Time(0.5), previous: Time(2.0) }, issues[1]);
+/// ```
+pub fn validate_time(points: &[Point]) -> Vec<TimeIssue> {
+    points
+        .windows(2)
+        .enumerate()
+        .filter_map(|(i, pair)| {
+            let (previous, time) = (pair[0].time, pair[1].time);
+            if time == previous {
+                Some(TimeIssue::Duplicate { index: i + 1, time })
+            } else if time < previous {
+                Some(TimeIssue::Backwards {
+                    index: i + 1,
+                    time,
+                    previous,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Sorts points by time, keeping points with the same time in their original order.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// use pos::time::Time;
+/// let mut points = vec![
+///     Point { time: Time(2.0), ..Default::default() },
+///     Point { time: Time(1.0), ..Default::default() },
+/// ];
+/// pos::qc::sort_by_time(&mut points);
+/// assert_eq!(Time(1.0), points[0].time);
+/// ```
+pub fn sort_by_time(points: &mut [Point]) {
+    points.sort_by(|a, b| a.time.0.total_cmp(&b.time.0));
+}
+
+/// Removes consecutive points with the same time, keeping the first, and returns the number of
+/// points removed.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// let mut points = vec![Point::default(), Point { altitude: 1.0, ..Default::default() }];
+/// assert_eq!(1, pos::qc::dedup(&mut points));
+/// assert_eq!(0.0, points[0].altitude);
+/// ```
+pub fn dedup(points: &mut Vec<Point>) -> usize {
+    let len = points.len();
+    points.dedup_by(|a, b| a.time == b.time);
+    len - points.len()
+}
+
+/// A source that skips the points of another source that exceed some limits.
+#[derive(Debug)]
+pub struct Filter<S: Source> {
+    good: Option<Point>,
+    limits: Limits,
+    skipped: usize,
+    source: S,
+}
+
+impl<S: Source> Filter<S> {
+    /// Filters a source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::qc::{Filter, Limits};
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let filter = Filter::new(source, Limits::default());
+    /// ```
+    pub fn new(source: S, limits: Limits) -> Filter<S> {
+        Filter {
+            good: None,
+            limits,
+            skipped: 0,
+            source,
+        }
+    }
+
+    /// Returns the number of points that have been skipped so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::qc::{Filter, Limits};
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let filter = Filter::new(source, Limits::default());
+    /// assert_eq!(0, filter.skipped());
+    /// ```
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+impl<S: Source> Source for Filter<S> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        while let Some(point) = self.source.source()? {
+            match self.good {
+                Some(ref good) if self.limits.exceeded(good, &point) => self.skipped += 1,
+                _ => {
+                    self.good = Some(point);
+                    return Ok(Some(point));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A source that fails on the first point of another source that isn't valid, for strict reading.
+///
+/// See `Point::validate` for the checks.
+#[derive(Debug)]
+pub struct Validate<S: Source> {
+    index: usize,
+    source: S,
+}
+
+impl<S: Source> Validate<S> {
+    /// Validates the points of a source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::qc::Validate;
+    /// use pos::Source;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let mut validate = Validate::new(source);
+    /// while let Some(point) = validate.source().unwrap() {}
+    /// ```
+    pub fn new(source: S) -> Validate<S> {
+        Validate { index: 0, source }
+    }
+}
+
+impl<S: Source> Source for Validate<S> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        let point = match self.source.source()? {
+            Some(point) => point,
+            None => return Ok(None),
+        };
+        if let Err(err) = point.validate() {
+            return Err(Error::InvalidPoint(self.index, err).into());
+        }
+        self.index += 1;
+        Ok(Some(point))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+    use std::vec::IntoIter;
+
+    #[derive(Debug)]
+    struct Points(IntoIter<Point>);
+
+    impl Source for Points {
+        fn source(&mut self) -> Result<Option<Point>, error::Error> {
+            Ok(self.0.next())
+        }
+    }
+
+    fn points() -> Vec<Point> {
+        let mut points: Vec<_> = (0..10)
+            .map(|i| Point {
+                time: Time(i as f64),
+                latitude: Radians::from_degrees(1e-4 * i as f64),
+                yaw: Radians::from_degrees(350. + 20. * i as f64).wrap(),
+                ..Default::default()
+            })
+            .collect();
+        points[3].latitude = Radians::from_degrees(1.);
+        points[6].roll = Radians::from_degrees(90.);
+        points[7].roll = Radians::from_degrees(-100.);
+        points
+    }
+
+    #[test]
+    fn outlier_indices() {
+        let limits = Limits {
+            max_angular_rate: Radians::from_degrees(45.),
+            ..Default::default()
+        };
+        assert_eq!(vec![3, 6, 7], outliers(&points(), &limits));
+        assert!(outliers(&[], &limits).is_empty());
+    }
+
+    #[test]
+    fn filter() {
+        let limits = Limits {
+            max_angular_rate: Radians::from_degrees(45.),
+            ..Default::default()
+        };
+        let mut filter = Filter::new(Points(points().into_iter()), limits);
+        let mut times = Vec::new();
+        while let Some(point) = filter.source().unwrap() {
+            times.push(point.time.0);
+        }
+        assert_eq!(vec![0., 1., 2., 4., 5., 8., 9.], times);
+        assert_eq!(3, filter.skipped());
+    }
+
+    #[test]
+    fn time_issues() {
+        let mut points: Vec<_> = [0., 1., 2., 604799., 0., 1., 1.]
+            .iter()
+            .map(|&time| Point {
+                time: Time(time),
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                TimeIssue::Backwards {
+                    index: 4,
+                    time: Time(0.),
+                    previous: Time(604799.),
+                },
+                TimeIssue::Duplicate {
+                    index: 6,
+                    time: Time(1.)
+                },
+            ],
+            validate_time(&points)
+        );
+        sort_by_time(&mut points);
+        assert_eq!(3, dedup(&mut points));
+        assert!(validate_time(&points).is_empty());
+        assert!(validate_time(&[]).is_empty());
+    }
+
+    #[test]
+    fn validate() {
+        let mut points = points();
+        points[2].latitude = Radians::from_degrees(95.);
+        let mut validate = Validate::new(Points(points.into_iter()));
+        assert!(validate.source().unwrap().is_some());
+        assert!(validate.source().unwrap().is_some());
+        match validate.source().unwrap_err() {
+            error::Error::Qc(Error::InvalidPoint(2, err)) => {
+                assert_eq!("The latitude is out of range: 95 degrees", err.to_string())
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn sbet() {
+        let points: Vec<_> = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        assert!(outliers(&points, &Limits::default()).is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+//! Unit quaternions for attitude math.
+//!
+//! Attitudes are converted using the aerospace (z-y-x) rotation sequence: yaw about the z axis,
+//! then pitch about the new y axis, then roll about the new x axis. The resulting rotation takes
+//! vectors from the body frame to the navigation frame.
+
+use crate::units::Radians;
+
+/// The dot product above which slerp falls back to linear interpolation.
+const SLERP_THRESHOLD: f64 = 0.9995;
+
+/// A quaternion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    /// Creates a unit quaternion from roll, pitch, and yaw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let quaternion = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(0.));
+    /// assert_eq!(1.0, quaternion.w);
+    /// ```
+    pub fn from_euler(roll: Radians<f64>, pitch: Radians<f64>, yaw: Radians<f64>) -> Quaternion {
+        let (sr, cr) = (roll.0 / 2.0).sin_cos();
+        let (sp, cp) = (pitch.0 / 2.0).sin_cos();
+        let (sy, cy) = (yaw.0 / 2.0).sin_cos();
+        Quaternion {
+            w: cr * cp * cy + sr * sp * sy,
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+        }
+    }
+
+    /// Converts this quaternion to roll, pitch, and yaw.
+    ///
+    /// Roll and yaw are in (-π, π], pitch is in [-π/2, π/2].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let quaternion = Quaternion::from_euler(Radians(0.1), Radians(0.2), Radians(0.3));
+    /// let (roll, pitch, yaw) = quaternion.to_euler();
+    /// assert!((roll.0 - 0.1).abs() < 1e-12);
+    /// ```
+    pub fn to_euler(&self) -> (Radians<f64>, Radians<f64>, Radians<f64>) {
+        let roll = (2.0 * (self.w * self.x + self.y * self.z))
+            .atan2(1.0 - 2.0 * (self.x * self.x + self.y * self.y));
+        let pitch = (2.0 * (self.w * self.y - self.z * self.x))
+            .clamp(-1.0, 1.0)
+            .asin();
+        let yaw = (2.0 * (self.w * self.z + self.x * self.y))
+            .atan2(1.0 - 2.0 * (self.y * self.y + self.z * self.z));
+        (Radians(roll), Radians(pitch), Radians(yaw))
+    }
+
+    /// Returns the rotation matrix of this unit quaternion.
+    ///
+    /// The matrix is row-major, and multiplies column vectors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let matrix = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(0.)).to_rotation_matrix();
+    /// assert_eq!([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]], matrix);
+    /// ```
+    pub fn to_rotation_matrix(&self) -> [[f64; 3]; 3] {
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ]
+    }
+
+    /// Returns the Hamilton product of two quaternions, i.e. the rotation `other` followed by this
+    /// rotation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let q1 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(0.25));
+    /// let q2 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(0.5));
+    /// let (_, _, yaw) = q1.multiply(&q2).to_euler();
+    /// assert!((yaw.0 - 0.75).abs() < 1e-12);
+    /// ```
+    pub fn multiply(&self, other: &Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        }
+    }
+
+    /// Returns the dot product of two quaternions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let quaternion = Quaternion::from_euler(Radians(0.1), Radians(0.2), Radians(0.3));
+    /// assert!((1.0 - quaternion.dot(&quaternion)).abs() < 1e-12);
+    /// ```
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Returns this quaternion scaled to unit length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// let quaternion = Quaternion { w: 2.0, x: 0.0, y: 0.0, z: 0.0 }.normalize();
+    /// assert_eq!(1.0, quaternion.w);
+    /// ```
+    pub fn normalize(&self) -> Quaternion {
+        let norm = self.dot(self).sqrt();
+        Quaternion {
+            w: self.w / norm,
+            x: self.x / norm,
+            y: self.y / norm,
+            z: self.z / norm,
+        }
+    }
+
+    /// Spherically interpolates between two unit quaternions along the shortest path.
+    ///
+    /// `factor` is zero at this quaternion and one at `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::quaternion::Quaternion;
+    /// use pos::Radians;
+    /// let q1 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(0.));
+    /// let q2 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians(1.));
+    /// let (_, _, yaw) = q1.slerp(&q2, 0.5).to_euler();
+    /// assert!((yaw.0 - 0.5).abs() < 1e-12);
+    /// ```
+    pub fn slerp(&self, other: &Quaternion, factor: f64) -> Quaternion {
+        let mut dot = self.dot(other);
+        let mut other = *other;
+        if dot < 0.0 {
+            other = Quaternion {
+                w: -other.w,
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+            };
+            dot = -dot;
+        }
+        let (a, b) = if dot > SLERP_THRESHOLD {
+            (1.0 - factor, factor)
+        } else {
+            let theta = dot.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - factor) * theta).sin() / sin_theta,
+                (factor * theta).sin() / sin_theta,
+            )
+        };
+        Quaternion {
+            w: a * self.w + b * other.w,
+            x: a * self.x + b * other.x,
+            y: a * self.y + b * other.y,
+            z: a * self.z + b * other.z,
+        }
+        .normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn euler_roundtrip() {
+        for &(roll, pitch, yaw) in &[(0.1, -0.2, 3.0), (-3.0, 1.2, -0.5), (0.0, 0.0, 0.0)] {
+            let (r, p, y) =
+                Quaternion::from_euler(Radians(roll), Radians(pitch), Radians(yaw)).to_euler();
+            assert!((roll - r.0).abs() < 1e-12);
+            assert!((pitch - p.0).abs() < 1e-12);
+            assert!((yaw - y.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn rotation_matrix() {
+        let (roll, pitch, yaw) = (0.1f64, -0.2f64, 3.0f64);
+        let matrix = Quaternion::from_euler(Radians(roll), Radians(pitch), Radians(yaw))
+            .to_rotation_matrix();
+        let (sr, cr) = roll.sin_cos();
+        let (sp, cp) = pitch.sin_cos();
+        let (sy, cy) = yaw.sin_cos();
+        let expected = [
+            [cp * cy, sr * sp * cy - cr * sy, cr * sp * cy + sr * sy],
+            [cp * sy, sr * sp * sy + cr * cy, cr * sp * sy - sr * cy],
+            [-sp, sr * cp, cr * cp],
+        ];
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((expected[i][j] - matrix[i][j]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn slerp_across_wrap() {
+        let q1 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians::from_degrees(179.0));
+        let q2 = Quaternion::from_euler(Radians(0.), Radians(0.), Radians::from_degrees(-179.0));
+        let (_, _, yaw) = q1.slerp(&q2, 0.5).to_euler();
+        assert!((180.0 - yaw.to_degrees().abs()).abs() < 1e-9);
+    }
+}
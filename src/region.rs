@@ -0,0 +1,309 @@
+//! Geographic regions, for clipping trajectories to a project area.
+//!
+//! A `Region` decides whether a point's latitude and longitude are inside it. A `BoundingBox` is
+//! always available, and with the `geo` feature `geo_types` polygons and multi-polygons are
+//! regions too, with their coordinates as longitude and latitude in degrees.
+//!
+//! Use `Trajectory::clip_region` to keep only the points inside a region, and
+//! `Trajectory::region_intervals` for the times the trajectory spends inside it. Going the other
+//! way, `BoundingBox::around` and `convex_hull` describe the area that some points cover, e.g. for
+//! catalog metadata or coverage maps.
+
+use crate::point::Point;
+
+/// An area that points can be inside of.
+pub trait Region {
+    /// Returns true if this point's latitude and longitude are inside the region.
+    fn contains(&self, point: &Point) -> bool;
+}
+
+/// A latitude and longitude bounding box, in degrees.
+///
+/// If the west edge is east of the east edge, the box crosses the antimeridian.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBox {
+    /// The western longitude.
+    pub west: f64,
+    /// The southern latitude.
+    pub south: f64,
+    /// The eastern longitude.
+    pub east: f64,
+    /// The northern latitude.
+    pub north: f64,
+}
+
+impl BoundingBox {
+    /// Creates a new bounding box from its edges, in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::region::BoundingBox;
+    /// let bbox = BoundingBox::new(-106.0, 39.0, -105.0, 40.0);
+    /// assert_eq!(40.0, bbox.north);
+    /// ```
+    pub fn new(west: f64, south: f64, east: f64, north: f64) -> BoundingBox {
+        BoundingBox {
+            west,
+            south,
+            east,
+            north,
+        }
+    }
+
+    /// Returns the smallest bounding box around some points, or none if there are no points.
+    ///
+    /// If the points are closer together across the antimeridian than around the other way, the
+    /// box crosses the antimeridian.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians};
+    /// use pos::region::BoundingBox;
+    /// let points: Vec<_> = [179.0, -179.0, 178.0]
+    ///     .iter()
+    ///     .map(|&lon| Point { longitude: Radians::from_degrees(lon), ..Default::default() })
+    ///     .collect();
+    /// let bbox = BoundingBox::around(&points).unwrap();
+    /// assert_eq!(178.0, bbox.west.round());
+    /// assert_eq!(-179.0, bbox.east.round());
+    /// ```
+    pub fn around(points: &[Point]) -> Option<BoundingBox> {
+        let first = points.first()?;
+        let mut longitudes: Vec<f64> = points
+            .iter()
+            .map(|point| point.longitude.wrap_pi().to_degrees())
+            .collect();
+        longitudes.sort_by(f64::total_cmp);
+        let (mut west, mut east) = (longitudes[0], longitudes[longitudes.len() - 1]);
+        let mut widest = west + 360. - east;
+        for pair in longitudes.windows(2) {
+            if pair[1] - pair[0] > widest {
+                widest = pair[1] - pair[0];
+                west = pair[1];
+                east = pair[0];
+            }
+        }
+        let latitude = first.latitude.to_degrees();
+        let (south, north) = points
+            .iter()
+            .fold((latitude, latitude), |(south, north), point| {
+                let latitude = point.latitude.to_degrees();
+                (south.min(latitude), north.max(latitude))
+            });
+        Some(BoundingBox::new(west, south, east, north))
+    }
+}
+
+impl Region for BoundingBox {
+    fn contains(&self, point: &Point) -> bool {
+        let latitude = point.latitude.to_degrees();
+        let longitude = point.longitude.wrap_pi().to_degrees();
+        let inside_longitude = if self.west <= self.east {
+            self.west <= longitude && longitude <= self.east
+        } else {
+            self.west <= longitude || longitude <= self.east
+        };
+        inside_longitude && self.south <= latitude && latitude <= self.north
+    }
+}
+
+#[cfg(feature = "geo")]
+impl Region for geo_types::Polygon<f64> {
+    fn contains(&self, point: &Point) -> bool {
+        let x = point.longitude.wrap_pi().to_degrees();
+        let y = point.latitude.to_degrees();
+        ring_contains(self.exterior(), x, y)
+            && !self
+                .interiors()
+                .iter()
+                .any(|ring| ring_contains(ring, x, y))
+    }
+}
+
+#[cfg(feature = "geo")]
+impl Region for geo_types::MultiPolygon<f64> {
+    fn contains(&self, point: &Point) -> bool {
+        self.iter().any(|polygon| polygon.contains(point))
+    }
+}
+
+/// Returns the convex hull of some points, as a closed, counterclockwise ring of longitude and
+/// latitude pairs in degrees.
+///
+/// Longitudes are unwrapped from the first point's, so a hull that crosses the antimeridian may
+/// have longitudes beyond ±180°. Fewer than three distinct points give a degenerate ring.
+///
+/// # Examples
+///
+/// ```
+/// use pos::{Point, Radians};
+/// use pos::region;
+/// let points: Vec<_> = [(0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 2.0)]
+///     .iter()
+///     .map(|&(longitude, latitude)| Point {
+///         longitude: Radians::from_degrees(longitude),
+///         latitude: Radians::from_degrees(latitude),
+///         ..Default::default()
+///     })
+///     .collect();
+/// let hull = region::convex_hull(&points);
+/// assert_eq!(5, hull.len());
+/// assert_eq!(hull[0], hull[4]);
+/// ```
+pub fn convex_hull(points: &[Point]) -> Vec<(f64, f64)> {
+    let origin = match points.first() {
+        Some(first) => first.longitude,
+        None => return Vec::new(),
+    };
+    let mut coordinates: Vec<(f64, f64)> = points
+        .iter()
+        .map(|point| {
+            let longitude = origin + point.longitude.angular_difference(origin);
+            (longitude.to_degrees(), point.latitude.to_degrees())
+        })
+        .collect();
+    coordinates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    coordinates.dedup();
+    if coordinates.len() == 1 {
+        return vec![coordinates[0]; 2];
+    }
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(coordinates.len() + 1);
+    for pass in 0..2 {
+        let start = hull.len();
+        for &coordinate in &coordinates {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], coordinate) <= 0.
+            {
+                let _ = hull.pop();
+            }
+            hull.push(coordinate);
+        }
+        let _ = hull.pop();
+        if pass == 0 {
+            coordinates.reverse();
+        }
+    }
+    if let Some(&first) = hull.first() {
+        hull.push(first);
+    }
+    hull
+}
+
+/// Returns true if a coordinate is inside a ring, by counting how many of its edges a ray to the
+/// east crosses.
+#[cfg(feature = "geo")]
+fn ring_contains(ring: &geo_types::LineString<f64>, x: f64, y: f64) -> bool {
+    ring.lines()
+        .filter(|line| {
+            let (start, end) = (line.start, line.end);
+            (start.y > y) != (end.y > y)
+                && x < start.x + (y - start.y) * (end.x - start.x) / (end.y - start.y)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::units::Radians;
+
+    fn point(longitude: f64, latitude: f64) -> Point {
+        Point {
+            longitude: Radians::from_degrees(longitude),
+            latitude: Radians::from_degrees(latitude),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn bounding_box() {
+        let bbox = BoundingBox::new(-106.0, 39.0, -105.0, 40.0);
+        assert!(bbox.contains(&point(-105.5, 39.5)));
+        assert!(bbox.contains(&point(254.5, 39.5)));
+        assert!(!bbox.contains(&point(-104.5, 39.5)));
+        assert!(!bbox.contains(&point(-105.5, 40.5)));
+
+        let antimeridian = BoundingBox::new(179.0, -1.0, -179.0, 1.0);
+        assert!(antimeridian.contains(&point(179.5, 0.0)));
+        assert!(antimeridian.contains(&point(-179.5, 0.0)));
+        assert!(!antimeridian.contains(&point(0.0, 0.0)));
+    }
+
+    #[test]
+    fn around() {
+        let points = [
+            point(-105.0, 40.0),
+            point(-106.0, 39.0),
+            point(-105.5, 39.5),
+        ];
+        assert_eq!(
+            BoundingBox::new(-106.0, 39.0, -105.0, 40.0),
+            round(BoundingBox::around(&points).unwrap())
+        );
+        let points = [point(179.0, 0.0), point(-178.0, -1.0), point(-179.0, 1.0)];
+        let bbox = BoundingBox::around(&points).unwrap();
+        assert_eq!(BoundingBox::new(179.0, -1.0, -178.0, 1.0), round(bbox));
+        assert!(points.iter().all(|point| bbox.contains(point)));
+        assert!(BoundingBox::around(&[]).is_none());
+    }
+
+    fn round(bbox: BoundingBox) -> BoundingBox {
+        let round = |value: f64| (value * 1e9).round() / 1e9;
+        BoundingBox::new(
+            round(bbox.west),
+            round(bbox.south),
+            round(bbox.east),
+            round(bbox.north),
+        )
+    }
+
+    #[test]
+    fn hull() {
+        let points = [
+            point(0.0, 0.0),
+            point(1.0, 0.5),
+            point(2.0, 0.0),
+            point(2.0, 2.0),
+            point(1.0, 1.0),
+            point(0.0, 2.0),
+            point(2.0, 0.0),
+        ];
+        let hull: Vec<_> = convex_hull(&points)
+            .into_iter()
+            .map(|(x, y)| (x.round(), y.round()))
+            .collect();
+        assert_eq!(
+            vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0), (0.0, 0.0)],
+            hull
+        );
+        let hull = convex_hull(&[point(179.0, 0.0), point(-179.0, 0.0), point(-179.0, 1.0)]);
+        assert!(hull.iter().all(|&(x, _)| x > 178.0));
+        assert_eq!(2, convex_hull(&[point(1.0, 1.0), point(1.0, 1.0)]).len());
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[cfg(feature = "geo")]
+    #[test]
+    fn polygon() {
+        use geo_types::{polygon, MultiPolygon};
+
+        let polygon = polygon!(
+            exterior: [(x: 0., y: 0.), (x: 10., y: 0.), (x: 10., y: 10.), (x: 0., y: 10.)],
+            interiors: [[(x: 4., y: 4.), (x: 6., y: 4.), (x: 6., y: 6.), (x: 4., y: 6.)]],
+        );
+        assert!(polygon.contains(&point(2.0, 2.0)));
+        assert!(!polygon.contains(&point(5.0, 5.0)));
+        assert!(!polygon.contains(&point(11.0, 5.0)));
+        let multi = MultiPolygon::new(vec![polygon]);
+        assert!(multi.contains(&point(2.0, 8.0)));
+    }
+}
@@ -0,0 +1,118 @@
+//! Reprojection of positions with PROJ.
+//!
+//! Coordinate reference systems are anything PROJ understands, e.g. `EPSG:4326`, `EPSG:2232`, or
+//! a PROJ pipeline. Coordinates are always in longitude/latitude or easting/northing order.
+//!
+//! Points store their position as geographic degrees, so projected coordinates are stored in
+//! degrees-valued fields, x as longitude and y as latitude, in the same way the trj format stores
+//! them. Writers then write them out unchanged. Heights aren't transformed.
+//!
+//! Requires the `proj` feature, which links to the PROJ C library.
+
+use crate::error::Error;
+use crate::point::Point;
+use crate::source::Source;
+use crate::units::Radians;
+use proj::Proj;
+
+/// A source that transforms the positions of another source from one CRS to another.
+#[derive(Debug)]
+pub struct Reproject<S: Source> {
+    proj: Proj,
+    source: S,
+}
+
+impl<S: Source> Reproject<S> {
+    /// Reprojects a source from one CRS to another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::reproject::Reproject;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let reproject = Reproject::new(source, "EPSG:4326", "EPSG:32613").unwrap();
+    /// ```
+    pub fn new(source: S, src_crs: &str, dst_crs: &str) -> Result<Reproject<S>, Error> {
+        Ok(Reproject {
+            proj: Proj::new_known_crs(src_crs, dst_crs, None)?,
+            source,
+        })
+    }
+
+    /// Transforms one point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::reproject::Reproject;
+    /// use pos::units::Radians;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let reproject = Reproject::new(source, "EPSG:4326", "EPSG:32613").unwrap();
+    /// let point = Point { longitude: Radians::from_degrees(-105.0), ..Default::default() };
+    /// let point = reproject.transform(point).unwrap();
+    /// assert!((point.longitude.to_degrees() - 500000.0).abs() < 1e-3);
+    /// ```
+    pub fn transform(&self, mut point: Point) -> Result<Point, Error> {
+        let (x, y) = self
+            .proj
+            .convert((point.longitude.to_degrees(), point.latitude.to_degrees()))?;
+        point.longitude = Radians::from_degrees(x);
+        point.latitude = Radians::from_degrees(y);
+        Ok(point)
+    }
+}
+
+impl<S: Source> Source for Reproject<S> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        match self.source.source()? {
+            Some(point) => self.transform(point).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::utm::Utm;
+    use std::collections::VecDeque;
+
+    #[derive(Debug)]
+    struct Points(VecDeque<Point>);
+
+    impl Source for Points {
+        fn source(&mut self) -> Result<Option<Point>, Error> {
+            Ok(self.0.pop_front())
+        }
+    }
+
+    #[test]
+    fn utm() {
+        let point = Point {
+            latitude: Radians::from_degrees(43.642567),
+            longitude: Radians::from_degrees(-79.387139),
+            altitude: 10.,
+            ..Default::default()
+        };
+        let second = Point {
+            altitude: 20.,
+            ..point
+        };
+        let points = Points(VecDeque::from(vec![point, second]));
+        let mut reproject = Reproject::new(points, "EPSG:4326", "EPSG:32617").unwrap();
+        let projected = reproject.source().unwrap().unwrap();
+        let utm = Utm::from_geodetic(point.latitude, point.longitude);
+        assert!((projected.longitude.to_degrees() - utm.easting).abs() < 1e-3);
+        assert!((projected.latitude.to_degrees() - utm.northing).abs() < 1e-3);
+        assert_eq!(10., projected.altitude);
+        assert_eq!(20., reproject.source().unwrap().unwrap().altitude);
+        assert!(reproject.source().unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_crs() {
+        assert!(Reproject::new(Points(VecDeque::new()), "EPSG:4326", "not a crs").is_err());
+    }
+}
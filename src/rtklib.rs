@@ -0,0 +1,325 @@
+//! RTKLIB solution files, e.g. from RTKPOST or Emlid Studio.
+//!
+//! Each line holds the time, either as a GPS week and seconds or as a date and time of day,
+//! followed by latitude, longitude, height, the solution quality, the number of satellites, and
+//! the standard deviations of the north, east, and up positions. Solutions with velocity output
+//! also hold the north, east, and up velocities and their standard deviations after the ratio.
+//! Header lines start with `%`, and the column header's time system, GPST or UTC, is the points'
+//! time base.
+//!
+//! Only solutions with latitude and longitude in decimal degrees can be read.
+
+use crate::error;
+use crate::point::{Accuracy, Point, SatelliteCount, Status};
+use crate::source::Source;
+use crate::time::{self, Time, TimeBase};
+use crate::units::Radians;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// The prefix of RTKLIB header lines.
+const COMMENT_PREFIX: char = '%';
+
+/// RTKLIB errors.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    /// A line has too few values.
+    #[error("Missing value {0} in line: {1}")]
+    MissingValue(usize, String),
+
+    /// A date or time of day could not be parsed.
+    #[error("Invalid date or time: {0}")]
+    InvalidTime(String),
+
+    /// The solution isn't in latitude and longitude decimal degrees, e.g. it is in ECEF.
+    #[error("Unsupported solution format: {0}")]
+    UnsupportedFormat(String),
+}
+
+/// An RTKLIB solution reader.
+#[derive(Debug)]
+pub struct Reader<R: BufRead> {
+    reader: R,
+    time_base: TimeBase,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::rtklib::Reader;
+    /// let reader = Reader::from_path("data/2-points.rtklib").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Ok(Reader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new reader from any `BufRead`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::rtklib::Reader;
+    /// use pos::time::Time;
+    /// let data = "2053 264225.000 35.1 139.1 45.1 1 12 0.005 0.004 0.013\n";
+    /// let mut reader = Reader::new(data.as_bytes());
+    /// assert_eq!(Time(264225.0), reader.read_point().unwrap().unwrap().time);
+    /// ```
+    pub fn new(reader: R) -> Reader<R> {
+        Reader {
+            reader,
+            time_base: TimeBase::GpsSecondsOfWeek,
+        }
+    }
+
+    /// Reads a point from this reader.
+    ///
+    /// The point's time is in seconds of the week, like sbet times. Times given as a date and time
+    /// of day are converted to seconds of the week in the same time system, and the point's time
+    /// base is GPS or UTC seconds of the week as the column header says. The point's accuracy
+    /// holds the standard deviations and the number of satellites. Velocities, if present, are the
+    /// y (north), x (east), and z (up) velocities. The solution quality is the point's status.
+    /// Covariances, age, and ratio are ignored.
+    ///
+    /// Returns none at the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::rtklib::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.rtklib").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// assert_eq!(0.0043, point.accuracy.unwrap().x);
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            } else if trimmed.starts_with(COMMENT_PREFIX) {
+                if let Some(time_base) = check_header(trimmed)? {
+                    self.time_base = time_base;
+                }
+            } else {
+                let mut point = parse(trimmed)?;
+                point.time_base = self.time_base;
+                return Ok(Some(point));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over an RTKLIB reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: BufRead> {
+    reader: Reader<R>,
+}
+
+impl<R: BufRead> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + BufRead> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// Returns the time base of a column header, or none if the line isn't the column header.
+///
+/// Returns an error if the column header describes a solution format or time system we can't
+/// read.
+fn check_header(line: &str) -> Result<Option<TimeBase>, Error> {
+    let is_dms = line.contains("latitude(d") && !line.contains("latitude(deg)");
+    if is_dms || line.contains("x-ecef") || line.contains("e-baseline") {
+        return Err(Error::UnsupportedFormat(line.to_string()));
+    } else if !line.contains("latitude(deg)") {
+        return Ok(None);
+    }
+    match line
+        .trim_start_matches(COMMENT_PREFIX)
+        .split_whitespace()
+        .next()
+    {
+        Some("GPST") => Ok(Some(TimeBase::GpsSecondsOfWeek)),
+        Some("UTC") => Ok(Some(TimeBase::UtcSecondsOfWeek)),
+        _ => Err(Error::UnsupportedFormat(line.to_string())),
+    }
+}
+
+fn parse(line: &str) -> Result<Point, error::Error> {
+    let values: Vec<_> = line.split_whitespace().collect();
+    let value = |i: usize| {
+        values
+            .get(i)
+            .ok_or_else(|| Error::MissingValue(i, line.to_string()))
+    };
+    let time = Time(if value(0)?.contains('/') {
+        seconds_of_week(value(0)?, value(1)?)?
+    } else {
+        value(1)?.parse()?
+    });
+    let optional = |i: usize| values.get(i).map(|s| s.parse()).transpose();
+    let satellite_count = value(6)?.parse()?;
+    let accuracy = Accuracy {
+        time,
+        y: value(7)?.parse()?,
+        x: value(8)?.parse()?,
+        z: value(9)?.parse()?,
+        satellite_count: Some(SatelliteCount::Unspecified(satellite_count)),
+        y_velocity: optional(18)?,
+        x_velocity: optional(19)?,
+        z_velocity: optional(20)?,
+        ..Default::default()
+    };
+    Ok(Point {
+        time,
+        latitude: Radians::from_degrees(value(2)?.parse()?),
+        longitude: Radians::from_degrees(value(3)?.parse()?),
+        altitude: value(4)?.parse()?,
+        y_velocity: optional(15)?,
+        x_velocity: optional(16)?,
+        z_velocity: optional(17)?,
+        accuracy: Some(accuracy),
+        status: status(value(5)?.parse()?),
+        ..Default::default()
+    })
+}
+
+/// Returns the status for an RTKLIB solution quality flag.
+fn status(quality: u8) -> Option<Status> {
+    match quality {
+        1 => Some(Status::Fixed),
+        2 => Some(Status::Float),
+        3 => Some(Status::Sbas),
+        4 => Some(Status::Dgps),
+        5 => Some(Status::Sps),
+        6 => Some(Status::Ppp),
+        _ => None,
+    }
+}
+
+/// Converts a date (`YYYY/MM/DD`) and time of day (`HH:MM:SS.SSS`) to seconds of the week.
+fn seconds_of_week(date: &str, time: &str) -> Result<f64, error::Error> {
+    let invalid = || Error::InvalidTime(format!("{} {}", date, time));
+    let date: Vec<i64> = date
+        .split('/')
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()?;
+    let time: Vec<f64> = time
+        .split(':')
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()?;
+    if date.len() != 3 || time.len() != 3 {
+        return Err(invalid().into());
+    }
+    Ok(
+        time::seconds_of_week(date[0], date[1], date[2])
+            + time[0] * 3600.
+            + time[1] * 60.
+            + time[2],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_file() {
+        let points = Reader::from_path("data/2-points.rtklib")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(Time(264225.0), points[0].time);
+        assert!((264225.2 - points[1].time.0).abs() < 1e-9);
+        assert!((35.123456789 - points[0].latitude.to_degrees()).abs() < 1e-9);
+        assert!((139.123456789 - points[0].longitude.to_degrees()).abs() < 1e-9);
+        assert_eq!(45.1234, points[0].altitude);
+        let accuracy = points[1].accuracy.unwrap();
+        assert_eq!(0.0812, accuracy.y);
+        assert_eq!(0.0743, accuracy.x);
+        assert_eq!(0.1821, accuracy.z);
+        assert_eq!(Some(Status::Fixed), points[0].status);
+        assert_eq!(Some(Status::Float), points[1].status);
+        assert_eq!(
+            Some(SatelliteCount::Unspecified(9)),
+            accuracy.satellite_count
+        );
+    }
+
+    #[test]
+    fn week_seconds() {
+        let data = "%  GPST latitude(deg) longitude(deg) height(m) Q ns sdn(m) sde(m) sdu(m)\n\
+                    2053 100.5 35.1 139.1 45.1 1 12 0.005 0.004 0.013\n";
+        let point = Reader::new(data.as_bytes()).read_point().unwrap().unwrap();
+        assert_eq!(Time(100.5), point.time);
+        assert_eq!(TimeBase::GpsSecondsOfWeek, point.time_base);
+    }
+
+    #[test]
+    fn time_system() {
+        let data = "%  UTC latitude(deg) longitude(deg) height(m) Q ns sdn(m) sde(m) sdu(m)\n\
+                    2053 100.5 35.1 139.1 45.1 1 12 0.005 0.004 0.013\n";
+        let point = Reader::new(data.as_bytes()).read_point().unwrap().unwrap();
+        assert_eq!(TimeBase::UtcSecondsOfWeek, point.time_base);
+        let data = "%  JST latitude(deg) longitude(deg) height(m) Q ns sdn(m) sde(m) sdu(m)\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+    }
+
+    #[test]
+    fn velocity() {
+        let data = "2053 100.5 35.1 139.1 45.1 1 12 0.005 0.004 0.013 0.001 0.002 0.003 0.0 0.0 \
+                    1.5 -2.5 0.1 0.02 0.03 0.04 0.0 0.0 0.0\n";
+        let point = Reader::new(data.as_bytes()).read_point().unwrap().unwrap();
+        assert_eq!(Some(1.5), point.y_velocity);
+        assert_eq!(Some(-2.5), point.x_velocity);
+        assert_eq!(Some(0.1), point.z_velocity);
+        let accuracy = point.accuracy.unwrap();
+        assert_eq!(Some(0.02), accuracy.y_velocity);
+        assert_eq!(Some(0.03), accuracy.x_velocity);
+        assert_eq!(Some(0.04), accuracy.z_velocity);
+    }
+
+    #[test]
+    fn unsupported() {
+        let data = "%  GPST x-ecef(m) y-ecef(m) z-ecef(m) Q ns sdx(m) sdy(m) sdz(m)\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+        let data = "%  GPST latitude(d'\") longitude(d'\") height(m) Q ns\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+        let data = "2053 100.5 35.1 139.1\n";
+        assert!(Reader::new(data.as_bytes()).read_point().is_err());
+    }
+
+    #[test]
+    fn date() {
+        assert_eq!(
+            264225.0,
+            seconds_of_week("2019/05/15", "01:23:45.000").unwrap()
+        );
+    }
+}
@@ -1,86 +1,1403 @@
 //! SBET file format.
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use failure::Error;
-use point::Point;
-use source::Source;
+use crate::error;
+use crate::format::Input;
+use crate::point::{Accuracy, Point};
+use crate::sink::Sink;
+use crate::source::{AccuracySource, ParseMode, Source};
+use crate::time::{Duration, Time};
+use crate::units::Radians;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "tokio")]
+use futures_core::Stream;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::f64::consts::PI;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::iter::IntoIterator;
 use std::path::Path;
-use units::Radians;
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// The size of one standard sbet record, in bytes.
+pub const RECORD_SIZE: u64 = 136;
+
+/// The default largest time step between records that a resynchronizing reader accepts, in
+/// seconds.
+pub const DEFAULT_MAX_TIME_STEP: Duration = Duration(1.);
+
+/// The number of records whose times are checked when detecting a record layout.
+const DETECT_RECORDS: u64 = 16;
+
+/// The largest time considered sensible when detecting byte order.
+const MAX_TIME: f64 = 1e10;
+
+/// The smallest non-zero magnitude considered sensible when detecting byte order.
+const MIN_MAGNITUDE: f64 = 1e-20;
+
+/// Sbet errors.
+///
+/// Records are counted from zero, and byte offsets are from where reading started.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A record couldn't be read, with the record's index and byte offset.
+    #[error("Could not read sbet record {0} at byte {1}")]
+    Read(u64, u64, #[source] io::Error),
+    /// The data ends partway through a record, with the record's index, its byte offset, and the
+    /// number of bytes that were read.
+    #[error("Short sbet record {0} at byte {1}: only {2} bytes")]
+    ShortRecord(u64, u64, usize),
+}
 
 /// An SBET reader.
 #[derive(Debug)]
-pub struct Reader<R: Read> {
+pub struct Reader<R: Read> {
+    buffer: Vec<u8>,
+    endianness: Endianness,
+    index: u64,
+    layout: RecordLayout,
+    parse_mode: ParseMode,
+    reader: R,
+    skipped: usize,
+    truncated: Option<Error>,
+}
+
+impl Reader<BufReader<Input>> {
+    /// Opens a reader for a path.
+    ///
+    /// Gzipped files are decompressed as they are read, which requires the `gzip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<Input>>, error::Error> {
+        Ok(Reader::new(BufReader::new(Input::open(path)?)))
+    }
+}
+
+impl<R: Read> Reader<R> {
+    /// Creates a new reader from any `Read`.
+    ///
+    /// The data is read as a stream, so this works with pipes and standard input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::new(File::open("data/2-points.sbet").unwrap());
+    /// ```
+    ///
+    /// Reading from standard input:
+    ///
+    /// ```no_run
+    /// use std::io;
+    /// use pos::sbet::Reader;
+    /// let stdin = io::stdin();
+    /// for point in Reader::new(stdin.lock()) {
+    ///     println!("{}", point.unwrap().time);
+    /// }
+    /// ```
+    pub fn new(reader: R) -> Reader<R> {
+        Reader {
+            buffer: Vec::new(),
+            endianness: Endianness::default(),
+            index: 0,
+            layout: RecordLayout::default(),
+            parse_mode: ParseMode::default(),
+            reader,
+            skipped: 0,
+            truncated: None,
+        }
+    }
+
+    /// Returns this reader's byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Endianness, Reader};
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(Endianness::Little, reader.endianness());
+    /// ```
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Sets this reader's byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Endianness, Reader};
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// reader.set_endianness(Endianness::Big);
+    /// ```
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Returns this reader's record layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Reader, RecordLayout};
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(&RecordLayout::standard(), reader.layout());
+    /// ```
+    pub fn layout(&self) -> &RecordLayout {
+        &self.layout
+    }
+
+    /// Sets this reader's record layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Reader, RecordLayout};
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// reader.set_layout(RecordLayout::without_angular_rates());
+    /// ```
+    pub fn set_layout(&mut self, layout: RecordLayout) {
+        self.layout = layout;
+    }
+
+    /// Sets how this reader handles a short final record.
+    ///
+    /// In strict mode, the default, a short final record is an error. In lenient mode, it is
+    /// treated as the end of the data, and is counted and reported by `truncated`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::ParseMode;
+    /// use pos::sbet::Reader;
+    /// let bytes = vec![0; 136 + 10];
+    /// let mut reader = Reader::new(bytes.as_slice());
+    /// reader.set_parse_mode(ParseMode::Lenient);
+    /// assert_eq!(1, reader.into_iter().count());
+    /// ```
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    /// Returns the number of malformed records that have been skipped in lenient mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(0, reader.skipped());
+    /// ```
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Returns the short final record that was skipped in lenient mode, e.g. because a logger
+    /// crashed partway through writing it.
+    ///
+    /// The record is reported as the error it would have been in strict mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::ParseMode;
+    /// use pos::sbet::{Error, Reader};
+    /// let bytes = vec![0; 136 + 10];
+    /// let mut reader = Reader::new(bytes.as_slice());
+    /// reader.set_parse_mode(ParseMode::Lenient);
+    /// assert!(reader.read_point().unwrap().is_some());
+    /// assert!(reader.read_point().unwrap().is_none());
+    /// match reader.truncated() {
+    ///     Some(&Error::ShortRecord(index, offset, len)) => {
+    ///         assert_eq!((1, 136, 10), (index, offset, len));
+    ///     }
+    ///     _ => panic!("expected a short record"),
+    /// }
+    /// ```
+    pub fn truncated(&self) -> Option<&Error> {
+        self.truncated.as_ref()
+    }
+
+    /// Reads a point from this reader.
+    ///
+    /// Returns none if the file is at its end when this reader starts reading. We have to do it
+    /// this way since sbet files don't have a point count. In lenient mode, a short final record
+    /// is skipped and none is returned. Errors say which record, and where, couldn't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let bytes = vec![0; 136 + 10];
+    /// let mut reader = Reader::new(bytes.as_slice());
+    /// let _ = reader.read_point().unwrap();
+    /// let err = reader.read_point().unwrap_err();
+    /// assert_eq!("Short sbet record 1 at byte 136: only 10 bytes", err.to_string());
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let size = self.layout.record_size();
+        let (index, offset) = (self.index, self.index * size);
+        self.buffer.resize(size as usize, 0);
+        let filled = fill(&mut self.reader, &mut self.buffer)
+            .map_err(|err| Error::Read(index, offset, err))?;
+        if filled == 0 {
+            Ok(None)
+        } else if filled < self.buffer.len() {
+            self.index += 1;
+            let err = Error::ShortRecord(index, offset, filled);
+            if self.parse_mode == ParseMode::Lenient {
+                self.skipped += 1;
+                self.truncated = Some(err);
+                Ok(None)
+            } else {
+                Err(err.into())
+            }
+        } else {
+            self.index += 1;
+            Ok(Some(self.layout.decode(&self.buffer, self.endianness)))
+        }
+    }
+
+    /// Pairs each point in this reader with an accuracy from an accuracy source, e.g. an smrmsg
+    /// reader.
+    ///
+    /// Accuracies are linearly interpolated to each point's time. Points before the first or after
+    /// the last accuracy are paired with the nearest accuracy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{sbet, smrmsg};
+    /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+    /// let accuracy_reader = smrmsg::Reader::from_path("data/2-points.smrmsg").unwrap();
+    /// for result in reader.with_accuracy(accuracy_reader) {
+    ///     let (point, accuracy) = result.unwrap();
+    ///     println!("{} {}", point.time, accuracy.x);
+    /// }
+    /// ```
+    pub fn with_accuracy<A: AccuracySource>(self, accuracy_source: A) -> WithAccuracy<R, A> {
+        WithAccuracy {
+            reader: self,
+            accuracy_source,
+            accuracies: None,
+        }
+    }
+
+    /// Turns this reader into one that skips over corrupted data.
+    ///
+    /// See `Resync`. The resynchronizing reader uses this reader's layout and byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap().resync();
+    /// assert_eq!(2, reader.count());
+    /// ```
+    pub fn resync(self) -> Resync<R> {
+        let size = self.layout.record_size();
+        Resync {
+            max_time_step: DEFAULT_MAX_TIME_STEP,
+            buffer: Vec::new(),
+            endianness: self.endianness,
+            index: self.index,
+            layout: self.layout,
+            offset: self.index * size,
+            previous: None,
+            reader: self.reader,
+            resyncing: false,
+            resyncs: 0,
+            skipped_bytes: 0,
+        }
+    }
+}
+
+impl<R: Read + Seek> Reader<R> {
+    /// Returns the number of points in this reader, computed from the length of the data.
+    ///
+    /// No points are parsed and the reader's position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(2, reader.point_count().unwrap());
+    /// ```
+    pub fn point_count(&mut self) -> Result<u64, error::Error> {
+        let position = self.reader.stream_position()?;
+        let end = self.reader.seek(SeekFrom::End(0))?;
+        let _ = self.reader.seek(SeekFrom::Start(position))?;
+        Ok(end / self.layout.record_size())
+    }
+
+    /// Returns the number of points in this reader.
+    ///
+    /// This is the same as `point_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(2, reader.len().unwrap());
+    /// ```
+    pub fn len(&mut self) -> Result<u64, error::Error> {
+        self.point_count()
+    }
+
+    /// Returns the times of the first and last points.
+    ///
+    /// Only those two times are read, and the reader's position is not changed. Returns none if
+    /// there are no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let (start, end) = reader.time_span().unwrap().unwrap();
+    /// assert!(start < end);
+    /// ```
+    pub fn time_span(&mut self) -> Result<Option<(Time, Time)>, error::Error> {
+        let count = self.point_count()?;
+        if count == 0 {
+            return Ok(None);
+        }
+        let position = self.reader.stream_position()?;
+        let start = self.read_time_at(0)?;
+        let end = self.read_time_at(count - 1)?;
+        let _ = self.reader.seek(SeekFrom::Start(position))?;
+        Ok(Some((start, end)))
+    }
+
+    /// Returns true if this reader contains no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert!(!reader.is_empty().unwrap());
+    /// ```
+    pub fn is_empty(&mut self) -> Result<bool, error::Error> {
+        self.point_count().map(|count| count == 0)
+    }
+
+    /// Positions this reader so the next point read is the point at the given index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// reader.seek(1).unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn seek(&mut self, index: u64) -> Result<(), error::Error> {
+        let _ = self
+            .reader
+            .seek(SeekFrom::Start(index * self.layout.record_size()))?;
+        self.index = index;
+        Ok(())
+    }
+
+    /// Reads the point at the given index.
+    ///
+    /// Returns none if the index is past the end of the data. After this call the reader is
+    /// positioned at the following point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let point = reader.read_point_at(1).unwrap().unwrap();
+    /// assert!(reader.read_point_at(2).unwrap().is_none());
+    /// ```
+    pub fn read_point_at(&mut self, index: u64) -> Result<Option<Point>, error::Error> {
+        self.seek(index)?;
+        self.read_point()
+    }
+
+    /// Reads the first point, without changing the reader's position.
+    ///
+    /// Returns none if there are no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let first = reader.first_point().unwrap().unwrap();
+    /// assert_eq!(first, reader.read_point().unwrap().unwrap());
+    /// ```
+    pub fn first_point(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point_and_return(0)
+    }
+
+    /// Reads the last point by seeking to it, without changing the reader's position.
+    ///
+    /// Returns none if there are no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let last = reader.last_point().unwrap().unwrap();
+    /// assert!(last.time > reader.first_point().unwrap().unwrap().time);
+    /// ```
+    pub fn last_point(&mut self) -> Result<Option<Point>, error::Error> {
+        match self.point_count()? {
+            0 => Ok(None),
+            count => self.read_point_and_return(count - 1),
+        }
+    }
+
+    /// Reads the point at an index and then seeks back to where the reader was.
+    fn read_point_and_return(&mut self, index: u64) -> Result<Option<Point>, error::Error> {
+        let position = self.index;
+        let point = self.read_point_at(index);
+        self.seek(position)?;
+        point
+    }
+
+    /// Positions this reader at the first point whose time is greater than or equal to the given
+    /// time, and returns that point's index.
+    ///
+    /// The points are assumed to be sorted by time, which lets this method do a binary search
+    /// across the file instead of a scan. If every point is before the time, the reader is
+    /// positioned at the end and the number of points is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// use pos::time::Time;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// let index = reader.seek_to_time(Time(151631.005)).unwrap();
+    /// assert_eq!(1, index);
+    /// ```
+    pub fn seek_to_time(&mut self, time: Time) -> Result<u64, error::Error> {
+        let mut low = 0;
+        let mut high = self.point_count()?;
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.read_time_at(middle)? < time {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        self.seek(low)?;
+        Ok(low)
+    }
+
+    /// Detects this reader's record layout and, if one is found, uses it.
+    ///
+    /// See `RecordLayout::detect`. Returns false, and leaves the layout unchanged, if no layout
+    /// fits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Reader, RecordLayout};
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert!(reader.detect_layout().unwrap());
+    /// assert_eq!(&RecordLayout::standard(), reader.layout());
+    /// ```
+    pub fn detect_layout(&mut self) -> Result<bool, error::Error> {
+        match RecordLayout::detect(&mut self.reader, self.endianness)? {
+            Some(layout) => {
+                self.layout = layout;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Detects this reader's byte order using its current layout and, if one is found, uses it.
+    ///
+    /// See `Endianness::detect`. Returns false, and leaves the byte order unchanged, if neither
+    /// byte order gives a sensible first record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Endianness, Reader};
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert!(reader.detect_endianness().unwrap());
+    /// assert_eq!(Endianness::Little, reader.endianness());
+    /// ```
+    pub fn detect_endianness(&mut self) -> Result<bool, error::Error> {
+        match Endianness::detect(&mut self.reader, &self.layout)? {
+            Some(endianness) => {
+                self.endianness = endianness;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn read_time_at(&mut self, index: u64) -> Result<Time, error::Error> {
+        let offset = index * self.layout.record_size() + self.layout.time_offset();
+        let _ = self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(Time(self.endianness.read_f64(&mut self.reader)?))
+    }
+}
+
+/// The byte order of sbet data.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Endianness {
+    /// Little-endian, used by almost all sbet files.
+    #[default]
+    Little,
+    /// Big-endian, written by some legacy hardware.
+    Big,
+}
+
+impl Endianness {
+    /// Detects the byte order of the sbet data in a reader.
+    ///
+    /// The first record is decoded with each byte order, and the first byte order that gives a
+    /// sensible, non-negative time and a latitude and longitude within range is returned. Little
+    /// endian is tried first. The reader's position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::sbet::{Endianness, RecordLayout};
+    /// let mut file = File::open("data/2-points.sbet").unwrap();
+    /// let endianness = Endianness::detect(&mut file, &RecordLayout::standard()).unwrap();
+    /// assert_eq!(Some(Endianness::Little), endianness);
+    /// ```
+    pub fn detect<R: Read + Seek>(
+        reader: &mut R,
+        layout: &RecordLayout,
+    ) -> Result<Option<Endianness>, error::Error> {
+        let position = reader.stream_position()?;
+        let mut bytes = vec![0; layout.record_size() as usize];
+        let result = reader.read_exact(&mut bytes);
+        let _ = reader.seek(SeekFrom::Start(position))?;
+        if let Err(err) = result {
+            return match err.kind() {
+                io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(err.into()),
+            };
+        }
+        Ok([Endianness::Little, Endianness::Big]
+            .iter()
+            .cloned()
+            .find(|&endianness| is_sensible(&layout.decode(&bytes, endianness))))
+    }
+
+    fn read_f64<R: Read>(self, reader: &mut R) -> io::Result<f64> {
+        match self {
+            Endianness::Little => reader.read_f64::<LittleEndian>(),
+            Endianness::Big => reader.read_f64::<BigEndian>(),
+        }
+    }
+
+    fn decode_f64(self, bytes: &[u8]) -> f64 {
+        match self {
+            Endianness::Little => LittleEndian::read_f64(bytes),
+            Endianness::Big => BigEndian::read_f64(bytes),
+        }
+    }
+}
+
+/// Reads into a buffer until it is full or the reader ends, returning the number of bytes read.
+///
+/// Unlike `read_exact`, this tells an end of file between records from one inside a record.
+fn fill<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+fn is_sensible(point: &Point) -> bool {
+    point.time >= Time(0.)
+        && is_plausible(point.time.0, MAX_TIME)
+        && is_plausible(point.latitude.0, PI / 2.0)
+        && is_plausible(point.longitude.0, 2.0 * PI)
+}
+
+/// Values decoded with the wrong byte order are usually either huge or vanishingly small.
+fn is_plausible(value: f64, max: f64) -> bool {
+    value == 0.0 || (value.abs() >= MIN_MAGNITUDE && value.abs() <= max)
+}
+
+/// A field in an sbet record.
+///
+/// Every field is an eight-byte float. Angles are in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum Field {
+    Time,
+    Latitude,
+    Longitude,
+    Altitude,
+    XVelocity,
+    YVelocity,
+    ZVelocity,
+    Roll,
+    Pitch,
+    Yaw,
+    WanderAngle,
+    XAcceleration,
+    YAcceleration,
+    ZAcceleration,
+    XAngularRate,
+    YAngularRate,
+    ZAngularRate,
+    /// A field that isn't read into the point.
+    Skip,
+}
+
+impl Field {
+    fn apply(self, point: &mut Point, value: f64) {
+        match self {
+            Field::Time => point.time = Time(value),
+            Field::Latitude => point.latitude = Radians(value),
+            Field::Longitude => point.longitude = Radians(value),
+            Field::Altitude => point.altitude = value,
+            Field::XVelocity => point.x_velocity = Some(value),
+            Field::YVelocity => point.y_velocity = Some(value),
+            Field::ZVelocity => point.z_velocity = Some(value),
+            Field::Roll => point.roll = Radians(value),
+            Field::Pitch => point.pitch = Radians(value),
+            Field::Yaw => point.yaw = Radians(value),
+            Field::WanderAngle => point.wander_angle = Some(Radians(value)),
+            Field::XAcceleration => point.x_acceleration = Some(value),
+            Field::YAcceleration => point.y_acceleration = Some(value),
+            Field::ZAcceleration => point.z_acceleration = Some(value),
+            Field::XAngularRate => point.x_angular_rate = Some(Radians(value)),
+            Field::YAngularRate => point.y_angular_rate = Some(Radians(value)),
+            Field::ZAngularRate => point.z_angular_rate = Some(Radians(value)),
+            Field::Skip => {}
+        }
+    }
+}
+
+/// The fields that make up one sbet record, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordLayout {
+    fields: Vec<Field>,
+}
+
+impl RecordLayout {
+    /// Creates a new record layout from a list of fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields don't include `Field::Time`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Field, RecordLayout};
+    /// let layout = RecordLayout::new(vec![Field::Time, Field::Latitude, Field::Longitude]);
+    /// assert_eq!(24, layout.record_size());
+    /// ```
+    pub fn new(fields: Vec<Field>) -> RecordLayout {
+        assert!(
+            fields.contains(&Field::Time),
+            "a record layout must have a time field"
+        );
+        RecordLayout { fields }
+    }
+
+    /// Returns the standard seventeen-field sbet layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::RecordLayout;
+    /// assert_eq!(136, RecordLayout::standard().record_size());
+    /// ```
+    pub fn standard() -> RecordLayout {
+        RecordLayout::new(vec![
+            Field::Time,
+            Field::Latitude,
+            Field::Longitude,
+            Field::Altitude,
+            Field::XVelocity,
+            Field::YVelocity,
+            Field::ZVelocity,
+            Field::Roll,
+            Field::Pitch,
+            Field::Yaw,
+            Field::WanderAngle,
+            Field::XAcceleration,
+            Field::YAcceleration,
+            Field::ZAcceleration,
+            Field::XAngularRate,
+            Field::YAngularRate,
+            Field::ZAngularRate,
+        ])
+    }
+
+    /// Returns the fourteen-field layout used by some vendors, which omits the angular rates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::RecordLayout;
+    /// assert_eq!(112, RecordLayout::without_angular_rates().record_size());
+    /// ```
+    pub fn without_angular_rates() -> RecordLayout {
+        let mut layout = RecordLayout::standard();
+        layout.fields.truncate(14);
+        layout
+    }
+
+    /// Detects the layout of the sbet data in a reader.
+    ///
+    /// Each known layout is tried in turn. A layout fits if the data length is a multiple of its
+    /// record size and the times of the first few records are finite, non-negative, and strictly
+    /// increasing. The reader's position is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::sbet::{Endianness, RecordLayout};
+    /// let mut file = File::open("data/2-points.sbet").unwrap();
+    /// let layout = RecordLayout::detect(&mut file, Endianness::Little).unwrap();
+    /// assert_eq!(Some(RecordLayout::standard()), layout);
+    /// ```
+    pub fn detect<R: Read + Seek>(
+        reader: &mut R,
+        endianness: Endianness,
+    ) -> Result<Option<RecordLayout>, error::Error> {
+        let position = reader.stream_position()?;
+        let len = reader.seek(SeekFrom::End(0))?;
+        let mut detected = None;
+        for layout in [
+            RecordLayout::standard(),
+            RecordLayout::without_angular_rates(),
+        ] {
+            if layout.fits(reader, len, endianness)? {
+                detected = Some(layout);
+                break;
+            }
+        }
+        let _ = reader.seek(SeekFrom::Start(position))?;
+        Ok(detected)
+    }
+
+    /// Returns this layout's fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Field, RecordLayout};
+    /// assert_eq!(Field::Time, RecordLayout::standard().fields()[0]);
+    /// ```
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Returns the size of one record, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::RecordLayout;
+    /// assert_eq!(136, RecordLayout::standard().record_size());
+    /// ```
+    pub fn record_size(&self) -> u64 {
+        8 * self.fields.len() as u64
+    }
+
+    fn time_offset(&self) -> u64 {
+        8 * self
+            .fields
+            .iter()
+            .position(|&field| field == Field::Time)
+            .expect("layouts always have a time field") as u64
+    }
+
+    fn fits<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        len: u64,
+        endianness: Endianness,
+    ) -> Result<bool, error::Error> {
+        let size = self.record_size();
+        if len == 0 || len % size != 0 {
+            return Ok(false);
+        }
+        let mut previous = None;
+        for index in 0..(len / size).min(DETECT_RECORDS) {
+            let _ = reader.seek(SeekFrom::Start(index * size + self.time_offset()))?;
+            let time = endianness.read_f64(reader)?;
+            if !time.is_finite() || time < 0.0 || previous.is_some_and(|previous| time <= previous)
+            {
+                return Ok(false);
+            }
+            previous = Some(time);
+        }
+        Ok(true)
+    }
+
+    /// Decodes one whole record.
+    fn decode(&self, bytes: &[u8], endianness: Endianness) -> Point {
+        let mut point = Point::default();
+        for (field, bytes) in self.fields.iter().zip(bytes.chunks_exact(8)) {
+            field.apply(&mut point, endianness.decode_f64(bytes));
+        }
+        point
+    }
+}
+
+impl Default for RecordLayout {
+    fn default() -> RecordLayout {
+        RecordLayout::standard()
+    }
+}
+
+/// A memory-mapped sbet reader.
+///
+/// Points are decoded directly from the mapped file, which makes random access cheap and avoids
+/// copying through a buffered reader. Any partial record at the end of the file is ignored.
+///
+/// Requires the `mmap` feature.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MmapReader {
+    endianness: Endianness,
+    index: usize,
+    layout: RecordLayout,
+    mmap: Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapReader {
+    /// Maps the file at the given path.
+    ///
+    /// The file should not be modified while it is mapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<MmapReader, error::Error> {
+        let file = File::open(path)?;
+        // Safe as long as the file isn't modified while mapped, which we document above.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(MmapReader {
+            endianness: Endianness::default(),
+            index: 0,
+            layout: RecordLayout::default(),
+            mmap,
+        })
+    }
+
+    /// Sets this reader's record layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{MmapReader, RecordLayout};
+    /// let mut reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// reader.set_layout(RecordLayout::without_angular_rates());
+    /// ```
+    pub fn set_layout(&mut self, layout: RecordLayout) {
+        self.layout = layout;
+    }
+
+    /// Sets this reader's byte order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{Endianness, MmapReader};
+    /// let mut reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// reader.set_endianness(Endianness::Big);
+    /// ```
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Returns the number of points in the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(2, reader.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.mmap.len() / self.layout.record_size() as usize
+    }
+
+    /// Returns the number of bytes after the last whole record, which are ignored.
+    ///
+    /// These are usually a short final record from a logger that stopped partway through writing
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(0, reader.remainder());
+    /// ```
+    pub fn remainder(&self) -> usize {
+        self.mmap.len() % self.layout.record_size() as usize
+    }
+
+    /// Returns true if the file contains no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// assert!(!reader.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the point at the given index, or none if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// let point = reader.point(1).unwrap();
+    /// assert!(reader.point(2).is_none());
+    /// ```
+    pub fn point(&self, index: usize) -> Option<Point> {
+        if index < self.len() {
+            let size = self.layout.record_size() as usize;
+            let start = index * size;
+            Some(
+                self.layout
+                    .decode(&self.mmap[start..start + size], self.endianness),
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Reads the next point and advances this reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::MmapReader;
+    /// let mut reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// let point = reader.read_point().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Option<Point> {
+        let point = self.point(self.index);
+        if point.is_some() {
+            self.index += 1;
+        }
+        point
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl MmapReader {
+    /// Returns a parallel iterator over all of the file's points, in file order.
+    ///
+    /// Records are fixed-size, so the mapped file is split between threads and each record is
+    /// decoded independently. This doesn't advance the reader.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rayon;
+    /// # extern crate pos;
+    /// # fn main() {
+    /// use pos::sbet::MmapReader;
+    /// use rayon::prelude::*;
+    /// let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(2, reader.par_points().count());
+    /// # }
+    /// ```
+    pub fn par_points(&self) -> impl IndexedParallelIterator<Item = Point> + '_ {
+        let size = self.layout.record_size() as usize;
+        self.mmap[..self.len() * size]
+            .par_chunks(size)
+            .map(move |bytes| self.layout.decode(bytes, self.endianness))
+    }
+}
+
+/// Reads all of the points in an sbet file in parallel, with the standard layout and
+/// little-endian records.
+///
+/// Use `MmapReader::par_points` for other layouts, or to process points without collecting them.
+///
+/// Requires the `rayon` feature.
+///
+/// # Examples
+///
+/// ```
+/// let points = pos::sbet::read_parallel("data/2-points.sbet").unwrap();
+/// assert_eq!(2, points.len());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn read_parallel<P: AsRef<Path>>(path: P) -> Result<Vec<Point>, error::Error> {
+    let reader = MmapReader::from_path(path)?;
+    Ok(reader.par_points().collect())
+}
+
+#[cfg(feature = "mmap")]
+impl Iterator for MmapReader {
+    type Item = Point;
+    fn next(&mut self) -> Option<Point> {
+        self.read_point()
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Source for MmapReader {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        Ok(self.read_point())
+    }
+}
+
+/// An asynchronous sbet reader, which is a stream of points.
+///
+/// A short final record is an error.
+///
+/// Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncReader<R: AsyncRead + Unpin> {
+    buffer: Vec<u8>,
+    endianness: Endianness,
+    filled: usize,
+    index: u64,
+    layout: RecordLayout,
     reader: R,
 }
 
-impl Reader<BufReader<File>> {
-    /// Opens a reader for a path.
+#[cfg(feature = "tokio")]
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    /// Creates a new asynchronous reader from any `AsyncRead`, e.g. a `tokio::fs::File`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use pos::sbet::Reader;
-    /// let reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// extern crate futures_util;
+    /// extern crate tokio;
+    /// # extern crate pos;
+    /// # fn main() {
+    /// use futures_util::StreamExt;
+    /// use pos::sbet::AsyncReader;
+    /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// let file = runtime.block_on(tokio::fs::File::open("data/2-points.sbet"));
+    /// let reader = AsyncReader::new(file.unwrap());
+    /// let points: Vec<_> = runtime.block_on(reader.collect());
+    /// assert_eq!(2, points.len());
+    /// # }
     /// ```
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, Error> {
-        Ok(Reader { reader: BufReader::new(File::open(path)?) })
+    pub fn new(reader: R) -> AsyncReader<R> {
+        AsyncReader {
+            buffer: Vec::new(),
+            endianness: Endianness::default(),
+            filled: 0,
+            index: 0,
+            layout: RecordLayout::default(),
+            reader,
+        }
     }
-}
 
-impl<R: Read> Reader<R> {
-    /// Reads a point from this reader.
+    /// Sets this reader's byte order.
     ///
-    /// Returns none if the file is at its end when this reader starts reading. We have to do it
-    /// this way since sbet files don't have a point count.
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::{AsyncReader, Endianness};
+    /// let mut reader = AsyncReader::new(&[][..]);
+    /// reader.set_endianness(Endianness::Big);
+    /// ```
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Sets this reader's record layout.
     ///
     /// # Examples
     ///
     /// ```
-    /// use pos::sbet::Reader;
-    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
-    /// let point = reader.read_point().unwrap().unwrap();
+    /// use pos::sbet::{AsyncReader, RecordLayout};
+    /// let mut reader = AsyncReader::new(&[][..]);
+    /// reader.set_layout(RecordLayout::without_angular_rates());
     /// ```
-    pub fn read_point(&mut self) -> Result<Option<Point>, Error> {
-        use std::io::ErrorKind;
+    pub fn set_layout(&mut self, layout: RecordLayout) {
+        self.layout = layout;
+    }
+}
 
-        let time = match self.reader.read_f64::<LittleEndian>() {
-            Ok(time) => time,
-            Err(err) => {
-                match err.kind() {
-                    ErrorKind::UnexpectedEof => return Ok(None),
-                    _ => return Err(err.into()),
+#[cfg(feature = "tokio")]
+impl<R: AsyncRead + Unpin> Stream for AsyncReader<R> {
+    type Item = Result<Point, error::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let size = this.layout.record_size() as usize;
+        this.buffer.resize(size, 0);
+        while this.filled < size {
+            let mut buf = ReadBuf::new(&mut this.buffer[this.filled..]);
+            match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => {
+                    let err = Error::Read(this.index, this.index * size as u64, err);
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    let filled = this.filled;
+                    this.filled = 0;
+                    return if filled == 0 {
+                        Poll::Ready(None)
+                    } else {
+                        let err = Error::ShortRecord(this.index, this.index * size as u64, filled);
+                        this.index += 1;
+                        Poll::Ready(Some(Err(err.into())))
+                    };
                 }
+                Poll::Ready(Ok(())) => this.filled += buf.filled().len(),
+            }
+        }
+        this.filled = 0;
+        this.index += 1;
+        Poll::Ready(Some(Ok(this.layout.decode(&this.buffer, this.endianness))))
+    }
+}
+
+/// An sbet reader paired with an accuracy source.
+#[derive(Debug)]
+pub struct WithAccuracy<R: Read, A: AccuracySource> {
+    reader: Reader<R>,
+    accuracy_source: A,
+    accuracies: Option<(Accuracy, Option<Accuracy>)>,
+}
+
+impl<R: Read, A: AccuracySource> WithAccuracy<R, A> {
+    /// Reads a point and its accuracy.
+    ///
+    /// Returns none if either the points or the accuracy source is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{sbet, smrmsg};
+    /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+    /// let accuracy_reader = smrmsg::Reader::from_path("data/2-points.smrmsg").unwrap();
+    /// let mut with_accuracy = reader.with_accuracy(accuracy_reader);
+    /// let (point, accuracy) = with_accuracy.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<(Point, Accuracy)>, error::Error> {
+        let point = match self.reader.read_point()? {
+            Some(point) => point,
+            None => return Ok(None),
+        };
+        if self.accuracies.is_none() {
+            self.accuracies = match self.accuracy_source.source()? {
+                Some(accuracy) => Some((accuracy, self.accuracy_source.source()?)),
+                None => return Ok(None),
+            };
+        }
+        let (mut previous, mut next) = self.accuracies.unwrap();
+        while let Some(accuracy) = next {
+            if accuracy.time > point.time {
+                break;
             }
+            previous = accuracy;
+            next = self.accuracy_source.source()?;
+        }
+        self.accuracies = Some((previous, next));
+        let accuracy = match next {
+            Some(next) if point.time > previous.time => previous.interpolate(&next, point.time),
+            _ => previous,
         };
-        Ok(Some(Point {
-            time: time,
-            latitude: Radians(self.reader.read_f64::<LittleEndian>()?),
-            longitude: Radians(self.reader.read_f64::<LittleEndian>()?),
-            altitude: self.reader.read_f64::<LittleEndian>()?,
-            x_velocity: Some(self.reader.read_f64::<LittleEndian>()?),
-            y_velocity: Some(self.reader.read_f64::<LittleEndian>()?),
-            z_velocity: Some(self.reader.read_f64::<LittleEndian>()?),
-            roll: Radians(self.reader.read_f64::<LittleEndian>()?),
-            pitch: Radians(self.reader.read_f64::<LittleEndian>()?),
-            yaw: Radians(self.reader.read_f64::<LittleEndian>()?),
-            wander_angle: Some(Radians(self.reader.read_f64::<LittleEndian>()?)),
-            x_acceleration: Some(self.reader.read_f64::<LittleEndian>()?),
-            y_acceleration: Some(self.reader.read_f64::<LittleEndian>()?),
-            z_acceleration: Some(self.reader.read_f64::<LittleEndian>()?),
-            x_angular_rate: Some(Radians(self.reader.read_f64::<LittleEndian>()?)),
-            y_angular_rate: Some(Radians(self.reader.read_f64::<LittleEndian>()?)),
-            z_angular_rate: Some(Radians(self.reader.read_f64::<LittleEndian>()?)),
-            ..Default::default()
-        }))
+        Ok(Some((point, accuracy)))
+    }
+}
+
+impl<R: Read, A: AccuracySource> Iterator for WithAccuracy<R, A> {
+    type Item = Result<(Point, Accuracy), error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_point().transpose()
+    }
+}
+
+/// An sbet reader that skips over corrupted data, created with `Reader::resync`.
+///
+/// A record is implausible if one of its values isn't finite, its latitude or longitude is out of
+/// range, or its time goes backwards or jumps too far ahead of the last good record. After an
+/// implausible record, the reader scans forward a byte at a time for the next plausible record.
+#[derive(Debug)]
+pub struct Resync<R: Read> {
+    /// The largest time step between consecutive records, in seconds.
+    ///
+    /// After a stretch of corrupted data, the step is multiplied by the number of records that
+    /// could have been lost.
+    pub max_time_step: Duration,
+    buffer: Vec<u8>,
+    endianness: Endianness,
+    index: u64,
+    layout: RecordLayout,
+    offset: u64,
+    previous: Option<(Time, u64)>,
+    reader: R,
+    resyncing: bool,
+    resyncs: usize,
+    skipped_bytes: u64,
+}
+
+impl<R: Read> Resync<R> {
+    /// Reads the next plausible point.
+    ///
+    /// Returns none at the end of the data. Trailing bytes that don't make a whole record are
+    /// skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let mut bytes = vec![0xff; 7];
+    /// bytes.extend(std::fs::read("data/2-points.sbet").unwrap());
+    /// let mut reader = Reader::new(bytes.as_slice()).resync();
+    /// assert!(reader.read_point().unwrap().is_some());
+    /// assert_eq!(7, reader.skipped_bytes());
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let size = self.layout.record_size() as usize;
+        loop {
+            if self.buffer.len() < size {
+                let start = self.buffer.len();
+                let (index, offset) = (self.index, self.offset + start as u64);
+                self.buffer.resize(size, 0);
+                let filled = fill(&mut self.reader, &mut self.buffer[start..])
+                    .map_err(|err| Error::Read(index, offset, err))?;
+                self.buffer.truncate(start + filled);
+                if self.buffer.len() < size {
+                    self.offset += self.buffer.len() as u64;
+                    self.skipped_bytes += self.buffer.len() as u64;
+                    self.buffer.clear();
+                    return Ok(None);
+                }
+            }
+            let point = self.layout.decode(&self.buffer, self.endianness);
+            if self.is_plausible(&point) {
+                self.buffer.clear();
+                self.index += 1;
+                self.offset += size as u64;
+                self.previous = Some((point.time, self.offset));
+                self.resyncing = false;
+                return Ok(Some(point));
+            }
+            if !self.resyncing {
+                self.resyncing = true;
+                self.resyncs += 1;
+            }
+            let _ = self.buffer.remove(0);
+            self.offset += 1;
+            self.skipped_bytes += 1;
+        }
+    }
+
+    /// Returns the number of times this reader has had to resynchronize.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap().resync();
+    /// assert_eq!(0, reader.resyncs());
+    /// ```
+    pub fn resyncs(&self) -> usize {
+        self.resyncs
+    }
+
+    /// Returns the number of bytes that have been skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Reader;
+    /// let reader = Reader::from_path("data/2-points.sbet").unwrap().resync();
+    /// assert_eq!(0, reader.skipped_bytes());
+    /// ```
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_bytes
+    }
+
+    fn is_plausible(&self, point: &Point) -> bool {
+        if !is_sensible(point) || point.validate().is_err() {
+            return false;
+        }
+        match self.previous {
+            Some((time, end)) => {
+                let records = (self.offset - end) / self.layout.record_size() + 1;
+                let step = point.time - time;
+                step >= Duration(0.) && step <= self.max_time_step * records as f64
+            }
+            None => true,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Resync<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_point().transpose()
+    }
+}
+
+impl<R: Debug + Read> Source for Resync<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
     }
 }
 
 impl<R: Read> IntoIterator for Reader<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     type IntoIter = ReaderIterator<R>;
     fn into_iter(self) -> Self::IntoIter {
         ReaderIterator { reader: self }
@@ -94,38 +1411,453 @@ pub struct ReaderIterator<R: Read> {
 }
 
 impl<R: Read> Iterator for ReaderIterator<R> {
-    type Item = Point;
+    type Item = Result<Point, error::Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.reader.read_point().unwrap()
+        self.reader.read_point().transpose()
     }
 }
 
 impl<R: Debug + Read> Source for Reader<R> {
-    fn source(&mut self) -> Result<Option<Point>, Error> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
         self.read_point()
     }
 }
 
+/// An SBET writer.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Writer;
+    /// let writer = Writer::from_path("/dev/null").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Ok(Writer::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new writer that writes to the given `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Writer;
+    /// let writer = Writer::new(Vec::new());
+    /// ```
+    pub fn new(writer: W) -> Writer<W> {
+        Writer { writer }
+    }
+
+    /// Writes a point to this writer.
+    ///
+    /// Sbet records have no optional fields, so any field that is `None` in the point is written
+    /// as zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::sbet::Writer;
+    /// let mut writer = Writer::new(Vec::new());
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        self.writer.write_f64::<LittleEndian>(point.time.0)?;
+        self.writer.write_f64::<LittleEndian>(point.latitude.0)?;
+        self.writer.write_f64::<LittleEndian>(point.longitude.0)?;
+        self.writer.write_f64::<LittleEndian>(point.altitude)?;
+        self.writer
+            .write_f64::<LittleEndian>(point.x_velocity.unwrap_or(0.0))?;
+        self.writer
+            .write_f64::<LittleEndian>(point.y_velocity.unwrap_or(0.0))?;
+        self.writer
+            .write_f64::<LittleEndian>(point.z_velocity.unwrap_or(0.0))?;
+        self.writer.write_f64::<LittleEndian>(point.roll.0)?;
+        self.writer.write_f64::<LittleEndian>(point.pitch.0)?;
+        self.writer.write_f64::<LittleEndian>(point.yaw.0)?;
+        self.writer
+            .write_f64::<LittleEndian>(point.wander_angle.unwrap_or_default().0)?;
+        self.writer
+            .write_f64::<LittleEndian>(point.x_acceleration.unwrap_or(0.0))?;
+        self.writer
+            .write_f64::<LittleEndian>(point.y_acceleration.unwrap_or(0.0))?;
+        self.writer
+            .write_f64::<LittleEndian>(point.z_acceleration.unwrap_or(0.0))?;
+        self.writer
+            .write_f64::<LittleEndian>(point.x_angular_rate.unwrap_or_default().0)?;
+        self.writer
+            .write_f64::<LittleEndian>(point.y_angular_rate.unwrap_or_default().0)?;
+        self.writer
+            .write_f64::<LittleEndian>(point.z_angular_rate.unwrap_or_default().0)?;
+        Ok(())
+    }
+
+    /// Consumes this writer and returns the underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sbet::Writer;
+    /// let writer = Writer::new(Vec::new());
+    /// let bytes = writer.into_inner();
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Debug + Write> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        Writer::into_inner(*self).flush()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::io::Cursor;
+
     #[test]
     fn read_file() {
         let reader = Reader::from_path("data/2-points.sbet").unwrap();
-        let points: Vec<_> = reader.into_iter().collect();
+        let points = reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
         assert_eq!(2, points.len());
         let point = points[0];
-        assert!((1.5163100e5 - point.time).abs() < 1e-2, "{}", point.time);
+        assert!((1.5163100e5 - point.time.0).abs() < 1e-2, "{}", point.time);
         assert!(
             (0.5680211 - point.latitude.0).abs() < 1e-7,
             "{:?}",
             point.latitude
         );
         assert!(
-            (1.5163110e5 - points[1].time).abs() < 1e-1,
+            (1.5163110e5 - points[1].time.0).abs() < 1e-1,
             "{}",
             points[1].time
         );
     }
+
+    #[derive(Debug)]
+    struct Accuracies(Vec<Accuracy>);
+
+    impl AccuracySource for Accuracies {
+        fn source(&mut self) -> Result<Option<Accuracy>, error::Error> {
+            if self.0.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(self.0.remove(0)))
+            }
+        }
+    }
+
+    fn accuracy(time: f64, x: f64) -> Accuracy {
+        Accuracy {
+            time: Time(time),
+            x,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn with_accuracy_interpolates() {
+        let reader = Reader::from_path("data/2-points.sbet").unwrap();
+        let accuracies = Accuracies(vec![accuracy(151631.0, 1.0), accuracy(151631.01, 2.0)]);
+        let pairs = reader
+            .with_accuracy(accuracies)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, pairs.len());
+        for &(point, accuracy) in &pairs {
+            assert!((point.time - accuracy.time).abs() < Duration(1e-9));
+            let expected = 1.0 + (point.time.0 - 151631.0) / 0.01;
+            assert!((expected - accuracy.x).abs() < 1e-6, "{}", accuracy.x);
+        }
+    }
+
+    #[test]
+    fn with_accuracy_nearest() {
+        let reader = Reader::from_path("data/2-points.sbet").unwrap();
+        let accuracies = Accuracies(vec![accuracy(151631.005, 1.0)]);
+        let pairs = reader
+            .with_accuracy(accuracies)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, pairs.len());
+        assert_eq!(1.0, pairs[0].1.x);
+        assert_eq!(1.0, pairs[1].1.x);
+
+        let reader = Reader::from_path("data/2-points.sbet").unwrap();
+        assert_eq!(0, reader.with_accuracy(Accuracies(Vec::new())).count());
+    }
+
+    #[test]
+    fn random_access() {
+        let mut writer = Writer::new(Vec::new());
+        for i in 0..10 {
+            let point = Point {
+                time: Time(i as f64),
+                ..Default::default()
+            };
+            writer.write_point(&point).unwrap();
+        }
+        let mut reader = Reader::new(Cursor::new(writer.into_inner()));
+        assert_eq!(10, reader.len().unwrap());
+        assert_eq!(Some((Time(0.0), Time(9.0))), reader.time_span().unwrap());
+        assert_eq!(Time(0.0), reader.read_point().unwrap().unwrap().time);
+        assert_eq!(Time(7.0), reader.read_point_at(7).unwrap().unwrap().time);
+        assert_eq!(Time(8.0), reader.read_point().unwrap().unwrap().time);
+        assert!(reader.read_point_at(10).unwrap().is_none());
+
+        assert_eq!(0, reader.seek_to_time(Time(-1.0)).unwrap());
+        assert_eq!(4, reader.seek_to_time(Time(3.5)).unwrap());
+        assert_eq!(Time(4.0), reader.read_point().unwrap().unwrap().time);
+        assert_eq!(3, reader.seek_to_time(Time(3.0)).unwrap());
+        assert_eq!(10, reader.seek_to_time(Time(11.0)).unwrap());
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap() {
+        let points = Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let reader = MmapReader::from_path("data/2-points.sbet").unwrap();
+        assert_eq!(points, reader.collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn from_path_gzip() {
+        let points = Reader::from_path("data/2-points.sbet.gz")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected, points);
+        let mut reader = Reader::from_path("data/2-points.sbet.gz").unwrap();
+        assert!(reader.seek(1).is_err());
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn from_path_gzip_without_feature() {
+        assert!(Reader::from_path("data/2-points.sbet.gz").is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = Writer::new(&mut bytes);
+            for i in 0..1000 {
+                writer
+                    .write_point(&Point {
+                        time: Time(i as f64),
+                        ..Default::default()
+                    })
+                    .unwrap();
+            }
+        }
+        let path = ::std::env::temp_dir().join("pos-rs-sbet-parallel.sbet");
+        ::std::fs::write(&path, &bytes).unwrap();
+        let points = read_parallel(&path).unwrap();
+        ::std::fs::remove_file(&path).unwrap();
+        assert_eq!(1000, points.len());
+        assert!(points
+            .iter()
+            .enumerate()
+            .all(|(i, p)| p.time == Time(i as f64)));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_reader() {
+        use futures_util::StreamExt;
+
+        let bytes = ::std::fs::read("data/2-points.sbet").unwrap();
+        let points = Reader::new(bytes.as_slice())
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let runtime = ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let read: Vec<_> = runtime.block_on(AsyncReader::new(bytes.as_slice()).collect());
+        let read = read.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(points, read);
+
+        let short = &bytes[..bytes.len() - 1];
+        let read: Vec<_> = runtime.block_on(AsyncReader::new(short).collect());
+        assert_eq!(2, read.len());
+        assert!(read[1].is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let mut reader = Reader::new(Cursor::new(Vec::new()));
+        assert_eq!(0, reader.point_count().unwrap());
+        assert!(reader.is_empty().unwrap());
+        assert_eq!(None, reader.time_span().unwrap());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = vec![0; 2 * RECORD_SIZE as usize];
+        bytes.truncate(bytes.len() - 1);
+        let mut reader = Reader::new(bytes.as_slice());
+        assert!(reader.read_point().unwrap().is_some());
+        match reader.read_point().unwrap_err() {
+            error::Error::Sbet(Error::ShortRecord(1, 136, 135)) => {}
+            err => panic!("unexpected error: {}", err),
+        }
+
+        let mut reader = Reader::new(bytes.as_slice());
+        reader.set_parse_mode(ParseMode::Lenient);
+        assert!(reader.read_point().unwrap().is_some());
+        assert!(reader.read_point().unwrap().is_none());
+        assert_eq!(1, reader.skipped());
+        match reader.truncated() {
+            Some(&Error::ShortRecord(1, 136, 135)) => {}
+            other => panic!("unexpected truncation: {:?}", other),
+        }
+        assert!(reader.read_point().unwrap().is_none());
+    }
+
+    #[test]
+    fn resync() {
+        let mut writer = Writer::new(Vec::new());
+        for i in 0..10 {
+            let point = Point {
+                time: Time(1000. + i as f64 * 0.005),
+                latitude: Radians::from_degrees(45.),
+                longitude: Radians::from_degrees(-120.),
+                ..Default::default()
+            };
+            writer.write_point(&point).unwrap();
+        }
+        let mut bytes = writer.into_inner();
+        let size = RECORD_SIZE as usize;
+        for byte in &mut bytes[6 * size..7 * size] {
+            *byte = 0xff;
+        }
+        let _ = bytes.splice(4 * size..4 * size, vec![0xff; 7]);
+
+        let mut reader = Reader::new(bytes.as_slice()).resync();
+        let times = reader
+            .by_ref()
+            .map(|point| point.unwrap().time)
+            .collect::<Vec<_>>();
+        assert_eq!(9, times.len());
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(2, reader.resyncs());
+        assert_eq!(7 + RECORD_SIZE, reader.skipped_bytes());
+    }
+
+    #[test]
+    fn layout() {
+        let mut bytes = Vec::new();
+        for i in 0..4 {
+            for j in 0..14 {
+                bytes
+                    .write_f64::<LittleEndian>((i * 14 + j) as f64)
+                    .unwrap();
+            }
+        }
+        let mut cursor = Cursor::new(bytes);
+        assert_eq!(
+            Some(RecordLayout::without_angular_rates()),
+            RecordLayout::detect(&mut cursor, Endianness::Little).unwrap()
+        );
+        let mut reader = Reader::new(cursor);
+        assert!(reader.detect_layout().unwrap());
+        assert_eq!(4, reader.point_count().unwrap());
+        let point = reader.read_point_at(1).unwrap().unwrap();
+        assert_eq!(Time(14.0), point.time);
+        assert_eq!(Some(27.0), point.z_acceleration);
+        assert_eq!(None, point.x_angular_rate);
+
+        reader.set_layout(RecordLayout::new(vec![Field::Skip, Field::Time]));
+        assert_eq!(Some((Time(1.0), Time(55.0))), reader.time_span().unwrap());
+    }
+
+    #[test]
+    fn no_layout() {
+        let mut cursor = Cursor::new(vec![0; 100]);
+        assert_eq!(
+            None,
+            RecordLayout::detect(&mut cursor, Endianness::Little).unwrap()
+        );
+    }
+
+    #[test]
+    fn big_endian() {
+        let points = Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut bytes = std::fs::read("data/2-points.sbet").unwrap();
+        for value in bytes.chunks_mut(8) {
+            value.reverse();
+        }
+        let mut reader = Reader::new(Cursor::new(bytes));
+        assert!(reader.detect_endianness().unwrap());
+        assert_eq!(Endianness::Big, reader.endianness());
+        assert!(reader.detect_layout().unwrap());
+        assert_eq!(
+            points,
+            reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn new() {
+        let bytes = std::fs::read("data/2-points.sbet").unwrap();
+        let reader = Reader::new(bytes.as_slice());
+        assert_eq!(2, reader.into_iter().count());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let points = Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut writer = Writer::new(Vec::new());
+        for point in &points {
+            writer.write_point(point).unwrap();
+        }
+        let bytes = writer.into_inner();
+        assert_eq!(272, bytes.len());
+        let reader = Reader::new(bytes.as_slice());
+        assert_eq!(
+            points,
+            reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap()
+        );
+    }
 }
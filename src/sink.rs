@@ -0,0 +1,536 @@
+//! Sinks of position points.
+//!
+//! A sink is the writing counterpart of a `Source`, so conversions can be written once, from any
+//! source to any sink.
+
+use crate::csv;
+use crate::csv::{AngleUnit, Dialect};
+use crate::error::Error;
+use crate::format;
+use crate::format::Format;
+use crate::geojson;
+use crate::gpx;
+use crate::kml;
+use crate::point::Point;
+use crate::pos;
+use crate::pos::{Column, ColumnMap};
+use crate::sbet;
+use crate::source;
+use crate::source::{Clip, Source, Window};
+use crate::trajectory::Trajectory;
+use crate::trj;
+use crate::utm::Zone;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// A sink of points.
+pub trait Sink: Debug {
+    /// Writes one point to the sink.
+    fn write_point(&mut self, point: &Point) -> Result<(), Error>;
+
+    /// Finishes writing, e.g. by writing a footer, and flushes the sink.
+    ///
+    /// Sinks take a box so they can be closed as trait objects.
+    fn close(self: Box<Self>) -> Result<(), Error>;
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn write_point(&mut self, point: &Point) -> Result<(), Error> {
+        (**self).write_point(point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), Error> {
+        (*self).close()
+    }
+}
+
+/// Creates a sink for the given path, choosing the format from the path's extension.
+///
+/// Writers are created with their default configuration. Formats that can only be read, e.g.
+/// pof, are errors, as are gzipped paths.
+///
+/// # Examples
+///
+/// ```
+/// let sink = pos::create("/tmp/pos-rs-create-example.gpx").unwrap();
+/// sink.close().unwrap();
+/// ```
+pub fn create<P: AsRef<Path>>(path: P) -> Result<Box<dyn Sink>, Error> {
+    let path = path.as_ref();
+    let format = Format::from_extension(path)
+        .ok_or_else(|| format::Error::UnknownFormat(path.display().to_string()))?;
+    create_format(path, format)
+}
+
+/// Creates a sink for the given path in the given format, ignoring the path's extension.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Format;
+/// let sink = pos::sink::create_format("/tmp/pos-rs-create-format-example", Format::Pos).unwrap();
+/// sink.close().unwrap();
+/// ```
+pub fn create_format<P: AsRef<Path>>(path: P, format: Format) -> Result<Box<dyn Sink>, Error> {
+    let path = path.as_ref();
+    check_writable(path, format)?;
+    match format {
+        Format::Csv => Ok(Box::new(csv::Writer::from_path(path)?)),
+        Format::GeoJson => Ok(Box::new(geojson::Writer::from_path(
+            path,
+            geojson::Mode::default(),
+        )?)),
+        Format::Gpx => Ok(Box::new(gpx::Writer::from_path(path)?)),
+        Format::Kml => Ok(Box::new(kml::Writer::from_path(path)?)),
+        Format::Pos => Ok(Box::new(pos::Writer::from_path(path)?)),
+        Format::Sbet => Ok(Box::new(sbet::Writer::from_path(path)?)),
+        Format::Trj => Ok(Box::new(trj::Writer::from_path(
+            path,
+            trj::Header::default(),
+        )?)),
+        format => Err(format::Error::Unwritable(format, path.display().to_string()).into()),
+    }
+}
+
+/// Returns an error for gzipped paths, which sinks can't write.
+fn check_writable(path: &Path, format: Format) -> Result<(), Error> {
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        Err(format::Error::Unwritable(format, path.display().to_string()).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Converts a file from one format to another, returning the number of points converted.
+///
+/// The input is opened with `open` and the output is created with `create`.
+///
+/// # Examples
+///
+/// ```
+/// let count = pos::convert("data/2-points.sbet", "/tmp/pos-rs-convert-example.csv").unwrap();
+/// assert_eq!(2, count);
+/// ```
+pub fn convert<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<usize, Error> {
+    let mut source = source::open(input)?;
+    let mut sink = create(output)?;
+    let count = copy(&mut source, &mut sink)?;
+    sink.close()?;
+    Ok(count)
+}
+
+/// Writes the points of a file that are inside a window to another file, in the same format.
+///
+/// Pos and CSV outputs have the input's columns, except for skipped ones. Returns the number of
+/// points written.
+///
+/// # Examples
+///
+/// ```
+/// use pos::source::Window;
+/// let count = pos::sink::clip(
+///     "data/2-points.sbet",
+///     "/tmp/pos-rs-clip-example.sbet",
+///     Window::Index(0, 1),
+/// ).unwrap();
+/// assert_eq!(1, count);
+/// ```
+pub fn clip<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    window: Window,
+) -> Result<usize, Error> {
+    let format = Format::from_path(&input)?;
+    let columns = match format {
+        Format::Csv => Some(csv::Reader::from_path(&input)?.columns().clone()),
+        Format::Pos => Some(pos::Reader::from_path(&input)?.columns().clone()),
+        _ => None,
+    }
+    .map(|columns| {
+        let columns: Vec<_> = columns
+            .columns()
+            .iter()
+            .copied()
+            .filter(|&column| column != Column::Skip)
+            .collect();
+        ColumnMap::from(columns)
+    });
+    let mut source = Clip::new(source::open(input)?, window);
+    check_writable(output.as_ref(), format)?;
+    let mut sink: Box<dyn Sink> = match (format, columns) {
+        (Format::Csv, Some(columns)) => {
+            let file = BufWriter::new(File::create(output)?);
+            Box::new(csv::Writer::with_columns(
+                file,
+                columns,
+                Dialect::default(),
+            )?)
+        }
+        (Format::Pos, Some(columns)) => {
+            let file = BufWriter::new(File::create(output)?);
+            Box::new(pos::Writer::with_columns(file, columns)?)
+        }
+        _ => create_format(output, format)?,
+    };
+    let count = copy(&mut source, &mut sink)?;
+    sink.close()?;
+    Ok(count)
+}
+
+/// How `pos2sbet` fills the sbet fields that the input doesn't have.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Kinematics {
+    /// Missing velocities, accelerations, and angular rates are written as zeros, and points are
+    /// converted one at a time.
+    #[default]
+    Zeros,
+    /// Missing velocities, accelerations, and angular rates are derived by differentiating the
+    /// positions and attitudes with `Trajectory::derive_kinematics`, which reads the whole input
+    /// into memory.
+    Derived,
+}
+
+/// Converts a file to an sbet file, returning the number of points converted.
+///
+/// Many tools only accept sbet input, but Inertial Explorer pos files and most other formats don't
+/// carry everything an sbet record has. The input is opened with `open`, so any readable format
+/// works, and the fields it's missing are filled in as set by `kinematics`. The wander angle is
+/// written as zero if the input doesn't have one.
+///
+/// # Examples
+///
+/// ```
+/// use pos::sink::{self, Kinematics};
+/// let count = sink::pos2sbet(
+///     "data/2-points.csv",
+///     "/tmp/pos-rs-pos2sbet-example.sbet",
+///     Kinematics::Derived,
+/// ).unwrap();
+/// assert_eq!(2, count);
+/// ```
+pub fn pos2sbet<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    kinematics: Kinematics,
+) -> Result<usize, Error> {
+    let mut source = source::open(input)?;
+    let mut sink = Box::new(sbet::Writer::from_path(output)?);
+    let count = match kinematics {
+        Kinematics::Zeros => copy(&mut source, &mut sink)?,
+        Kinematics::Derived => {
+            let mut trajectory = Trajectory::from_source(source)?;
+            trajectory.derive_kinematics();
+            for point in &trajectory {
+                sink.write_point(point)?;
+            }
+            trajectory.len()
+        }
+    };
+    sink.close()?;
+    Ok(count)
+}
+
+/// Converts a file to a CSV file with the given columns, returning the number of points
+/// converted.
+///
+/// This is the reverse of `pos2sbet`, for pulling just the fields that an analysis needs into a
+/// spreadsheet. The input is opened with `open`, so it doesn't have to be an sbet file. Build the
+/// columns with `ColumnMap::from_fields`; easting and northing columns are in the zone of the
+/// first point, so that they're continuous across the file.
+///
+/// # Examples
+///
+/// ```
+/// use pos::csv::AngleUnit;
+/// use pos::pos::ColumnMap;
+/// let count = pos::sink::sbet2csv(
+///     "data/2-points.sbet",
+///     "/tmp/pos-rs-sbet2csv-example.csv",
+///     ColumnMap::from_fields("time,easting,northing,altitude,yaw").unwrap(),
+///     AngleUnit::Radians,
+/// ).unwrap();
+/// assert_eq!(2, count);
+/// ```
+pub fn sbet2csv<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    columns: ColumnMap,
+    angle_unit: AngleUnit,
+) -> Result<usize, Error> {
+    let mut source = source::open(input)?;
+    let file = BufWriter::new(File::create(output)?);
+    let mut writer = csv::Writer::with_columns(file, columns, Dialect::default())?;
+    writer.angle_unit = angle_unit;
+    let mut count = 0;
+    while let Some(point) = source.source()? {
+        if writer.utm_zone.is_none() {
+            writer.utm_zone = Some(Zone::for_position(point.latitude, point.longitude));
+        }
+        writer.write_point(&point)?;
+        count += 1;
+    }
+    writer.into_inner().flush()?;
+    Ok(count)
+}
+
+/// Converts a file to a pos file with the given columns, returning the number of points
+/// converted.
+///
+/// Like `sbet2csv`, but the output is a whitespace-separated pos file, which pos readers map back
+/// to points from its header line. Easting and northing columns are in the zone of the first
+/// point. Yaw is relative to the wander angle, so columns with a yaw need a wander angle too, or
+/// writing a point is an error.
+///
+/// # Examples
+///
+/// ```
+/// use pos::pos::ColumnMap;
+/// let count = pos::sink::sbet2pos(
+///     "data/2-points.sbet",
+///     "/tmp/pos-rs-sbet2pos-example.pos",
+///     ColumnMap::from_fields("time,lat,lon,altitude,vx,vy,vz").unwrap(),
+/// ).unwrap();
+/// assert_eq!(2, count);
+/// ```
+pub fn sbet2pos<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    columns: ColumnMap,
+) -> Result<usize, Error> {
+    let mut source = source::open(input)?;
+    let file = BufWriter::new(File::create(output)?);
+    let mut writer = pos::Writer::with_columns(file, columns)?;
+    let mut count = 0;
+    while let Some(point) = source.source()? {
+        if writer.utm_zone.is_none() {
+            writer.utm_zone = Some(Zone::for_position(point.latitude, point.longitude));
+        }
+        writer.write_point(&point)?;
+        count += 1;
+    }
+    writer.into_inner().flush()?;
+    Ok(count)
+}
+
+/// Writes every point from a source to a sink, returning the number of points written.
+///
+/// The sink isn't closed.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Sink;
+/// let mut source = pos::open("data/2-points.sbet").unwrap();
+/// let mut sink = Box::new(pos::sbet::Writer::new(Vec::new()));
+/// assert_eq!(2, pos::sink::copy(&mut source, &mut sink).unwrap());
+/// sink.close().unwrap();
+/// ```
+pub fn copy<S: Source + ?Sized, K: Sink + ?Sized>(
+    source: &mut S,
+    sink: &mut K,
+) -> Result<usize, Error> {
+    let mut count = 0;
+    while let Some(point) = source.source()? {
+        sink.write_point(&point)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::csv;
+    use crate::geojson;
+    use crate::gpx;
+    use crate::kml;
+    use crate::pos;
+    use crate::sbet;
+    use crate::time::{Duration, Time};
+    use crate::trj;
+    use crate::units::Radians;
+    use std::io::Cursor;
+
+    #[test]
+    fn sinks() {
+        let sinks: Vec<Box<dyn Sink>> = vec![
+            Box::new(csv::Writer::new(Vec::new()).unwrap()),
+            Box::new(geojson::Writer::new(Vec::new(), geojson::Mode::Features).unwrap()),
+            Box::new(gpx::Writer::new(Vec::new()).unwrap()),
+            Box::new(kml::Writer::new(Vec::new())),
+            Box::new(
+                pos::Writer::with_columns(
+                    Vec::new(),
+                    ColumnMap::default().column(Column::WanderAngle),
+                )
+                .unwrap(),
+            ),
+            Box::new(sbet::Writer::new(Vec::new())),
+            Box::new(trj::Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap()),
+        ];
+        for mut sink in sinks {
+            let mut source = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+            assert_eq!(2, copy(&mut source, &mut sink).unwrap());
+            sink.close().unwrap();
+        }
+    }
+
+    #[test]
+    fn convert_roundtrip() {
+        let path = ::std::env::temp_dir().join("pos-rs-sink-convert.csv");
+        assert_eq!(2, convert("data/2-points.sbet", &path).unwrap());
+        let expected: Vec<_> = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        let actual: Vec<_> = source::open(&path)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(expected.len(), actual.len());
+        assert!((expected[1].time - actual[1].time).abs() < Duration(1e-6));
+        ::std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn clip_keeps_columns() {
+        let input = ::std::env::temp_dir().join("pos-rs-sink-clip-columns-input.pos");
+        let output = ::std::env::temp_dir().join("pos-rs-sink-clip-columns-output.pos");
+        ::std::fs::write(
+            &input,
+            "time lat lon q vz\n1 45 -105 1 0.5\n2 45 -105 1 0.25\n3 45 -105 1 0.125\n",
+        )
+        .unwrap();
+        assert_eq!(2, clip(&input, &output, Window::Index(1, 3)).unwrap());
+        let mut reader = pos::Reader::from_path(&output).unwrap();
+        assert_eq!(
+            &[
+                Column::Time,
+                Column::Latitude,
+                Column::Longitude,
+                Column::ZVelocity
+            ],
+            reader.columns().columns()
+        );
+        assert_eq!(Some(0.25), reader.read_point().unwrap().unwrap().z_velocity);
+        ::std::fs::remove_file(input).unwrap();
+        ::std::fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn clip_same_format() {
+        let path = ::std::env::temp_dir().join("pos-rs-sink-clip");
+        assert_eq!(
+            1,
+            clip("data/2-points.sbet", &path, Window::Index(1, 2)).unwrap()
+        );
+        let points: Vec<_> = sbet::Reader::from_path(&path)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(1, points.len());
+        let expected = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(expected, points[0]);
+        ::std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn pos2sbet_derived() {
+        let input = ::std::env::temp_dir().join("pos-rs-sink-pos2sbet.pos");
+        let mut writer = pos::Writer::from_path(&input).unwrap();
+        for i in 0..5 {
+            let point = Point {
+                time: Time(151631. + i as f64),
+                latitude: Radians::from_degrees(40.),
+                altitude: 1000. + 2. * i as f64,
+                ..Default::default()
+            };
+            writer.write_point(&point).unwrap();
+        }
+        Box::new(writer).close().unwrap();
+        let output = ::std::env::temp_dir().join("pos-rs-sink-pos2sbet.sbet");
+        assert_eq!(5, pos2sbet(&input, &output, Kinematics::Zeros).unwrap());
+        let points: Vec<_> = sbet::Reader::from_path(&output)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(5, points.len());
+        assert_eq!(Some(0.), points[2].z_velocity);
+        assert_eq!(5, pos2sbet(&input, &output, Kinematics::Derived).unwrap());
+        let points: Vec<_> = sbet::Reader::from_path(&output)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.unwrap())
+            .collect();
+        assert_eq!(Time(151633.), points[2].time);
+        assert!((points[2].z_velocity.unwrap() - 2.).abs() < 1e-6);
+        assert!(points[2].z_acceleration.unwrap().abs() < 1e-6);
+        ::std::fs::remove_file(input).unwrap();
+        ::std::fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn sbet2pos_fields() {
+        let path = ::std::env::temp_dir().join("pos-rs-sink-sbet2pos.pos");
+        let columns = ColumnMap::from_fields("time,vz,lat").unwrap();
+        assert_eq!(2, sbet2pos("data/2-points.sbet", &path, columns).unwrap());
+        let expected = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .read_point()
+            .unwrap()
+            .unwrap();
+        let mut reader = pos::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            &[Column::Time, Column::ZVelocity, Column::Latitude],
+            reader.columns().columns()
+        );
+        let point = reader.read_point().unwrap().unwrap();
+        assert!((expected.time.0 - point.time.0).abs() < 1e-6);
+        assert!((expected.z_velocity.unwrap() - point.z_velocity.unwrap()).abs() < 1e-3);
+        assert!((expected.latitude.0 - point.latitude.0).abs() < 1e-9);
+        ::std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn sbet2csv_fields() {
+        let path = ::std::env::temp_dir().join("pos-rs-sink-sbet2csv.csv");
+        let columns = ColumnMap::from_fields("time,northing,roll").unwrap();
+        assert_eq!(
+            2,
+            sbet2csv("data/2-points.sbet", &path, columns, AngleUnit::Radians).unwrap()
+        );
+        let expected = sbet::Reader::from_path("data/2-points.sbet")
+            .unwrap()
+            .read_point()
+            .unwrap()
+            .unwrap();
+        let output = ::std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(3, lines.len());
+        assert_eq!("time,northing,roll", lines[0]);
+        let values: Vec<f64> = lines[1].split(',').map(|v| v.parse().unwrap()).collect();
+        assert!((expected.time.0 - values[0]).abs() < 1e-6);
+        assert!((expected.to_utm().northing - values[1]).abs() < 1e-3);
+        assert!((expected.roll.0 - values[2]).abs() < 1e-8);
+        ::std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn create_unwritable() {
+        assert!(create("a.pof").is_err());
+        assert!(create("a.txt").is_err());
+        assert!(create("a.sbet.gz").is_err());
+    }
+}
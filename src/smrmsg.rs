@@ -0,0 +1,154 @@
+//! Smrmsg files hold the accuracy information for sbet files.
+//!
+//! Each record holds the root mean square error of the position, velocity, and attitude for one
+//! epoch.
+
+use crate::error::Error;
+use crate::point::Accuracy;
+use crate::time::Time;
+use crate::units::Radians;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::iter::IntoIterator;
+use std::path::Path;
+
+/// An smrmsg reader.
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    reader: R,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::smrmsg::Reader;
+    /// let reader = Reader::from_path("data/2-points.smrmsg").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, Error> {
+        Ok(Reader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> Reader<R> {
+    fn new(reader: R) -> Reader<R> {
+        Reader { reader }
+    }
+
+    /// Reads an accuracy record from this reader.
+    ///
+    /// Returns none if the file is at its end when this reader starts reading. Smrmsg files don't
+    /// record pdop or satellite counts, so the pdop is zero and the satellite count is none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::smrmsg::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.smrmsg").unwrap();
+    /// let accuracy = reader.read_accuracy().unwrap().unwrap();
+    /// ```
+    pub fn read_accuracy(&mut self) -> Result<Option<Accuracy>, Error> {
+        use std::io::ErrorKind;
+
+        let time = match self.reader.read_f64::<LittleEndian>() {
+            Ok(time) => time,
+            Err(err) => match err.kind() {
+                ErrorKind::UnexpectedEof => return Ok(None),
+                _ => return Err(err.into()),
+            },
+        };
+        let north = self.reader.read_f64::<LittleEndian>()?;
+        let east = self.reader.read_f64::<LittleEndian>()?;
+        let down = self.reader.read_f64::<LittleEndian>()?;
+        let north_velocity = self.reader.read_f64::<LittleEndian>()?;
+        let east_velocity = self.reader.read_f64::<LittleEndian>()?;
+        let down_velocity = self.reader.read_f64::<LittleEndian>()?;
+        let roll = self.reader.read_f64::<LittleEndian>()?;
+        let pitch = self.reader.read_f64::<LittleEndian>()?;
+        let yaw = self.reader.read_f64::<LittleEndian>()?;
+
+        Ok(Some(Accuracy {
+            time: Time(time),
+            y: north,
+            x: east,
+            z: down,
+            roll: from_arc_minutes(roll),
+            pitch: from_arc_minutes(pitch),
+            yaw: from_arc_minutes(yaw),
+            pdop: 0.0,
+            satellite_count: None,
+            y_velocity: Some(north_velocity),
+            x_velocity: Some(east_velocity),
+            z_velocity: Some(down_velocity),
+        }))
+    }
+}
+
+fn from_arc_minutes(arc_minutes: f64) -> Radians<f64> {
+    Radians::from_degrees(arc_minutes / 60.0)
+}
+
+impl<R: Read> IntoIterator for Reader<R> {
+    type Item = Result<Accuracy, Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over an smrmsg reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: Read> {
+    reader: Reader<R>,
+}
+
+impl<R: Read> Iterator for ReaderIterator<R> {
+    type Item = Result<Accuracy, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_accuracy().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use byteorder::WriteBytesExt;
+
+    #[test]
+    fn read_file() {
+        let accuracies = Reader::from_path("data/2-points.smrmsg")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, accuracies.len());
+        assert_eq!(Time(151631.0), accuracies[0].time);
+        assert_eq!(Time(151631.01), accuracies[1].time);
+    }
+
+    #[test]
+    fn fields() {
+        let mut bytes = Vec::new();
+        for &value in &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 60.0, 120.0, 180.0] {
+            bytes.write_f64::<LittleEndian>(value).unwrap();
+        }
+        let accuracy = Reader::new(bytes.as_slice())
+            .read_accuracy()
+            .unwrap()
+            .unwrap();
+        assert_eq!(Time(1.0), accuracy.time);
+        assert_eq!(2.0, accuracy.y);
+        assert_eq!(3.0, accuracy.x);
+        assert_eq!(4.0, accuracy.z);
+        assert_eq!(Some(5.0), accuracy.y_velocity);
+        assert_eq!(Some(6.0), accuracy.x_velocity);
+        assert_eq!(Some(7.0), accuracy.z_velocity);
+        assert!((1.0 - accuracy.roll.to_degrees()).abs() < 1e-12);
+        assert!((2.0 - accuracy.pitch.to_degrees()).abs() < 1e-12);
+        assert!((3.0 - accuracy.yaw.to_degrees()).abs() < 1e-12);
+    }
+}
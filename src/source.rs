@@ -1,23 +1,358 @@
 //! Sources of position points.
 
-use failure::Error;
-use pof;
-use point::{Accuracy, Point};
-use poq;
+use crate::csv;
+use crate::error::Error;
+use crate::format;
+use crate::format::{Format, Input};
+use crate::igi;
+use crate::nmea;
+use crate::novatel;
+use crate::pof;
+use crate::point::{Accuracy, Point};
+use crate::poq;
+use crate::pos;
+use crate::pospac;
+use crate::rtklib;
+use crate::sbet;
+use crate::smrmsg;
+use crate::time::Time;
+use crate::trj;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{BufReader, Read, Seek};
 use std::iter::IntoIterator;
+use std::ops::RangeBounds;
 use std::path::Path;
 
+/// Opens a source of points for the given path, detecting the file's format.
+///
+/// See `Format::from_path` for how the format is detected. Formats that can only be written, e.g.
+/// GPX, are errors. Gzipped pos and sbet files are decompressed as they are read, which requires
+/// the `gzip` feature.
+///
+/// # Examples
+///
+/// ```
+/// let source = pos::open("data/2-points.sbet").unwrap();
+/// assert_eq!(2, source.into_iter().count());
+/// ```
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Box<dyn Source>, Error> {
+    let path = path.as_ref();
+    if format::is_gzip(path)? {
+        return open_gzip(path);
+    }
+    match Format::from_path(path)? {
+        Format::Csv => Ok(Box::new(csv::Reader::from_path(path)?)),
+        Format::Igi => Ok(Box::new(igi::Reader::from_path(path)?)),
+        Format::Nmea => Ok(Box::new(nmea::Reader::from_path(path)?)),
+        Format::Novatel => Ok(Box::new(novatel::Reader::from_path(path)?)),
+        Format::Pof => pof::Reader::open_file_source(path),
+        Format::Pos => pos::Reader::open_file_source(path),
+        Format::Pospac => Ok(Box::new(pospac::Reader::from_path(path)?)),
+        Format::Rtklib => Ok(Box::new(rtklib::Reader::from_path(path)?)),
+        Format::Sbet => sbet::Reader::open_file_source(path),
+        Format::Trj => Ok(Box::new(trj::Reader::from_path(path)?)),
+        format @ Format::GeoJson | format @ Format::Gpx | format @ Format::Kml => {
+            Err(format::Error::Unreadable(format, path.display().to_string()).into())
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn open_gzip(path: &Path) -> Result<Box<dyn Source>, Error> {
+    match Format::from_path(path)? {
+        Format::Pos => pos::Reader::open_file_source(path),
+        Format::Sbet => sbet::Reader::open_file_source(path),
+        format => Err(format::Error::GzipFormat(format, path.display().to_string()).into()),
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+fn open_gzip(path: &Path) -> Result<Box<dyn Source>, Error> {
+    Err(format::Error::GzipFeature(path.display().to_string()).into())
+}
+
+/// How readers handle malformed records.
+///
+/// Field-collected files often end partway through a record, e.g. after a logger loses power. In
+/// lenient mode, readers skip malformed records and count them instead of returning an error.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ParseMode {
+    /// Malformed records are errors.
+    #[default]
+    Strict,
+    /// Malformed records are skipped.
+    Lenient,
+}
+
 /// A source of points.
 pub trait Source: Debug {
     /// Reads one point from the source.
     fn source(&mut self) -> Result<Option<Point>, Error>;
+
+    /// Returns an iterator over this source's points that borrows the source, rather than
+    /// consuming it like `into_iter`.
+    ///
+    /// Use this to read some points and then keep using the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// use pos::sbet::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.sbet").unwrap();
+    /// assert_eq!(1, reader.points().take(1).count());
+    /// assert!(reader.read_point().unwrap().is_some());
+    /// assert!(reader.read_point().unwrap().is_none());
+    /// ```
+    fn points(&mut self) -> Points<'_, Self>
+    where
+        Self: Sized,
+    {
+        Points { source: self }
+    }
+
+    /// Reads up to `n` points from this source.
+    ///
+    /// Fewer than `n` points are returned only at the end of the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// let mut source = pos::open("data/2-points.sbet").unwrap();
+    /// assert_eq!(1, source.read_points(1).unwrap().len());
+    /// assert_eq!(1, source.read_points(10).unwrap().len());
+    /// assert!(source.read_points(10).unwrap().is_empty());
+    /// ```
+    fn read_points(&mut self, n: usize) -> Result<Vec<Point>, Error> {
+        // Don't trust `n` for the allocation, e.g. `usize::MAX` to mean "everything".
+        let mut points = Vec::with_capacity(n.min(4096));
+        while points.len() < n {
+            match self.source()? {
+                Some(point) => points.push(point),
+                None => break,
+            }
+        }
+        Ok(points)
+    }
+
+    /// Returns an iterator over chunks of up to `size` points, e.g. for batch processing.
+    ///
+    /// The last chunk may be smaller. The iterator stops after an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// let mut source = pos::open("data/2-points.sbet").unwrap();
+    /// let chunks = source.chunks(1).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(2, chunks.len());
+    /// ```
+    fn chunks(&mut self, size: usize) -> Chunks<'_, Self>
+    where
+        Self: Sized,
+    {
+        assert!(size > 0, "chunk size must be greater than zero");
+        Chunks {
+            done: false,
+            size,
+            source: self,
+        }
+    }
+
+    /// Returns a source that applies a function to each of this source's points as they're read.
+    ///
+    /// Nothing is buffered, so conversions stay streaming. See `pipeline::Pipeline` for the
+    /// common transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// use pos::time::Duration;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let mut shifted = source.map_points(|mut point| {
+    ///     point.time += Duration(18.0);
+    ///     point
+    /// });
+    /// assert!(shifted.source().unwrap().unwrap().time.0 > 151649.0);
+    /// ```
+    fn map_points<F: FnMut(Point) -> Point>(self, f: F) -> MapPoints<Self, F>
+    where
+        Self: Sized,
+    {
+        MapPoints { f, source: self }
+    }
+
+    /// Returns a source of only this source's points that match a predicate.
+    ///
+    /// Points are checked as they're read, so nothing is buffered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let mut high = source.filter(|point| point.altitude > 1000.0);
+    /// assert!(high.source().unwrap().is_none());
+    /// ```
+    fn filter<P: FnMut(&Point) -> bool>(self, predicate: P) -> FilterPoints<Self, P>
+    where
+        Self: Sized,
+    {
+        FilterPoints {
+            predicate,
+            source: self,
+        }
+    }
+
+    /// Returns a source of only this source's points whose times are in a range, e.g.
+    /// `start..end`.
+    ///
+    /// The source doesn't have to be sorted by time, so every point is read. To stop early or
+    /// clip by index, use `Clip`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// use pos::time::Time;
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let mut source = source.filter_time(Time(151631.005)..Time(151632.0));
+    /// assert!(source.source().unwrap().is_some());
+    /// assert!(source.source().unwrap().is_none());
+    /// ```
+    fn filter_time<R: RangeBounds<Time> + Debug>(self, range: R) -> FilterTime<Self, R>
+    where
+        Self: Sized,
+    {
+        FilterTime {
+            range,
+            source: self,
+        }
+    }
+}
+
+/// A source that applies a function to the points of another source.
+pub struct MapPoints<S: Source, F: FnMut(Point) -> Point> {
+    f: F,
+    source: S,
+}
+
+impl<S: Source, F: FnMut(Point) -> Point> Debug for MapPoints<S, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MapPoints")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Source, F: FnMut(Point) -> Point> Source for MapPoints<S, F> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        Ok(self.source.source()?.map(&mut self.f))
+    }
+}
+
+/// A source of the points of another source that match a predicate.
+pub struct FilterPoints<S: Source, P: FnMut(&Point) -> bool> {
+    predicate: P,
+    source: S,
+}
+
+impl<S: Source, P: FnMut(&Point) -> bool> Debug for FilterPoints<S, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterPoints")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Source, P: FnMut(&Point) -> bool> Source for FilterPoints<S, P> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        while let Some(point) = self.source.source()? {
+            if (self.predicate)(&point) {
+                return Ok(Some(point));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A source of the points of another source whose times are in a range.
+#[derive(Debug)]
+pub struct FilterTime<S: Source, R: RangeBounds<Time> + Debug> {
+    range: R,
+    source: S,
+}
+
+impl<S: Source, R: RangeBounds<Time> + Debug> Source for FilterTime<S, R> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        while let Some(point) = self.source.source()? {
+            if self.range.contains(&point.time) {
+                return Ok(Some(point));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A borrowing iterator over the points of a source.
+#[derive(Debug)]
+pub struct Points<'a, S: Source + 'a> {
+    source: &'a mut S,
+}
+
+impl<'a, S: Source> Iterator for Points<'a, S> {
+    type Item = Result<Point, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.source().transpose()
+    }
+}
+
+/// A borrowing iterator over chunks of the points of a source.
+#[derive(Debug)]
+pub struct Chunks<'a, S: Source + 'a> {
+    done: bool,
+    size: usize,
+    source: &'a mut S,
+}
+
+impl<'a, S: Source> Iterator for Chunks<'a, S> {
+    type Item = Result<Vec<Point>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.source.read_points(self.size) {
+            Ok(ref points) if points.is_empty() => {
+                self.done = true;
+                None
+            }
+            Ok(points) => {
+                self.done = points.len() < self.size;
+                Some(Ok(points))
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<S: Source + ?Sized> Source for Box<S> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        (**self).source()
+    }
 }
 
-impl IntoIterator for Box<Source> {
-    type Item = Point;
+impl IntoIterator for Box<dyn Source> {
+    type Item = Result<Point, Error>;
     type IntoIter = SourceIterator;
     fn into_iter(self) -> Self::IntoIter {
         SourceIterator { source: self }
@@ -27,13 +362,13 @@ impl IntoIterator for Box<Source> {
 /// An iterator over a boxed point source.
 #[derive(Debug)]
 pub struct SourceIterator {
-    source: Box<Source>,
+    source: Box<dyn Source>,
 }
 
 impl Iterator for SourceIterator {
-    type Item = Point;
-    fn next(&mut self) -> Option<Point> {
-        self.source.source().unwrap()
+    type Item = Result<Point, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.source().transpose()
     }
 }
 
@@ -49,43 +384,72 @@ impl<R: Debug + Seek + Read> AccuracySource for poq::Reader<R> {
     }
 }
 
+impl<R: Debug + Read> AccuracySource for smrmsg::Reader<R> {
+    fn source(&mut self) -> Result<Option<Accuracy>, Error> {
+        self.read_accuracy()
+    }
+}
+
 /// A source of points that is based in a file.
 pub trait FileSource {
     /// Open a new file source from a file.
-    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<Source>, Error>;
+    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<dyn Source>, Error>;
 }
 
 impl FileSource for pof::Reader<BufReader<File>> {
-    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<Source>, Error> {
+    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<dyn Source>, Error> {
         Ok(Box::new(pof::Reader::from_path(path)?))
     }
 }
 
+impl FileSource for pos::Reader<BufReader<Input>> {
+    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<dyn Source>, Error> {
+        Ok(Box::new(pos::Reader::from_path(path)?))
+    }
+}
+
+impl FileSource for sbet::Reader<BufReader<Input>> {
+    fn open_file_source<P: AsRef<Path>>(path: P) -> Result<Box<dyn Source>, Error> {
+        Ok(Box::new(sbet::Reader::from_path(path)?))
+    }
+}
+
 /// A source of accuracy information
 pub trait FileAccuracySource {
     /// Opens a new accuracy source from a file.
-    fn open_file_accuracy_source<P: AsRef<Path>>(path: P) -> Result<Box<AccuracySource>, Error>;
+    fn open_file_accuracy_source<P: AsRef<Path>>(path: P)
+        -> Result<Box<dyn AccuracySource>, Error>;
 }
 
 impl FileAccuracySource for poq::Reader<BufReader<File>> {
-    fn open_file_accuracy_source<P: AsRef<Path>>(path: P) -> Result<Box<AccuracySource>, Error> {
+    fn open_file_accuracy_source<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Box<dyn AccuracySource>, Error> {
         Ok(Box::new(poq::Reader::from_path(path)?))
     }
 }
 
+impl FileAccuracySource for smrmsg::Reader<BufReader<File>> {
+    fn open_file_accuracy_source<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Box<dyn AccuracySource>, Error> {
+        Ok(Box::new(smrmsg::Reader::from_path(path)?))
+    }
+}
+
 /// A source of points that includes accuracy information.
 #[derive(Debug)]
 pub struct CombinedSource {
-    source: Box<Source>,
-    accuracy_source: Box<AccuracySource>,
+    source: Box<dyn Source>,
+    accuracy_source: Box<dyn AccuracySource>,
     accuracies: (Option<Accuracy>, Option<Accuracy>),
 }
 
 impl CombinedSource {
     /// Creates a new combined source from two boxes.
     pub fn new(
-        source: Box<Source>,
-        mut accuracy_source: Box<AccuracySource>,
+        source: Box<dyn Source>,
+        mut accuracy_source: Box<dyn AccuracySource>,
     ) -> Result<CombinedSource, Error> {
         let accuracies = (accuracy_source.source()?, accuracy_source.source()?);
         Ok(CombinedSource {
@@ -104,8 +468,9 @@ impl Source for CombinedSource {
         };
         // Since we populate the accuracies on create, if these are none we've run out of
         // accuracies.
-        if self.accuracies.0.is_none() || self.accuracies.1.is_none() ||
-            point.time < self.accuracies.0.unwrap().time
+        if self.accuracies.0.is_none()
+            || self.accuracies.1.is_none()
+            || point.time < self.accuracies.0.unwrap().time
         {
             return Ok(Some(point));
         }
@@ -120,16 +485,18 @@ impl Source for CombinedSource {
                 return Ok(Some(point));
             }
         }
-        point.accuracy = Some(self.accuracies.0.unwrap().interpolate(
-            &self.accuracies.1.unwrap(),
-            point.time,
-        ));
+        point.accuracy = Some(
+            self.accuracies
+                .0
+                .unwrap()
+                .interpolate(&self.accuracies.1.unwrap(), point.time),
+        );
         Ok(Some(point))
     }
 }
 
 impl IntoIterator for CombinedSource {
-    type Item = Point;
+    type Item = Result<Point, Error>;
     type IntoIter = CombinedSourceIterator;
     fn into_iter(self) -> Self::IntoIter {
         CombinedSourceIterator { source: self }
@@ -143,9 +510,84 @@ pub struct CombinedSourceIterator {
 }
 
 impl Iterator for CombinedSourceIterator {
-    type Item = Point;
-    fn next(&mut self) -> Option<Point> {
-        self.source.source().unwrap()
+    type Item = Result<Point, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.source().transpose()
+    }
+}
+
+/// A window of a source's points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Window {
+    /// Points whose times are between a start and end time, inclusive.
+    Time(Time, Time),
+    /// Points whose indices are in `start..end`, counting from zero.
+    Index(usize, usize),
+}
+
+impl Window {
+    /// Returns true if the window contains a point with the given index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::source::Window;
+    /// use pos::time::Time;
+    /// let point = Point { time: Time(12.0), ..Default::default() };
+    /// assert!(Window::Time(Time(10.0), Time(20.0)).contains(0, &point));
+    /// assert!(!Window::Index(1, 3).contains(0, &point));
+    /// ```
+    pub fn contains(&self, index: usize, point: &Point) -> bool {
+        match *self {
+            Window::Time(start, end) => point.time >= start && point.time <= end,
+            Window::Index(start, end) => index >= start && index < end,
+        }
+    }
+}
+
+/// A source that only returns the points inside a window of another source.
+#[derive(Debug)]
+pub struct Clip<S: Source> {
+    index: usize,
+    source: S,
+    window: Window,
+}
+
+impl<S: Source> Clip<S> {
+    /// Clips a source to a window.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::source::{Clip, Window};
+    /// let source = pos::open("data/2-points.sbet").unwrap();
+    /// let clip = Clip::new(source, Window::Index(1, 2));
+    /// ```
+    pub fn new(source: S, window: Window) -> Clip<S> {
+        Clip {
+            index: 0,
+            source,
+            window,
+        }
+    }
+}
+
+impl<S: Source> Source for Clip<S> {
+    fn source(&mut self) -> Result<Option<Point>, Error> {
+        while let Some(point) = self.source.source()? {
+            let index = self.index;
+            self.index += 1;
+            if self.window.contains(index, &point) {
+                return Ok(Some(point));
+            }
+            if let Window::Index(_, end) = self.window {
+                if self.index >= end {
+                    break;
+                }
+            }
+        }
+        Ok(None)
     }
 }
 
@@ -153,8 +595,86 @@ impl Iterator for CombinedSourceIterator {
 mod tests {
     use super::*;
 
-    use pof;
-    use poq;
+    use crate::pof;
+    use crate::poq;
+    use crate::sbet;
+    use crate::smrmsg;
+
+    #[test]
+    fn clip() {
+        let source = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        assert_eq!(1, count(Clip::new(source, Window::Index(1, 5))));
+        let source = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        assert_eq!(0, count(Clip::new(source, Window::Index(0, 0))));
+        let source = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        assert_eq!(
+            1,
+            count(Clip::new(
+                source,
+                Window::Time(Time(151631.005), Time(151632.))
+            ))
+        );
+    }
+
+    #[test]
+    fn points() {
+        let mut source = open("data/2-points.sbet").unwrap();
+        let first = source.points().next().unwrap().unwrap();
+        let rest = source.points().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(1, rest.len());
+        assert!(rest[0].time > first.time);
+    }
+
+    #[test]
+    fn chunks() {
+        let mut source = open("data/2-points.sbet").unwrap();
+        assert_eq!(2, source.read_points(2).unwrap().len());
+        let mut source = open("data/2-points.sbet").unwrap();
+        assert_eq!(2, source.read_points(usize::MAX).unwrap().len());
+        let mut source = open("data/2-points.sbet").unwrap();
+        let chunks = source.chunks(3).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(1, chunks.len());
+        assert_eq!(2, chunks[0].len());
+    }
+
+    #[test]
+    fn map_points() {
+        let source = open("data/2-points.sbet").unwrap();
+        let mut count = 0;
+        let mut source = source.map_points(|point| {
+            count += 1;
+            Point {
+                time: Time(count as f64),
+                ..point
+            }
+        });
+        while let Some(point) = source.source().unwrap() {
+            assert!(point.time.0 < 3.);
+        }
+        drop(source);
+        assert_eq!(2, count);
+    }
+
+    #[test]
+    fn filter() {
+        let source = open("data/2-points.sbet").unwrap();
+        assert_eq!(1, count(source.filter_time(..Time(151631.005))));
+        let source = open("data/2-points.sbet").unwrap();
+        assert_eq!(2, count(source.filter_time(Time(151631.)..=Time(151632.))));
+        let source = open("data/2-points.sbet").unwrap();
+        let source = source
+            .filter_time(Time(151631.)..)
+            .filter(|point| point.time > Time(151631.005));
+        assert_eq!(1, count(source));
+    }
+
+    fn count<S: Source>(mut source: S) -> usize {
+        let mut count = 0;
+        while source.source().unwrap().is_some() {
+            count += 1;
+        }
+        count
+    }
 
     #[test]
     fn read_pof() {
@@ -166,8 +686,8 @@ mod tests {
     #[test]
     fn read_pof_with_poq() {
         let source = pof::Reader::open_file_source("data/sbet_mission_1.pof").unwrap();
-        let accuracy_source = poq::Reader::open_file_accuracy_source("data/sbet_mission_1.poq")
-            .unwrap();
+        let accuracy_source =
+            poq::Reader::open_file_accuracy_source("data/sbet_mission_1.poq").unwrap();
         let accuracies: Vec<_> = CombinedSource::new(source, accuracy_source)
             .unwrap()
             .into_iter()
@@ -175,4 +695,59 @@ mod tests {
             .collect();
         assert_eq!(20, accuracies.len());
     }
+
+    #[test]
+    fn read_sbet() {
+        let source = sbet::Reader::open_file_source("data/2-points.sbet").unwrap();
+        let points: Vec<_> = source.into_iter().collect();
+        assert_eq!(2, points.len());
+    }
+
+    #[test]
+    fn generic_source() {
+        assert_eq!(
+            2,
+            count(sbet::Reader::from_path("data/2-points.sbet").unwrap())
+        );
+        assert_eq!(
+            2,
+            count(sbet::Reader::open_file_source("data/2-points.sbet").unwrap())
+        );
+    }
+
+    #[test]
+    fn read_sbet_with_smrmsg() {
+        let source = sbet::Reader::open_file_source("data/2-points.sbet").unwrap();
+        let accuracy_source =
+            smrmsg::Reader::open_file_accuracy_source("data/2-points.smrmsg").unwrap();
+        let points = CombinedSource::new(source, accuracy_source)
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+        assert!(points.iter().all(|point| point.accuracy.is_some()));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn open_gzip() {
+        let points = open("data/2-points.sbet.gz")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = open("data/2-points.sbet")
+            .unwrap()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected, points);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn open_gzip_without_feature() {
+        assert!(open("data/2-points.sbet.gz").is_err());
+    }
 }
@@ -0,0 +1,436 @@
+//! SQLite trajectory stores.
+//!
+//! Points are stored in a `points` table, one row per epoch, with an index on time, so points can
+//! be queried by time window. Columns are named after point fields, with accuracy fields prefixed
+//! by `accuracy_`, and values are in the point's own units, so angles are in radians. Optional
+//! fields are null when missing. The status is stored by name, e.g. `fixed`. Times are stored
+//! without their base, so only points in GPS seconds of the week can be inserted.
+//!
+//! Requires the `sqlite` feature.
+
+use crate::error;
+use crate::point::{Accuracy, Point, SatelliteCount, Status};
+use crate::source::Source;
+use crate::time::{self, Time, TimeBase};
+use crate::units::Radians;
+use rusqlite::{Connection, Row};
+use std::path::Path;
+
+const FIELDS: &str = "time, longitude, latitude, altitude, roll, pitch, yaw, distance, \
+                      x_velocity, y_velocity, z_velocity, wander_angle, x_acceleration, \
+                      y_acceleration, z_acceleration, x_angular_rate, y_angular_rate, \
+                      z_angular_rate, accuracy_time, accuracy_x, accuracy_y, accuracy_z, \
+                      accuracy_roll, accuracy_pitch, accuracy_yaw, accuracy_pdop, \
+                      accuracy_x_velocity, accuracy_y_velocity, accuracy_z_velocity, \
+                      satellite_count, gps_satellite_count, glonass_satellite_count, status";
+
+const CREATE: &str = "CREATE TABLE IF NOT EXISTS points (
+    time REAL NOT NULL,
+    longitude REAL NOT NULL,
+    latitude REAL NOT NULL,
+    altitude REAL NOT NULL,
+    roll REAL NOT NULL,
+    pitch REAL NOT NULL,
+    yaw REAL NOT NULL,
+    distance REAL,
+    x_velocity REAL,
+    y_velocity REAL,
+    z_velocity REAL,
+    wander_angle REAL,
+    x_acceleration REAL,
+    y_acceleration REAL,
+    z_acceleration REAL,
+    x_angular_rate REAL,
+    y_angular_rate REAL,
+    z_angular_rate REAL,
+    accuracy_time REAL,
+    accuracy_x REAL,
+    accuracy_y REAL,
+    accuracy_z REAL,
+    accuracy_roll REAL,
+    accuracy_pitch REAL,
+    accuracy_yaw REAL,
+    accuracy_pdop REAL,
+    accuracy_x_velocity REAL,
+    accuracy_y_velocity REAL,
+    accuracy_z_velocity REAL,
+    satellite_count INTEGER,
+    gps_satellite_count INTEGER,
+    glonass_satellite_count INTEGER,
+    status TEXT
+);
+CREATE INDEX IF NOT EXISTS points_time ON points (time);";
+
+/// A SQLite trajectory store.
+#[derive(Debug)]
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    /// Opens a database file, creating it and the points table if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sqlite::Database;
+    /// let path = std::env::temp_dir().join("pos-rs-sqlite-doctest.sqlite");
+    /// let database = Database::open(&path).unwrap();
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Database, error::Error> {
+        Database::new(Connection::open(path)?)
+    }
+
+    /// Opens a new in-memory database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sqlite::Database;
+    /// let database = Database::open_in_memory().unwrap();
+    /// ```
+    pub fn open_in_memory() -> Result<Database, error::Error> {
+        Database::new(Connection::open_in_memory()?)
+    }
+
+    /// Creates a database from an open connection, creating the points table if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rusqlite;
+    /// # extern crate pos;
+    /// # fn main() {
+    /// use pos::sqlite::Database;
+    /// let connection = rusqlite::Connection::open_in_memory().unwrap();
+    /// let database = Database::new(connection).unwrap();
+    /// # }
+    /// ```
+    pub fn new(connection: Connection) -> Result<Database, error::Error> {
+        connection.execute_batch(CREATE)?;
+        Ok(Database { connection })
+    }
+
+    /// Inserts points in a single transaction, returning the number inserted.
+    ///
+    /// Returns an error, and inserts nothing, if a point's time isn't in GPS seconds of the week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::sqlite::Database;
+    /// let mut database = Database::open_in_memory().unwrap();
+    /// assert_eq!(1, database.insert(vec![Point::default()]).unwrap());
+    /// ```
+    pub fn insert<I: IntoIterator<Item = Point>>(
+        &mut self,
+        points: I,
+    ) -> Result<usize, error::Error> {
+        let transaction = self.connection.transaction()?;
+        let mut count = 0;
+        {
+            let mut statement = transaction.prepare_cached(&format!(
+                "INSERT INTO points ({}) VALUES ({})",
+                FIELDS,
+                vec!["?"; 33].join(", ")
+            ))?;
+            for point in points {
+                if point.time_base != TimeBase::GpsSecondsOfWeek {
+                    return Err(
+                        time::Error::Base(TimeBase::GpsSecondsOfWeek, point.time_base).into(),
+                    );
+                }
+                let accuracy = point.accuracy;
+                let satellite_count = accuracy.and_then(|a| a.satellite_count);
+                let (total, gps, glonass) = match satellite_count {
+                    Some(SatelliteCount::Unspecified(n)) => (Some(n), None, None),
+                    Some(count @ SatelliteCount::Specified { gps, glonass }) => {
+                        (Some(count.total()), Some(gps), Some(glonass))
+                    }
+                    None => (None, None, None),
+                };
+                let _ = statement.execute(rusqlite::params![
+                    point.time.0,
+                    point.longitude.0,
+                    point.latitude.0,
+                    point.altitude,
+                    point.roll.0,
+                    point.pitch.0,
+                    point.yaw.0,
+                    point.distance,
+                    point.x_velocity,
+                    point.y_velocity,
+                    point.z_velocity,
+                    point.wander_angle.map(|a| a.0),
+                    point.x_acceleration,
+                    point.y_acceleration,
+                    point.z_acceleration,
+                    point.x_angular_rate.map(|a| a.0),
+                    point.y_angular_rate.map(|a| a.0),
+                    point.z_angular_rate.map(|a| a.0),
+                    accuracy.map(|a| a.time.0),
+                    accuracy.map(|a| a.x),
+                    accuracy.map(|a| a.y),
+                    accuracy.map(|a| a.z),
+                    accuracy.map(|a| a.roll.0),
+                    accuracy.map(|a| a.pitch.0),
+                    accuracy.map(|a| a.yaw.0),
+                    accuracy.map(|a| a.pdop),
+                    accuracy.and_then(|a| a.x_velocity),
+                    accuracy.and_then(|a| a.y_velocity),
+                    accuracy.and_then(|a| a.z_velocity),
+                    total,
+                    gps,
+                    glonass,
+                    point.status.map(Status::name),
+                ])?;
+                count += 1;
+            }
+        }
+        transaction.commit()?;
+        Ok(count)
+    }
+
+    /// Returns the number of stored points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sqlite::Database;
+    /// let database = Database::open_in_memory().unwrap();
+    /// assert_eq!(0, database.count().unwrap());
+    /// ```
+    pub fn count(&self) -> Result<usize, error::Error> {
+        let count: i64 = self
+            .connection
+            .query_row("SELECT COUNT(*) FROM points", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Returns the points with times in `[start, end)`, in time order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::sqlite::Database;
+    /// use pos::time::Time;
+    /// let mut database = Database::open_in_memory().unwrap();
+    /// let points = (0..10).map(|i| Point { time: Time(i as f64), ..Default::default() });
+    /// database.insert(points).unwrap();
+    /// assert_eq!(3, database.query(Time(2.0), Time(5.0)).unwrap().len());
+    /// ```
+    pub fn query(&self, start: Time, end: Time) -> Result<Vec<Point>, error::Error> {
+        let mut statement = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM points WHERE time >= ?1 AND time < ?2 ORDER BY time, rowid",
+            FIELDS
+        ))?;
+        let points = statement
+            .query_map(rusqlite::params![start.0, end.0], |row| point(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(points)
+    }
+
+    /// Returns a reader over all points, in time order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::sqlite::Database;
+    /// let database = Database::open_in_memory().unwrap();
+    /// let mut reader = database.reader();
+    /// assert!(reader.read_point().unwrap().is_none());
+    /// ```
+    pub fn reader(&self) -> Reader<'_> {
+        Reader {
+            cursor: (f64::NEG_INFINITY, i64::MIN),
+            database: self,
+        }
+    }
+}
+
+/// Reads the points of a database one at a time, in time order.
+///
+/// Each read queries for the point after the last one, so points inserted while reading are read
+/// if they come later.
+#[derive(Debug)]
+pub struct Reader<'a> {
+    cursor: (f64, i64),
+    database: &'a Database,
+}
+
+impl<'a> Reader<'a> {
+    /// Reads the next point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::sqlite::Database;
+    /// let mut database = Database::open_in_memory().unwrap();
+    /// database.insert(vec![Point::default()]).unwrap();
+    /// let mut reader = database.reader();
+    /// assert!(reader.read_point().unwrap().is_some());
+    /// assert!(reader.read_point().unwrap().is_none());
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        let mut statement = self.database.connection.prepare_cached(&format!(
+            "SELECT rowid, {} FROM points WHERE time > ?1 OR (time = ?1 AND rowid > ?2) \
+             ORDER BY time, rowid LIMIT 1",
+            FIELDS
+        ))?;
+        let mut rows = statement.query(rusqlite::params![self.cursor.0, self.cursor.1])?;
+        match rows.next()? {
+            Some(row) => {
+                let point = point(row, 1)?;
+                self.cursor = (point.time.0, row.get(0)?);
+                Ok(Some(point))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a> IntoIterator for Reader<'a> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over the points of a database reader.
+#[derive(Debug)]
+pub struct ReaderIterator<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> Iterator for ReaderIterator<'a> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<'a> Source for Reader<'a> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// Reads a point from the `FIELDS` columns of a row, starting at `offset`.
+fn point(row: &Row, offset: usize) -> Result<Point, rusqlite::Error> {
+    let get = |i: usize| -> Result<Option<f64>, rusqlite::Error> { row.get(offset + i) };
+    let required = |i: usize| -> Result<f64, rusqlite::Error> { row.get(offset + i) };
+    let satellite_count = match (
+        row.get::<_, Option<u16>>(offset + 29)?,
+        row.get::<_, Option<u16>>(offset + 30)?,
+        row.get::<_, Option<u16>>(offset + 31)?,
+    ) {
+        (_, Some(gps), Some(glonass)) => Some(SatelliteCount::Specified { gps, glonass }),
+        (Some(n), _, _) => Some(SatelliteCount::Unspecified(n)),
+        _ => None,
+    };
+    let accuracy = match get(18)? {
+        Some(time) => Some(Accuracy {
+            time: Time(time),
+            x: required(19)?,
+            y: required(20)?,
+            z: required(21)?,
+            roll: Radians(required(22)?),
+            pitch: Radians(required(23)?),
+            yaw: Radians(required(24)?),
+            pdop: required(25)?,
+            satellite_count,
+            x_velocity: get(26)?,
+            y_velocity: get(27)?,
+            z_velocity: get(28)?,
+        }),
+        None => None,
+    };
+    Ok(Point {
+        time: Time(required(0)?),
+        time_base: TimeBase::GpsSecondsOfWeek,
+        longitude: Radians(required(1)?),
+        latitude: Radians(required(2)?),
+        altitude: required(3)?,
+        roll: Radians(required(4)?),
+        pitch: Radians(required(5)?),
+        yaw: Radians(required(6)?),
+        distance: get(7)?,
+        x_velocity: get(8)?,
+        y_velocity: get(9)?,
+        z_velocity: get(10)?,
+        wander_angle: get(11)?.map(Radians),
+        x_acceleration: get(12)?,
+        y_acceleration: get(13)?,
+        z_acceleration: get(14)?,
+        x_angular_rate: get(15)?.map(Radians),
+        y_angular_rate: get(16)?.map(Radians),
+        z_angular_rate: get(17)?.map(Radians),
+        accuracy,
+        status: row
+            .get::<_, Option<String>>(offset + 32)?
+            .and_then(|name| Status::from_name(&name)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(time: f64) -> Point {
+        Point {
+            time: Time(time),
+            latitude: Radians(0.5),
+            x_velocity: Some(1.5),
+            status: Some(Status::Float),
+            accuracy: Some(Accuracy {
+                time: Time(time),
+                z: 0.02,
+                satellite_count: Some(SatelliteCount::Specified { gps: 8, glonass: 4 }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut database = Database::open_in_memory().unwrap();
+        let points = vec![point(2.), point(1.), Point::default()];
+        assert_eq!(3, database.insert(points.clone()).unwrap());
+        assert_eq!(3, database.count().unwrap());
+        let read = database
+            .reader()
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec![points[2], points[1], points[0]], read);
+    }
+
+    #[test]
+    fn query() {
+        let mut database = Database::open_in_memory().unwrap();
+        let _ = database.insert((0..10).map(|i| point(i as f64))).unwrap();
+        let points = database.query(Time(2.5), Time(5.)).unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(Time(3.), points[0].time);
+        assert_eq!(point(4.), points[1]);
+    }
+
+    #[test]
+    fn duplicate_times() {
+        let mut database = Database::open_in_memory().unwrap();
+        let _ = database
+            .insert(vec![point(1.), point(1.), point(0.)])
+            .unwrap();
+        let mut reader = database.reader();
+        assert_eq!(Time(0.), reader.source().unwrap().unwrap().time);
+        assert_eq!(Time(1.), reader.source().unwrap().unwrap().time);
+        assert_eq!(Time(1.), reader.source().unwrap().unwrap().time);
+        assert!(reader.source().unwrap().is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! Summary statistics of trajectories.
+
+use crate::compare::Offset;
+use crate::point::Point;
+use crate::time::Duration;
+use crate::units::Radians;
+
+/// The minimum, maximum, and mean of a value.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Extent {
+    /// The smallest value.
+    pub min: f64,
+    /// The largest value.
+    pub max: f64,
+    /// The mean value.
+    pub mean: f64,
+}
+
+/// Summary statistics of a trajectory, as used in flight and drive reports.
+///
+/// Angles are in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// The number of points.
+    pub count: usize,
+    /// The time from the first point to the last.
+    pub duration: Duration,
+    /// The average number of points per second, or zero if the duration is zero.
+    pub sample_rate: f64,
+    /// The altitude, in meters.
+    pub altitude: Extent,
+    /// The horizontal speed, in meters per second.
+    ///
+    /// Uses the points' velocities if every point has them, otherwise the speed between
+    /// consecutive points. Is none if there's only one point and it has no velocity.
+    pub speed: Option<Extent>,
+    /// The roll.
+    pub roll: Extent,
+    /// The pitch.
+    pub pitch: Extent,
+    /// The yaw.
+    ///
+    /// The mean is a circular mean, so headings either side of north average to north, and it's
+    /// in the same range as the points' yaws. The minimum and maximum are of the yaws as they are.
+    pub yaw: Extent,
+    /// The total distance traveled between consecutive points, in meters.
+    pub distance: f64,
+    /// The number of satellites, or none if no point has a satellite count.
+    pub satellite_count: Option<Extent>,
+    /// The position dilution of precision, or none if no point has one.
+    ///
+    /// Accuracies with a zero PDOP are taken not to have one.
+    pub pdop: Option<Extent>,
+}
+
+impl Statistics {
+    /// Computes statistics over some points, which should be sorted by time.
+    ///
+    /// Returns none if there are no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::statistics::Statistics;
+    /// use pos::time::{Duration, Time};
+    /// let points: Vec<_> = (0..11)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: i as f64, ..Default::default() })
+    ///     .collect();
+    /// let statistics = Statistics::new(&points).unwrap();
+    /// assert_eq!(Duration(10.0), statistics.duration);
+    /// assert_eq!(5.0, statistics.altitude.mean);
+    /// assert_eq!(10.0, statistics.distance);
+    /// ```
+    pub fn new(points: &[Point]) -> Option<Statistics> {
+        let (first, last) = (points.first()?, points.last()?);
+        let duration = last.time - first.time;
+        let mut distance = 0.;
+        let mut derived_speeds = Vec::new();
+        for pair in points.windows(2) {
+            let offset = Offset::between(&pair[1], &pair[0]);
+            let horizontal = offset.north.hypot(offset.east);
+            distance += horizontal.hypot(offset.up);
+            let dt = pair[1].time - pair[0].time;
+            if dt > Duration(0.) {
+                derived_speeds.push(horizontal / dt.0);
+            }
+        }
+        let velocities: Option<Vec<f64>> = points.iter().map(Point::speed).collect();
+        Some(Statistics {
+            count: points.len(),
+            duration,
+            sample_rate: if duration > Duration(0.) {
+                (points.len() - 1) as f64 / duration.0
+            } else {
+                0.
+            },
+            altitude: Extent::new(points.iter().map(|p| p.altitude))?,
+            speed: Extent::new(velocities.unwrap_or(derived_speeds)),
+            roll: Extent::new(points.iter().map(|p| p.roll.0))?,
+            pitch: Extent::new(points.iter().map(|p| p.pitch.0))?,
+            yaw: Extent {
+                mean: circular_mean(points.iter().map(|p| p.yaw)),
+                ..Extent::new(points.iter().map(|p| p.yaw.0))?
+            },
+            distance,
+            satellite_count: Extent::new(
+                points
+                    .iter()
+                    .filter_map(|p| p.accuracy?.satellite_count)
+                    .map(|count| f64::from(count.total())),
+            ),
+            pdop: Extent::new(
+                points
+                    .iter()
+                    .filter_map(|p| p.accuracy.map(|a| a.pdop))
+                    .filter(|&pdop| pdop > 0.),
+            ),
+        })
+    }
+}
+
+impl Extent {
+    /// Computes the extent of some values.
+    ///
+    /// Returns none if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::statistics::Extent;
+    /// let extent = Extent::new(vec![1.0, 2.0, 6.0]).unwrap();
+    /// assert_eq!(1.0, extent.min);
+    /// assert_eq!(6.0, extent.max);
+    /// assert_eq!(3.0, extent.mean);
+    /// assert!(Extent::new(Vec::new()).is_none());
+    /// ```
+    pub fn new<I: IntoIterator<Item = f64>>(values: I) -> Option<Extent> {
+        let mut count = 0;
+        let mut extent = Extent {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.,
+        };
+        for value in values {
+            extent.min = extent.min.min(value);
+            extent.max = extent.max.max(value);
+            extent.mean += value;
+            count += 1;
+        }
+        if count == 0 {
+            None
+        } else {
+            extent.mean /= count as f64;
+            Some(extent)
+        }
+    }
+}
+
+/// Returns the direction of the sum of some angles' unit vectors, wrapped like the angles.
+fn circular_mean<I: Iterator<Item = Radians<f64>> + Clone>(angles: I) -> f64 {
+    let (sin, cos) = angles.clone().fold((0., 0.), |(sin, cos), angle| {
+        (sin + angle.0.sin(), cos + angle.0.cos())
+    });
+    Radians(f64::atan2(sin, cos)).wrap_like(angles).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::point::{Accuracy, SatelliteCount};
+    use crate::time::Time;
+
+    #[test]
+    fn speed_from_velocities() {
+        let points: Vec<_> = (0..3)
+            .map(|i| Point {
+                time: Time(i as f64),
+                x_velocity: Some(3.),
+                y_velocity: Some(4. * i as f64),
+                z_velocity: Some(100.),
+                ..Default::default()
+            })
+            .collect();
+        let speed = Statistics::new(&points).unwrap().speed.unwrap();
+        assert_eq!(3., speed.min);
+        assert!((speed.max - 73f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn speed_from_positions() {
+        let points: Vec<_> = (0..3)
+            .map(|i| Point {
+                time: Time(2. * i as f64),
+                latitude: Radians::from_degrees(1e-4 * i as f64),
+                roll: Radians(0.1 * i as f64),
+                ..Default::default()
+            })
+            .collect();
+        let statistics = Statistics::new(&points).unwrap();
+        let speed = statistics.speed.unwrap();
+        assert!((speed.mean - 5.529).abs() < 1e-3);
+        assert!((statistics.distance - 22.115).abs() < 1e-3);
+        assert_eq!(0.5, statistics.sample_rate);
+        assert_eq!(0.2, statistics.roll.max);
+    }
+
+    #[test]
+    fn observability() {
+        let points: Vec<_> = (0..4)
+            .map(|i| Point {
+                time: Time(i as f64),
+                accuracy: Some(Accuracy {
+                    pdop: if i == 0 { 0. } else { i as f64 },
+                    satellite_count: Some(SatelliteCount::Unspecified(8 + i)),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .collect();
+        let statistics = Statistics::new(&points).unwrap();
+        let satellite_count = statistics.satellite_count.unwrap();
+        assert_eq!(8., satellite_count.min);
+        assert_eq!(9.5, satellite_count.mean);
+        let pdop = statistics.pdop.unwrap();
+        assert_eq!(1., pdop.min);
+        assert_eq!(3., pdop.max);
+    }
+
+    #[test]
+    fn yaw_mean() {
+        let points = |yaws: &[f64]| -> Vec<Point> {
+            yaws.iter()
+                .map(|&yaw| Point {
+                    yaw: Radians::from_degrees(yaw),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let mean = |yaws: &[f64]| {
+            Statistics::new(&points(yaws))
+                .unwrap()
+                .yaw
+                .mean
+                .to_degrees()
+        };
+        let from_north = (mean(&[359., 1.]) + 180.).rem_euclid(360.) - 180.;
+        assert!(from_north.abs() < 1e-9);
+        assert!((0. ..360.).contains(&mean(&[359., 1.])));
+        assert!((mean(&[-179., 179., 180.]) + 180.).abs() < 1e-9);
+        assert!((mean(&[10., 20., 30.]) - 20.).abs() < 1e-9);
+        let yaw = Statistics::new(&points(&[359., 1.])).unwrap().yaw;
+        assert_eq!(1., yaw.min.to_degrees().round());
+        assert_eq!(359., yaw.max.to_degrees().round());
+    }
+
+    #[test]
+    fn single_point() {
+        let statistics = Statistics::new(&[Point::default()]).unwrap();
+        assert_eq!(1, statistics.count);
+        assert_eq!(0., statistics.sample_rate);
+        assert!(statistics.speed.is_none());
+        assert!(statistics.satellite_count.is_none());
+        assert!(statistics.pdop.is_none());
+        assert!(Statistics::new(&[]).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Follow files that are still being written, e.g. by a logger in the field.
+//!
+//! `Tail` wraps a reader and, instead of ending at the end of the data, waits for more data to be
+//! written. Any reader can be built on top of it, so a growing sbet file is followed with
+//! `sbet::Reader::new(Tail::from_path(path)?)`, and a growing text file with a `BufReader` in the
+//! middle. Records that are only partially written when the tail catches up are completed once
+//! the rest of the record is written.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The default time to wait before checking for more data.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A reader that waits for more data at the end of its data.
+#[derive(Debug)]
+pub struct Tail<R: Read> {
+    /// The time to wait before checking for more data.
+    pub interval: Duration,
+    /// How long to wait without new data before ending, or none to wait forever.
+    pub timeout: Option<Duration>,
+    reader: R,
+}
+
+impl Tail<File> {
+    /// Opens a file to follow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::tail::Tail;
+    /// let tail = Tail::from_path("data/2-points.sbet").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Tail<File>> {
+        File::open(path).map(Tail::new)
+    }
+}
+
+impl<R: Read> Tail<R> {
+    /// Follows a reader, waiting forever for more data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use pos::sbet::Reader;
+    /// use pos::tail::Tail;
+    /// let mut tail = Tail::from_path("data/2-points.sbet").unwrap();
+    /// tail.timeout = Some(Duration::from_millis(10));
+    /// let reader = Reader::new(tail);
+    /// assert_eq!(2, reader.into_iter().count());
+    /// ```
+    pub fn new(reader: R) -> Tail<R> {
+        Tail {
+            interval: DEFAULT_INTERVAL,
+            timeout: None,
+            reader,
+        }
+    }
+
+    /// Returns the underlying reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::tail::Tail;
+    /// let file = Tail::from_path("data/2-points.sbet").unwrap().into_inner();
+    /// ```
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for Tail<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = Instant::now();
+        loop {
+            let n = self.reader.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            if self
+                .timeout
+                .is_some_and(|timeout| start.elapsed() >= timeout)
+            {
+                return Ok(0);
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+    use std::io::{BufRead, BufReader};
+
+    /// A reader that returns each chunk in turn, with an empty read before each one.
+    #[derive(Debug)]
+    struct Growing {
+        chunks: Vec<Vec<u8>>,
+        waiting: bool,
+    }
+
+    impl Read for Growing {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.waiting = !self.waiting;
+            if self.waiting || self.chunks.is_empty() {
+                return Ok(0);
+            }
+            let n = self.chunks[0].len().min(buf.len());
+            buf[..n].copy_from_slice(&self.chunks[0][..n]);
+            let _ = self.chunks[0].drain(..n);
+            if self.chunks[0].is_empty() {
+                let _ = self.chunks.remove(0);
+            }
+            Ok(n)
+        }
+    }
+
+    fn tail(chunks: Vec<Vec<u8>>) -> Tail<Growing> {
+        let mut tail = Tail::new(Growing {
+            chunks,
+            waiting: false,
+        });
+        tail.interval = Duration::from_millis(1);
+        tail.timeout = Some(Duration::from_millis(20));
+        tail
+    }
+
+    #[test]
+    fn lines() {
+        let chunks = vec![
+            b"1 45".to_vec(),
+            b" -105\n2".to_vec(),
+            b" 46 -106\n".to_vec(),
+        ];
+        let lines = BufReader::new(tail(chunks))
+            .lines()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(vec!["1 45 -105", "2 46 -106"], lines);
+    }
+
+    #[test]
+    fn partial_record() {
+        let bytes = ::std::fs::read("data/2-points.sbet").unwrap();
+        let chunks = bytes.chunks(100).map(|chunk| chunk.to_vec()).collect();
+        let points = sbet::Reader::new(tail(chunks))
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, points.len());
+    }
+}
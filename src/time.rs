@@ -0,0 +1,814 @@
+//! GPS time.
+//!
+//! Point times are `Time`s rather than bare seconds, and the span between two of them is a
+//! `Duration`, so a time can't be passed where a duration belongs or added to another time.
+//!
+//! Most formats store time as GPS seconds of the week, which wraps to zero at midnight between
+//! Saturday and Sunday. `GpsTime` carries the week number along with the seconds, and
+//! `unwrap_week` makes the times of multi-day datasets keep increasing across week boundaries.
+//!
+//! GPS time doesn't have leap seconds, so it runs ahead of UTC. `leap_seconds` looks up the
+//! difference in a table of every leap second since the GPS epoch, which is used to convert
+//! between GPS time and UTC or Unix time.
+
+use crate::error;
+use crate::point::Point;
+use crate::source::Source;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// The number of days between 1970-01-01 and the GPS epoch, 1980-01-06.
+const GPS_EPOCH_DAYS: i64 = 3657;
+
+/// The number of seconds in a GPS week.
+pub const SECONDS_PER_WEEK: f64 = 7. * SECONDS_PER_DAY;
+
+/// The number of seconds in a day.
+pub const SECONDS_PER_DAY: f64 = 86400.;
+
+/// The UTC dates on which leap seconds took effect, with the difference between GPS time and UTC
+/// from that date on.
+const LEAP_SECONDS: &[(i64, i64, i64, f64)] = &[
+    (1981, 7, 1, 1.),
+    (1982, 7, 1, 2.),
+    (1983, 7, 1, 3.),
+    (1985, 7, 1, 4.),
+    (1988, 1, 1, 5.),
+    (1990, 1, 1, 6.),
+    (1991, 1, 1, 7.),
+    (1992, 7, 1, 8.),
+    (1993, 7, 1, 9.),
+    (1994, 7, 1, 10.),
+    (1996, 1, 1, 11.),
+    (1997, 7, 1, 12.),
+    (1999, 1, 1, 13.),
+    (2006, 1, 1, 14.),
+    (2009, 1, 1, 15.),
+    (2012, 7, 1, 16.),
+    (2015, 7, 1, 17.),
+    (2017, 1, 1, 18.),
+];
+
+/// Time errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// Times in two different bases were combined.
+    #[error("Cannot combine times in {0} with times in {1}")]
+    Base(TimeBase, TimeBase),
+}
+
+/// A GPS week number and the seconds since the start of that week.
+///
+/// Times are ordered by week, then by seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpsTime {
+    /// The number of weeks since the GPS epoch, 1980-01-06, without rollover.
+    pub week: u16,
+    /// The seconds since the start of the week.
+    pub seconds: f64,
+}
+
+impl GpsTime {
+    /// Creates a GPS time, carrying any seconds outside of [0, 604800) into the week.
+    ///
+    /// This turns an unwrapped time, e.g. from `unwrap_week`, back into a week and seconds of
+    /// week. Returns none if the seconds aren't finite or the week would be before the GPS epoch
+    /// or after week 65535.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// let time = GpsTime::new(2053, 604800.0 + 12.5).unwrap();
+    /// assert_eq!(2054, time.week);
+    /// assert_eq!(12.5, time.seconds);
+    /// ```
+    pub fn new(week: u16, seconds: f64) -> Option<GpsTime> {
+        if !seconds.is_finite() {
+            return None;
+        }
+        let weeks = (seconds / SECONDS_PER_WEEK).floor();
+        Some(GpsTime {
+            week: u16::try_from(i64::from(week) + weeks as i64).ok()?,
+            seconds: seconds - weeks * SECONDS_PER_WEEK,
+        })
+    }
+
+    /// Returns the number of seconds since the GPS epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// assert_eq!(604801.0, GpsTime::new(1, 1.0).unwrap().seconds_since_epoch());
+    /// ```
+    pub fn seconds_since_epoch(&self) -> f64 {
+        f64::from(self.week) * SECONDS_PER_WEEK + self.seconds
+    }
+
+    /// Formats this time as an ISO 8601 UTC date and time, with millisecond precision.
+    ///
+    /// `leap_seconds` is the difference between GPS time and UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// let time = GpsTime::new(2053, 264225.0).unwrap();
+    /// assert_eq!("2019-05-15T01:23:27.000Z", time.to_utc_string(18.0));
+    /// ```
+    pub fn to_utc_string(&self, leap_seconds: f64) -> String {
+        to_utc_string(self.week, self.seconds, leap_seconds)
+    }
+
+    /// Converts a Unix time, i.e. seconds since 1970-01-01 in UTC, to GPS time.
+    ///
+    /// Returns none for times before the GPS epoch, which can't be represented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// let time = GpsTime::from_unix(1557883389.0).unwrap();
+    /// assert_eq!(GpsTime::new(2053, 264207.0).unwrap(), time);
+    /// ```
+    pub fn from_unix(unix: f64) -> Option<GpsTime> {
+        let seconds = unix - GPS_EPOCH_DAYS as f64 * SECONDS_PER_DAY;
+        GpsTime::new(0, seconds + leap_seconds_at_utc(seconds))
+    }
+
+    /// Converts this time to Unix time, i.e. seconds since 1970-01-01 in UTC, using the leap
+    /// second table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// assert_eq!(1557883389.0, GpsTime::new(2053, 264207.0).unwrap().to_unix());
+    /// ```
+    pub fn to_unix(&self) -> f64 {
+        GPS_EPOCH_DAYS as f64 * SECONDS_PER_DAY + self.seconds_since_epoch() - leap_seconds(*self)
+    }
+
+    /// Formats this time as an ISO 8601 UTC date and time, using the leap second table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::GpsTime;
+    /// assert_eq!("2019-05-15T01:23:09.000Z", GpsTime::new(2053, 264207.0).unwrap().to_utc());
+    /// ```
+    pub fn to_utc(&self) -> String {
+        self.to_utc_string(leap_seconds(*self))
+    }
+}
+
+/// A point's time, in seconds.
+///
+/// Most formats use GPS seconds of the week, but some don't, e.g. NMEA times before the date is
+/// known are UTC seconds of the day. A point's `time_base` says which, so that times in different
+/// bases aren't mixed. Times can be compared and shifted by a `Duration`, and subtracting two
+/// times gives the `Duration` between them. Adding two times is an error, since it's meaningless.
+///
+/// # Examples
+///
+/// ```
+/// use pos::time::{Duration, Time};
+/// let time = Time(264225.0) + Duration(0.5);
+/// assert_eq!(Duration(1.5), Time(264226.0) - Time(264224.5));
+/// assert!(time < Time(264226.0));
+/// assert_eq!(264225.5, f64::from(time));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Time(pub f64);
+
+impl Time {
+    /// Creates a time from a day of the week, where zero is Sunday, and the seconds of that day.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::{Time, SECONDS_PER_DAY};
+    /// assert_eq!(Time(3.0 * SECONDS_PER_DAY + 5027.0), Time::from_seconds_of_day(3, 5027.0));
+    /// ```
+    pub fn from_seconds_of_day(day: u8, seconds: f64) -> Time {
+        Time(f64::from(day) * SECONDS_PER_DAY + seconds)
+    }
+
+    /// Returns the seconds of the week, wrapping times that have been unwrapped past the end of
+    /// the week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::{Time, SECONDS_PER_WEEK};
+    /// assert_eq!(12.5, Time(SECONDS_PER_WEEK + 12.5).seconds_of_week());
+    /// ```
+    pub fn seconds_of_week(self) -> f64 {
+        self.0.rem_euclid(SECONDS_PER_WEEK)
+    }
+
+    /// Returns the seconds of the day.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::Time;
+    /// assert_eq!(5027.0, Time::from_seconds_of_day(3, 5027.0).seconds_of_day());
+    /// ```
+    pub fn seconds_of_day(self) -> f64 {
+        self.0.rem_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Returns the later of two times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::Time;
+    /// assert_eq!(Time(2.0), Time(1.0).max(Time(2.0)));
+    /// ```
+    pub fn max(self, other: Time) -> Time {
+        Time(self.0.max(other.0))
+    }
+
+    /// Returns the earlier of two times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::Time;
+    /// assert_eq!(Time(1.0), Time(1.0).min(Time(2.0)));
+    /// ```
+    pub fn min(self, other: Time) -> Time {
+        Time(self.0.min(other.0))
+    }
+}
+
+impl From<f64> for Time {
+    fn from(seconds: f64) -> Time {
+        Time(seconds)
+    }
+}
+
+impl From<Time> for f64 {
+    fn from(time: Time) -> f64 {
+        time.0
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Add<Duration> for Time {
+    type Output = Time;
+    fn add(self, other: Duration) -> Time {
+        Time(self.0 + other.0)
+    }
+}
+
+impl AddAssign<Duration> for Time {
+    fn add_assign(&mut self, other: Duration) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub<Duration> for Time {
+    type Output = Time;
+    fn sub(self, other: Duration) -> Time {
+        Time(self.0 - other.0)
+    }
+}
+
+impl SubAssign<Duration> for Time {
+    fn sub_assign(&mut self, other: Duration) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sub for Time {
+    type Output = Duration;
+    fn sub(self, other: Time) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
+
+/// What a point's time counts from.
+///
+/// # Examples
+///
+/// ```
+/// use pos::time::TimeBase;
+/// assert_eq!(TimeBase::GpsSecondsOfWeek, TimeBase::default());
+/// assert_eq!("UTC seconds of the day", TimeBase::UtcSecondsOfDay.to_string());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeBase {
+    /// GPS seconds of the week, which most formats use.
+    #[default]
+    GpsSecondsOfWeek,
+    /// UTC seconds of the week, e.g. NMEA times once the date is known.
+    UtcSecondsOfWeek,
+    /// UTC seconds of the day, e.g. NMEA times before the date is known.
+    UtcSecondsOfDay,
+}
+
+impl TimeBase {
+    /// Returns the time base shared by some points, or none if there are no points.
+    ///
+    /// Returns an error if the points' times are in different bases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::TimeBase;
+    /// let gps = Point::default();
+    /// let utc = Point { time_base: TimeBase::UtcSecondsOfDay, ..Default::default() };
+    /// assert_eq!(Some(TimeBase::GpsSecondsOfWeek), TimeBase::of(&[gps, gps]).unwrap());
+    /// assert!(TimeBase::of(&[gps, utc]).is_err());
+    /// ```
+    pub fn of<'a, I: IntoIterator<Item = &'a Point>>(points: I) -> Result<Option<TimeBase>, Error> {
+        let mut base = None;
+        for point in points {
+            match base {
+                None => base = Some(point.time_base),
+                Some(base) if base != point.time_base => {
+                    return Err(Error::Base(base, point.time_base))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(base)
+    }
+}
+
+impl fmt::Display for TimeBase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TimeBase::GpsSecondsOfWeek => "GPS seconds of the week",
+            TimeBase::UtcSecondsOfWeek => "UTC seconds of the week",
+            TimeBase::UtcSecondsOfDay => "UTC seconds of the day",
+        })
+    }
+}
+
+/// The span between two times, in seconds.
+///
+/// Unlike `std::time::Duration`, this can be negative, e.g. when a time goes backwards.
+///
+/// # Examples
+///
+/// ```
+/// use pos::time::Duration;
+/// assert_eq!(Duration(0.5), (Duration(3.0) - Duration(2.0)) / 2.0);
+/// assert_eq!(Duration(1.0), Duration(-1.0).abs());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Duration(pub f64);
+
+impl Duration {
+    /// Returns the absolute value of this duration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::Duration;
+    /// assert_eq!(Duration(2.0), Duration(-2.0).abs());
+    /// ```
+    pub fn abs(self) -> Duration {
+        Duration(self.0.abs())
+    }
+}
+
+impl From<f64> for Duration {
+    fn from(seconds: f64) -> Duration {
+        Duration(seconds)
+    }
+}
+
+impl From<Duration> for f64 {
+    fn from(duration: Duration) -> f64 {
+        duration.0
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    fn from(duration: std::time::Duration) -> Duration {
+        Duration(duration.as_secs_f64())
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+    fn sub(self, other: Duration) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+    fn neg(self) -> Duration {
+        Duration(-self.0)
+    }
+}
+
+impl Mul<f64> for Duration {
+    type Output = Duration;
+    fn mul(self, other: f64) -> Duration {
+        Duration(self.0 * other)
+    }
+}
+
+impl Mul<Duration> for f64 {
+    type Output = Duration;
+    fn mul(self, other: Duration) -> Duration {
+        Duration(self * other.0)
+    }
+}
+
+impl Div<f64> for Duration {
+    type Output = Duration;
+    fn div(self, other: f64) -> Duration {
+        Duration(self.0 / other)
+    }
+}
+
+impl Div for Duration {
+    type Output = f64;
+    fn div(self, other: Duration) -> f64 {
+        self.0 / other.0
+    }
+}
+
+/// Returns the difference between GPS time and UTC at a GPS time, in seconds.
+///
+/// Times after the last known leap second use the last known difference.
+///
+/// # Examples
+///
+/// ```
+/// use pos::time::{self, GpsTime};
+/// assert_eq!(0.0, time::leap_seconds(GpsTime::new(0, 0.0).unwrap()));
+/// assert_eq!(18.0, time::leap_seconds(GpsTime::new(2053, 0.0).unwrap()));
+/// ```
+pub fn leap_seconds(time: GpsTime) -> f64 {
+    let seconds = time.seconds_since_epoch();
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(year, month, day, leap_seconds)| {
+            seconds >= seconds_since_epoch(year, month, day) + leap_seconds
+        })
+        .map_or(0., |&(_, _, _, leap_seconds)| leap_seconds)
+}
+
+/// Returns the difference between GPS time and UTC at a UTC time, measured in seconds since the
+/// GPS epoch.
+fn leap_seconds_at_utc(seconds: f64) -> f64 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(year, month, day, _)| seconds >= seconds_since_epoch(year, month, day))
+        .map_or(0., |&(_, _, _, leap_seconds)| leap_seconds)
+}
+
+/// Returns the number of seconds from the GPS epoch to midnight UTC on a date, ignoring leap
+/// seconds.
+fn seconds_since_epoch(year: i64, month: i64, day: i64) -> f64 {
+    (days_from_civil(year, month, day) - GPS_EPOCH_DAYS) as f64 * SECONDS_PER_DAY
+}
+
+/// Makes the times of some points keep increasing across GPS week boundaries, returning the
+/// number of boundaries crossed.
+///
+/// A time that is more than half a week earlier than the time before it is taken to be in the
+/// next week, and a week's worth of seconds is added to it and to every point after it. The
+/// times become seconds since the start of the first point's week, and can be turned back into
+/// a week and seconds of week with `GpsTime::new`.
+///
+/// # Examples
+///
+/// ```
+/// use pos::Point;
+/// use pos::time::Time;
+/// let mut points: Vec<_> = [604799.0, 0.0, 1.0]
+///     .iter()
+///     .map(|&time| Point { time: Time(time), ..Default::default() })
+///     .collect();
+/// assert_eq!(1, pos::time::unwrap_week(&mut points));
+/// assert_eq!(Time(604800.0), points[1].time);
+/// ```
+pub fn unwrap_week(points: &mut [Point]) -> usize {
+    let mut unwrapper = Unwrapper::default();
+    for point in points.iter_mut() {
+        unwrapper.unwrap(point);
+    }
+    unwrapper.rollovers
+}
+
+/// A source that unwraps the GPS weeks of another source's points.
+///
+/// See `unwrap_week` for how rollovers are detected.
+#[derive(Debug)]
+pub struct UnwrapWeek<S: Source> {
+    source: S,
+    unwrapper: Unwrapper,
+}
+
+impl<S: Source> UnwrapWeek<S> {
+    /// Unwraps the weeks of a source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Trajectory;
+    /// use pos::time::UnwrapWeek;
+    /// let source = UnwrapWeek::new(pos::open("data/2-points.sbet").unwrap());
+    /// let trajectory = Trajectory::from_source(source).unwrap();
+    /// ```
+    pub fn new(source: S) -> UnwrapWeek<S> {
+        UnwrapWeek {
+            source,
+            unwrapper: Unwrapper::default(),
+        }
+    }
+
+    /// Returns the number of week boundaries crossed so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::time::UnwrapWeek;
+    /// let source = UnwrapWeek::new(pos::open("data/2-points.sbet").unwrap());
+    /// assert_eq!(0, source.rollovers());
+    /// ```
+    pub fn rollovers(&self) -> usize {
+        self.unwrapper.rollovers
+    }
+}
+
+impl<S: Source> Source for UnwrapWeek<S> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        Ok(self.source.source()?.map(|mut point| {
+            self.unwrapper.unwrap(&mut point);
+            point
+        }))
+    }
+}
+
+/// A source that corrects the times of another source's points, e.g. for a known time-tagging
+/// offset between sensors.
+///
+/// Each time becomes `origin + (time - origin) * factor + offset`.
+#[derive(Debug)]
+pub struct Retime<S: Source> {
+    /// The duration added to every time.
+    pub offset: Duration,
+    /// The factor that times are scaled by, relative to the origin.
+    pub factor: f64,
+    /// The time that isn't moved by scaling.
+    pub origin: Time,
+
+    source: S,
+}
+
+impl<S: Source> Retime<S> {
+    /// Wraps a source, initially without changing its times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Source;
+    /// use pos::time::{Duration, Retime};
+    /// let mut source = Retime::new(pos::open("data/2-points.sbet").unwrap());
+    /// source.offset = Duration(-18.0);
+    /// let point = source.source().unwrap().unwrap();
+    /// assert!((point.time.0 - 151613.002836).abs() < 1e-6);
+    /// ```
+    pub fn new(source: S) -> Retime<S> {
+        Retime {
+            offset: Duration(0.),
+            factor: 1.,
+            origin: Time(0.),
+            source,
+        }
+    }
+}
+
+impl<S: Source> Source for Retime<S> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        Ok(self.source.source()?.map(|mut point| {
+            point.time = self.origin + (point.time - self.origin) * self.factor + self.offset;
+            point
+        }))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Unwrapper {
+    previous: Option<Time>,
+    rollovers: usize,
+}
+
+impl Unwrapper {
+    fn unwrap(&mut self, point: &mut Point) {
+        if let Some(previous) = self.previous {
+            let time = point.time + Duration(self.rollovers as f64 * SECONDS_PER_WEEK);
+            if previous - time > Duration(SECONDS_PER_WEEK / 2.) {
+                self.rollovers += 1;
+            }
+        }
+        point.time += Duration(self.rollovers as f64 * SECONDS_PER_WEEK);
+        self.previous = Some(point.time);
+    }
+}
+
+/// Returns the number of seconds from the start of the GPS week to the given date.
+///
+/// GPS weeks start at midnight between Saturday and Sunday.
+///
+/// # Examples
+///
+/// ```
+/// use pos::time::{self, SECONDS_PER_DAY};
+/// assert_eq!(3.0 * SECONDS_PER_DAY, time::seconds_of_week(2019, 5, 15));
+/// ```
+pub fn seconds_of_week(year: i64, month: i64, day: i64) -> f64 {
+    (days_from_civil(year, month, day) - GPS_EPOCH_DAYS).rem_euclid(7) as f64 * SECONDS_PER_DAY
+}
+
+/// Formats a GPS week and seconds of the week as an ISO 8601 UTC date and time, with millisecond
+/// precision.
+///
+/// `leap_seconds` is the difference between GPS time and UTC.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!("1980-01-06T00:00:00.000Z", pos::time::to_utc_string(0, 0.0, 0.0));
+/// ```
+pub fn to_utc_string(week: u16, seconds_of_week: f64, leap_seconds: f64) -> String {
+    let seconds = (GPS_EPOCH_DAYS as f64 * SECONDS_PER_DAY)
+        + f64::from(week) * SECONDS_PER_WEEK
+        + seconds_of_week
+        - leap_seconds;
+    let milliseconds = (seconds * 1000.).round() as i64;
+    let days = milliseconds.div_euclid(86_400_000);
+    let milliseconds = milliseconds.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000
+    )
+}
+
+/// Returns the number of days since 1970-01-01 for a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Returns the date in the proleptic Gregorian calendar for a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days() {
+        assert_eq!(0, days_from_civil(1970, 1, 1));
+        assert_eq!(GPS_EPOCH_DAYS, days_from_civil(1980, 1, 6));
+        assert_eq!(0.0, seconds_of_week(2019, 5, 12));
+        assert_eq!(3.0 * SECONDS_PER_DAY, seconds_of_week(2019, 5, 15));
+        for &days in &[-1, 0, 59, 60, 11016, 18031, 730000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days, days_from_civil(year, month, day));
+        }
+    }
+
+    #[test]
+    fn gps_time() {
+        assert!(GpsTime::new(0, -1.).is_none());
+        assert!(GpsTime::new(u16::MAX, SECONDS_PER_WEEK).is_none());
+        assert!(GpsTime::new(2053, f64::NAN).is_none());
+        assert!(GpsTime::from_unix(0.).is_none());
+        assert_eq!(
+            GpsTime::new(2052, 604799.).unwrap(),
+            GpsTime::new(2053, -1.).unwrap()
+        );
+        assert!(GpsTime::new(2053, 0.).unwrap() > GpsTime::new(2052, 604799.).unwrap());
+        assert_eq!(
+            GpsTime::new(0, 3. * SECONDS_PER_WEEK + 5.).unwrap(),
+            GpsTime {
+                week: 3,
+                seconds: 5.,
+            }
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut time = Time(SECONDS_PER_WEEK - 0.5);
+        time += Duration(1.);
+        assert_eq!(Time(SECONDS_PER_WEEK + 0.5), time);
+        assert_eq!(0.5, time.seconds_of_week());
+        assert_eq!(Duration(-1.), Time(1.) - Time(2.));
+        assert_eq!(Time(1.5), Time(1.) + 0.5 * Duration(1.));
+        assert_eq!(4., Duration(2.) / Duration(0.5));
+        assert_eq!(Duration(0.25), std::time::Duration::from_millis(250).into());
+        assert!(Time(1.) < Time(2.));
+    }
+
+    #[test]
+    fn unwrap() {
+        let mut points: Vec<_> = [604790., 604799., 1., 2., 1.5, 604700., 10.]
+            .iter()
+            .map(|&time| Point {
+                time: Time(time),
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(2, unwrap_week(&mut points));
+        let times: Vec<_> = points.iter().map(|p| p.time.0).collect();
+        assert_eq!(
+            vec![
+                604790.,
+                604799.,
+                604801.,
+                604802.,
+                604801.5,
+                2. * SECONDS_PER_WEEK - 100.,
+                2. * SECONDS_PER_WEEK + 10.,
+            ],
+            times
+        );
+    }
+
+    #[test]
+    fn leap() {
+        let before = GpsTime::new(0, seconds_since_epoch(2017, 1, 1) + 16.).unwrap();
+        assert_eq!(17., leap_seconds(before));
+        assert_eq!("2016-12-31T23:59:59.000Z", before.to_utc());
+        let after = GpsTime::new(0, seconds_since_epoch(2017, 1, 1) + 18.).unwrap();
+        assert_eq!(18., leap_seconds(after));
+        assert_eq!("2017-01-01T00:00:00.000Z", after.to_utc());
+        for &unix in &[315964800., 1483228799., 1483228800., 1557883389.5] {
+            let time = GpsTime::from_unix(unix).unwrap();
+            assert_eq!(unix, time.to_unix());
+        }
+    }
+
+    #[test]
+    fn utc() {
+        assert_eq!("1980-01-06T00:00:00.000Z", to_utc_string(0, 0., 0.));
+        assert_eq!(
+            "2019-05-15T01:23:27.000Z",
+            to_utc_string(2053, 264225., 18.)
+        );
+        assert_eq!("2019-05-12T00:00:00.000Z", to_utc_string(2053, -0.0004, 0.));
+    }
+}
@@ -0,0 +1,1405 @@
+//! Trajectories are collections of points, sorted by time.
+
+use crate::compare::Offset;
+use crate::error::Error;
+use crate::geodesy::Method;
+use crate::interpolate;
+use crate::nearest;
+use crate::point::{Point, Tolerance};
+use crate::qc;
+use crate::qc::{Limits, TimeIssue};
+use crate::region::{self, BoundingBox, Region};
+use crate::source::Source;
+use crate::statistics::Statistics;
+use crate::time::{Duration, Time, TimeBase};
+use crate::units::Radians;
+use std::iter::FromIterator;
+use std::slice::Iter;
+use std::vec::IntoIter;
+
+/// A collection of points, sorted by time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    points: Vec<Point>,
+}
+
+/// A gap in a trajectory's timestamps, e.g. from a GNSS outage or a logger dropout.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Gap {
+    /// The time of the last point before the gap.
+    pub start: Time,
+    /// The time of the first point after the gap.
+    pub end: Time,
+    /// The length of the gap.
+    pub duration: Duration,
+}
+
+/// The extent of a trajectory in space and time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounds {
+    /// The latitude and longitude bounding box, in degrees.
+    pub bbox: BoundingBox,
+    /// The lowest altitude.
+    pub min_altitude: f64,
+    /// The highest altitude.
+    pub max_altitude: f64,
+    /// The time of the first point.
+    pub start: Time,
+    /// The time of the last point.
+    pub end: Time,
+}
+
+/// How `Trajectory::merge` resolves the time span that both trajectories cover.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Overlap {
+    /// Keep the first trajectory's points and drop the second's.
+    #[default]
+    PreferFirst,
+    /// Keep the second trajectory's points and drop the first's.
+    PreferSecond,
+    /// Average the first trajectory's points with the second interpolated at their times.
+    Average,
+}
+
+impl Trajectory {
+    /// Creates a new trajectory from some points, sorting them by time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// assert_eq!(1, trajectory.len());
+    /// ```
+    pub fn new(mut points: Vec<Point>) -> Trajectory {
+        qc::sort_by_time(&mut points);
+        Trajectory { points }
+    }
+
+    /// Reads all points from a source into a trajectory.
+    ///
+    /// Returns an error if the points' times are in different time bases, e.g. NMEA points from
+    /// before and after the date is known, since sorting them together would be meaningless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{sbet, Trajectory};
+    /// let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+    /// let trajectory = Trajectory::from_source(reader).unwrap();
+    /// assert_eq!(2, trajectory.len());
+    /// ```
+    pub fn from_source<S: Source>(mut source: S) -> Result<Trajectory, Error> {
+        let mut points = Vec::new();
+        while let Some(point) = source.source()? {
+            points.push(point);
+        }
+        let _ = TimeBase::of(&points)?;
+        Ok(Trajectory::new(points))
+    }
+
+    /// Returns this trajectory's points as a slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// assert_eq!(Point::default(), trajectory.points()[0]);
+    /// ```
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Returns the number of points in this trajectory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Trajectory;
+    /// assert_eq!(0, Trajectory::default().len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns true if this trajectory has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Trajectory;
+    /// assert!(Trajectory::default().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns an iterator over this trajectory's points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// assert_eq!(1, trajectory.iter().count());
+    /// ```
+    pub fn iter(&self) -> Iter<'_, Point> {
+        self.points.iter()
+    }
+
+    /// Inserts a point, keeping the trajectory sorted by time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let mut trajectory = Trajectory::default();
+    /// trajectory.push(Point { time: Time(2.0), ..Default::default() });
+    /// trajectory.push(Point { time: Time(1.0), ..Default::default() });
+    /// assert_eq!(Time(1.0), trajectory.points()[0].time);
+    /// ```
+    pub fn push(&mut self, point: Point) {
+        let index = self.points.partition_point(|p| p.time <= point.time);
+        self.points.insert(index, point);
+    }
+
+    /// Returns the point that is closest in time to the given time.
+    ///
+    /// Returns none if the trajectory is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// assert_eq!(Time(4.0), trajectory.point_at(Time(4.2)).unwrap().time);
+    /// ```
+    pub fn point_at(&self, time: Time) -> Option<&Point> {
+        let index = self.points.partition_point(|p| p.time < time);
+        if index == 0 {
+            self.points.first()
+        } else if index == self.points.len() {
+            self.points.last()
+        } else if time - self.points[index - 1].time <= self.points[index].time - time {
+            Some(&self.points[index - 1])
+        } else {
+            Some(&self.points[index])
+        }
+    }
+
+    /// Returns every gap between consecutive points that is longer than `max_dt`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::{Duration, Time};
+    /// let trajectory: Trajectory = [0.0, 1.0, 2.0, 5.0, 6.0]
+    ///     .iter()
+    ///     .map(|&time| Point { time: Time(time), ..Default::default() })
+    ///     .collect();
+    /// let gaps = trajectory.gaps(Duration(1.5));
+    /// assert_eq!(1, gaps.len());
+    /// assert_eq!(Time(2.0), gaps[0].start);
+    /// assert_eq!(Duration(3.0), gaps[0].duration);
+    /// ```
+    pub fn gaps(&self, max_dt: Duration) -> Vec<Gap> {
+        self.points
+            .windows(2)
+            .filter(|pair| pair[1].time - pair[0].time > max_dt)
+            .map(|pair| Gap {
+                start: pair[0].time,
+                end: pair[1].time,
+                duration: pair[1].time - pair[0].time,
+            })
+            .collect()
+    }
+
+    /// Splits this trajectory at every gap longer than `max_dt`, e.g. into one trajectory per line
+    /// of a survey.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::{Duration, Time};
+    /// let trajectory: Trajectory = [0.0, 1.0, 2.0, 5.0, 6.0]
+    ///     .iter()
+    ///     .map(|&time| Point { time: Time(time), ..Default::default() })
+    ///     .collect();
+    /// let segments = trajectory.split_on_gaps(Duration(1.5));
+    /// assert_eq!(2, segments.len());
+    /// assert_eq!(3, segments[0].len());
+    /// ```
+    pub fn split_on_gaps(&self, max_dt: Duration) -> Vec<Trajectory> {
+        chunk_by(&self.points, |a, b| b.time - a.time <= max_dt)
+            .into_iter()
+            .map(|points| Trajectory {
+                points: points.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Splits this trajectory into consecutive spans of a fixed duration, starting at the first
+    /// point.
+    ///
+    /// Spans without any points are skipped, and a duration that isn't positive returns the whole
+    /// trajectory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::{Duration, Time};
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// let segments = trajectory.split_every(Duration(4.0));
+    /// assert_eq!(3, segments.len());
+    /// assert_eq!(Time(4.0), segments[1].points()[0].time);
+    /// ```
+    pub fn split_every(&self, duration: Duration) -> Vec<Trajectory> {
+        let start = match self.points.first() {
+            Some(first) => first.time,
+            None => return Vec::new(),
+        };
+        if duration <= Duration(0.) {
+            return vec![self.clone()];
+        }
+        let span = |point: &Point| ((point.time - start) / duration).floor();
+        chunk_by(&self.points, |a, b| span(a) == span(b))
+            .into_iter()
+            .map(|points| Trajectory {
+                points: points.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the points whose motion from the previous good point exceeds some
+    /// physical limits.
+    ///
+    /// See the `qc` module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::qc::Limits;
+    /// use pos::time::Time;
+    /// let mut points: Vec<_> = (0..5)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// points[2].altitude = 1e6;
+    /// let trajectory = Trajectory::new(points);
+    /// assert_eq!(vec![2], trajectory.outliers(&Limits::default()));
+    /// ```
+    pub fn outliers(&self, limits: &Limits) -> Vec<usize> {
+        qc::outliers(&self.points, limits)
+    }
+
+    /// Checks that this trajectory's times strictly increase.
+    ///
+    /// Trajectories are sorted when they are created, so the only possible issues are duplicated
+    /// times. Use `qc::validate_time` to check points in the order they were read, e.g. to catch
+    /// a logger that restarted its seconds of week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Point::default(), Point::default()]);
+    /// assert_eq!(1, trajectory.validate_time().len());
+    /// ```
+    pub fn validate_time(&self) -> Vec<TimeIssue> {
+        qc::validate_time(&self.points)
+    }
+
+    /// Returns true if both trajectories have the same number of points and each pair is
+    /// approximately equal, e.g. after a round trip through a file format.
+    ///
+    /// See `Point::approx_eq`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::point::Tolerance;
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// let other = Trajectory::new(vec![Point { altitude: 1e-6, ..Default::default() }]);
+    /// assert!(trajectory.approx_eq(&other, &Tolerance::default()));
+    /// assert!(!trajectory.approx_eq(&Trajectory::default(), &Tolerance::default()));
+    /// ```
+    pub fn approx_eq(&self, other: &Trajectory, tolerance: &Tolerance) -> bool {
+        self.len() == other.len()
+            && self
+                .points
+                .iter()
+                .zip(&other.points)
+                .all(|(a, b)| a.approx_eq(b, tolerance))
+    }
+
+    /// Removes points whose times duplicate an earlier point, returning the number removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let mut trajectory = Trajectory::new(vec![Point::default(), Point::default()]);
+    /// assert_eq!(1, trajectory.dedup());
+    /// assert!(trajectory.validate_time().is_empty());
+    /// ```
+    pub fn dedup(&mut self) -> usize {
+        qc::dedup(&mut self.points)
+    }
+
+    /// Adds an offset, in seconds, to every point's time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::{Duration, Time};
+    /// let point = Point { time: Time(20.0), ..Default::default() };
+    /// let mut trajectory = Trajectory::new(vec![point]);
+    /// trajectory.shift_time(Duration(-18.0));
+    /// assert_eq!(Time(2.0), trajectory.points()[0].time);
+    /// ```
+    pub fn shift_time(&mut self, offset: Duration) {
+        for point in &mut self.points {
+            point.time += offset;
+        }
+    }
+
+    /// Scales every point's time by a factor, relative to an origin time that doesn't move.
+    ///
+    /// A negative factor reverses the order of the points, which are sorted again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let point = Point { time: Time(20.0), ..Default::default() };
+    /// let mut trajectory = Trajectory::new(vec![point]);
+    /// trajectory.scale_time(1.5, Time(10.0));
+    /// assert_eq!(Time(25.0), trajectory.points()[0].time);
+    /// ```
+    pub fn scale_time(&mut self, factor: f64, origin: Time) {
+        for point in &mut self.points {
+            point.time = origin + (point.time - origin) * factor;
+        }
+        if factor < 0. {
+            qc::sort_by_time(&mut self.points);
+        }
+    }
+
+    /// Fills in missing velocities, accelerations, and angular rates by differentiating the
+    /// positions, velocities, and attitudes.
+    ///
+    /// Derivatives are central differences of the neighboring points, or one-sided differences
+    /// at the ends. Only fields that are none are filled, so values from the source file are
+    /// kept. Velocities and accelerations are in meters per second (squared) in the local x
+    /// (east), y (north), and z (up) directions. Angular rates are the rates of change of roll,
+    /// pitch, and yaw, which approximate the body rates for a near-level platform. Points whose
+    /// neighbors have the same time are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let mut trajectory: Trajectory = (0..5)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: (i * i) as f64, ..Default::default() })
+    ///     .collect();
+    /// trajectory.derive_kinematics();
+    /// let point = trajectory.points()[2];
+    /// assert_eq!(Some(4.0), point.z_velocity);
+    /// assert_eq!(Some(2.0), point.z_acceleration);
+    /// ```
+    pub fn derive_kinematics(&mut self) {
+        let velocities = derivatives(&self.points, |point, previous| {
+            let offset = Offset::between(point, previous);
+            Some([offset.east, offset.north, offset.up])
+        });
+        let angular_rates = derivatives(&self.points, |point, previous| {
+            let offset = Offset::between(point, previous);
+            Some([offset.roll.0, offset.pitch.0, offset.yaw.0])
+        });
+        for ((point, velocity), angular_rate) in
+            self.points.iter_mut().zip(velocities).zip(angular_rates)
+        {
+            if let Some([x, y, z]) = velocity {
+                let _ = point.x_velocity.get_or_insert(x);
+                let _ = point.y_velocity.get_or_insert(y);
+                let _ = point.z_velocity.get_or_insert(z);
+            }
+            if let Some([x, y, z]) = angular_rate {
+                let _ = point.x_angular_rate.get_or_insert(Radians(x));
+                let _ = point.y_angular_rate.get_or_insert(Radians(y));
+                let _ = point.z_angular_rate.get_or_insert(Radians(z));
+            }
+        }
+        let accelerations = derivatives(&self.points, |point, previous| {
+            Some([
+                point.x_velocity? - previous.x_velocity?,
+                point.y_velocity? - previous.y_velocity?,
+                point.z_velocity? - previous.z_velocity?,
+            ])
+        });
+        for (point, acceleration) in self.points.iter_mut().zip(accelerations) {
+            if let Some([x, y, z]) = acceleration {
+                let _ = point.x_acceleration.get_or_insert(x);
+                let _ = point.y_acceleration.get_or_insert(y);
+                let _ = point.z_acceleration.get_or_insert(z);
+            }
+        }
+    }
+
+    /// Returns summary statistics of this trajectory, or none if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::{Duration, Time};
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// let statistics = trajectory.statistics().unwrap();
+    /// assert_eq!(Duration(9.0), statistics.duration);
+    /// assert_eq!(1.0, statistics.sample_rate);
+    /// ```
+    pub fn statistics(&self) -> Option<Statistics> {
+        Statistics::new(&self.points)
+    }
+
+    /// Returns a new trajectory with only the points between two times, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// assert_eq!(3, trajectory.clip(Time(2.0), Time(4.0)).len());
+    /// ```
+    pub fn clip(&self, start: Time, end: Time) -> Trajectory {
+        let first = self.points.partition_point(|p| p.time < start);
+        let last = self.points.partition_point(|p| p.time <= end).max(first);
+        Trajectory {
+            points: self.points[first..last].to_vec(),
+        }
+    }
+
+    /// Returns the distance traveled along the track up to each point, or chainage, in meters.
+    ///
+    /// Distances are summed between consecutive points along the ellipsoid, ignoring altitude, so
+    /// the first point is always at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::geodesy::Method;
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..3)
+    ///     .map(|i| Point {
+    ///         time: Time(i as f64),
+    ///         longitude: Radians::from_degrees(i as f64),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let chainage = trajectory.chainage(Method::Vincenty);
+    /// assert_eq!(0.0, chainage[0]);
+    /// assert!((chainage[2] - 222_638.982).abs() < 1e-3);
+    /// ```
+    pub fn chainage(&self, method: Method) -> Vec<f64> {
+        let mut distance = 0.;
+        let mut chainage = Vec::with_capacity(self.points.len());
+        for (i, point) in self.points.iter().enumerate() {
+            if i > 0 {
+                distance += method.distance(&self.points[i - 1], point);
+            }
+            chainage.push(distance);
+        }
+        chainage
+    }
+
+    /// Sets every point's distance to its chainage, in meters.
+    ///
+    /// This overwrites any distances that were read from the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::geodesy::Method;
+    /// use pos::time::Time;
+    /// let mut trajectory: Trajectory = (0..3)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// trajectory.set_distances(Method::Haversine);
+    /// assert_eq!(Some(0.0), trajectory.points()[2].distance);
+    /// ```
+    pub fn set_distances(&mut self, method: Method) {
+        let chainage = self.chainage(method);
+        for (point, distance) in self.points.iter_mut().zip(chainage) {
+            point.distance = Some(distance);
+        }
+    }
+
+    /// Returns the extent of this trajectory in space and time, or none if it's empty.
+    ///
+    /// The bounding box crosses the antimeridian if the trajectory does, see
+    /// `BoundingBox::around`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point {
+    ///         time: Time(i as f64),
+    ///         latitude: Radians::from_degrees(i as f64),
+    ///         altitude: -(i as f64),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let bounds = trajectory.bounds().unwrap();
+    /// assert_eq!(9.0, bounds.bbox.north.round());
+    /// assert_eq!(-9.0, bounds.min_altitude);
+    /// assert_eq!(Time(9.0), bounds.end);
+    /// ```
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+        let (min_altitude, max_altitude) = self
+            .points
+            .iter()
+            .fold((first.altitude, first.altitude), |(min, max), point| {
+                (min.min(point.altitude), max.max(point.altitude))
+            });
+        Some(Bounds {
+            bbox: BoundingBox::around(&self.points)?,
+            min_altitude,
+            max_altitude,
+            start: first.time,
+            end: last.time,
+        })
+    }
+
+    /// Returns the convex hull of this trajectory's footprint, as a closed ring of longitude and
+    /// latitude pairs in degrees.
+    ///
+    /// See `region::convex_hull`, and `geojson::write_polygon` to export it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Point::default()]);
+    /// assert_eq!(2, trajectory.convex_hull().len());
+    /// ```
+    pub fn convex_hull(&self) -> Vec<(f64, f64)> {
+        region::convex_hull(&self.points)
+    }
+
+    /// Returns the point nearest to a latitude and longitude, or none if the trajectory is empty.
+    ///
+    /// This scans every point, so use a `nearest::Index` for many queries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point {
+    ///         time: Time(i as f64),
+    ///         longitude: Radians::from_degrees(i as f64 * 0.001),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let point = trajectory.nearest_to(Radians(0.0), Radians::from_degrees(0.0071)).unwrap();
+    /// assert_eq!(Time(7.0), point.time);
+    /// ```
+    pub fn nearest_to(&self, latitude: Radians<f64>, longitude: Radians<f64>) -> Option<&Point> {
+        nearest::scan(&self.points, latitude, longitude)
+    }
+
+    /// Returns a new trajectory with only the points inside a region, e.g. a project area.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::region::BoundingBox;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { longitude: Radians::from_degrees(i as f64), ..Default::default() })
+    ///     .collect();
+    /// let bbox = BoundingBox::new(2.5, -1.0, 5.5, 1.0);
+    /// assert_eq!(3, trajectory.clip_region(&bbox).len());
+    /// ```
+    pub fn clip_region<R: Region>(&self, region: &R) -> Trajectory {
+        Trajectory {
+            points: self
+                .points
+                .iter()
+                .filter(|point| region.contains(point))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the first and last times of each run of consecutive points inside a region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::region::BoundingBox;
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = [0.0, 3.0, 4.0, 9.0, 3.0]
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(i, &longitude)| Point {
+    ///         time: Time(i as f64),
+    ///         longitude: Radians::from_degrees(longitude),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let bbox = BoundingBox::new(2.5, -1.0, 5.5, 1.0);
+    /// let intervals = trajectory.region_intervals(&bbox);
+    /// assert_eq!(vec![(Time(1.0), Time(2.0)), (Time(4.0), Time(4.0))], intervals);
+    /// ```
+    pub fn region_intervals<R: Region>(&self, region: &R) -> Vec<(Time, Time)> {
+        chunk_by(&self.points, |a, b| {
+            region.contains(a) == region.contains(b)
+        })
+        .into_iter()
+        .filter(|points| region.contains(&points[0]))
+        .map(|points| (points[0].time, points[points.len() - 1].time))
+        .collect()
+    }
+
+    /// Linearly interpolates a point at the given time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: i as f64, ..Default::default() })
+    ///     .collect();
+    /// let point = trajectory.interpolate_at(Time(4.5)).unwrap();
+    /// assert_eq!(4.5, point.altitude);
+    /// ```
+    pub fn interpolate_at(&self, time: Time) -> Result<Point, Error> {
+        let index = self.bracket(time)?;
+        if self.points[index].time == time {
+            Ok(self.points[index])
+        } else {
+            Ok(self.points[index - 1].interpolate(&self.points[index], time))
+        }
+    }
+
+    /// Interpolates the position and attitude, or exterior orientation, at each event time, e.g.
+    /// each camera exposure.
+    ///
+    /// Attitudes are interpolated with `Point::slerp`. Returns an error if any event is outside
+    /// the trajectory, so clip events to `bounds` first if the cameras fired before alignment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::events::Reader;
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..3)
+    ///     .map(|i| Point { time: Time(151631.0 + i as f64 * 0.005), ..Default::default() })
+    ///     .collect();
+    /// let times: Vec<_> = Reader::from_path("data/2-points.events")
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .map(|event| event.unwrap().time)
+    ///     .collect();
+    /// let points = trajectory.at_events(&times).unwrap();
+    /// assert_eq!(Time(151631.008), points[1].time);
+    /// ```
+    pub fn at_events(&self, times: &[Time]) -> Result<Vec<Point>, Error> {
+        times.iter().map(|&time| self.pose_at(time)).collect()
+    }
+
+    /// Interpolates the position and attitude at a time, like `interpolate_at` but with
+    /// `Point::slerp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Radians, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = [359.0, 1.0]
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(i, &yaw)| Point {
+    ///         time: Time(i as f64),
+    ///         yaw: Radians::from_degrees(yaw),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    /// let point = trajectory.pose_at(Time(0.5)).unwrap();
+    /// assert!((point.yaw.to_degrees() - 360.0).abs() < 1e-9);
+    /// ```
+    pub fn pose_at(&self, time: Time) -> Result<Point, Error> {
+        let index = self.bracket(time)?;
+        if self.points[index].time == time {
+            Ok(self.points[index])
+        } else {
+            Ok(self.points[index - 1].slerp(&self.points[index], time))
+        }
+    }
+
+    /// Returns the index of the first point at or after a time, which is never the first point,
+    /// or an error if the time is outside this trajectory.
+    fn bracket(&self, time: Time) -> Result<usize, Error> {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) if self.points.len() > 1 => (first, last),
+            _ => return Err(interpolate::Error::OnePoint.into()),
+        };
+        if time < first.time {
+            Err(interpolate::Error::TimeBelowMinimum(time).into())
+        } else if time > last.time {
+            Err(interpolate::Error::TimeAboveMaximum(time).into())
+        } else {
+            Ok(self.points.partition_point(|p| p.time < time).max(1))
+        }
+    }
+
+    /// Resamples this trajectory to evenly spaced epochs at a fixed rate, in hertz.
+    ///
+    /// The epochs are whole multiples of the sample interval, e.g. every 0.005 s at 200 Hz, from
+    /// the first point to the last. Each point is interpolated from its neighbors with
+    /// `Point::slerp`, so attitudes are interpolated properly across angle wraps. Gaps are
+    /// interpolated across like anything else, so check `gaps` first if that matters. Returns an
+    /// empty trajectory if the rate isn't finite and positive or there are fewer than two points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = [0.0, 0.013, 0.03]
+    ///     .iter()
+    ///     .map(|&time| Point { time: Time(time), ..Default::default() })
+    ///     .collect();
+    /// let resampled = trajectory.resample(100.0);
+    /// assert_eq!(4, resampled.len());
+    /// assert!((resampled.points()[1].time.0 - 0.01).abs() < 1e-12);
+    /// ```
+    pub fn resample(&self, hz: f64) -> Trajectory {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) if self.points.len() > 1 && hz.is_finite() && hz > 0. => {
+                (first, last)
+            }
+            _ => return Trajectory::default(),
+        };
+        let start = (first.time.0 * hz).ceil() as i64;
+        let end = (last.time.0 * hz).floor() as i64;
+        // Don't trust the rate for the allocation, since a huge one means a huge number of epochs.
+        let count = end.saturating_sub(start).saturating_add(1).max(0);
+        let mut points = Vec::with_capacity(count.min(4096) as usize);
+        let mut index = 1;
+        for epoch in start..=end {
+            let time = Time(epoch as f64 / hz);
+            while index < self.points.len() - 1 && self.points[index].time < time {
+                index += 1;
+            }
+            let (previous, next) = (&self.points[index - 1], &self.points[index]);
+            points.push(if next.time == time {
+                *next
+            } else if previous.time == time {
+                *previous
+            } else {
+                previous.slerp(next, time)
+            });
+        }
+        Trajectory { points }
+    }
+
+    /// Returns a new trajectory with every nth point, starting with the first.
+    ///
+    /// A factor of zero is treated as one, which keeps every point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), ..Default::default() })
+    ///     .collect();
+    /// let decimated = trajectory.decimate(4);
+    /// assert_eq!(3, decimated.len());
+    /// assert_eq!(Time(8.0), decimated.points()[2].time);
+    /// ```
+    pub fn decimate(&self, n: usize) -> Trajectory {
+        Trajectory {
+            points: self.points.iter().step_by(n.max(1)).cloned().collect(),
+        }
+    }
+
+    /// Returns a new trajectory with one point for every `window` points, whose time and position
+    /// are the average of those points'.
+    ///
+    /// Averaging smooths out noise that plain decimation would alias into the thinned
+    /// trajectory. Everything other than the time and position, e.g. the attitude, is taken from
+    /// the middle point of each window. A last, partial window is averaged too. A window of zero
+    /// is treated as one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Point, Trajectory};
+    /// use pos::time::Time;
+    /// let trajectory: Trajectory = (0..10)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: i as f64, ..Default::default() })
+    ///     .collect();
+    /// let averaged = trajectory.decimate_average(4);
+    /// assert_eq!(3, averaged.len());
+    /// assert_eq!(1.5, averaged.points()[0].altitude);
+    /// assert_eq!(Time(8.5), averaged.points()[2].time);
+    /// ```
+    pub fn decimate_average(&self, window: usize) -> Trajectory {
+        Trajectory {
+            points: self.points.chunks(window.max(1)).map(average).collect(),
+        }
+    }
+
+    /// Merges this trajectory with another, e.g. two segments of a split sbet, sorted by time.
+    ///
+    /// Where the two trajectories cover the same time span, the points are taken from only one of
+    /// them, or averaged, so that the merged trajectory doesn't zigzag between two slightly
+    /// different solutions. Averaged points keep this trajectory's times and accuracies. Points
+    /// outside the overlap are kept from both.
+    ///
+    /// Returns an error if the two trajectories' times are in different time bases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::{Overlap, Point, Trajectory};
+    /// use pos::time::Time;
+    /// let first: Trajectory = (0..6)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: 0.0, ..Default::default() })
+    ///     .collect();
+    /// let second: Trajectory = (4..10)
+    ///     .map(|i| Point { time: Time(i as f64), altitude: 2.0, ..Default::default() })
+    ///     .collect();
+    /// let merged = first.merge(&second, Overlap::PreferFirst).unwrap();
+    /// assert_eq!(10, merged.len());
+    /// assert_eq!(0.0, merged.points()[5].altitude);
+    /// let merged = first.merge(&second, Overlap::Average).unwrap();
+    /// assert_eq!(1.0, merged.points()[5].altitude);
+    /// ```
+    pub fn merge(&self, other: &Trajectory, overlap: Overlap) -> Result<Trajectory, Error> {
+        let _ = TimeBase::of(self.iter().chain(other))?;
+        let span = |t: &Trajectory| Some((t.points.first()?.time, t.points.last()?.time));
+        let (start, end) = match (span(self), span(other)) {
+            (Some((a, b)), Some((c, d))) => (a.max(c), b.min(d)),
+            _ => return Ok(Trajectory::new(self.iter().chain(other).cloned().collect())),
+        };
+        let within = |point: &Point| start <= point.time && point.time <= end;
+        let points = match overlap {
+            Overlap::PreferFirst => self
+                .iter()
+                .chain(other.iter().filter(|p| !within(p)))
+                .cloned()
+                .collect(),
+            Overlap::PreferSecond => self
+                .iter()
+                .filter(|p| !within(p))
+                .chain(other)
+                .cloned()
+                .collect(),
+            Overlap::Average => self
+                .iter()
+                .map(|point| {
+                    if within(point) {
+                        let other = other
+                            .interpolate_at(point.time)
+                            .ok()
+                            .or_else(|| other.point_at(point.time).cloned())
+                            .unwrap_or(*point);
+                        midpoint(point, &other)
+                    } else {
+                        *point
+                    }
+                })
+                .chain(other.iter().filter(|p| !within(p)).cloned())
+                .collect(),
+        };
+        Ok(Trajectory::new(points))
+    }
+}
+
+impl From<Vec<Point>> for Trajectory {
+    fn from(points: Vec<Point>) -> Trajectory {
+        Trajectory::new(points)
+    }
+}
+
+impl FromIterator<Point> for Trajectory {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Trajectory {
+        Trajectory::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Trajectory {
+    type Item = Point;
+    type IntoIter = IntoIter<Point>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.points.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Trajectory {
+    type Item = &'a Point;
+    type IntoIter = Iter<'a, Point>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.points.iter()
+    }
+}
+
+/// Splits points into runs where each point and the one after it satisfy a predicate.
+///
+/// This is `slice::chunk_by`, which needs a newer Rust than this crate's minimum.
+fn chunk_by<F: FnMut(&Point, &Point) -> bool>(points: &[Point], mut same: F) -> Vec<&[Point]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for (i, pair) in points.windows(2).enumerate() {
+        if !same(&pair[0], &pair[1]) {
+            chunks.push(&points[start..=i]);
+            start = i + 1;
+        }
+    }
+    if start < points.len() {
+        chunks.push(&points[start..]);
+    }
+    chunks
+}
+
+/// Averages the times and positions of some points, taking everything else from the middle one.
+///
+/// Longitudes are averaged as differences from the first point's, so windows that cross the
+/// antimeridian don't average to the other side of the world, and then wrapped to [-π, π).
+fn average(points: &[Point]) -> Point {
+    let first = &points[0];
+    let count = points.len() as f64;
+    let mean = |value: &dyn Fn(&Point) -> f64| points.iter().map(value).sum::<f64>() / count;
+    let mut point = points[points.len() / 2];
+    point.time = first.time + Duration(mean(&|p| (p.time - first.time).0));
+    point.latitude = Radians(mean(&|p| p.latitude.0));
+    point.longitude = (first.longitude
+        + Radians(mean(&|p| p.longitude.angular_difference(first.longitude).0)))
+    .wrap_pi();
+    point.altitude = mean(&|p| p.altitude);
+    point
+}
+
+/// Averages two points at the same time, keeping the first's time and accuracy.
+fn midpoint(first: &Point, second: &Point) -> Point {
+    Point {
+        time: first.time,
+        ..first.blend(second, 0.5)
+    }
+}
+
+/// Differentiates some points, given the difference between a point and the one before it.
+fn derivatives<F>(points: &[Point], difference: F) -> Vec<Option<[f64; 3]>>
+where
+    F: Fn(&Point, &Point) -> Option<[f64; 3]>,
+{
+    (0..points.len())
+        .map(|i| {
+            let previous = &points[i.saturating_sub(1)];
+            let next = &points[(i + 1).min(points.len() - 1)];
+            let dt = next.time - previous.time;
+            if dt > Duration(0.) {
+                let difference = difference(next, previous)?;
+                let dt = dt.0;
+                Some([difference[0] / dt, difference[1] / dt, difference[2] / dt])
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::sbet;
+
+    fn trajectory() -> Trajectory {
+        (0..5)
+            .rev()
+            .map(|i| Point {
+                time: Time(i as f64),
+                altitude: 10.0 * i as f64,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kinematics() {
+        let mut trajectory: Trajectory = (0..4)
+            .map(|i| Point {
+                time: Time(0.5 * i as f64),
+                latitude: Radians::from_degrees(1e-5 * i as f64),
+                yaw: Radians::from_degrees(359. + i as f64),
+                x_velocity: if i == 1 { Some(7.) } else { None },
+                ..Default::default()
+            })
+            .collect();
+        trajectory.derive_kinematics();
+        let points = trajectory.points();
+        let north = Offset::between(&points[1], &points[0]).north / 0.5;
+        assert!((points[0].y_velocity.unwrap() - north).abs() < 1e-9);
+        assert!((points[3].y_velocity.unwrap() - north).abs() < 1e-9);
+        assert_eq!(Some(7.), points[1].x_velocity);
+        assert!(points[2].x_velocity.unwrap().abs() < 1e-9);
+        assert!((points[2].z_angular_rate.unwrap().to_degrees() - 2.).abs() < 1e-9);
+        assert!(points[2].y_acceleration.unwrap().abs() < 1e-6);
+        assert!((points[2].x_acceleration.unwrap() + 7.).abs() < 1e-9);
+        assert!(points[2].course().is_some());
+
+        let mut trajectory = Trajectory::new(vec![Point::default(), Point::default()]);
+        trajectory.derive_kinematics();
+        assert_eq!(None, trajectory.points()[0].x_velocity);
+    }
+
+    #[test]
+    fn sorted() {
+        let trajectory = trajectory();
+        let times: Vec<_> = trajectory.iter().map(|p| p.time.0).collect();
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0, 4.0], times);
+    }
+
+    #[test]
+    fn point_at() {
+        let trajectory = trajectory();
+        assert_eq!(Time(0.0), trajectory.point_at(Time(-1.0)).unwrap().time);
+        assert_eq!(Time(1.0), trajectory.point_at(Time(1.4)).unwrap().time);
+        assert_eq!(Time(2.0), trajectory.point_at(Time(1.6)).unwrap().time);
+        assert_eq!(Time(4.0), trajectory.point_at(Time(10.0)).unwrap().time);
+        assert!(Trajectory::default().point_at(Time(0.0)).is_none());
+    }
+
+    #[test]
+    fn gaps() {
+        let mut trajectory = trajectory();
+        assert!(trajectory.gaps(Duration(1.0)).is_empty());
+        trajectory.push(Point {
+            time: Time(10.0),
+            ..Default::default()
+        });
+        assert_eq!(
+            vec![Gap {
+                start: Time(4.0),
+                end: Time(10.0),
+                duration: Duration(6.0),
+            }],
+            trajectory.gaps(Duration(1.0))
+        );
+        assert_eq!(5, trajectory.gaps(Duration(0.5)).len());
+        assert!(Trajectory::default().gaps(Duration(0.0)).is_empty());
+    }
+
+    #[test]
+    fn retime() {
+        let mut trajectory = trajectory();
+        trajectory.shift_time(Duration(1.0));
+        trajectory.scale_time(2.0, Time(1.0));
+        let times: Vec<_> = trajectory.iter().map(|p| p.time.0).collect();
+        assert_eq!(vec![1.0, 3.0, 5.0, 7.0, 9.0], times);
+        trajectory.scale_time(-1.0, Time(0.0));
+        assert_eq!(Time(-9.0), trajectory.points()[0].time);
+        assert_eq!(40.0, trajectory.points()[0].altitude);
+    }
+
+    #[test]
+    fn clip() {
+        let trajectory = trajectory();
+        let times: Vec<_> = trajectory
+            .clip(Time(0.5), Time(3.0))
+            .iter()
+            .map(|p| p.time.0)
+            .collect();
+        assert_eq!(vec![1.0, 2.0, 3.0], times);
+        assert!(trajectory.clip(Time(3.0), Time(1.0)).is_empty());
+        assert_eq!(trajectory, trajectory.clip(Time(-1.0), Time(10.0)));
+    }
+
+    #[test]
+    fn interpolate_at() {
+        let trajectory = trajectory();
+        assert_eq!(25.0, trajectory.interpolate_at(Time(2.5)).unwrap().altitude);
+        assert_eq!(40.0, trajectory.interpolate_at(Time(4.0)).unwrap().altitude);
+        assert_eq!(0.0, trajectory.interpolate_at(Time(0.0)).unwrap().altitude);
+        assert!(trajectory.interpolate_at(Time(-0.1)).is_err());
+        assert!(trajectory.interpolate_at(Time(4.1)).is_err());
+        assert!(Trajectory::new(vec![Point::default()])
+            .interpolate_at(Time(0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn at_events() {
+        let trajectory: Trajectory = [0.0, 1.0, 2.0]
+            .iter()
+            .map(|&time| Point {
+                time: Time(time),
+                altitude: time,
+                yaw: Radians::from_degrees(if time > 0.5 { 1.0 } else { 359.0 }),
+                ..Default::default()
+            })
+            .collect();
+        let points = trajectory
+            .at_events(&[Time(1.5), Time(0.5), Time(1.0)])
+            .unwrap();
+        assert_eq!(
+            vec![1.5, 0.5, 1.0],
+            points.iter().map(|p| p.altitude).collect::<Vec<_>>()
+        );
+        assert!((points[1].yaw.to_degrees() - 360.0).abs() < 1e-9);
+        assert_eq!(trajectory.points()[1], points[2]);
+        assert!(trajectory.at_events(&[Time(1.0), Time(2.5)]).is_err());
+        assert!(trajectory.at_events(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn resample() {
+        let trajectory: Trajectory = [0.0, 0.5, 2.0]
+            .iter()
+            .map(|&time| Point {
+                time: Time(100.0 + time),
+                altitude: time,
+                yaw: Radians::from_degrees(if time > 1.0 { 1.0 } else { 359.0 }),
+                ..Default::default()
+            })
+            .collect();
+        let resampled = trajectory.resample(4.0);
+        let times: Vec<_> = resampled.iter().map(|p| p.time.0).collect();
+        assert_eq!(
+            vec![100.0, 100.25, 100.5, 100.75, 101.0, 101.25, 101.5, 101.75, 102.0],
+            times
+        );
+        assert_eq!(0.25, resampled.points()[1].altitude);
+        assert_eq!(trajectory.points()[1], resampled.points()[2]);
+        assert!((resampled.points()[5].yaw.to_degrees() - 360.0).abs() < 1e-9);
+        assert!(trajectory.resample(0.0).is_empty());
+        assert!(trajectory.resample(f64::NAN).is_empty());
+        assert!(trajectory.resample(f64::INFINITY).is_empty());
+        assert!(Trajectory::new(vec![Point::default()])
+            .resample(1.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn decimate() {
+        let trajectory = trajectory();
+        let times: Vec<_> = trajectory.decimate(2).iter().map(|p| p.time.0).collect();
+        assert_eq!(vec![0.0, 2.0, 4.0], times);
+        assert_eq!(trajectory, trajectory.decimate(0));
+    }
+
+    #[test]
+    fn decimate_average() {
+        let trajectory: Trajectory = [179.5, -179.5, -179.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &longitude)| Point {
+                time: Time(i as f64),
+                longitude: Radians::from_degrees(longitude),
+                yaw: Radians(i as f64),
+                ..Default::default()
+            })
+            .collect();
+        let averaged = trajectory.decimate_average(2);
+        assert_eq!(2, averaged.len());
+        let point = averaged.points()[0];
+        assert_eq!(Time(0.5), point.time);
+        assert!((point.longitude.to_degrees() + 180.0).abs() < 1e-9);
+        assert_eq!(Radians(1.0), point.yaw);
+        assert_eq!(trajectory.points()[2], averaged.points()[1]);
+    }
+
+    #[test]
+    fn midpoint_wraps() {
+        let point = |longitude: f64, yaw: f64| Point {
+            time: Time(1.0),
+            longitude: Radians::from_degrees(longitude),
+            yaw: Radians::from_degrees(yaw),
+            altitude: longitude,
+            ..Default::default()
+        };
+        let average = midpoint(&point(179.0, 359.0), &point(-179.0, 3.0));
+        assert_eq!(Time(1.0), average.time);
+        assert!((average.longitude.to_degrees() + 180.0).abs() < 1e-9);
+        assert!((average.yaw.to_degrees() - 1.0).abs() < 1e-9);
+        assert_eq!(0.0, average.altitude);
+    }
+
+    #[test]
+    fn merge() {
+        let first = trajectory();
+        let second: Trajectory = [3.5, 4.5, 5.5]
+            .iter()
+            .map(|&time| Point {
+                time: Time(time),
+                altitude: 100.0,
+                ..Default::default()
+            })
+            .collect();
+        let times =
+            |trajectory: Trajectory| -> Vec<f64> { trajectory.iter().map(|p| p.time.0).collect() };
+        assert_eq!(
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 4.5, 5.5],
+            times(first.merge(&second, Overlap::PreferFirst).unwrap())
+        );
+        assert_eq!(
+            vec![0.0, 1.0, 2.0, 3.0, 3.5, 4.5, 5.5],
+            times(first.merge(&second, Overlap::PreferSecond).unwrap())
+        );
+        let merged = first.merge(&second, Overlap::Average).unwrap();
+        assert_eq!(
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 4.5, 5.5],
+            times(merged.clone())
+        );
+        assert_eq!(30.0, merged.points()[3].altitude);
+        assert_eq!(70.0, merged.points()[4].altitude);
+        assert_eq!(
+            first,
+            first
+                .merge(&Trajectory::default(), Overlap::PreferSecond)
+                .unwrap()
+        );
+
+        let split = Trajectory::new(vec![second.points()[0]]);
+        let merged = second.merge(&split, Overlap::Average).unwrap();
+        assert_eq!(second, merged);
+
+        let utc: Trajectory = second
+            .iter()
+            .map(|&point| Point {
+                time_base: TimeBase::UtcSecondsOfWeek,
+                ..point
+            })
+            .collect();
+        assert!(first.merge(&utc, Overlap::PreferFirst).is_err());
+    }
+
+    #[test]
+    fn split() {
+        let trajectory: Trajectory = [0.0, 0.5, 3.0, 3.5, 4.0, 9.0]
+            .iter()
+            .map(|&time| Point {
+                time: Time(time),
+                ..Default::default()
+            })
+            .collect();
+        let lengths = |segments: Vec<Trajectory>| -> Vec<usize> {
+            segments.iter().map(|segment| segment.len()).collect()
+        };
+        assert_eq!(
+            vec![2, 3, 1],
+            lengths(trajectory.split_on_gaps(Duration(1.0)))
+        );
+        assert_eq!(vec![6], lengths(trajectory.split_on_gaps(Duration(10.0))));
+        assert_eq!(
+            vec![2, 2, 1, 1],
+            lengths(trajectory.split_every(Duration(2.0)))
+        );
+        assert_eq!(vec![6], lengths(trajectory.split_every(Duration(0.0))));
+        assert!(Trajectory::default()
+            .split_on_gaps(Duration(1.0))
+            .is_empty());
+        assert!(Trajectory::default().split_every(Duration(1.0)).is_empty());
+    }
+
+    #[test]
+    fn region() {
+        use crate::region::BoundingBox;
+
+        let trajectory: Trajectory = [-1.0, 0.5, 0.5, 2.0, 0.5, 0.5, 0.5]
+            .iter()
+            .enumerate()
+            .map(|(i, &latitude)| Point {
+                time: Time(i as f64),
+                latitude: Radians::from_degrees(latitude),
+                ..Default::default()
+            })
+            .collect();
+        let bbox = BoundingBox::new(-1.0, 0.0, 1.0, 1.0);
+        let clipped = trajectory.clip_region(&bbox);
+        assert_eq!(5, clipped.len());
+        assert_eq!(Time(1.0), clipped.points()[0].time);
+        assert_eq!(
+            vec![(Time(1.0), Time(2.0)), (Time(4.0), Time(6.0))],
+            trajectory.region_intervals(&bbox)
+        );
+        assert!(Trajectory::default().region_intervals(&bbox).is_empty());
+    }
+
+    #[test]
+    fn chainage() {
+        let mut trajectory: Trajectory = [0.0, 0.001, 0.001, 0.003]
+            .iter()
+            .enumerate()
+            .map(|(i, &latitude)| Point {
+                time: Time(i as f64),
+                latitude: Radians::from_degrees(latitude),
+                altitude: 100.0 * i as f64,
+                ..Default::default()
+            })
+            .collect();
+        let chainage = trajectory.chainage(Method::Vincenty);
+        assert_eq!(chainage[1], chainage[2]);
+        assert!((chainage[3] - 3.0 * chainage[1]).abs() < 1e-3);
+        assert!((chainage[1] - 110.574).abs() < 1e-3);
+        trajectory.set_distances(Method::Haversine);
+        let distances: Vec<_> = trajectory.iter().map(|p| p.distance.unwrap()).collect();
+        assert_eq!(trajectory.chainage(Method::Haversine), distances);
+        assert!(Trajectory::default().chainage(Method::Vincenty).is_empty());
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = trajectory().bounds().unwrap();
+        assert_eq!(0.0, bounds.min_altitude);
+        assert_eq!(40.0, bounds.max_altitude);
+        assert_eq!(Time(0.0), bounds.start);
+        assert_eq!(Time(4.0), bounds.end);
+        assert_eq!(BoundingBox::default(), bounds.bbox);
+        assert!(Trajectory::default().bounds().is_none());
+    }
+
+    #[test]
+    fn from_sbet() {
+        let reader = sbet::Reader::from_path("data/2-points.sbet").unwrap();
+        let trajectory = Trajectory::from_source(reader).unwrap();
+        assert_eq!(2, trajectory.len());
+        let time = trajectory.points()[0].time + Duration(0.001);
+        assert_eq!(time, trajectory.interpolate_at(time).unwrap().time);
+    }
+}
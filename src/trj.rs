@@ -0,0 +1,613 @@
+//! TerraScan binary trajectory files.
+//!
+//! Each file starts with a header block, followed by fixed-size position records. Positions are
+//! stored as x, y, and z coordinates in the trajectory's coordinate system, with heading, roll,
+//! and pitch in degrees.
+
+use crate::error;
+use crate::point::Point;
+use crate::sink::Sink;
+use crate::source::Source;
+use crate::time::Time;
+use crate::units::Radians;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::iter::IntoIterator;
+use std::path::Path;
+
+/// The magic bytes at the start of every trj file.
+pub const SIGNATURE: &[u8; 8] = b"TSCANTRJ";
+
+/// The file version written by trj writers.
+pub const VERSION: i32 = 20010715;
+
+/// The size of the header block written by trj writers, in bytes.
+pub const HEADER_SIZE: i32 = 1384;
+
+/// The size of a position record written by trj writers, in bytes.
+pub const RECORD_SIZE: i32 = 80;
+
+/// The length of the header's video and waveform file names and video time ranges, which aren't
+/// read.
+const VIDEO_LENGTH: usize = 3 * 400 + 4 * 8;
+
+/// Trj errors.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum Error {
+    /// The file doesn't start with the trj signature.
+    #[error("Invalid trj signature: {0:?}")]
+    Signature([u8; 8]),
+
+    /// The header or record size is smaller than this reader understands.
+    #[error("Invalid {0} size: {1}")]
+    Size(&'static str, i32),
+}
+
+/// A trj header block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    /// The file version.
+    pub version: i32,
+
+    /// The number of position records.
+    pub position_count: i32,
+
+    /// A description of the trajectory.
+    pub description: String,
+
+    /// The system identifier, used by TerraScan to look up lever arms.
+    pub system: u8,
+
+    /// The quality of the whole trajectory, from one (best) to five.
+    pub quality: u8,
+
+    /// The first time stamp.
+    pub begin_time: f64,
+
+    /// The last time stamp.
+    pub end_time: f64,
+
+    /// The trajectory's number before any splitting.
+    pub original_number: i32,
+
+    /// The trajectory's flightline number.
+    pub number: i32,
+
+    /// The trajectory's group name.
+    pub group: String,
+
+    /// The size of the header block, in bytes.
+    ///
+    /// Writers always write `HEADER_SIZE`.
+    pub header_size: i32,
+
+    /// The size of each position record, in bytes.
+    ///
+    /// Writers always write `RECORD_SIZE`.
+    pub record_size: i32,
+}
+
+impl Default for Header {
+    fn default() -> Header {
+        Header {
+            version: VERSION,
+            position_count: 0,
+            description: String::new(),
+            system: 0,
+            quality: 1,
+            begin_time: 0.,
+            end_time: 0.,
+            original_number: 0,
+            number: 0,
+            group: String::new(),
+            header_size: HEADER_SIZE,
+            record_size: RECORD_SIZE,
+        }
+    }
+}
+
+impl Header {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Header, error::Error> {
+        let mut signature = [0; 8];
+        reader.read_exact(&mut signature)?;
+        if &signature != SIGNATURE {
+            return Err(Error::Signature(signature).into());
+        }
+        let version = reader.read_i32::<LittleEndian>()?;
+        let header_size = reader.read_i32::<LittleEndian>()?;
+        if header_size < HEADER_SIZE {
+            return Err(Error::Size("header", header_size).into());
+        }
+        let position_count = reader.read_i32::<LittleEndian>()?;
+        let record_size = reader.read_i32::<LittleEndian>()?;
+        if record_size < RECORD_SIZE {
+            return Err(Error::Size("record", record_size).into());
+        }
+        let description = read_string(reader, 79)?;
+        let system = reader.read_u8()?;
+        let quality = reader.read_u8()?;
+        skip(reader, 7)?;
+        let begin_time = reader.read_f64::<LittleEndian>()?;
+        let end_time = reader.read_f64::<LittleEndian>()?;
+        let original_number = reader.read_i32::<LittleEndian>()?;
+        let number = reader.read_i32::<LittleEndian>()?;
+        skip(reader, VIDEO_LENGTH)?;
+        let group = read_string(reader, 16)?;
+        skip(reader, (header_size - HEADER_SIZE) as usize)?;
+        Ok(Header {
+            version,
+            position_count,
+            description,
+            system,
+            quality,
+            begin_time,
+            end_time,
+            original_number,
+            number,
+            group,
+            header_size,
+            record_size,
+        })
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), error::Error> {
+        writer.write_all(SIGNATURE)?;
+        writer.write_i32::<LittleEndian>(self.version)?;
+        writer.write_i32::<LittleEndian>(HEADER_SIZE)?;
+        writer.write_i32::<LittleEndian>(self.position_count)?;
+        writer.write_i32::<LittleEndian>(RECORD_SIZE)?;
+        write_string(writer, &self.description, 79)?;
+        writer.write_u8(self.system)?;
+        writer.write_u8(self.quality)?;
+        writer.write_all(&[0; 7])?;
+        writer.write_f64::<LittleEndian>(self.begin_time)?;
+        writer.write_f64::<LittleEndian>(self.end_time)?;
+        writer.write_i32::<LittleEndian>(self.original_number)?;
+        writer.write_i32::<LittleEndian>(self.number)?;
+        writer.write_all(&[0; VIDEO_LENGTH])?;
+        write_string(writer, &self.group, 16)?;
+        Ok(())
+    }
+}
+
+/// A trj position record.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[allow(missing_docs)]
+pub struct Record {
+    pub time: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// The heading, in degrees.
+    pub heading: f64,
+    /// The roll, in degrees.
+    pub roll: f64,
+    /// The pitch, in degrees.
+    pub pitch: f64,
+    /// The quality of the horizontal position, zero if unknown.
+    pub xy_quality: i32,
+    /// The quality of the elevation, zero if unknown.
+    pub z_quality: i32,
+    /// The quality of the heading, zero if unknown.
+    pub heading_quality: i32,
+    /// The quality of the roll and pitch, zero if unknown.
+    pub roll_pitch_quality: i32,
+    /// A user mark.
+    pub mark: u8,
+    /// Flags.
+    pub flag: u8,
+}
+
+impl Record {
+    /// Converts this record to a point, assuming the trajectory is in geographic coordinates.
+    ///
+    /// X is longitude and y is latitude, both in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::trj::Record;
+    /// let record = Record { x: -105.0, y: 40.0, heading: 90.0, ..Default::default() };
+    /// let point = record.to_point();
+    /// assert_eq!(90.0, point.yaw.to_degrees());
+    /// ```
+    pub fn to_point(&self) -> Point {
+        Point {
+            time: Time(self.time),
+            longitude: Radians::from_degrees(self.x),
+            latitude: Radians::from_degrees(self.y),
+            altitude: self.z,
+            roll: Radians::from_degrees(self.roll),
+            pitch: Radians::from_degrees(self.pitch),
+            yaw: Radians::from_degrees(self.heading),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a record from a point, with longitude and latitude in degrees as x and y.
+    ///
+    /// The heading is the point's true heading, since trj files have no wander angle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::Point;
+    /// use pos::time::Time;
+    /// use pos::trj::Record;
+    /// let record = Record::from_point(&Point { time: Time(1.0), ..Default::default() });
+    /// assert_eq!(1.0, record.time);
+    /// ```
+    pub fn from_point(point: &Point) -> Record {
+        Record {
+            time: point.time.0,
+            x: point.longitude.to_degrees(),
+            y: point.latitude.to_degrees(),
+            z: point.altitude,
+            heading: point.true_heading().to_degrees(),
+            roll: point.roll.to_degrees(),
+            pitch: point.pitch.to_degrees(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A trj reader.
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    /// The file's header block.
+    pub header: Header,
+
+    index: i32,
+    reader: R,
+}
+
+impl Reader<BufReader<File>> {
+    /// Opens a reader for a path and reads the header block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::trj::Reader;
+    /// let reader = Reader::from_path("data/2-points.trj").unwrap();
+    /// assert_eq!(2, reader.header.position_count);
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<BufReader<File>>, error::Error> {
+        Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> Reader<R> {
+    /// Creates a new reader from any `Read` and reads the header block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use pos::trj::Reader;
+    /// let reader = Reader::new(File::open("data/2-points.trj").unwrap()).unwrap();
+    /// ```
+    pub fn new(mut reader: R) -> Result<Reader<R>, error::Error> {
+        let header = Header::read_from(&mut reader)?;
+        Ok(Reader {
+            header,
+            index: 0,
+            reader,
+        })
+    }
+
+    /// Reads the next position record.
+    ///
+    /// Returns none once the header's position count has been read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::trj::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.trj").unwrap();
+    /// let record = reader.read_record().unwrap().unwrap();
+    /// ```
+    pub fn read_record(&mut self) -> Result<Option<Record>, error::Error> {
+        if self.index >= self.header.position_count {
+            return Ok(None);
+        }
+        let record = Record {
+            time: self.reader.read_f64::<LittleEndian>()?,
+            x: self.reader.read_f64::<LittleEndian>()?,
+            y: self.reader.read_f64::<LittleEndian>()?,
+            z: self.reader.read_f64::<LittleEndian>()?,
+            heading: self.reader.read_f64::<LittleEndian>()?,
+            roll: self.reader.read_f64::<LittleEndian>()?,
+            pitch: self.reader.read_f64::<LittleEndian>()?,
+            xy_quality: self.reader.read_i32::<LittleEndian>()?,
+            z_quality: self.reader.read_i32::<LittleEndian>()?,
+            heading_quality: self.reader.read_i32::<LittleEndian>()?,
+            roll_pitch_quality: self.reader.read_i32::<LittleEndian>()?,
+            mark: self.reader.read_u8()?,
+            flag: self.reader.read_u8()?,
+        };
+        skip(&mut self.reader, (self.header.record_size - 74) as usize)?;
+        self.index += 1;
+        Ok(Some(record))
+    }
+
+    /// Reads the next position record as a point.
+    ///
+    /// See `Record::to_point` for how records are converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::trj::Reader;
+    /// let mut reader = Reader::from_path("data/2-points.trj").unwrap();
+    /// let point = reader.read_point().unwrap().unwrap();
+    /// ```
+    pub fn read_point(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_record()
+            .map(|record| record.map(|record| record.to_point()))
+    }
+}
+
+impl<R: Read> IntoIterator for Reader<R> {
+    type Item = Result<Point, error::Error>;
+    type IntoIter = ReaderIterator<R>;
+    fn into_iter(self) -> Self::IntoIter {
+        ReaderIterator { reader: self }
+    }
+}
+
+/// An iterator over a trj reader.
+#[derive(Debug)]
+pub struct ReaderIterator<R: Read> {
+    reader: Reader<R>,
+}
+
+impl<R: Read> Iterator for ReaderIterator<R> {
+    type Item = Result<Point, error::Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_point().transpose()
+    }
+}
+
+impl<R: Debug + Read> Source for Reader<R> {
+    fn source(&mut self) -> Result<Option<Point>, error::Error> {
+        self.read_point()
+    }
+}
+
+/// A trj writer.
+///
+/// The header block's position count and time range are filled in from the written records when
+/// the writer is closed.
+#[derive(Debug)]
+pub struct Writer<W: Write + Seek> {
+    header: Header,
+    writer: W,
+}
+
+impl Writer<BufWriter<File>> {
+    /// Creates a writer for a path and writes the header block.
+    ///
+    /// If the file already exists, it will be truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::trj::{Header, Writer};
+    /// let writer = Writer::from_path("/dev/null", Header::default()).unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        header: Header,
+    ) -> Result<Writer<BufWriter<File>>, error::Error> {
+        Writer::new(BufWriter::new(File::create(path)?), header)
+    }
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Creates a new writer and writes the header block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use pos::trj::{Header, Writer};
+    /// let header = Header { group: "north".to_string(), ..Default::default() };
+    /// let writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+    /// ```
+    pub fn new(mut writer: W, mut header: Header) -> Result<Writer<W>, error::Error> {
+        header.position_count = 0;
+        header.header_size = HEADER_SIZE;
+        header.record_size = RECORD_SIZE;
+        header.write_to(&mut writer)?;
+        Ok(Writer { header, writer })
+    }
+
+    /// Writes a position record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use pos::trj::{Header, Record, Writer};
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap();
+    /// writer.write_record(&Record::default()).unwrap();
+    /// ```
+    pub fn write_record(&mut self, record: &Record) -> Result<(), error::Error> {
+        self.writer.write_f64::<LittleEndian>(record.time)?;
+        self.writer.write_f64::<LittleEndian>(record.x)?;
+        self.writer.write_f64::<LittleEndian>(record.y)?;
+        self.writer.write_f64::<LittleEndian>(record.z)?;
+        self.writer.write_f64::<LittleEndian>(record.heading)?;
+        self.writer.write_f64::<LittleEndian>(record.roll)?;
+        self.writer.write_f64::<LittleEndian>(record.pitch)?;
+        self.writer.write_i32::<LittleEndian>(record.xy_quality)?;
+        self.writer.write_i32::<LittleEndian>(record.z_quality)?;
+        self.writer
+            .write_i32::<LittleEndian>(record.heading_quality)?;
+        self.writer
+            .write_i32::<LittleEndian>(record.roll_pitch_quality)?;
+        self.writer.write_u8(record.mark)?;
+        self.writer.write_u8(record.flag)?;
+        self.writer.write_all(&[0; 6])?;
+        if self.header.position_count == 0 {
+            self.header.begin_time = record.time;
+        }
+        self.header.end_time = record.time;
+        self.header.position_count += 1;
+        Ok(())
+    }
+
+    /// Writes a point as a position record.
+    ///
+    /// See `Record::from_point` for how points are converted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use pos::Point;
+    /// use pos::trj::{Header, Writer};
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap();
+    /// writer.write_point(&Point::default()).unwrap();
+    /// ```
+    pub fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        self.write_record(&Record::from_point(point))
+    }
+
+    /// Rewrites the header block with the position count and time range, and returns the
+    /// underlying `Write`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use pos::trj::{Header, Writer};
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap();
+    /// let cursor = writer.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<W, error::Error> {
+        let position = self.writer.stream_position()?;
+        let _ = self.writer.seek(SeekFrom::Start(0))?;
+        self.header.write_to(&mut self.writer)?;
+        let _ = self.writer.seek(SeekFrom::Start(position))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn skip<R: Read>(reader: &mut R, count: usize) -> Result<(), error::Error> {
+    let copied = ::std::io::copy(&mut reader.take(count as u64), &mut ::std::io::sink())?;
+    if copied < count as u64 {
+        return Err(::std::io::Error::from(::std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R, length: usize) -> Result<String, error::Error> {
+    let mut bytes = vec![0; length];
+    reader.read_exact(&mut bytes)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(length);
+    Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+/// Writes a string into a fixed-length, nul-padded field, truncating it if it's too long.
+fn write_string<W: Write>(writer: &mut W, s: &str, length: usize) -> Result<(), error::Error> {
+    let mut bytes = vec![0; length];
+    let len = s.len().min(length - 1);
+    bytes[..len].copy_from_slice(&s.as_bytes()[..len]);
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+impl<W: Debug + Write + Seek> Sink for Writer<W> {
+    fn write_point(&mut self, point: &Point) -> Result<(), error::Error> {
+        Writer::write_point(self, point)
+    }
+
+    fn close(self: Box<Self>) -> Result<(), error::Error> {
+        let _ = Writer::close(*self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn read_file() {
+        let reader = Reader::from_path("data/2-points.trj").unwrap();
+        assert_eq!(VERSION, reader.header.version);
+        assert_eq!("2-points", reader.header.description);
+        assert_eq!("north", reader.header.group);
+        assert_eq!(7, reader.header.number);
+        assert_eq!(151631.0, reader.header.begin_time);
+        assert_eq!(151631.01, reader.header.end_time);
+        let points = reader.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(Time(151631.01), points[1].time);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let header = Header {
+            description: "a description".to_string(),
+            group: "a group name that is too long".to_string(),
+            quality: 2,
+            ..Default::default()
+        };
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        let record = Record {
+            time: 1.0,
+            x: 500000.0,
+            y: 4000000.0,
+            z: 1600.0,
+            heading: 359.0,
+            roll: 1.0,
+            pitch: -1.0,
+            xy_quality: 1,
+            mark: 3,
+            ..Default::default()
+        };
+        writer.write_record(&record).unwrap();
+        writer
+            .write_record(&Record {
+                time: 2.0,
+                ..record
+            })
+            .unwrap();
+        let bytes = writer.close().unwrap().into_inner();
+        assert_eq!((HEADER_SIZE + 2 * RECORD_SIZE) as usize, bytes.len());
+
+        let mut reader = Reader::new(bytes.as_slice()).unwrap();
+        assert_eq!(2, reader.header.position_count);
+        assert_eq!(1.0, reader.header.begin_time);
+        assert_eq!(2.0, reader.header.end_time);
+        assert_eq!(2, reader.header.quality);
+        assert_eq!("a group name th", reader.header.group);
+        assert_eq!(record, reader.read_record().unwrap().unwrap());
+        assert_eq!(2.0, reader.read_record().unwrap().unwrap().time);
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn true_heading() {
+        let point = Point {
+            yaw: Radians::from_degrees(100.),
+            wander_angle: Some(Radians::from_degrees(10.)),
+            ..Default::default()
+        };
+        let record = Record::from_point(&point);
+        assert!((record.heading - 90.).abs() < 1e-9);
+        assert_eq!(point.true_heading(), record.to_point().true_heading());
+    }
+
+    #[test]
+    fn signature() {
+        assert!(Reader::new(&b"NOTATRJFILE"[..]).is_err());
+    }
+}
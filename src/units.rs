@@ -1,12 +1,14 @@
 //! Unit newtypes.
 
 use std::f64::consts::PI;
+use std::fmt;
 use std::ops::{Add, Mul, Sub};
 
 /// Newtype wrapper around a radian value.
 ///
 /// It's so easy to forget if you're using radians or degrees.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Radians<T>(pub T);
 
 impl Radians<f64> {
@@ -37,6 +39,172 @@ impl Radians<f64> {
     pub fn to_degrees(self) -> f64 {
         self.0 * 180.0 / PI
     }
+
+    /// Wraps this angle to [-π, π).
+    ///
+    /// This is the same as `Radians::wrap_pi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// let angle = Radians::from_degrees(350.0).wrap();
+    /// assert!((angle.to_degrees() + 10.0).abs() < 1e-9);
+    /// ```
+    pub fn wrap(self) -> Radians<f64> {
+        self.wrap_pi()
+    }
+
+    /// Returns the signed shortest difference from another angle to this one, in [-π, π).
+    ///
+    /// Naive subtraction across the ±π boundary is off by a full turn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// let difference = Radians::from_degrees(-179.0).angular_difference(Radians::from_degrees(179.0));
+    /// assert!((difference.to_degrees() - 2.0).abs() < 1e-9);
+    /// ```
+    pub fn angular_difference(self, other: Radians<f64>) -> Radians<f64> {
+        (self - other).wrap_pi()
+    }
+
+    /// Wraps this angle to [-π, π).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::f64::consts::PI;
+    /// use pos::units::Radians;
+    /// assert_eq!(-PI, Radians(PI).wrap_pi().0);
+    /// ```
+    pub fn wrap_pi(self) -> Radians<f64> {
+        Radians((self.0 + PI).rem_euclid(2.0 * PI) - PI)
+    }
+
+    /// Wraps this angle to [0, 2π).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// let angle = Radians::from_degrees(-90.0).wrap_two_pi();
+    /// assert!((angle.to_degrees() - 270.0).abs() < 1e-9);
+    /// ```
+    pub fn wrap_two_pi(self) -> Radians<f64> {
+        Radians(self.0.rem_euclid(2.0 * PI))
+    }
+
+    /// Wraps this angle to [-π, π) if any of the angles it was computed from are negative, and
+    /// otherwise to [0, 2π), so that headings stay in the convention of the file they came from.
+    pub(crate) fn wrap_like<I: IntoIterator<Item = Radians<f64>>>(self, inputs: I) -> Radians<f64> {
+        if inputs.into_iter().any(|angle| angle.0 < 0.) {
+            self.wrap_pi()
+        } else {
+            self.wrap_two_pi()
+        }
+    }
+}
+
+/// Newtype wrapper around a degree value.
+///
+/// Most file formats and users think in degrees, so this is the unit for values on their way in
+/// or out. Convert to `Radians` for math.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Degrees<T>(pub T);
+
+impl Degrees<f64> {
+    /// Converts this degrees value to radians.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::f64::consts::PI;
+    /// use pos::units::Degrees;
+    /// assert_eq!(PI, Degrees(180.0).to_radians().0);
+    /// ```
+    pub fn to_radians(self) -> Radians<f64> {
+        Radians::from_degrees(self.0)
+    }
+
+    /// Returns the signed shortest difference from another angle to this one, in [-180, 180).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Degrees;
+    /// assert_eq!(Degrees(-20.0), Degrees(350.0).angular_difference(Degrees(10.0)));
+    /// ```
+    pub fn angular_difference(self, other: Degrees<f64>) -> Degrees<f64> {
+        (self - other).wrap_pi()
+    }
+
+    /// Wraps this angle to [-180, 180).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Degrees;
+    /// assert_eq!(Degrees(-10.0), Degrees(350.0).wrap_pi());
+    /// ```
+    pub fn wrap_pi(self) -> Degrees<f64> {
+        Degrees((self.0 + 180.0).rem_euclid(360.0) - 180.0)
+    }
+
+    /// Wraps this angle to [0, 360).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Degrees;
+    /// assert_eq!(Degrees(270.0), Degrees(-90.0).wrap_two_pi());
+    /// ```
+    pub fn wrap_two_pi(self) -> Degrees<f64> {
+        Degrees(self.0.rem_euclid(360.0))
+    }
+}
+
+impl From<Degrees<f64>> for Radians<f64> {
+    fn from(degrees: Degrees<f64>) -> Radians<f64> {
+        degrees.to_radians()
+    }
+}
+
+impl From<Radians<f64>> for Degrees<f64> {
+    fn from(radians: Radians<f64>) -> Degrees<f64> {
+        Degrees(radians.to_degrees())
+    }
+}
+
+/// Radians are displayed in degrees, which are easier to read, with a degree sign.
+///
+/// The formatter's precision applies to the degrees.
+///
+/// # Examples
+///
+/// ```
+/// use pos::units::Radians;
+/// assert_eq!("45.00°", format!("{:.2}", Radians::from_degrees(45.0)));
+/// ```
+impl fmt::Display for Radians<f64> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Degrees::from(*self), f)
+    }
+}
+
+/// # Examples
+///
+/// ```
+/// use pos::units::Degrees;
+/// assert_eq!("-105.5°", Degrees(-105.5).to_string());
+/// ```
+impl fmt::Display for Degrees<f64> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str("°")
+    }
 }
 
 impl Add for Radians<f64> {
@@ -59,3 +227,38 @@ impl Mul<Radians<f64>> for f64 {
         Radians(self * other.0)
     }
 }
+
+impl Mul<f64> for Radians<f64> {
+    type Output = Radians<f64>;
+    fn mul(self, other: f64) -> Radians<f64> {
+        Radians(self.0 * other)
+    }
+}
+
+impl Add for Degrees<f64> {
+    type Output = Degrees<f64>;
+    fn add(self, other: Degrees<f64>) -> Degrees<f64> {
+        Degrees(self.0 + other.0)
+    }
+}
+
+impl Sub for Degrees<f64> {
+    type Output = Degrees<f64>;
+    fn sub(self, other: Degrees<f64>) -> Degrees<f64> {
+        Degrees(self.0 - other.0)
+    }
+}
+
+impl Mul<Degrees<f64>> for f64 {
+    type Output = Degrees<f64>;
+    fn mul(self, other: Degrees<f64>) -> Degrees<f64> {
+        Degrees(self * other.0)
+    }
+}
+
+impl Mul<f64> for Degrees<f64> {
+    type Output = Degrees<f64>;
+    fn mul(self, other: f64) -> Degrees<f64> {
+        Degrees(self.0 * other)
+    }
+}
@@ -0,0 +1,269 @@
+//! Universal Transverse Mercator coordinates.
+//!
+//! Conversions use Krüger's series to fourth order in the third flattening, which is accurate to
+//! well under a millimeter within a zone.
+
+use crate::geodesy::{FLATTENING, SEMI_MAJOR_AXIS};
+use crate::units::Radians;
+
+/// The scale factor on each zone's central meridian.
+const SCALE_FACTOR: f64 = 0.9996;
+
+/// The easting of each zone's central meridian, in meters.
+const FALSE_EASTING: f64 = 500_000.;
+
+/// The northing of the equator in the southern hemisphere, in meters.
+const FALSE_NORTHING: f64 = 10_000_000.;
+
+/// A UTM zone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zone {
+    /// The zone number, from 1 to 60.
+    pub number: u8,
+    /// True for the northern hemisphere.
+    pub north: bool,
+}
+
+impl Zone {
+    /// Returns the zone that contains a position, including the exceptions for Norway and
+    /// Svalbard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// use pos::utm::Zone;
+    /// let zone = Zone::for_position(Radians::from_degrees(43.64), Radians::from_degrees(-79.39));
+    /// assert_eq!(Zone { number: 17, north: true }, zone);
+    /// ```
+    pub fn for_position(latitude: Radians<f64>, longitude: Radians<f64>) -> Zone {
+        let latitude = latitude.to_degrees();
+        let longitude = longitude.wrap().to_degrees();
+        let mut number = (((longitude + 180.) / 6.).floor() as u8 % 60) + 1;
+        if (56. ..64.).contains(&latitude) && (3. ..12.).contains(&longitude) {
+            number = 32;
+        } else if (72. ..84.).contains(&latitude) && (0. ..42.).contains(&longitude) {
+            number = if longitude < 9. {
+                31
+            } else if longitude < 21. {
+                33
+            } else if longitude < 33. {
+                35
+            } else {
+                37
+            };
+        }
+        Zone {
+            number,
+            north: latitude >= 0.,
+        }
+    }
+
+    /// Returns this zone's central meridian.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::utm::Zone;
+    /// let zone = Zone { number: 17, north: true };
+    /// assert_eq!(-81.0, zone.central_meridian().to_degrees());
+    /// ```
+    pub fn central_meridian(&self) -> Radians<f64> {
+        Radians::from_degrees(f64::from(self.number) * 6. - 183.)
+    }
+}
+
+/// A position in UTM coordinates, in meters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Utm {
+    /// The easting, including the false easting.
+    pub easting: f64,
+    /// The northing, including the false northing in the southern hemisphere.
+    pub northing: f64,
+    /// The zone.
+    pub zone: Zone,
+}
+
+impl Utm {
+    /// Converts a geodetic position to UTM, in the zone that contains it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// use pos::utm::Utm;
+    /// let utm = Utm::from_geodetic(Radians::from_degrees(43.642567), Radians::from_degrees(-79.387139));
+    /// assert_eq!(17, utm.zone.number);
+    /// assert!((utm.easting - 630084.0).abs() < 1.0);
+    /// assert!((utm.northing - 4833439.0).abs() < 1.0);
+    /// ```
+    pub fn from_geodetic(latitude: Radians<f64>, longitude: Radians<f64>) -> Utm {
+        Utm::from_geodetic_in_zone(latitude, longitude, Zone::for_position(latitude, longitude))
+    }
+
+    /// Converts a geodetic position to UTM in a given zone, e.g. to match a point cloud that was
+    /// projected into a neighboring zone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::units::Radians;
+    /// use pos::utm::{Utm, Zone};
+    /// let zone = Zone { number: 18, north: true };
+    /// let utm = Utm::from_geodetic_in_zone(Radians::from_degrees(43.6), Radians::from_degrees(-79.4), zone);
+    /// assert_eq!(zone, utm.zone);
+    /// assert!(utm.easting < 500000.0);
+    /// ```
+    pub fn from_geodetic_in_zone(
+        latitude: Radians<f64>,
+        longitude: Radians<f64>,
+        zone: Zone,
+    ) -> Utm {
+        let series = Series::new();
+        let longitude = (longitude - zone.central_meridian()).wrap().0;
+        let sin = latitude.0.sin();
+        let e = 2. * series.n.sqrt() / (1. + series.n);
+        let t = (sin.atanh() - e * (e * sin).atanh()).sinh();
+        let xi_prime = t.atan2(longitude.cos());
+        let eta_prime = (longitude.sin() / (1. + t * t).sqrt()).atanh();
+        let mut xi = xi_prime;
+        let mut eta = eta_prime;
+        for (j, alpha) in series.alpha.iter().enumerate() {
+            let k = 2. * (j + 1) as f64;
+            xi += alpha * (k * xi_prime).sin() * (k * eta_prime).cosh();
+            eta += alpha * (k * xi_prime).cos() * (k * eta_prime).sinh();
+        }
+        let scale = SCALE_FACTOR * series.rectifying_radius;
+        Utm {
+            easting: FALSE_EASTING + scale * eta,
+            northing: if zone.north { 0. } else { FALSE_NORTHING } + scale * xi,
+            zone,
+        }
+    }
+
+    /// Converts this position to geodetic latitude and longitude.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pos::utm::{Utm, Zone};
+    /// let utm = Utm { easting: 500000.0, northing: 0.0, zone: Zone { number: 31, north: true } };
+    /// let (latitude, longitude) = utm.to_geodetic();
+    /// assert!(latitude.0.abs() < 1e-12);
+    /// assert!((longitude.to_degrees() - 3.0).abs() < 1e-12);
+    /// ```
+    pub fn to_geodetic(&self) -> (Radians<f64>, Radians<f64>) {
+        let series = Series::new();
+        let scale = SCALE_FACTOR * series.rectifying_radius;
+        let false_northing = if self.zone.north { 0. } else { FALSE_NORTHING };
+        let xi = (self.northing - false_northing) / scale;
+        let eta = (self.easting - FALSE_EASTING) / scale;
+        let mut xi_prime = xi;
+        let mut eta_prime = eta;
+        for (j, beta) in series.beta.iter().enumerate() {
+            let k = 2. * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let mut latitude = chi;
+        for (j, delta) in series.delta.iter().enumerate() {
+            latitude += delta * (2. * (j + 1) as f64 * chi).sin();
+        }
+        let longitude = self.zone.central_meridian().0 + eta_prime.sinh().atan2(xi_prime.cos());
+        (Radians(latitude), Radians(longitude).wrap())
+    }
+}
+
+/// The coefficients of Krüger's series.
+struct Series {
+    n: f64,
+    rectifying_radius: f64,
+    alpha: [f64; 4],
+    beta: [f64; 4],
+    delta: [f64; 4],
+}
+
+impl Series {
+    fn new() -> Series {
+        let n = FLATTENING / (2. - FLATTENING);
+        let n2 = n * n;
+        let n3 = n2 * n;
+        let n4 = n3 * n;
+        Series {
+            n,
+            rectifying_radius: SEMI_MAJOR_AXIS / (1. + n) * (1. + n2 / 4. + n4 / 64.),
+            alpha: [
+                n / 2. - 2. * n2 / 3. + 5. * n3 / 16. + 41. * n4 / 180.,
+                13. * n2 / 48. - 3. * n3 / 5. + 557. * n4 / 1440.,
+                61. * n3 / 240. - 103. * n4 / 140.,
+                49561. * n4 / 161280.,
+            ],
+            beta: [
+                n / 2. - 2. * n2 / 3. + 37. * n3 / 96. - n4 / 360.,
+                n2 / 48. + n3 / 15. - 437. * n4 / 1440.,
+                17. * n3 / 480. - 37. * n4 / 840.,
+                4397. * n4 / 161280.,
+            ],
+            delta: [
+                2. * n - 2. * n2 / 3. - 2. * n3 + 116. * n4 / 45.,
+                7. * n2 / 3. - 8. * n3 / 5. - 227. * n4 / 45.,
+                56. * n3 / 15. - 136. * n4 / 35.,
+                4279. * n4 / 630.,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zones() {
+        let zone = |latitude: f64, longitude: f64| {
+            Zone::for_position(
+                Radians::from_degrees(latitude),
+                Radians::from_degrees(longitude),
+            )
+        };
+        assert_eq!(1, zone(0., -180.).number);
+        assert_eq!(60, zone(0., 179.9).number);
+        assert_eq!(1, zone(0., 180.).number);
+        assert_eq!(32, zone(60., 4.).number);
+        assert_eq!(31, zone(50., 4.).number);
+        assert_eq!(33, zone(78., 10.).number);
+        assert_eq!(37, zone(78., 40.).number);
+        assert!(!zone(-33.9, 151.2).north);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for &(latitude, longitude) in &[
+            (0., 0.),
+            (43.642567, -79.387139),
+            (-33.8568, 151.2153),
+            (32.545, -116.978),
+            (60., 4.),
+            (83.9, 20.),
+            (-79.9, -0.1),
+        ] {
+            let utm = Utm::from_geodetic(
+                Radians::from_degrees(latitude),
+                Radians::from_degrees(longitude),
+            );
+            let (actual_latitude, actual_longitude) = utm.to_geodetic();
+            assert!((actual_latitude.to_degrees() - latitude).abs() < 1e-9);
+            assert!((actual_longitude.to_degrees() - longitude).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn southern_hemisphere() {
+        let utm = Utm::from_geodetic(Radians::from_degrees(-0.0001), Radians::from_degrees(3.));
+        assert!((utm.northing - (FALSE_NORTHING - 11.053)).abs() < 1e-3);
+        assert!((utm.easting - FALSE_EASTING).abs() < 1e-6);
+    }
+}